@@ -6,13 +6,22 @@ use core_chat::*;
 use core_gateway::bancho_endpoints::{routes::*, *};
 use core_geoip::*;
 use core_signature::*;
+use infra_beatmaps::{BeatmapFileStorage, CliBeatmapFilesConfigs};
 use infra_services::IntoService;
 use peace_api::{ApiFrameConfig, WebApplication};
 use peace_db::{
     peace::{Peace, PeaceDbConfig},
     DbConfig, DbConnection,
 };
-use peace_repositories::users::{DynUsersRepository, UsersRepositoryImpl};
+use peace_repositories::{
+    beatmaps::{BeatmapsRepositoryImpl, DynBeatmapsRepository},
+    chat::ChatRepositoryImpl,
+    comments::{CommentsRepositoryImpl, DynCommentsRepository},
+    error_reports::{DynErrorReportsRepository, ErrorReportsRepositoryImpl},
+    favourites::{DynFavouritesRepository, FavouritesRepositoryImpl},
+    scores::{DynScoresRepository, ScoresRepositoryImpl},
+    users::{DynUsersRepository, UsersRepositoryImpl},
+};
 use peace_runtime::cfg::RuntimeConfig;
 use std::{net::SocketAddr, sync::Arc};
 use utoipa::OpenApi;
@@ -46,6 +55,18 @@ pub struct BanchoStandaloneConfig {
     #[command(flatten)]
     pub bancho_background_service_configs: CliBanchoBackgroundServiceConfigs,
 
+    #[command(flatten)]
+    pub beatmap_mirror: CliBeatmapMirrorConfigs,
+
+    #[command(flatten)]
+    pub registration: CliBanchoRegistrationConfigs,
+
+    #[command(flatten)]
+    pub menu: CliBanchoMenuConfigs,
+
+    #[command(flatten)]
+    pub beatmap_files: CliBeatmapFilesConfigs,
+
     #[command(flatten)]
     pub chat_background_service_configs: CliChatBackgroundServiceConfigs,
 
@@ -64,6 +85,9 @@ pub struct BanchoStandaloneConfig {
     #[command(flatten)]
     pub chat_snapshot: CliChatServiceSnapshotConfigs,
 
+    #[command(flatten)]
+    pub chat_filters: CliChatFilterConfigs,
+
     #[command(flatten)]
     pub bancho_state_snapshot: CliBanchoStateServiceSnapshotConfigs,
 }
@@ -76,6 +100,11 @@ pub struct App {
     pub signature_service: DynSignatureService,
     pub bancho_state_service: DynBanchoStateService,
     pub users_repository: DynUsersRepository,
+    pub beatmaps_repository: DynBeatmapsRepository,
+    pub favourites_repository: DynFavouritesRepository,
+    pub comments_repository: DynCommentsRepository,
+    pub error_reports_repository: DynErrorReportsRepository,
+    pub scores_repository: DynScoresRepository,
     pub password_service: DynPasswordService,
     pub geoip_service: DynGeoipService,
     pub chat_service: DynChatService,
@@ -122,6 +151,22 @@ impl App {
         let users_repository =
             UsersRepositoryImpl::new(peace_db_conn.clone()).into_service();
 
+        let beatmaps_repository =
+            BeatmapsRepositoryImpl::new(peace_db_conn.clone()).into_service();
+
+        let favourites_repository =
+            FavouritesRepositoryImpl::new(peace_db_conn.clone()).into_service();
+
+        let comments_repository =
+            CommentsRepositoryImpl::new(peace_db_conn.clone()).into_service();
+
+        let error_reports_repository =
+            ErrorReportsRepositoryImpl::new(peace_db_conn.clone())
+                .into_service();
+
+        let scores_repository =
+            ScoresRepositoryImpl::new(peace_db_conn.clone()).into_service();
+
         let password_service = PasswordServiceImpl::default();
         let password_cache_store = password_service.cache_store().clone();
         let password_service = password_service.into_service();
@@ -136,6 +181,8 @@ impl App {
         let chat_service = ChatServiceSnapshotLoader::load(
             &cfg.chat_snapshot,
             users_repository.clone(),
+            ChatRepositoryImpl::new(peace_db_conn.clone()).into_service(),
+            ChatFilters::with_cfg(&cfg.chat_filters),
         )
         .await
         .into_service();
@@ -187,6 +234,16 @@ impl App {
             bancho_background_service.clone(),
             geoip_service.clone(),
             chat_service.clone(),
+            beatmaps_repository.clone(),
+            favourites_repository.clone(),
+            comments_repository.clone(),
+            error_reports_repository.clone(),
+            scores_repository.clone(),
+            BeatmapMirror::with_cfg(&cfg.beatmap_mirror),
+            RegistrationGuard::with_cfg(&cfg.registration),
+            ReloadableMenuContent::with_cfg(&cfg.menu),
+            BeatmapFileStorage::with_cfg(&cfg.beatmap_files),
+            cfg.menu.check_updates_manifest.to_owned(),
         )
         .into_service();
 
@@ -208,6 +265,11 @@ impl App {
             signature_service,
             bancho_state_service,
             users_repository,
+            beatmaps_repository,
+            favourites_repository,
+            comments_repository,
+            error_reports_repository,
+            scores_repository,
             password_service,
             geoip_service,
             chat_service,
@@ -244,6 +306,7 @@ impl WebApplication for App {
 
         if self.cfg.debug_endpoints {
             router = router.merge(BanchoDebugRouter::new_router(
+                self.bancho_service.clone(),
                 self.bancho_state_service.clone(),
             ))
         }
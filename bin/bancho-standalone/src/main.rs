@@ -8,6 +8,7 @@ extern crate peace_api;
 pub mod app;
 pub use app::*;
 
+use core_chat::FLUSH_BATCH_SIZE;
 use peace_snapshot::SnapshotConfig;
 
 pub async fn run(
@@ -19,6 +20,12 @@ pub async fn run(
     // Start serving the HTTP(s) server with the `App` instance.
     peace_api::http::serve(app.clone()).await;
 
+    // Buffered chat messages would be lost on exit
+    match app.chat_service.message_writer().flush(FLUSH_BATCH_SIZE).await {
+        Ok(written) => info!("Chat messages written on shutdown: {written}"),
+        Err(err) => error!("Failed to write chat messages on shutdown: {err}"),
+    }
+
     if cfg.chat_snapshot.should_save_snapshot() {
         let _ = app
             .chat_service
@@ -204,6 +204,18 @@ impl bancho_state_rpc_server::BanchoStateRpc for BanchoStateRpcImpl {
         Ok(Response::new(res))
     }
 
+    async fn update_only_friend_pm_allowed(
+        &self,
+        request: Request<UpdateOnlyFriendPmAllowedRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res = self
+            .bancho_state_service
+            .update_only_friend_pm_allowed(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
     async fn update_user_bancho_status(
         &self,
         request: Request<UpdateUserBanchoStatusRequest>,
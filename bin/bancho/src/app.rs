@@ -9,6 +9,7 @@ use core_geoip::{
     DynGeoipService, GeoipRpcConfig, GeoipServiceBuilder, GeoipServiceImpl,
     GeoipServiceRemote,
 };
+use infra_beatmaps::{BeatmapFileStorage, CliBeatmapFilesConfigs};
 use infra_services::{FromRpcClient, IntoService};
 use pb_bancho::{bancho_rpc_server::BanchoRpcServer, BANCHO_DESCRIPTOR_SET};
 use pb_bancho_state::bancho_state_rpc_client::BanchoStateRpcClient;
@@ -17,7 +18,14 @@ use peace_db::{
     peace::{Peace, PeaceDbConfig},
     DbConfig, DbConnection,
 };
-use peace_repositories::users::{DynUsersRepository, UsersRepositoryImpl};
+use peace_repositories::{
+    beatmaps::{BeatmapsRepositoryImpl, DynBeatmapsRepository},
+    comments::{CommentsRepositoryImpl, DynCommentsRepository},
+    error_reports::{DynErrorReportsRepository, ErrorReportsRepositoryImpl},
+    favourites::{DynFavouritesRepository, FavouritesRepositoryImpl},
+    scores::{DynScoresRepository, ScoresRepositoryImpl},
+    users::{DynUsersRepository, UsersRepositoryImpl},
+};
 use peace_rpc::{
     interceptor::client_ip, RpcApplication, RpcClientConfig, RpcFrameConfig,
 };
@@ -53,6 +61,18 @@ pub struct BanchoConfig {
     #[command(flatten)]
     pub bancho_background_service_configs: CliBanchoBackgroundServiceConfigs,
 
+    #[command(flatten)]
+    pub beatmap_mirror: CliBeatmapMirrorConfigs,
+
+    #[command(flatten)]
+    pub registration: CliBanchoRegistrationConfigs,
+
+    #[command(flatten)]
+    pub menu: CliBanchoMenuConfigs,
+
+    #[command(flatten)]
+    pub beatmap_files: CliBeatmapFilesConfigs,
+
     #[arg(long, short = 'P')]
     pub geo_db_path: Option<String>,
 }
@@ -65,6 +85,11 @@ pub struct App {
     pub chat_rpc_client: ChatRpcClient<Channel>,
     pub geoip_service: DynGeoipService,
     pub users_repository: DynUsersRepository,
+    pub beatmaps_repository: DynBeatmapsRepository,
+    pub favourites_repository: DynFavouritesRepository,
+    pub comments_repository: DynCommentsRepository,
+    pub error_reports_repository: DynErrorReportsRepository,
+    pub scores_repository: DynScoresRepository,
     pub bancho_state_service: DynBanchoStateService,
     pub chat_service: DynChatService,
     pub password_service: DynPasswordService,
@@ -96,6 +121,22 @@ impl App {
         let users_repository =
             UsersRepositoryImpl::new(peace_db_conn.clone()).into_service();
 
+        let beatmaps_repository =
+            BeatmapsRepositoryImpl::new(peace_db_conn.clone()).into_service();
+
+        let favourites_repository =
+            FavouritesRepositoryImpl::new(peace_db_conn.clone()).into_service();
+
+        let comments_repository =
+            CommentsRepositoryImpl::new(peace_db_conn.clone()).into_service();
+
+        let error_reports_repository =
+            ErrorReportsRepositoryImpl::new(peace_db_conn.clone())
+                .into_service();
+
+        let scores_repository =
+            ScoresRepositoryImpl::new(peace_db_conn.clone()).into_service();
+
         let bancho_state_service = BanchoStateServiceRemote::from_client(
             bancho_state_rpc_client.clone(),
         )
@@ -129,6 +170,16 @@ impl App {
             bancho_background_service.clone(),
             geoip_service.clone(),
             chat_service.clone(),
+            beatmaps_repository.clone(),
+            favourites_repository.clone(),
+            comments_repository.clone(),
+            error_reports_repository.clone(),
+            scores_repository.clone(),
+            BeatmapMirror::with_cfg(&cfg.beatmap_mirror),
+            RegistrationGuard::with_cfg(&cfg.registration),
+            ReloadableMenuContent::with_cfg(&cfg.menu),
+            BeatmapFileStorage::with_cfg(&cfg.beatmap_files),
+            cfg.menu.check_updates_manifest.to_owned(),
         )
         .into_service();
 
@@ -141,6 +192,11 @@ impl App {
             chat_rpc_client,
             geoip_service,
             users_repository,
+            beatmaps_repository,
+            favourites_repository,
+            comments_repository,
+            error_reports_repository,
+            scores_repository,
             bancho_state_service,
             chat_service,
             password_service,
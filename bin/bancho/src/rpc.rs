@@ -71,6 +71,20 @@ impl bancho_rpc_server::BanchoRpc for BanchoRpcImpl {
         Ok(Response::new(res))
     }
 
+    async fn client_register(
+        &self,
+        request: Request<ClientRegisterRequest>,
+    ) -> Result<Response<ClientRegisterResponse>, Status> {
+        let client_ip = ClientIp::from_request(&request)?;
+
+        let res = self
+            .bancho_service
+            .client_register(client_ip.into(), request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
     async fn request_status_update(
         &self,
         raw_user_query: Request<RawUserQuery>,
@@ -163,6 +177,16 @@ impl bancho_rpc_server::BanchoRpc for BanchoRpcImpl {
         Ok(Response::new(res))
     }
 
+    async fn spectate_start(
+        &self,
+        request: Request<SpectateStartRequest>,
+    ) -> Result<Response<HandleCompleted>, Status> {
+        let res =
+            self.bancho_service.spectate_start(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
     async fn spectate_stop(
         &self,
         raw_user_query: Request<RawUserQuery>,
@@ -210,4 +234,180 @@ impl bancho_rpc_server::BanchoRpc for BanchoRpcImpl {
 
         Ok(Response::new(res))
     }
+
+    async fn match_invite(
+        &self,
+        request: Request<MatchInviteRequest>,
+    ) -> Result<Response<HandleCompleted>, Status> {
+        let res =
+            self.bancho_service.match_invite(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_search(
+        &self,
+        request: Request<OsuSearchRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res = self.bancho_service.osu_search(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_search_set(
+        &self,
+        request: Request<OsuSearchSetRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res =
+            self.bancho_service.osu_search_set(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_get_beatmap_info(
+        &self,
+        request: Request<OsuGetBeatmapInfoRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res = self
+            .bancho_service
+            .osu_getbeatmapinfo(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_get_favourites(
+        &self,
+        request: Request<OsuGetFavouritesRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res =
+            self.bancho_service.osu_getfavourites(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_add_favourite(
+        &self,
+        request: Request<OsuAddFavouriteRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res =
+            self.bancho_service.osu_addfavourite(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_rate(
+        &self,
+        request: Request<OsuRateRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res = self.bancho_service.osu_rate(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_mark_as_read(
+        &self,
+        request: Request<OsuMarkAsReadRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res =
+            self.bancho_service.osu_mark_as_read(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_comment(
+        &self,
+        request: Request<OsuCommentRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res = self.bancho_service.osu_comment(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_error(
+        &self,
+        request: Request<OsuErrorRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res = self.bancho_service.osu_error(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_get_seasonal(
+        &self,
+        request: Request<OsuGetSeasonalRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res =
+            self.bancho_service.osu_get_seasonal(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_get_beatmap_file(
+        &self,
+        request: Request<OsuGetBeatmapFileRequest>,
+    ) -> Result<Response<BeatmapFile>, Status> {
+        let res = self
+            .bancho_service
+            .osu_get_beatmap_file(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn osu_check_updates(
+        &self,
+        request: Request<OsuCheckUpdatesRequest>,
+    ) -> Result<Response<HttpResponse>, Status> {
+        let res =
+            self.bancho_service.osu_check_updates(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn get_difficulty_rating(
+        &self,
+        request: Request<DifficultyRatingRequest>,
+    ) -> Result<Response<DifficultyRating>, Status> {
+        let res = self
+            .bancho_service
+            .get_difficulty_rating(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn get_error_report_groups(
+        &self,
+        request: Request<GetErrorReportGroupsRequest>,
+    ) -> Result<Response<ErrorReportGroups>, Status> {
+        let res = self
+            .bancho_service
+            .get_error_report_groups(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn get_menu_content(
+        &self,
+        request: Request<GetMenuContentRequest>,
+    ) -> Result<Response<MenuContent>, Status> {
+        let res =
+            self.bancho_service.get_menu_content(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn update_menu_content(
+        &self,
+        request: Request<MenuContent>,
+    ) -> Result<Response<MenuContent>, Status> {
+        let res = self
+            .bancho_service
+            .update_menu_content(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
 }
@@ -6,7 +6,10 @@ use peace_db::{
     peace::{Peace, PeaceDbConfig},
     DbConfig, DbConnection,
 };
-use peace_repositories::users::{DynUsersRepository, UsersRepositoryImpl};
+use peace_repositories::{
+    chat::ChatRepositoryImpl,
+    users::{DynUsersRepository, UsersRepositoryImpl},
+};
 use peace_rpc::{RpcApplication, RpcFrameConfig};
 use peace_runtime::cfg::RuntimeConfig;
 use std::{net::SocketAddr, sync::Arc};
@@ -33,6 +36,9 @@ pub struct ChatServiceConfig {
 
     #[command(flatten)]
     pub chat_snapshot: CliChatServiceSnapshotConfigs,
+
+    #[command(flatten)]
+    pub chat_filters: CliChatFilterConfigs,
 }
 
 #[derive(Clone)]
@@ -60,6 +66,8 @@ impl App {
         let chat_service = ChatServiceSnapshotLoader::load(
             &cfg.chat_snapshot,
             users_repository.clone(),
+            ChatRepositoryImpl::new(peace_db_conn.clone()).into_service(),
+            ChatFilters::with_cfg(&cfg.chat_filters),
         )
         .await
         .into_service();
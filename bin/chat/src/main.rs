@@ -11,6 +11,7 @@ pub mod rpc;
 pub use app::*;
 pub use rpc::*;
 
+use core_chat::FLUSH_BATCH_SIZE;
 use peace_snapshot::SnapshotConfig;
 
 pub async fn run(
@@ -22,6 +23,12 @@ pub async fn run(
     // Start serving the RPC server with the `App` instance.
     peace_rpc::server::serve(app.clone()).await;
 
+    // Buffered chat messages would be lost on exit
+    match app.chat_service.message_writer().flush(FLUSH_BATCH_SIZE).await {
+        Ok(written) => info!("Chat messages written on shutdown: {written}"),
+        Err(err) => error!("Failed to write chat messages on shutdown: {err}"),
+    }
+
     if cfg.chat_snapshot.should_save_snapshot() {
         let _ = app
             .chat_service
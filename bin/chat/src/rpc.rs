@@ -1,5 +1,7 @@
 use core_chat::{ChatError, DynChatService};
-use pb_bancho_state::{BanchoPackets, RawUserQuery};
+use pb_bancho_state::{
+    BanchoPackets, RawUserQuery, UpdateOnlyFriendPmAllowedRequest,
+};
 use pb_base::ExecSuccess;
 use pb_chat::*;
 use tonic::{Request, Response, Status};
@@ -40,6 +42,48 @@ impl chat_rpc_server::ChatRpc for ChatRpcImpl {
         Ok(Response::new(res))
     }
 
+    async fn update_only_friend_pm_allowed(
+        &self,
+        request: Request<UpdateOnlyFriendPmAllowedRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res = self
+            .chat_service
+            .update_only_friend_pm_allowed(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn block_user(
+        &self,
+        request: Request<BlockUserRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res = self.chat_service.block_user(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn unblock_user(
+        &self,
+        request: Request<UnblockUserRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res = self.chat_service.unblock_user(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn get_blocked_users(
+        &self,
+        request: Request<RawUserQuery>,
+    ) -> Result<Response<BlockedUsers>, Status> {
+        let res = self
+            .chat_service
+            .get_blocked_users(request.into_inner().into_user_query()?)
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
     async fn get_public_channels(
         &self,
         _: Request<GetPublicChannelsRequest>,
@@ -58,6 +102,156 @@ impl chat_rpc_server::ChatRpc for ChatRpcImpl {
         Ok(Response::new(res))
     }
 
+    async fn mark_messages_read(
+        &self,
+        request: Request<MarkMessagesReadRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res =
+            self.chat_service.mark_messages_read(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn kick_channel_user(
+        &self,
+        request: Request<KickChannelUserRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res =
+            self.chat_service.kick_channel_user(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn mute_channel_user(
+        &self,
+        request: Request<MuteChannelUserRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res =
+            self.chat_service.mute_channel_user(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn create_private_channel(
+        &self,
+        request: Request<CreatePrivateChannelRequest>,
+    ) -> Result<Response<ChannelInfo>, Status> {
+        let res = self
+            .chat_service
+            .create_private_channel(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn create_group_channel(
+        &self,
+        request: Request<CreateGroupChannelRequest>,
+    ) -> Result<Response<ChannelInfo>, Status> {
+        let res = self
+            .chat_service
+            .create_group_channel(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn invite_channel_user(
+        &self,
+        request: Request<InviteChannelUserRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res =
+            self.chat_service.invite_channel_user(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn remove_channel_user(
+        &self,
+        request: Request<RemoveChannelUserRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res =
+            self.chat_service.remove_channel_user(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn transfer_channel_ownership(
+        &self,
+        request: Request<TransferChannelOwnershipRequest>,
+    ) -> Result<Response<ChannelInfo>, Status> {
+        let res = self
+            .chat_service
+            .transfer_channel_ownership(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn archive_channel(
+        &self,
+        request: Request<ArchiveChannelRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res =
+            self.chat_service.archive_channel(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn join_spectator_channel(
+        &self,
+        request: Request<JoinSpectatorChannelRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res = self
+            .chat_service
+            .join_spectator_channel(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn leave_spectator_channel(
+        &self,
+        request: Request<LeaveSpectatorChannelRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res = self
+            .chat_service
+            .leave_spectator_channel(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn create_channel(
+        &self,
+        request: Request<CreateChannelRequest>,
+    ) -> Result<Response<ChannelInfo>, Status> {
+        let res =
+            self.chat_service.create_channel(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn update_channel(
+        &self,
+        request: Request<UpdateChannelRequest>,
+    ) -> Result<Response<ChannelInfo>, Status> {
+        let res =
+            self.chat_service.update_channel(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn delete_channel(
+        &self,
+        request: Request<DeleteChannelRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res =
+            self.chat_service.delete_channel(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
     async fn join_channel(
         &self,
         request: Request<JoinChannelRequest>,
@@ -85,6 +279,50 @@ impl chat_rpc_server::ChatRpc for ChatRpcImpl {
         Ok(Response::new(res))
     }
 
+    async fn get_message_history(
+        &self,
+        request: Request<GetMessageHistoryRequest>,
+    ) -> Result<Response<MessageHistory>, Status> {
+        let res =
+            self.chat_service.get_message_history(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn moderate_message(
+        &self,
+        request: Request<ModerateMessageRequest>,
+    ) -> Result<Response<ModerateMessageResponse>, Status> {
+        let res =
+            self.chat_service.moderate_message(request.into_inner()).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn get_chat_filter_rules(
+        &self,
+        request: Request<GetChatFilterRulesRequest>,
+    ) -> Result<Response<ChatFilterRules>, Status> {
+        let res = self
+            .chat_service
+            .get_chat_filter_rules(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn update_chat_filter_rules(
+        &self,
+        request: Request<ChatFilterRules>,
+    ) -> Result<Response<ChatFilterRules>, Status> {
+        let res = self
+            .chat_service
+            .update_chat_filter_rules(request.into_inner())
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
     async fn pull_chat_packets(
         &self,
         request: Request<RawUserQuery>,
@@ -4,6 +4,10 @@ use domain_users::{
     CreateUser, Email, Password, UsernameAscii, UsernameUnicode,
 };
 use dotenvy::dotenv;
+use pb_performance::{
+    performance_rpc_client::PerformanceRpcClient, RecalculateUserRequest,
+    StartRecalculationRequest, StopRecalculationRequest,
+};
 use peace_db::{Database, DbConnection};
 use peace_repositories::users::{UsersRepository, UsersRepositoryImpl};
 
@@ -53,6 +57,56 @@ pub enum Commands {
         #[arg(long)]
         md5_password: Option<String>,
     },
+    #[clap(
+        about = "[peace] Recalculate the pp and stats of one user, or start \
+                 recalculating every user in the performance service"
+    )]
+    RecalculatePerformance {
+        #[arg(long, default_value = "http://127.0.0.1:5016")]
+        performance_rpc: String,
+
+        /// Every user if not set
+        #[arg(long)]
+        user_id: Option<i32>,
+
+        /// Bancho game mode, e.g. `4` for osu!standard relax, every game
+        /// mode if not set
+        #[arg(long)]
+        mode: Option<i32>,
+
+        /// Calculate the pp of every score again first
+        #[arg(long)]
+        recalculate_scores: bool,
+
+        /// Continue a stopped recalculation of every user
+        #[arg(long)]
+        resume: bool,
+    },
+    #[clap(about = "[peace] Rebuild the leaderboards of one user, or start \
+                 rebuilding the leaderboards of every user in the performance \
+                 service")]
+    RebuildLeaderboards {
+        #[arg(long, default_value = "http://127.0.0.1:5016")]
+        performance_rpc: String,
+
+        /// Every user if not set
+        #[arg(long)]
+        user_id: Option<i32>,
+
+        /// Bancho game mode, e.g. `4` for osu!standard relax, every game
+        /// mode if not set
+        #[arg(long)]
+        mode: Option<i32>,
+
+        /// Continue a stopped rebuild of every user
+        #[arg(long)]
+        resume: bool,
+    },
+    #[clap(about = "[peace] Stop recalculating every user, it can be resumed")]
+    StopPerformanceRecalculation {
+        #[arg(long, default_value = "http://127.0.0.1:5016")]
+        performance_rpc: String,
+    },
 }
 
 #[tokio::main]
@@ -154,5 +208,103 @@ async fn main() {
             .unwrap();
             println!("Success")
         },
+        Commands::RecalculatePerformance {
+            performance_rpc,
+            user_id,
+            mode,
+            recalculate_scores,
+            resume,
+        } => {
+            let mut client =
+                PerformanceRpcClient::connect(performance_rpc).await.unwrap();
+
+            match user_id {
+                Some(user_id) => {
+                    println!("Recalculating user <{user_id}>...");
+                    let res = client
+                        .recalculate_user(RecalculateUserRequest {
+                            user_id,
+                            mode,
+                            recalculate_scores,
+                            rebuild_leaderboards: false,
+                        })
+                        .await
+                        .unwrap()
+                        .into_inner();
+
+                    for performance in res.performances {
+                        println!(
+                            "mode {}: {:.2}pp, {:.2}% accuracy, {} ranked \
+                             score",
+                            performance.mode,
+                            performance.pp,
+                            performance.accuracy,
+                            performance.ranked_score
+                        );
+                    }
+                },
+                None => {
+                    println!("Starting recalculation of every user...");
+                    client
+                        .start_recalculation(StartRecalculationRequest {
+                            mode,
+                            recalculate_scores,
+                            resume,
+                            rebuild_leaderboards: false,
+                        })
+                        .await
+                        .unwrap();
+                },
+            }
+            println!("Success")
+        },
+        Commands::RebuildLeaderboards {
+            performance_rpc,
+            user_id,
+            mode,
+            resume,
+        } => {
+            let mut client =
+                PerformanceRpcClient::connect(performance_rpc).await.unwrap();
+
+            match user_id {
+                Some(user_id) => {
+                    println!("Rebuilding leaderboards of user <{user_id}>...");
+                    client
+                        .recalculate_user(RecalculateUserRequest {
+                            user_id,
+                            mode,
+                            recalculate_scores: false,
+                            rebuild_leaderboards: true,
+                        })
+                        .await
+                        .unwrap();
+                },
+                None => {
+                    println!("Starting rebuild of every user...");
+                    client
+                        .start_recalculation(StartRecalculationRequest {
+                            mode,
+                            recalculate_scores: false,
+                            resume,
+                            rebuild_leaderboards: true,
+                        })
+                        .await
+                        .unwrap();
+                },
+            }
+            println!("Success")
+        },
+        Commands::StopPerformanceRecalculation { performance_rpc } => {
+            let mut client =
+                PerformanceRpcClient::connect(performance_rpc).await.unwrap();
+
+            println!("Stopping recalculation...");
+            client
+                .stop_recalculation(StopRecalculationRequest {})
+                .await
+                .unwrap();
+            println!("Success")
+        },
     }
 }
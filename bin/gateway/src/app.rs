@@ -122,6 +122,7 @@ impl WebApplication for App {
 
         if self.cfg.debug_endpoints {
             router = router.merge(BanchoDebugRouter::new_router(
+                self.bancho_service.clone(),
                 self.bancho_state_service.clone(),
             ))
         }
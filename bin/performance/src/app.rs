@@ -0,0 +1,101 @@
+use crate::PerformanceRpcImpl;
+use clap_serde_derive::ClapSerde;
+use core_performance::*;
+use infra_beatmaps::{BeatmapFileStorage, CliBeatmapFilesConfigs};
+use infra_services::IntoService;
+use pb_performance::{
+    performance_rpc_server::PerformanceRpcServer, PERFORMANCE_DESCRIPTOR_SET,
+};
+use peace_db::{peace::PeaceDbConfig, DbConfig};
+use peace_repositories::{
+    beatmaps::BeatmapsRepositoryImpl, leaderboards::LeaderboardsRepositoryImpl,
+    scores::ScoresRepositoryImpl, user_stats::UserStatsRepositoryImpl,
+    users::UsersRepositoryImpl,
+};
+use peace_rpc::{RpcApplication, RpcFrameConfig};
+use peace_runtime::cfg::RuntimeConfig;
+use std::{net::SocketAddr, sync::Arc};
+use tonic::{
+    async_trait,
+    transport::{server::Router, Server},
+};
+
+/// PEACE Performance service
+#[peace_config]
+#[command(
+    name = "performance",
+    author,
+    version,
+    about,
+    propagate_version = true
+)]
+pub struct PerformanceConfig {
+    #[command(flatten)]
+    pub runtime_cfg: RuntimeConfig,
+
+    #[command(flatten)]
+    pub frame_cfg: RpcFrameConfig,
+
+    #[command(flatten)]
+    pub peace_db: PeaceDbConfig,
+
+    #[command(flatten)]
+    pub beatmap_files: CliBeatmapFilesConfigs,
+
+    #[command(flatten)]
+    pub recalculation: CliPerformanceRecalculationConfigs,
+}
+
+#[derive(Clone)]
+pub struct App {
+    pub cfg: Arc<PerformanceConfig>,
+    pub performance_service: DynPerformanceService,
+    pub performance_rpc: PerformanceRpcImpl,
+}
+
+impl App {
+    pub async fn initialize(cfg: Arc<PerformanceConfig>) -> Self {
+        let peace_db_conn = cfg
+            .peace_db
+            .connect()
+            .await
+            .expect("failed to connect peace db, please check.");
+
+        let performance_service = PerformanceServiceImpl::new(
+            ScoresRepositoryImpl::new(peace_db_conn.clone()).into_service(),
+            BeatmapsRepositoryImpl::new(peace_db_conn.clone()).into_service(),
+            UsersRepositoryImpl::new(peace_db_conn.clone()).into_service(),
+            UserStatsRepositoryImpl::new(peace_db_conn.clone()).into_service(),
+            LeaderboardsRepositoryImpl::new(peace_db_conn).into_service(),
+            BeatmapFileStorage::with_cfg(&cfg.beatmap_files),
+            RecalculationConfig::with_cfg(&cfg.recalculation),
+        )
+        .into_service();
+
+        let performance_rpc =
+            PerformanceRpcImpl::new(performance_service.clone());
+
+        Self { cfg, performance_service, performance_rpc }
+    }
+}
+
+#[async_trait]
+impl RpcApplication for App {
+    fn frame_cfg(&self) -> &RpcFrameConfig {
+        &self.cfg.frame_cfg
+    }
+
+    fn default_listen_addr(&self) -> Option<SocketAddr> {
+        Some("127.0.0.1:5016".parse().unwrap())
+    }
+
+    fn service_descriptors(&self) -> Option<&[&[u8]]> {
+        Some(&[PERFORMANCE_DESCRIPTOR_SET])
+    }
+
+    async fn service(&self, mut configured_server: Server) -> Router {
+        configured_server.add_service(PerformanceRpcServer::new(
+            self.performance_rpc.clone(),
+        ))
+    }
+}
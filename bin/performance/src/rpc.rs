@@ -0,0 +1,114 @@
+use core_performance::{error::PerformanceError, DynPerformanceService};
+use domain_bancho::GameMode;
+use num_traits::FromPrimitive;
+use pb_base::ExecSuccess;
+use pb_performance::{
+    performance_rpc_server, CalculateScorePpRequest, RecalculateUserRequest,
+    ScorePp, StartRecalculationRequest, StopRecalculationRequest,
+    SubmittedScorePerformance, UserPerformances,
+};
+use tonic::{Request, Response, Status};
+
+#[inline]
+fn game_mode(mode: i32) -> Result<GameMode, PerformanceError> {
+    GameMode::from_i32(mode).ok_or(PerformanceError::InvalidMode(mode))
+}
+
+#[derive(Clone)]
+pub struct PerformanceRpcImpl {
+    pub performance_service: DynPerformanceService,
+}
+
+impl PerformanceRpcImpl {
+    pub fn new(performance_service: DynPerformanceService) -> Self {
+        Self { performance_service }
+    }
+}
+
+#[tonic::async_trait]
+impl performance_rpc_server::PerformanceRpc for PerformanceRpcImpl {
+    async fn calculate_score_pp(
+        &self,
+        request: Request<CalculateScorePpRequest>,
+    ) -> Result<Response<ScorePp>, Status> {
+        let CalculateScorePpRequest { score_id, mode } = request.into_inner();
+
+        let res = self
+            .performance_service
+            .calculate_score_pp(game_mode(mode)?, score_id)
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn process_submitted_score(
+        &self,
+        request: Request<CalculateScorePpRequest>,
+    ) -> Result<Response<SubmittedScorePerformance>, Status> {
+        let CalculateScorePpRequest { score_id, mode } = request.into_inner();
+
+        let res = self
+            .performance_service
+            .process_submitted_score(game_mode(mode)?, score_id)
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn recalculate_user(
+        &self,
+        request: Request<RecalculateUserRequest>,
+    ) -> Result<Response<UserPerformances>, Status> {
+        let RecalculateUserRequest {
+            user_id,
+            mode,
+            recalculate_scores,
+            rebuild_leaderboards,
+        } = request.into_inner();
+
+        let performances = self
+            .performance_service
+            .recalculate_user(
+                user_id,
+                mode.map(game_mode).transpose()?,
+                recalculate_scores,
+                rebuild_leaderboards,
+            )
+            .await?;
+
+        Ok(Response::new(UserPerformances { performances }))
+    }
+
+    async fn start_recalculation(
+        &self,
+        request: Request<StartRecalculationRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let StartRecalculationRequest {
+            mode,
+            recalculate_scores,
+            resume,
+            rebuild_leaderboards,
+        } = request.into_inner();
+
+        let res = self
+            .performance_service
+            .start_recalculation(
+                mode.map(game_mode).transpose()?,
+                recalculate_scores,
+                rebuild_leaderboards,
+                resume,
+            )
+            .await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn stop_recalculation(
+        &self,
+        _: Request<StopRecalculationRequest>,
+    ) -> Result<Response<ExecSuccess>, Status> {
+        let res = self.performance_service.stop_recalculation().await?;
+
+        Ok(Response::new(res))
+    }
+}
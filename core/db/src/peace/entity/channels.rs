@@ -15,6 +15,8 @@ pub struct Model {
     pub icon: Option<String>,
     pub auto_join: bool,
     pub creator_id: Option<i64>,
+    pub owner_id: Option<i32>,
+    pub archived_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -10,6 +10,7 @@ pub struct Model {
     pub beatmap_id: i32,
     #[sea_orm(primary_key, auto_increment = false)]
     pub ranking_type: RankingType,
+    #[sea_orm(primary_key, auto_increment = false)]
     pub user_id: i32,
     pub score_id: i64,
 }
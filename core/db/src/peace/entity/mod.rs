@@ -9,6 +9,8 @@ pub mod channel_privileges;
 pub mod channel_users;
 pub mod channels;
 pub mod chat_messages;
+pub mod comments;
+pub mod error_reports;
 pub mod favourite_beatmaps;
 pub mod followers;
 pub mod leaderboard_fruits;
@@ -37,6 +39,8 @@ pub mod scores_standard_relax;
 pub mod scores_taiko;
 pub mod scores_taiko_relax;
 pub mod sea_orm_active_enums;
+pub mod unread_messages;
+pub mod user_blocks;
 pub mod user_pp_fruits;
 pub mod user_pp_fruits_relax;
 pub mod user_pp_mania;
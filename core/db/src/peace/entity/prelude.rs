@@ -7,6 +7,8 @@ pub use super::channel_privileges::Entity as ChannelPrivileges;
 pub use super::channel_users::Entity as ChannelUsers;
 pub use super::channels::Entity as Channels;
 pub use super::chat_messages::Entity as ChatMessages;
+pub use super::comments::Entity as Comments;
+pub use super::error_reports::Entity as ErrorReports;
 pub use super::favourite_beatmaps::Entity as FavouriteBeatmaps;
 pub use super::followers::Entity as Followers;
 pub use super::leaderboard_fruits::Entity as LeaderboardFruits;
@@ -34,6 +36,8 @@ pub use super::scores_standard_autopilot::Entity as ScoresStandardAutopilot;
 pub use super::scores_standard_relax::Entity as ScoresStandardRelax;
 pub use super::scores_taiko::Entity as ScoresTaiko;
 pub use super::scores_taiko_relax::Entity as ScoresTaikoRelax;
+pub use super::unread_messages::Entity as UnreadMessages;
+pub use super::user_blocks::Entity as UserBlocks;
 pub use super::user_pp_fruits::Entity as UserPpFruits;
 pub use super::user_pp_fruits_relax::Entity as UserPpFruitsRelax;
 pub use super::user_pp_mania::Entity as UserPpMania;
@@ -33,6 +33,18 @@ pub enum ChannelType {
     Spectaor,
 }
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "comment_target")]
+pub enum CommentTarget {
+    #[sea_orm(string_value = "beatmap")]
+    Beatmap,
+    #[sea_orm(string_value = "beatmapset")]
+    Beatmapset,
+    #[sea_orm(string_value = "replay")]
+    Replay,
+    #[sea_orm(string_value = "song")]
+    Song,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "game_mode")]
 pub enum GameMode {
     #[sea_orm(string_value = "Fruits")]
@@ -29,6 +29,10 @@ pub enum Relation {
     BanchoClientHardwareRecords,
     #[sea_orm(has_many = "super::chat_messages::Entity")]
     ChatMessages,
+    #[sea_orm(has_many = "super::comments::Entity")]
+    Comments,
+    #[sea_orm(has_many = "super::error_reports::Entity")]
+    ErrorReports,
     #[sea_orm(has_many = "super::favourite_beatmaps::Entity")]
     FavouriteBeatmaps,
     #[sea_orm(has_many = "super::leaderboard_fruits::Entity")]
@@ -113,6 +117,18 @@ impl Related<super::chat_messages::Entity> for Entity {
     }
 }
 
+impl Related<super::comments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Comments.def()
+    }
+}
+
+impl Related<super::error_reports::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ErrorReports.def()
+    }
+}
+
 impl Related<super::favourite_beatmaps::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::FavouriteBeatmaps.def()
@@ -10,6 +10,13 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(versions::init_tables::Migration),
             Box::new(versions::create_seed_data::Migration),
+            Box::new(versions::create_comments::Migration),
+            Box::new(versions::create_error_reports::Migration),
+            Box::new(versions::update_leaderboard_primary_keys::Migration),
+            Box::new(versions::create_default_channels::Migration),
+            Box::new(versions::create_unread_messages::Migration),
+            Box::new(versions::add_channel_owners::Migration),
+            Box::new(versions::create_user_blocks::Migration),
         ]
     }
 }
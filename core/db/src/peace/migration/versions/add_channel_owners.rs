@@ -0,0 +1,102 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for stmt in channel_owners::add_columns() {
+            manager.alter_table(stmt).await?;
+        }
+
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            manager
+                .create_foreign_key(channel_owners::create_foreign_key())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            manager
+                .drop_foreign_key(channel_owners::drop_foreign_key())
+                .await?;
+        }
+
+        for stmt in channel_owners::drop_columns() {
+            manager.alter_table(stmt).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Owners of the private and group channels, which are archived instead of
+/// deleted to keep their messages.
+pub mod channel_owners {
+    use sea_orm_migration::prelude::*;
+
+    use super::super::init_tables::{channels::Channels, users::Users};
+
+    const FOREIGN_KEY_OWNER_ID: &str = "FK_channels_owner_id";
+
+    #[derive(Iden)]
+    pub enum ChannelOwners {
+        OwnerId,
+        ArchivedAt,
+    }
+
+    // Sqlite alters one column per statement
+    pub fn add_columns() -> Vec<TableAlterStatement> {
+        vec![
+            Table::alter()
+                .table(Channels::Table)
+                .add_column_if_not_exists(
+                    ColumnDef::new(ChannelOwners::OwnerId).integer().null(),
+                )
+                .to_owned(),
+            Table::alter()
+                .table(Channels::Table)
+                .add_column_if_not_exists(
+                    ColumnDef::new(ChannelOwners::ArchivedAt)
+                        .timestamp_with_time_zone()
+                        .null(),
+                )
+                .to_owned(),
+        ]
+    }
+
+    pub fn drop_columns() -> Vec<TableAlterStatement> {
+        vec![
+            Table::alter()
+                .table(Channels::Table)
+                .drop_column(ChannelOwners::OwnerId)
+                .to_owned(),
+            Table::alter()
+                .table(Channels::Table)
+                .drop_column(ChannelOwners::ArchivedAt)
+                .to_owned(),
+        ]
+    }
+
+    pub fn create_foreign_key() -> ForeignKeyCreateStatement {
+        sea_query::ForeignKey::create()
+            .name(FOREIGN_KEY_OWNER_ID)
+            .from(Channels::Table, ChannelOwners::OwnerId)
+            .to(Users::Table, Users::Id)
+            .on_delete(ForeignKeyAction::SetNull)
+            .on_update(ForeignKeyAction::Cascade)
+            .to_owned()
+    }
+
+    pub fn drop_foreign_key() -> ForeignKeyDropStatement {
+        sea_query::ForeignKey::drop()
+            .name(FOREIGN_KEY_OWNER_ID)
+            .table(Channels::Table)
+            .to_owned()
+    }
+}
@@ -0,0 +1,184 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(Iden)]
+pub enum CommentTarget {
+    #[iden = "comment_target"]
+    Enum = -1,
+    #[iden = "beatmap"]
+    Beatmap = 0,
+    #[iden = "beatmapset"]
+    Beatmapset = 1,
+    #[iden = "replay"]
+    Replay = 2,
+    #[iden = "song"]
+    Song = 3,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DbBackend::Postgres {
+            manager
+                .create_type(
+                    extension::postgres::Type::create()
+                        .as_enum(CommentTarget::Enum)
+                        .values([
+                            CommentTarget::Beatmap,
+                            CommentTarget::Beatmapset,
+                            CommentTarget::Replay,
+                            CommentTarget::Song,
+                        ])
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager.create_table(comments::create()).await?;
+
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for stmt in comments::create_foreign_keys() {
+                manager.create_foreign_key(stmt).await?;
+            }
+        }
+
+        for stmt in comments::create_indexes() {
+            manager.create_index(stmt).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for stmt in comments::drop_foreign_keys() {
+                manager.drop_foreign_key(stmt).await?;
+            }
+        }
+
+        for stmt in comments::drop_indexes() {
+            manager.drop_index(stmt).await?;
+        }
+
+        manager.drop_table(comments::drop()).await?;
+
+        if manager.get_database_backend() == DbBackend::Postgres {
+            manager
+                .drop_type(
+                    extension::postgres::Type::drop()
+                        .name(CommentTarget::Enum)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+pub mod comments {
+    use sea_orm_migration::prelude::*;
+
+    use super::{super::init_tables::users::Users, CommentTarget};
+
+    const FOREIGN_KEY_USER_ID: &str = "FK_comments_user_id";
+    const INDEX_TARGET: &str = "IDX_comments_target";
+
+    #[derive(Iden)]
+    pub enum Comments {
+        Table,
+        Id,
+        UserId,
+        Target,
+        TargetId,
+        Time,
+        Comment,
+        Colour,
+        Privileges,
+        CreatedAt,
+    }
+
+    pub fn create() -> TableCreateStatement {
+        Table::create()
+            .table(Comments::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(Comments::Id)
+                    .big_integer()
+                    .primary_key()
+                    .auto_increment()
+                    .not_null(),
+            )
+            .col(ColumnDef::new(Comments::UserId).integer().not_null())
+            .col(
+                ColumnDef::new(Comments::Target)
+                    .enumeration(
+                        CommentTarget::Enum,
+                        [
+                            CommentTarget::Beatmap,
+                            CommentTarget::Beatmapset,
+                            CommentTarget::Replay,
+                            CommentTarget::Song,
+                        ],
+                    )
+                    .not_null(),
+            )
+            .col(ColumnDef::new(Comments::TargetId).big_integer().not_null())
+            .col(ColumnDef::new(Comments::Time).integer().not_null())
+            .col(ColumnDef::new(Comments::Comment).text().not_null())
+            .col(ColumnDef::new(Comments::Colour).string().string_len(6).null())
+            .col(
+                ColumnDef::new(Comments::Privileges)
+                    .integer()
+                    .default(1)
+                    .not_null(),
+            )
+            .col(
+                ColumnDef::new(Comments::CreatedAt)
+                    .timestamp_with_time_zone()
+                    .default(Expr::current_timestamp())
+                    .not_null(),
+            )
+            .to_owned()
+    }
+
+    pub fn drop() -> TableDropStatement {
+        Table::drop().table(Comments::Table).to_owned()
+    }
+
+    pub fn create_foreign_keys() -> Vec<ForeignKeyCreateStatement> {
+        vec![sea_query::ForeignKey::create()
+            .name(FOREIGN_KEY_USER_ID)
+            .from(Comments::Table, Comments::UserId)
+            .to(Users::Table, Users::Id)
+            .on_delete(ForeignKeyAction::Cascade)
+            .on_update(ForeignKeyAction::Cascade)
+            .to_owned()]
+    }
+
+    pub fn drop_foreign_keys() -> Vec<ForeignKeyDropStatement> {
+        vec![sea_query::ForeignKey::drop()
+            .name(FOREIGN_KEY_USER_ID)
+            .table(Comments::Table)
+            .to_owned()]
+    }
+
+    pub fn create_indexes() -> Vec<IndexCreateStatement> {
+        vec![sea_query::Index::create()
+            .name(INDEX_TARGET)
+            .table(Comments::Table)
+            .col(Comments::Target)
+            .col(Comments::TargetId)
+            .to_owned()]
+    }
+
+    pub fn drop_indexes() -> Vec<IndexDropStatement> {
+        vec![sea_query::Index::drop()
+            .table(Comments::Table)
+            .name(INDEX_TARGET)
+            .to_owned()]
+    }
+}
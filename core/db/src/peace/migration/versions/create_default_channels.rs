@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+use super::init_tables::{channels::Channels, ChannelType};
+
+/// `(id, name, description, auto_join)` of the public channels every server
+/// starts with, the ids the chat service used before channels were loaded
+/// from the database.
+const DEFAULT_CHANNELS: [(i64, &str, &str, bool); 2] = [
+    (0, "#osu", "default channel", true),
+    (1, "#peace", "peace channel", false),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let mut insert = Query::insert();
+        insert
+            .into_table(Channels::Table)
+            .columns([
+                Channels::Id,
+                Channels::ChannelType,
+                Channels::Name,
+                Channels::Description,
+                Channels::AutoJoin,
+            ])
+            .on_conflict(
+                OnConflict::column(Channels::Id).do_nothing().to_owned(),
+            );
+
+        for (id, name, description, auto_join) in DEFAULT_CHANNELS {
+            insert.values_panic([
+                id.into(),
+                Expr::val(ChannelType::Public.to_string())
+                    .as_enum(ChannelType::Enum),
+                name.into(),
+                description.into(),
+                auto_join.into(),
+            ]);
+        }
+
+        manager.exec_stmt(insert).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let delete = Query::delete()
+            .from_table(Channels::Table)
+            .and_where(
+                Expr::col(Channels::Id)
+                    .is_in(DEFAULT_CHANNELS.map(|(id, ..)| id)),
+            )
+            .to_owned();
+
+        manager.exec_stmt(delete).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(error_reports::create()).await?;
+
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for stmt in error_reports::create_foreign_keys() {
+                manager.create_foreign_key(stmt).await?;
+            }
+        }
+
+        for stmt in error_reports::create_indexes() {
+            manager.create_index(stmt).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for stmt in error_reports::drop_foreign_keys() {
+                manager.drop_foreign_key(stmt).await?;
+            }
+        }
+
+        for stmt in error_reports::drop_indexes() {
+            manager.drop_index(stmt).await?;
+        }
+
+        manager.drop_table(error_reports::drop()).await?;
+
+        Ok(())
+    }
+}
+
+pub mod error_reports {
+    use sea_orm_migration::prelude::*;
+
+    use super::super::init_tables::users::Users;
+
+    const FOREIGN_KEY_USER_ID: &str = "FK_error_reports_user_id";
+    const INDEX_SIGNATURE: &str = "IDX_error_reports_signature";
+    const INDEX_CLIENT_VERSION: &str = "IDX_error_reports_client_version";
+
+    #[derive(Iden)]
+    pub enum ErrorReports {
+        Table,
+        Id,
+        UserId,
+        Username,
+        ClientVersion,
+        ExeHash,
+        OsuMode,
+        GameMode,
+        GameTime,
+        AudioTime,
+        Culture,
+        BeatmapId,
+        BeatmapMd5,
+        Signature,
+        Exception,
+        Stacktrace,
+        Feedback,
+        Config,
+        Soft,
+        BeatmapCount,
+        Compatibility,
+        Ram,
+        CreatedAt,
+    }
+
+    pub fn create() -> TableCreateStatement {
+        Table::create()
+            .table(ErrorReports::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(ErrorReports::Id)
+                    .big_integer()
+                    .primary_key()
+                    .auto_increment()
+                    .not_null(),
+            )
+            .col(ColumnDef::new(ErrorReports::UserId).integer().null())
+            .col(ColumnDef::new(ErrorReports::Username).string().null())
+            .col(
+                ColumnDef::new(ErrorReports::ClientVersion).string().not_null(),
+            )
+            .col(ColumnDef::new(ErrorReports::ExeHash).string().null())
+            .col(ColumnDef::new(ErrorReports::OsuMode).string().null())
+            .col(ColumnDef::new(ErrorReports::GameMode).string().null())
+            .col(ColumnDef::new(ErrorReports::GameTime).integer().null())
+            .col(ColumnDef::new(ErrorReports::AudioTime).integer().null())
+            .col(ColumnDef::new(ErrorReports::Culture).string().null())
+            .col(ColumnDef::new(ErrorReports::BeatmapId).integer().null())
+            .col(
+                ColumnDef::new(ErrorReports::BeatmapMd5)
+                    .string()
+                    .string_len(32)
+                    .null(),
+            )
+            .col(
+                ColumnDef::new(ErrorReports::Signature)
+                    .string()
+                    .string_len(32)
+                    .not_null(),
+            )
+            .col(ColumnDef::new(ErrorReports::Exception).text().not_null())
+            .col(ColumnDef::new(ErrorReports::Stacktrace).text().not_null())
+            .col(ColumnDef::new(ErrorReports::Feedback).text().null())
+            .col(ColumnDef::new(ErrorReports::Config).text().null())
+            .col(
+                ColumnDef::new(ErrorReports::Soft)
+                    .boolean()
+                    .default(false)
+                    .not_null(),
+            )
+            .col(ColumnDef::new(ErrorReports::BeatmapCount).integer().null())
+            .col(
+                ColumnDef::new(ErrorReports::Compatibility)
+                    .boolean()
+                    .default(false)
+                    .not_null(),
+            )
+            .col(ColumnDef::new(ErrorReports::Ram).big_integer().null())
+            .col(
+                ColumnDef::new(ErrorReports::CreatedAt)
+                    .timestamp_with_time_zone()
+                    .default(Expr::current_timestamp())
+                    .not_null(),
+            )
+            .to_owned()
+    }
+
+    pub fn drop() -> TableDropStatement {
+        Table::drop().table(ErrorReports::Table).to_owned()
+    }
+
+    pub fn create_foreign_keys() -> Vec<ForeignKeyCreateStatement> {
+        vec![sea_query::ForeignKey::create()
+            .name(FOREIGN_KEY_USER_ID)
+            .from(ErrorReports::Table, ErrorReports::UserId)
+            .to(Users::Table, Users::Id)
+            .on_delete(ForeignKeyAction::SetNull)
+            .on_update(ForeignKeyAction::Cascade)
+            .to_owned()]
+    }
+
+    pub fn drop_foreign_keys() -> Vec<ForeignKeyDropStatement> {
+        vec![sea_query::ForeignKey::drop()
+            .name(FOREIGN_KEY_USER_ID)
+            .table(ErrorReports::Table)
+            .to_owned()]
+    }
+
+    pub fn create_indexes() -> Vec<IndexCreateStatement> {
+        vec![
+            sea_query::Index::create()
+                .name(INDEX_SIGNATURE)
+                .table(ErrorReports::Table)
+                .col(ErrorReports::Signature)
+                .to_owned(),
+            sea_query::Index::create()
+                .name(INDEX_CLIENT_VERSION)
+                .table(ErrorReports::Table)
+                .col(ErrorReports::ClientVersion)
+                .to_owned(),
+        ]
+    }
+
+    pub fn drop_indexes() -> Vec<IndexDropStatement> {
+        vec![
+            sea_query::Index::drop()
+                .table(ErrorReports::Table)
+                .name(INDEX_SIGNATURE)
+                .to_owned(),
+            sea_query::Index::drop()
+                .table(ErrorReports::Table)
+                .name(INDEX_CLIENT_VERSION)
+                .to_owned(),
+        ]
+    }
+}
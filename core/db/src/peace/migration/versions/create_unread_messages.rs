@@ -0,0 +1,111 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(unread_messages::create()).await?;
+
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for stmt in unread_messages::create_foreign_keys() {
+                manager.create_foreign_key(stmt).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for stmt in unread_messages::drop_foreign_keys() {
+                manager.drop_foreign_key(stmt).await?;
+            }
+        }
+
+        manager.drop_table(unread_messages::drop()).await?;
+
+        Ok(())
+    }
+}
+
+pub mod unread_messages {
+    use sea_orm_migration::prelude::*;
+
+    use super::super::init_tables::{
+        chat_messages::ChatMessages, users::Users,
+    };
+
+    const FOREIGN_KEY_USER_ID: &str = "FK_unread_messages_user_id";
+    const FOREIGN_KEY_MESSAGE_ID: &str = "FK_unread_messages_message_id";
+
+    #[derive(Iden)]
+    pub enum UnreadMessages {
+        Table,
+        UserId,
+        MessageId,
+        CreatedAt,
+    }
+
+    pub fn create() -> TableCreateStatement {
+        Table::create()
+            .table(UnreadMessages::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(UnreadMessages::UserId).integer().not_null())
+            .col(
+                ColumnDef::new(UnreadMessages::MessageId)
+                    .big_integer()
+                    .not_null(),
+            )
+            .col(
+                ColumnDef::new(UnreadMessages::CreatedAt)
+                    .timestamp_with_time_zone()
+                    .default(Expr::current_timestamp())
+                    .not_null(),
+            )
+            .primary_key(
+                sea_query::Index::create()
+                    .col(UnreadMessages::UserId)
+                    .col(UnreadMessages::MessageId),
+            )
+            .to_owned()
+    }
+
+    pub fn drop() -> TableDropStatement {
+        Table::drop().table(UnreadMessages::Table).to_owned()
+    }
+
+    pub fn create_foreign_keys() -> Vec<ForeignKeyCreateStatement> {
+        vec![
+            sea_query::ForeignKey::create()
+                .name(FOREIGN_KEY_USER_ID)
+                .from(UnreadMessages::Table, UnreadMessages::UserId)
+                .to(Users::Table, Users::Id)
+                .on_delete(ForeignKeyAction::Cascade)
+                .on_update(ForeignKeyAction::Cascade)
+                .to_owned(),
+            sea_query::ForeignKey::create()
+                .name(FOREIGN_KEY_MESSAGE_ID)
+                .from(UnreadMessages::Table, UnreadMessages::MessageId)
+                .to(ChatMessages::Table, ChatMessages::Id)
+                .on_delete(ForeignKeyAction::Cascade)
+                .on_update(ForeignKeyAction::Cascade)
+                .to_owned(),
+        ]
+    }
+
+    pub fn drop_foreign_keys() -> Vec<ForeignKeyDropStatement> {
+        vec![
+            sea_query::ForeignKey::drop()
+                .name(FOREIGN_KEY_USER_ID)
+                .table(UnreadMessages::Table)
+                .to_owned(),
+            sea_query::ForeignKey::drop()
+                .name(FOREIGN_KEY_MESSAGE_ID)
+                .table(UnreadMessages::Table)
+                .to_owned(),
+        ]
+    }
+}
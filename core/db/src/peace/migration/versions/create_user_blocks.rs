@@ -0,0 +1,106 @@
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(user_blocks::create()).await?;
+
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for stmt in user_blocks::create_foreign_keys() {
+                manager.create_foreign_key(stmt).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            for stmt in user_blocks::drop_foreign_keys() {
+                manager.drop_foreign_key(stmt).await?;
+            }
+        }
+
+        manager.drop_table(user_blocks::drop()).await?;
+
+        Ok(())
+    }
+}
+
+/// Users blocked by the user.
+pub mod user_blocks {
+    use sea_orm_migration::prelude::*;
+
+    use super::super::init_tables::users::Users;
+
+    const FOREIGN_KEY_USER_ID: &str = "FK_user_blocks_user_id";
+    const FOREIGN_KEY_BLOCK_ID: &str = "FK_user_blocks_block_id";
+
+    #[derive(Iden)]
+    pub enum UserBlocks {
+        Table,
+        UserId,
+        BlockId,
+        CreatedAt,
+    }
+
+    pub fn create() -> TableCreateStatement {
+        Table::create()
+            .table(UserBlocks::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(UserBlocks::UserId).integer().not_null())
+            .col(ColumnDef::new(UserBlocks::BlockId).integer().not_null())
+            .col(
+                ColumnDef::new(UserBlocks::CreatedAt)
+                    .timestamp_with_time_zone()
+                    .default(Expr::current_timestamp())
+                    .not_null(),
+            )
+            .primary_key(
+                sea_query::Index::create()
+                    .col(UserBlocks::UserId)
+                    .col(UserBlocks::BlockId),
+            )
+            .to_owned()
+    }
+
+    pub fn drop() -> TableDropStatement {
+        Table::drop().table(UserBlocks::Table).to_owned()
+    }
+
+    pub fn create_foreign_keys() -> Vec<ForeignKeyCreateStatement> {
+        vec![
+            sea_query::ForeignKey::create()
+                .name(FOREIGN_KEY_USER_ID)
+                .from(UserBlocks::Table, UserBlocks::UserId)
+                .to(Users::Table, Users::Id)
+                .on_delete(ForeignKeyAction::Cascade)
+                .on_update(ForeignKeyAction::Cascade)
+                .to_owned(),
+            sea_query::ForeignKey::create()
+                .name(FOREIGN_KEY_BLOCK_ID)
+                .from(UserBlocks::Table, UserBlocks::BlockId)
+                .to(Users::Table, Users::Id)
+                .on_delete(ForeignKeyAction::Cascade)
+                .on_update(ForeignKeyAction::Cascade)
+                .to_owned(),
+        ]
+    }
+
+    pub fn drop_foreign_keys() -> Vec<ForeignKeyDropStatement> {
+        vec![
+            sea_query::ForeignKey::drop()
+                .name(FOREIGN_KEY_USER_ID)
+                .table(UserBlocks::Table)
+                .to_owned(),
+            sea_query::ForeignKey::drop()
+                .name(FOREIGN_KEY_BLOCK_ID)
+                .table(UserBlocks::Table)
+                .to_owned(),
+        ]
+    }
+}
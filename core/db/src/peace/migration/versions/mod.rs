@@ -1,2 +1,9 @@
+pub mod add_channel_owners;
+pub mod create_comments;
+pub mod create_default_channels;
+pub mod create_error_reports;
 pub mod create_seed_data;
+pub mod create_unread_messages;
+pub mod create_user_blocks;
 pub mod init_tables;
+pub mod update_leaderboard_primary_keys;
@@ -0,0 +1,76 @@
+use sea_orm::{ConnectionTrait, DbBackend};
+use sea_orm_migration::prelude::*;
+
+/// Every `leaderboard_*` table, keyed by `(beatmap_id, ranking_type)` in
+/// `init_tables`, which only fits the first place of each beatmap.
+const LEADERBOARD_TABLES: [&str; 8] = [
+    "leaderboard_standard",
+    "leaderboard_taiko",
+    "leaderboard_fruits",
+    "leaderboard_mania",
+    "leaderboard_standard_relax",
+    "leaderboard_standard_autopilot",
+    "leaderboard_taiko_relax",
+    "leaderboard_fruits_relax",
+];
+
+/// Primary keys can not be altered through the schema statements, so the
+/// constraint is replaced with plain SQL. Sqlite can not alter primary keys
+/// at all.
+fn replace_primary_key_sql(
+    backend: DbBackend,
+    table: &str,
+    columns: &str,
+) -> Option<String> {
+    match backend {
+        DbBackend::Postgres => Some(format!(
+            r#"ALTER TABLE "{table}" DROP CONSTRAINT "{table}_pkey", ADD PRIMARY KEY ({columns})"#
+        )),
+        DbBackend::MySql => Some(format!(
+            "ALTER TABLE `{table}` DROP PRIMARY KEY, ADD PRIMARY KEY ({})",
+            columns.replace('"', "`")
+        )),
+        DbBackend::Sqlite => None,
+    }
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+impl Migration {
+    async fn replace_primary_keys(
+        manager: &SchemaManager<'_>,
+        columns: &str,
+    ) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+
+        for table in LEADERBOARD_TABLES {
+            if let Some(sql) = replace_primary_key_sql(
+                manager.get_database_backend(),
+                table,
+                columns,
+            ) {
+                conn.execute_unprepared(&sql).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        Self::replace_primary_keys(
+            manager,
+            r#""beatmap_id", "ranking_type", "user_id""#,
+        )
+        .await
+    }
+
+    /// Fails if a beatmap has the scores of more than one user.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        Self::replace_primary_keys(manager, r#""beatmap_id", "ranking_type""#)
+            .await
+    }
+}
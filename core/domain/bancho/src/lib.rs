@@ -44,6 +44,25 @@ pub enum BanchoPrivileges {
     Tournament      = 1 << 5,
 }
 
+impl BanchoPrivileges {
+    /// Client privileges of the users granted the privilege with this name
+    /// (case insensitive), unknown names are treated as normal users.
+    pub fn from_privilege_name(name: &str) -> Self {
+        let role = match name.to_ascii_lowercase().as_str() {
+            "supporter" => Self::Supporter,
+            "moderator" => Self::Moderator | Self::Supporter,
+            "administrator" | "admin" => {
+                Self::Administrator | Self::Moderator | Self::Supporter
+            },
+            "developer" => Self::Developer | Self::Supporter,
+            "tournament" => Self::Tournament,
+            _ => Self::none(),
+        };
+
+        Self::Normal | role
+    }
+}
+
 impl serde::Serialize for BanchoPrivileges {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -144,6 +163,49 @@ impl<'de> serde::Deserialize<'de> for Mods {
     }
 }
 
+impl Mods {
+    /// Parses the mod acronyms used by osu!lazer (e.g. `DT`), mods without
+    /// a legacy equivalent are `None`.
+    #[rustfmt::skip]
+    pub fn from_acronym(acronym: &str) -> Option<Self> {
+        Some(match acronym.to_ascii_uppercase().as_str() {
+            "NM" => Self::NoMod,
+            "NF" => Self::NoFail,
+            "EZ" => Self::Easy,
+            "TD" => Self::TouchScreen,
+            "HD" => Self::Hidden,
+            "HR" => Self::HardRock,
+            "SD" => Self::SuddenDeath,
+            "DT" => Self::DoubleTime,
+            "RX" => Self::Relax,
+            "HT" => Self::HalfTime,
+            "NC" => Self::NightCore | Self::DoubleTime,
+            "FL" => Self::FlashLight,
+            "AT" => Self::Auto,
+            "SO" => Self::SpunOut,
+            "AP" => Self::AutoPilot,
+            "PF" => Self::Perfect | Self::SuddenDeath,
+            "1K" => Self::Key1,
+            "2K" => Self::Key2,
+            "3K" => Self::Key3,
+            "4K" => Self::Key4,
+            "5K" => Self::Key5,
+            "6K" => Self::Key6,
+            "7K" => Self::Key7,
+            "8K" => Self::Key8,
+            "9K" => Self::Key9,
+            "FI" => Self::FadeIn,
+            "RD" => Self::Random,
+            "CN" => Self::Cinema,
+            "TP" => Self::Target,
+            "DS" => Self::KeyCoop,
+            "SV2" => Self::ScoreV2,
+            "MR" => Self::Mirror,
+            _ => return None,
+        })
+    }
+}
+
 #[rustfmt::skip]
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Primitive, Serialize, Deserialize)]
 pub enum UserOnlineStatus {
@@ -187,6 +249,27 @@ impl PresenceFilter {
     }
 }
 
+/// Ranked status filter sent by the osu!direct panel (`r` param).
+#[rustfmt::skip]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Primitive, Serialize, Deserialize)]
+pub enum OsuDirectRankStatus {
+    Ranked        = 0,
+    Pending       = 2,
+    Qualified     = 3,
+    #[default]
+    All           = 4,
+    Graveyard     = 5,
+    RankedPlayed  = 7,
+    Loved         = 8,
+}
+
+impl OsuDirectRankStatus {
+    #[inline]
+    pub fn val(&self) -> i32 {
+        *self as i32
+    }
+}
+
 #[rustfmt::skip]
 #[derive(Debug, Clone, Copy, PartialEq, EnumString, Serialize, Deserialize)]
 pub enum BanchoCountryCode {
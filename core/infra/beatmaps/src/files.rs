@@ -0,0 +1,144 @@
+use clap::Parser;
+use clap_serde_derive::ClapSerde;
+use std::{io, path::PathBuf, time::Duration};
+
+#[derive(Debug, Clone, Parser, ClapSerde, Serialize, Deserialize)]
+pub struct CliBeatmapFilesConfigs {
+    /// Directory of the stored `.osu` files, laid out as `{bid}/{md5}.osu`.
+    #[default("./.data/beatmaps".to_owned())]
+    #[arg(long, default_value = "./.data/beatmaps")]
+    pub beatmap_files_dir: String,
+
+    /// Url missing `.osu` files are downloaded from, the beatmap id is
+    /// appended to it (e.g. `https://osu.ppy.sh/osu/`).
+    #[arg(long)]
+    pub beatmap_file_download_url: Option<String>,
+
+    #[default(10)]
+    #[arg(long, default_value = "10")]
+    pub beatmap_file_download_timeout_secs: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BeatmapFileError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Download(#[from] reqwest::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct BeatmapFileContent {
+    pub content: Vec<u8>,
+    /// The file was not stored yet, so it is a new version of the beatmap.
+    pub downloaded: bool,
+}
+
+/// `.osu` files keyed by beatmap id and md5, so every version of a
+/// difficulty can be stored side by side.
+#[derive(Debug, Clone)]
+pub struct BeatmapFileStorage {
+    pub dir: PathBuf,
+    pub download_url: Option<String>,
+    pub client: reqwest::Client,
+}
+
+impl BeatmapFileStorage {
+    #[inline]
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        download_url: Option<String>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            dir: dir.into(),
+            download_url,
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    #[inline]
+    pub fn with_cfg(cfg: &CliBeatmapFilesConfigs) -> Self {
+        Self::new(
+            &cfg.beatmap_files_dir,
+            cfg.beatmap_file_download_url.to_owned(),
+            Duration::from_secs(cfg.beatmap_file_download_timeout_secs),
+        )
+    }
+
+    #[inline]
+    pub fn path(&self, beatmap_id: i32, md5: &str) -> PathBuf {
+        self.dir.join(beatmap_id.to_string()).join(format!("{md5}.osu"))
+    }
+
+    /// Returns the stored file, downloads it if it is missing and a download
+    /// url is configured.
+    ///
+    /// `Ok(None)` if the file is not available, or the downloaded file does
+    /// not match `md5`.
+    pub async fn get(
+        &self,
+        beatmap_id: i32,
+        md5: &str,
+    ) -> Result<Option<BeatmapFileContent>, BeatmapFileError> {
+        match tokio::fs::read(self.path(beatmap_id, md5)).await {
+            Ok(content) => {
+                return Ok(Some(BeatmapFileContent {
+                    content,
+                    downloaded: false,
+                }))
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {},
+            Err(err) => return Err(err.into()),
+        }
+
+        let Some(content) = self.download(beatmap_id).await? else {
+            return Ok(None);
+        };
+
+        let downloaded_md5 = self.save(beatmap_id, &content).await?;
+
+        Ok((downloaded_md5 == md5)
+            .then_some(BeatmapFileContent { content, downloaded: true }))
+    }
+
+    /// Stores the file under its own md5 and returns it.
+    pub async fn save(
+        &self,
+        beatmap_id: i32,
+        content: &[u8],
+    ) -> Result<String, BeatmapFileError> {
+        let md5 = format!("{:x}", md5::compute(content));
+        let path = self.path(beatmap_id, &md5);
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(path, content).await?;
+
+        Ok(md5)
+    }
+
+    async fn download(
+        &self,
+        beatmap_id: i32,
+    ) -> Result<Option<Vec<u8>>, BeatmapFileError> {
+        let Some(download_url) = self.download_url.as_deref() else {
+            return Ok(None);
+        };
+
+        let res = self
+            .client
+            .get(format!("{download_url}{beatmap_id}"))
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        Ok((!res.is_empty()).then(|| res.to_vec()))
+    }
+}
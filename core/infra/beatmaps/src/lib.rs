@@ -0,0 +1,6 @@
+#[macro_use]
+extern crate serde;
+
+pub mod files;
+
+pub use files::*;
@@ -17,7 +17,18 @@ fn build_all(builder: ProtoBuilder) -> Result<(), Box<dyn std::error::Error>> {
     builder.build("base")?;
     builder.build("frame.logs")?;
     builder.build("services.chat")?;
-    builder.build("services.bancho")?;
+    builder.build_with_attrs(
+        "services.bancho",
+        &[StructAttr::new(
+            SERDE,
+            &[
+                "ErrorReportGroup",
+                "ErrorReportGroups",
+                "MainMenuIcon",
+                "MenuContent",
+            ],
+        )],
+    )?;
     builder.build_with_attrs(
         "services.bancho_state",
         &[StructAttr::new(
@@ -43,6 +54,22 @@ fn build_all(builder: ProtoBuilder) -> Result<(), Box<dyn std::error::Error>> {
 
     builder.build("services.signature")?;
     builder.build("services.events")?;
+    builder.build_with_attrs(
+        "services.performance",
+        &[StructAttr::new(
+            SERDE,
+            &[
+                "CalculateScorePpRequest",
+                "ScorePp",
+                "UserPerformance",
+                "SubmittedScorePerformance",
+                "RecalculateUserRequest",
+                "UserPerformances",
+                "StartRecalculationRequest",
+                "StopRecalculationRequest",
+            ],
+        )],
+    )?;
 
     Ok(())
 }
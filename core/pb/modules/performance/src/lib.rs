@@ -0,0 +1,18 @@
+#![allow(dead_code)]
+#![allow(non_snake_case)]
+
+mod peace {
+    use pb_base as base;
+
+    pub mod services {
+        pub mod performance {
+            include!("../../../generated/peace.services.performance.rs");
+
+            pub const PERFORMANCE_DESCRIPTOR_SET: &[u8] = include_bytes!(
+                "../../../generated/peace.services.performance.descriptor.bin"
+            );
+        }
+    }
+}
+
+pub use peace::services::performance::*;
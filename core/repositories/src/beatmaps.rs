@@ -0,0 +1,359 @@
+use crate::GetBeatmapError;
+use peace_db::{
+    peace::{
+        entity::{
+            beatmap_ratings, beatmaps,
+            sea_orm_active_enums::{GameMode, RankStatus},
+        },
+        Peace,
+    },
+    prelude::Decimal,
+    sea_query::{Alias, Expr, Func, OnConflict},
+    *,
+};
+use std::{collections::HashMap, sync::Arc};
+
+pub type DynBeatmapsRepository = Arc<dyn BeatmapsRepository + Send + Sync>;
+
+#[derive(Debug, Default, Clone)]
+pub struct SearchBeatmapsets {
+    /// Every keyword must match at least one of title, artist, mapper,
+    /// difficulty name, source or tags (case insensitive).
+    pub keywords: Vec<String>,
+    pub rank_status: Option<Vec<RankStatus>>,
+    pub game_mode: Option<GameMode>,
+    pub offset: u64,
+    pub limit: u64,
+}
+
+#[async_trait]
+pub trait BeatmapsRepository {
+    async fn get_beatmap(
+        &self,
+        beatmap_id: Option<i32>,
+        beatmapset_id: Option<i32>,
+        beatmap_md5: Option<&str>,
+    ) -> Result<beatmaps::Model, GetBeatmapError>;
+
+    /// `file_name` is the `.osu` file name, e.g.
+    /// `Artist - Title (Mapper) [Difficulty].osu`.
+    async fn get_beatmap_by_file_name(
+        &self,
+        file_name: &str,
+    ) -> Result<beatmaps::Model, GetBeatmapError>;
+
+    async fn get_beatmapset(
+        &self,
+        beatmapset_id: i32,
+    ) -> Result<Vec<beatmaps::Model>, DbErr>;
+
+    /// Returns every beatmap matching one of the file names or ids in a single
+    /// query.
+    async fn get_beatmaps_by_file_names_or_ids(
+        &self,
+        file_names: Vec<String>,
+        beatmap_ids: Vec<i32>,
+    ) -> Result<Vec<beatmaps::Model>, DbErr>;
+
+    /// Returns the matched beatmapsets (newest first), each one contains all
+    /// of its beatmaps.
+    async fn search_beatmapsets(
+        &self,
+        query: SearchBeatmapsets,
+    ) -> Result<Vec<Vec<beatmaps::Model>>, DbErr>;
+
+    async fn get_beatmap_rating(
+        &self,
+        user_id: i32,
+        beatmap_md5: &str,
+    ) -> Result<Option<i16>, DbErr>;
+
+    /// Returns `None` if the beatmap has not been rated yet.
+    async fn get_beatmap_average_rating(
+        &self,
+        beatmap_md5: &str,
+    ) -> Result<Option<f64>, DbErr>;
+
+    /// Each user can only rate a beatmap once, later ratings are ignored.
+    async fn rate_beatmap(
+        &self,
+        user_id: i32,
+        beatmap_md5: &str,
+        rating: i16,
+    ) -> Result<(), DbErr>;
+
+    /// Stores the nomod difficulty calculated from the `.osu` file, beatmaps
+    /// which have been updated to another md5 meanwhile are left untouched.
+    async fn update_beatmap_difficulty(
+        &self,
+        beatmap_id: i32,
+        beatmap_md5: &str,
+        stars: f64,
+        max_combo: i32,
+    ) -> Result<(), DbErr>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct BeatmapsRepositoryImpl {
+    pub conn: DbConnection<Peace>,
+}
+
+impl BeatmapsRepositoryImpl {
+    pub fn new(conn: DbConnection<Peace>) -> BeatmapsRepositoryImpl {
+        Self { conn }
+    }
+
+    pub fn into_service(self) -> DynBeatmapsRepository {
+        Arc::new(self) as DynBeatmapsRepository
+    }
+}
+
+#[async_trait]
+impl BeatmapsRepository for BeatmapsRepositoryImpl {
+    async fn get_beatmap(
+        &self,
+        beatmap_id: Option<i32>,
+        beatmapset_id: Option<i32>,
+        beatmap_md5: Option<&str>,
+    ) -> Result<beatmaps::Model, GetBeatmapError> {
+        // an empty `Condition::any()` would match any beatmap
+        if beatmap_id.is_none()
+            && beatmapset_id.is_none()
+            && beatmap_md5.is_none()
+        {
+            return Err(GetBeatmapError::BeatmapNotExists);
+        }
+
+        beatmaps::Entity::find()
+            .filter(
+                Condition::any()
+                    .add_option(
+                        beatmap_id.map(|bid| beatmaps::Column::Bid.eq(bid)),
+                    )
+                    .add_option(
+                        beatmapset_id.map(|sid| beatmaps::Column::Sid.eq(sid)),
+                    )
+                    .add_option(
+                        beatmap_md5.map(|md5| beatmaps::Column::Md5.eq(md5)),
+                    ),
+            )
+            .one(self.conn.as_ref())
+            .await
+            .map_err(GetBeatmapError::from)?
+            .ok_or(GetBeatmapError::BeatmapNotExists)
+    }
+
+    async fn get_beatmap_by_file_name(
+        &self,
+        file_name: &str,
+    ) -> Result<beatmaps::Model, GetBeatmapError> {
+        beatmaps::Entity::find()
+            .filter(beatmaps::Column::FileName.eq(file_name))
+            .one(self.conn.as_ref())
+            .await
+            .map_err(GetBeatmapError::from)?
+            .ok_or(GetBeatmapError::BeatmapNotExists)
+    }
+
+    async fn get_beatmapset(
+        &self,
+        beatmapset_id: i32,
+    ) -> Result<Vec<beatmaps::Model>, DbErr> {
+        beatmaps::Entity::find()
+            .filter(beatmaps::Column::Sid.eq(beatmapset_id))
+            .order_by_asc(beatmaps::Column::Stars)
+            .all(self.conn.as_ref())
+            .await
+    }
+
+    async fn get_beatmaps_by_file_names_or_ids(
+        &self,
+        file_names: Vec<String>,
+        beatmap_ids: Vec<i32>,
+    ) -> Result<Vec<beatmaps::Model>, DbErr> {
+        if file_names.is_empty() && beatmap_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        beatmaps::Entity::find()
+            .filter(
+                Condition::any()
+                    .add_option(
+                        (!file_names.is_empty()).then(|| {
+                            beatmaps::Column::FileName.is_in(file_names)
+                        }),
+                    )
+                    .add_option(
+                        (!beatmap_ids.is_empty())
+                            .then(|| beatmaps::Column::Bid.is_in(beatmap_ids)),
+                    ),
+            )
+            .all(self.conn.as_ref())
+            .await
+    }
+
+    async fn search_beatmapsets(
+        &self,
+        query: SearchBeatmapsets,
+    ) -> Result<Vec<Vec<beatmaps::Model>>, DbErr> {
+        const SEARCHABLE_COLUMNS: [beatmaps::Column; 6] = [
+            beatmaps::Column::Title,
+            beatmaps::Column::Artist,
+            beatmaps::Column::MapperName,
+            beatmaps::Column::DiffName,
+            beatmaps::Column::Source,
+            beatmaps::Column::Tags,
+        ];
+
+        let SearchBeatmapsets {
+            keywords,
+            rank_status,
+            game_mode,
+            offset,
+            limit,
+        } = query;
+
+        // Postgres enums can not be compared with text directly
+        let as_text = |column: beatmaps::Column| {
+            Expr::expr(Expr::col(column).as_enum(Alias::new("text")))
+        };
+
+        let condition =
+            keywords.iter().fold(
+                Condition::all()
+                    .add_option(rank_status.map(|status| {
+                        as_text(beatmaps::Column::RankStatus).is_in(status)
+                    }))
+                    .add_option(game_mode.map(|mode| {
+                        as_text(beatmaps::Column::GameMode).eq(mode)
+                    })),
+                |condition, keyword| {
+                    let pattern = format!("%{}%", keyword.to_lowercase());
+
+                    condition.add(SEARCHABLE_COLUMNS.into_iter().fold(
+                        Condition::any(),
+                        |any, column| {
+                            any.add(
+                                Expr::expr(Func::lower(Expr::col(column)))
+                                    .like(pattern.as_str()),
+                            )
+                        },
+                    ))
+                },
+            );
+
+        let beatmapset_ids = beatmaps::Entity::find()
+            .select_only()
+            .column(beatmaps::Column::Sid)
+            .filter(condition)
+            .group_by(beatmaps::Column::Sid)
+            .order_by_desc(Expr::col(beatmaps::Column::LastUpdate).max())
+            .order_by_desc(beatmaps::Column::Sid)
+            .offset(offset)
+            .limit(limit)
+            .into_tuple::<i32>()
+            .all(self.conn.as_ref())
+            .await?;
+
+        if beatmapset_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut beatmapsets = beatmaps::Entity::find()
+            .filter(beatmaps::Column::Sid.is_in(beatmapset_ids.clone()))
+            .order_by_asc(beatmaps::Column::Stars)
+            .all(self.conn.as_ref())
+            .await?
+            .into_iter()
+            .fold(HashMap::<i32, Vec<beatmaps::Model>>::new(), |mut map, b| {
+                map.entry(b.sid).or_default().push(b);
+                map
+            });
+
+        Ok(beatmapset_ids
+            .into_iter()
+            .filter_map(|sid| beatmapsets.remove(&sid))
+            .collect())
+    }
+
+    async fn get_beatmap_rating(
+        &self,
+        user_id: i32,
+        beatmap_md5: &str,
+    ) -> Result<Option<i16>, DbErr> {
+        Ok(beatmap_ratings::Entity::find_by_id((
+            user_id,
+            beatmap_md5.to_owned(),
+        ))
+        .one(self.conn.as_ref())
+        .await?
+        .map(|model| model.rating))
+    }
+
+    async fn get_beatmap_average_rating(
+        &self,
+        beatmap_md5: &str,
+    ) -> Result<Option<f64>, DbErr> {
+        Ok(beatmap_ratings::Entity::find()
+            .select_only()
+            .column_as(
+                Expr::expr(Func::avg(
+                    Expr::col(beatmap_ratings::Column::Rating)
+                        .cast_as(Alias::new("float8")),
+                )),
+                "average",
+            )
+            .filter(beatmap_ratings::Column::MapMd5.eq(beatmap_md5))
+            .into_tuple::<Option<f64>>()
+            .one(self.conn.as_ref())
+            .await?
+            .flatten())
+    }
+
+    async fn rate_beatmap(
+        &self,
+        user_id: i32,
+        beatmap_md5: &str,
+        rating: i16,
+    ) -> Result<(), DbErr> {
+        beatmap_ratings::Entity::insert(beatmap_ratings::ActiveModel {
+            user_id: Set(user_id),
+            map_md5: Set(beatmap_md5.to_owned()),
+            rating: Set(rating),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([
+                beatmap_ratings::Column::UserId,
+                beatmap_ratings::Column::MapMd5,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(self.conn.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update_beatmap_difficulty(
+        &self,
+        beatmap_id: i32,
+        beatmap_md5: &str,
+        stars: f64,
+        max_combo: i32,
+    ) -> Result<(), DbErr> {
+        let stars =
+            Decimal::from_f64_retain(stars).unwrap_or_default().round_dp(2);
+
+        beatmaps::Entity::update_many()
+            .col_expr(beatmaps::Column::Stars, Expr::value(stars))
+            .col_expr(beatmaps::Column::MaxCombo, Expr::value(max_combo))
+            .filter(beatmaps::Column::Bid.eq(beatmap_id))
+            .filter(beatmaps::Column::Md5.eq(beatmap_md5))
+            .exec(self.conn.as_ref())
+            .await?;
+
+        Ok(())
+    }
+}
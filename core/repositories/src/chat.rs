@@ -0,0 +1,752 @@
+use peace_db::{
+    peace::{
+        entity::{
+            channel_privileges, channel_users, channels, chat_messages,
+            privileges,
+            sea_orm_active_enums::{ChannelHandleType, ChannelType},
+            unread_messages, users,
+        },
+        Peace,
+    },
+    prelude::DateTimeWithTimeZone,
+    sea_query::{Alias, Expr, Func, OnConflict, Query},
+    *,
+};
+use std::sync::Arc;
+
+pub type DynChatRepository = Arc<dyn ChatRepository + Send + Sync>;
+
+/// Ids of the private conversations start here.
+pub const PRIVATE_CHANNEL_ID_BASE: i64 = 1 << 62;
+
+/// Ids of the `#spec_<host id>` channels.
+pub const SPECTATOR_CHANNEL_ID_BASE: i64 = 1 << 61;
+
+/// Ids of the `#multi_<match id>` channels.
+pub const MULTIPLAYER_CHANNEL_ID_BASE: i64 = 1 << 60;
+
+/// Ids of the `#group_<n>` channels, channels created by admins are numbered
+/// below.
+pub const GROUP_CHANNEL_ID_BASE: i64 = 1 << 59;
+
+/// Rows are inserted in chunks, keeps the statements below the bind
+/// parameter limit of the database.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct CreateChatMessage {
+    /// Reserved by the chat service, the message is written after it has
+    /// been delivered.
+    pub id: i64,
+    pub sender_id: i32,
+    pub channel_id: i64,
+    pub timestamp: DateTimeWithTimeZone,
+    pub content_string: String,
+    pub content_html: Option<String>,
+    pub is_action: bool,
+    /// The offline user the private message is kept unread for.
+    pub unread_by: Option<i32>,
+}
+
+/// A private message sent while its target was offline.
+#[derive(Debug, Clone)]
+pub struct UnreadChatMessage {
+    pub id: i64,
+    pub sender_id: i32,
+    pub sender_name: String,
+    pub timestamp: DateTimeWithTimeZone,
+    pub content_string: String,
+    pub is_action: bool,
+}
+
+impl FromQueryResult for UnreadChatMessage {
+    fn from_query_result(res: &QueryResult, pre: &str) -> Result<Self, DbErr> {
+        Ok(Self {
+            id: res.try_get(pre, "id")?,
+            sender_id: res.try_get(pre, "sender_id")?,
+            sender_name: res.try_get(pre, "sender_name")?,
+            timestamp: res.try_get(pre, "timestamp")?,
+            content_string: res.try_get(pre, "content_string")?,
+            is_action: res.try_get(pre, "is_action")?,
+        })
+    }
+}
+
+/// A channel the messages are written to, created if it does not exist yet.
+#[derive(Debug, Clone)]
+pub struct CreateChatChannel {
+    pub id: i64,
+    pub channel_type: ChannelType,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Members of private channels.
+    pub user_ids: Vec<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateGroupChannel {
+    pub owner_id: i32,
+    pub description: Option<String>,
+    /// Members besides the owner.
+    pub member_ids: Vec<i32>,
+}
+
+/// A private or group channel with the ids of its members.
+#[derive(Debug, Clone)]
+pub struct ChannelWithMembers {
+    pub channel: channels::Model,
+    pub member_ids: Vec<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatePublicChannel {
+    pub name: String,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub auto_join: bool,
+    pub creator_id: Option<i64>,
+}
+
+/// `None` fields are kept as they are.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateChannel {
+    pub name: Option<String>,
+    pub description: Option<Option<String>>,
+    pub icon: Option<Option<String>>,
+    pub auto_join: Option<bool>,
+}
+
+/// A privilege level a user needs to act in a channel.
+#[derive(Debug, Clone)]
+pub struct ChannelPrivilege {
+    pub channel_id: i64,
+    pub handle: ChannelHandleType,
+    /// The priority of the required privilege.
+    pub required_level: i32,
+}
+
+#[async_trait]
+pub trait ChatRepository {
+    async fn get_public_channels(&self) -> Result<Vec<channels::Model>, DbErr>;
+
+    async fn get_channel_privileges(
+        &self,
+    ) -> Result<Vec<ChannelPrivilege>, DbErr>;
+
+    /// The channel takes the next free id below the private conversations.
+    async fn create_public_channel(
+        &self,
+        channel: CreatePublicChannel,
+    ) -> Result<channels::Model, DbErr>;
+
+    /// Returns `None` if the channel does not exist.
+    async fn update_channel(
+        &self,
+        channel_id: i64,
+        update: UpdateChannel,
+    ) -> Result<Option<channels::Model>, DbErr>;
+
+    /// Deletes the channel with its members and messages, returns `false`
+    /// if it does not exist.
+    async fn delete_channel(&self, channel_id: i64) -> Result<bool, DbErr>;
+
+    /// Creates the private channel of the two users owned by the first one,
+    /// an existing channel is restored if archived.
+    async fn create_private_channel(
+        &self,
+        channel_id: i64,
+        owner_id: i32,
+        target_user_id: i32,
+    ) -> Result<channels::Model, DbErr>;
+
+    /// The channel takes the next free group channel id.
+    async fn create_group_channel(
+        &self,
+        channel: CreateGroupChannel,
+    ) -> Result<channels::Model, DbErr>;
+
+    /// Returns `None` if the channel does not exist or is archived.
+    async fn get_channel_with_members(
+        &self,
+        channel_id: i64,
+    ) -> Result<Option<ChannelWithMembers>, DbErr>;
+
+    /// Returns `false` if the user is already a member.
+    async fn add_channel_user(
+        &self,
+        channel_id: i64,
+        user_id: i32,
+    ) -> Result<bool, DbErr>;
+
+    /// Returns `false` if the user is not a member.
+    async fn remove_channel_user(
+        &self,
+        channel_id: i64,
+        user_id: i32,
+    ) -> Result<bool, DbErr>;
+
+    async fn set_channel_owner(
+        &self,
+        channel_id: i64,
+        owner_id: i32,
+    ) -> Result<(), DbErr>;
+
+    /// Keeps the channel with its messages, returns `false` if it does not
+    /// exist or is already archived.
+    async fn archive_channel(&self, channel_id: i64) -> Result<bool, DbErr>;
+
+    /// Takes `count` ids from the sequence of `chat_messages`, they are
+    /// never handed out again even if the messages are not written.
+    async fn reserve_message_ids(&self, count: u64) -> Result<Vec<i64>, DbErr>;
+
+    /// Creates the missing channels, then writes the messages in one
+    /// transaction.
+    async fn save_messages(
+        &self,
+        channels: Vec<CreateChatChannel>,
+        messages: Vec<CreateChatMessage>,
+    ) -> Result<(), DbErr>;
+
+    /// Returns the unread private messages of the user, the oldest first.
+    async fn get_unread_messages(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<UnreadChatMessage>, DbErr>;
+
+    /// Marks the private messages sent to the user as read, only the ones
+    /// from `sender_id` if set. Returns the count of marked messages.
+    async fn mark_messages_read(
+        &self,
+        user_id: i32,
+        sender_id: Option<i32>,
+    ) -> Result<u64, DbErr>;
+
+    /// Marks the given private messages sent to the user as read. Returns
+    /// the count of marked messages.
+    async fn mark_messages_read_by_ids(
+        &self,
+        user_id: i32,
+        message_ids: Vec<i64>,
+    ) -> Result<u64, DbErr>;
+
+    /// Returns at most `limit` messages of the channel older than
+    /// `before_message_id`, the latest first.
+    async fn get_channel_messages(
+        &self,
+        channel_id: i64,
+        before_message_id: Option<i64>,
+        limit: u64,
+    ) -> Result<Vec<chat_messages::Model>, DbErr>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ChatRepositoryImpl {
+    pub conn: DbConnection<Peace>,
+}
+
+impl ChatRepositoryImpl {
+    pub fn new(conn: DbConnection<Peace>) -> ChatRepositoryImpl {
+        Self { conn }
+    }
+
+    pub fn into_service(self) -> DynChatRepository {
+        Arc::new(self) as DynChatRepository
+    }
+}
+
+#[async_trait]
+impl ChatRepository for ChatRepositoryImpl {
+    async fn get_public_channels(&self) -> Result<Vec<channels::Model>, DbErr> {
+        channels::Entity::find()
+            // Postgres enums can not be compared with text directly
+            .filter(
+                Expr::expr(
+                    Expr::col(channels::Column::ChannelType)
+                        .as_enum(Alias::new("text")),
+                )
+                .eq(ChannelType::Public),
+            )
+            .order_by_asc(channels::Column::Id)
+            .all(self.conn.as_ref())
+            .await
+    }
+
+    async fn get_channel_privileges(
+        &self,
+    ) -> Result<Vec<ChannelPrivilege>, DbErr> {
+        Ok(channel_privileges::Entity::find()
+            .select_only()
+            .column(channel_privileges::Column::ChannelId)
+            .column(channel_privileges::Column::Handle)
+            .column(privileges::Column::Priority)
+            .inner_join(privileges::Entity)
+            .into_tuple::<(i64, ChannelHandleType, i16)>()
+            .all(self.conn.as_ref())
+            .await?
+            .into_iter()
+            .map(|(channel_id, handle, priority)| ChannelPrivilege {
+                channel_id,
+                handle,
+                required_level: priority.into(),
+            })
+            .collect())
+    }
+
+    async fn create_public_channel(
+        &self,
+        channel: CreatePublicChannel,
+    ) -> Result<channels::Model, DbErr> {
+        // A concurrent creation fails on the primary key
+        let last_id = channels::Entity::find()
+            .select_only()
+            .column_as(Expr::col(channels::Column::Id).max(), "id")
+            .filter(channels::Column::Id.lt(GROUP_CHANNEL_ID_BASE))
+            .into_tuple::<Option<i64>>()
+            .one(self.conn.as_ref())
+            .await?
+            .flatten();
+
+        channels::ActiveModel {
+            id: Set(last_id.map(|id| id + 1).unwrap_or_default()),
+            channel_type: Set(ChannelType::Public),
+            name: Set(Some(channel.name)),
+            description: Set(channel.description),
+            icon: Set(channel.icon),
+            auto_join: Set(channel.auto_join),
+            creator_id: Set(channel.creator_id),
+            ..Default::default()
+        }
+        .insert(self.conn.as_ref())
+        .await
+    }
+
+    async fn update_channel(
+        &self,
+        channel_id: i64,
+        update: UpdateChannel,
+    ) -> Result<Option<channels::Model>, DbErr> {
+        let Some(model) = channels::Entity::find_by_id(channel_id)
+            .one(self.conn.as_ref())
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let mut active = model.clone().into_active_model();
+
+        if let Some(name) = update.name {
+            active.name = Set(Some(name));
+        }
+        if let Some(description) = update.description {
+            active.description = Set(description);
+        }
+        if let Some(icon) = update.icon {
+            active.icon = Set(icon);
+        }
+        if let Some(auto_join) = update.auto_join {
+            active.auto_join = Set(auto_join);
+        }
+
+        if !active.is_changed() {
+            return Ok(Some(model));
+        }
+
+        Ok(Some(active.update(self.conn.as_ref()).await?))
+    }
+
+    async fn delete_channel(&self, channel_id: i64) -> Result<bool, DbErr> {
+        Ok(channels::Entity::delete_by_id(channel_id)
+            .exec(self.conn.as_ref())
+            .await?
+            .rows_affected
+            > 0)
+    }
+
+    async fn create_private_channel(
+        &self,
+        channel_id: i64,
+        owner_id: i32,
+        target_user_id: i32,
+    ) -> Result<channels::Model, DbErr> {
+        let txn = self.conn.begin().await?;
+
+        // The channel may exist already, created by a message
+        channels::Entity::insert(channels::ActiveModel {
+            id: Set(channel_id),
+            channel_type: Set(ChannelType::Private),
+            owner_id: Set(Some(owner_id)),
+            creator_id: Set(Some(owner_id as i64)),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::column(channels::Column::Id).do_nothing().to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        channels::Entity::update_many()
+            .col_expr(
+                channels::Column::ArchivedAt,
+                Expr::value(None::<DateTimeWithTimeZone>),
+            )
+            .col_expr(
+                channels::Column::OwnerId,
+                Func::coalesce([
+                    Expr::col(channels::Column::OwnerId).into(),
+                    Expr::val(owner_id).into(),
+                ])
+                .into(),
+            )
+            .filter(channels::Column::Id.eq(channel_id))
+            .exec(&txn)
+            .await?;
+
+        channel_users::Entity::insert_many([owner_id, target_user_id].map(
+            |user_id| channel_users::ActiveModel {
+                channel_id: Set(channel_id),
+                user_id: Set(user_id),
+            },
+        ))
+        .on_conflict(
+            OnConflict::columns([
+                channel_users::Column::ChannelId,
+                channel_users::Column::UserId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        let channel = channels::Entity::find_by_id(channel_id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| {
+                DbErr::RecordNotFound(format!("channel {channel_id}"))
+            })?;
+
+        txn.commit().await?;
+
+        Ok(channel)
+    }
+
+    async fn create_group_channel(
+        &self,
+        channel: CreateGroupChannel,
+    ) -> Result<channels::Model, DbErr> {
+        let txn = self.conn.begin().await?;
+
+        // A concurrent creation fails on the primary key
+        let last_id = channels::Entity::find()
+            .select_only()
+            .column_as(Expr::col(channels::Column::Id).max(), "id")
+            .filter(channels::Column::Id.gte(GROUP_CHANNEL_ID_BASE))
+            .filter(channels::Column::Id.lt(MULTIPLAYER_CHANNEL_ID_BASE))
+            .into_tuple::<Option<i64>>()
+            .one(&txn)
+            .await?
+            .flatten();
+
+        let id = last_id.map(|id| id + 1).unwrap_or(GROUP_CHANNEL_ID_BASE);
+
+        let model = channels::ActiveModel {
+            id: Set(id),
+            channel_type: Set(ChannelType::Group),
+            name: Set(Some(format!("#group_{}", id - GROUP_CHANNEL_ID_BASE))),
+            description: Set(channel.description),
+            owner_id: Set(Some(channel.owner_id)),
+            creator_id: Set(Some(channel.owner_id as i64)),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        let mut member_ids = channel.member_ids;
+        member_ids.push(channel.owner_id);
+        member_ids.sort_unstable();
+        member_ids.dedup();
+
+        for chunk in member_ids.chunks(INSERT_CHUNK_SIZE) {
+            channel_users::Entity::insert_many(chunk.iter().map(|user_id| {
+                channel_users::ActiveModel {
+                    channel_id: Set(id),
+                    user_id: Set(*user_id),
+                }
+            }))
+            .exec_without_returning(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(model)
+    }
+
+    async fn get_channel_with_members(
+        &self,
+        channel_id: i64,
+    ) -> Result<Option<ChannelWithMembers>, DbErr> {
+        let Some(channel) = channels::Entity::find_by_id(channel_id)
+            .filter(channels::Column::ArchivedAt.is_null())
+            .one(self.conn.as_ref())
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let member_ids = channel_users::Entity::find()
+            .select_only()
+            .column(channel_users::Column::UserId)
+            .filter(channel_users::Column::ChannelId.eq(channel_id))
+            .into_tuple::<i32>()
+            .all(self.conn.as_ref())
+            .await?;
+
+        Ok(Some(ChannelWithMembers { channel, member_ids }))
+    }
+
+    async fn add_channel_user(
+        &self,
+        channel_id: i64,
+        user_id: i32,
+    ) -> Result<bool, DbErr> {
+        Ok(channel_users::Entity::insert(channel_users::ActiveModel {
+            channel_id: Set(channel_id),
+            user_id: Set(user_id),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                channel_users::Column::ChannelId,
+                channel_users::Column::UserId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(self.conn.as_ref())
+        .await?
+            > 0)
+    }
+
+    async fn remove_channel_user(
+        &self,
+        channel_id: i64,
+        user_id: i32,
+    ) -> Result<bool, DbErr> {
+        Ok(channel_users::Entity::delete_many()
+            .filter(channel_users::Column::ChannelId.eq(channel_id))
+            .filter(channel_users::Column::UserId.eq(user_id))
+            .exec(self.conn.as_ref())
+            .await?
+            .rows_affected
+            > 0)
+    }
+
+    async fn set_channel_owner(
+        &self,
+        channel_id: i64,
+        owner_id: i32,
+    ) -> Result<(), DbErr> {
+        channels::Entity::update_many()
+            .col_expr(channels::Column::OwnerId, Expr::value(owner_id))
+            .filter(channels::Column::Id.eq(channel_id))
+            .exec(self.conn.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn archive_channel(&self, channel_id: i64) -> Result<bool, DbErr> {
+        Ok(channels::Entity::update_many()
+            .col_expr(
+                channels::Column::ArchivedAt,
+                Expr::current_timestamp().into(),
+            )
+            .filter(channels::Column::Id.eq(channel_id))
+            .filter(channels::Column::ArchivedAt.is_null())
+            .exec(self.conn.as_ref())
+            .await?
+            .rows_affected
+            > 0)
+    }
+
+    async fn reserve_message_ids(&self, count: u64) -> Result<Vec<i64>, DbErr> {
+        self.conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"SELECT nextval(pg_get_serial_sequence('chat_messages', 'id')) AS "id" FROM generate_series(1, $1)"#,
+                [(count as i64).into()],
+            ))
+            .await?
+            .iter()
+            .map(|row| row.try_get::<i64>("", "id"))
+            .collect()
+    }
+
+    async fn save_messages(
+        &self,
+        channels: Vec<CreateChatChannel>,
+        messages: Vec<CreateChatMessage>,
+    ) -> Result<(), DbErr> {
+        let txn = self.conn.begin().await?;
+
+        let mut channel_users = Vec::new();
+        let channels = channels
+            .into_iter()
+            .map(|channel| {
+                channel_users.extend(channel.user_ids.into_iter().map(
+                    |user_id| channel_users::ActiveModel {
+                        channel_id: Set(channel.id),
+                        user_id: Set(user_id),
+                    },
+                ));
+
+                channels::ActiveModel {
+                    id: Set(channel.id),
+                    channel_type: Set(channel.channel_type),
+                    name: Set(channel.name),
+                    description: Set(channel.description),
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if !channels.is_empty() {
+            channels::Entity::insert_many(channels)
+                .on_conflict(
+                    OnConflict::column(channels::Column::Id)
+                        .do_nothing()
+                        .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        for chunk in channel_users.chunks(INSERT_CHUNK_SIZE) {
+            channel_users::Entity::insert_many(chunk.to_vec())
+                .on_conflict(
+                    OnConflict::columns([
+                        channel_users::Column::ChannelId,
+                        channel_users::Column::UserId,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        let unread_messages = messages
+            .iter()
+            .filter_map(|message| {
+                message.unread_by.map(|user_id| unread_messages::ActiveModel {
+                    user_id: Set(user_id),
+                    message_id: Set(message.id),
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for chunk in messages.chunks(INSERT_CHUNK_SIZE) {
+            chat_messages::Entity::insert_many(chunk.iter().cloned().map(
+                |message| chat_messages::ActiveModel {
+                    id: Set(message.id),
+                    sender_id: Set(message.sender_id),
+                    channel_id: Set(message.channel_id),
+                    timestamp: Set(message.timestamp),
+                    content_string: Set(message.content_string),
+                    content_html: Set(message.content_html),
+                    is_action: Set(message.is_action),
+                },
+            ))
+            .exec_without_returning(&txn)
+            .await?;
+        }
+
+        for chunk in unread_messages.chunks(INSERT_CHUNK_SIZE) {
+            unread_messages::Entity::insert_many(chunk.to_vec())
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        txn.commit().await
+    }
+
+    async fn get_unread_messages(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<UnreadChatMessage>, DbErr> {
+        unread_messages::Entity::find()
+            .select_only()
+            .column_as(chat_messages::Column::Id, "id")
+            .column_as(chat_messages::Column::SenderId, "sender_id")
+            .column_as(users::Column::Name, "sender_name")
+            .column_as(chat_messages::Column::Timestamp, "timestamp")
+            .column_as(chat_messages::Column::ContentString, "content_string")
+            .column_as(chat_messages::Column::IsAction, "is_action")
+            .join(
+                JoinType::InnerJoin,
+                unread_messages::Relation::ChatMessages.def(),
+            )
+            .join(JoinType::InnerJoin, chat_messages::Relation::Users.def())
+            .filter(unread_messages::Column::UserId.eq(user_id))
+            .order_by_asc(chat_messages::Column::Id)
+            .into_model::<UnreadChatMessage>()
+            .all(self.conn.as_ref())
+            .await
+    }
+
+    async fn mark_messages_read(
+        &self,
+        user_id: i32,
+        sender_id: Option<i32>,
+    ) -> Result<u64, DbErr> {
+        Ok(unread_messages::Entity::delete_many()
+            .filter(unread_messages::Column::UserId.eq(user_id))
+            .apply_if(sender_id, |query, sender_id| {
+                query.filter(
+                    unread_messages::Column::MessageId.in_subquery(
+                        Query::select()
+                            .column(chat_messages::Column::Id)
+                            .from(chat_messages::Entity)
+                            .and_where(
+                                chat_messages::Column::SenderId.eq(sender_id),
+                            )
+                            .to_owned(),
+                    ),
+                )
+            })
+            .exec(self.conn.as_ref())
+            .await?
+            .rows_affected)
+    }
+
+    async fn mark_messages_read_by_ids(
+        &self,
+        user_id: i32,
+        message_ids: Vec<i64>,
+    ) -> Result<u64, DbErr> {
+        Ok(unread_messages::Entity::delete_many()
+            .filter(unread_messages::Column::UserId.eq(user_id))
+            .filter(unread_messages::Column::MessageId.is_in(message_ids))
+            .exec(self.conn.as_ref())
+            .await?
+            .rows_affected)
+    }
+
+    async fn get_channel_messages(
+        &self,
+        channel_id: i64,
+        before_message_id: Option<i64>,
+        limit: u64,
+    ) -> Result<Vec<chat_messages::Model>, DbErr> {
+        chat_messages::Entity::find()
+            .filter(chat_messages::Column::ChannelId.eq(channel_id))
+            .apply_if(before_message_id, |query, before_message_id| {
+                query.filter(chat_messages::Column::Id.lt(before_message_id))
+            })
+            .order_by_desc(chat_messages::Column::Id)
+            .limit(limit)
+            .all(self.conn.as_ref())
+            .await
+    }
+}
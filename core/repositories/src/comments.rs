@@ -0,0 +1,127 @@
+use peace_db::{
+    peace::{
+        entity::{comments, sea_orm_active_enums::CommentTarget},
+        Peace,
+    },
+    sea_query::{Alias, Expr},
+    *,
+};
+use std::sync::Arc;
+
+pub type DynCommentsRepository = Arc<dyn CommentsRepository + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct CreateComment {
+    pub user_id: i32,
+    pub target: CommentTarget,
+    pub target_id: i64,
+    /// Offset of the comment in the beatmap (ms).
+    pub time: i32,
+    pub comment: String,
+    /// Hex colour without `#`, e.g. `FFAA00`.
+    pub colour: Option<String>,
+    pub privileges: i32,
+}
+
+#[async_trait]
+pub trait CommentsRepository {
+    /// Returns all comments that can be shown while playing (or watching a
+    /// replay of) the beatmap, ordered by time.
+    async fn get_comments(
+        &self,
+        beatmap_id: i32,
+        beatmapset_id: i32,
+        score_id: i64,
+    ) -> Result<Vec<comments::Model>, DbErr>;
+
+    async fn create_comment(
+        &self,
+        comment: CreateComment,
+    ) -> Result<comments::Model, DbErr>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct CommentsRepositoryImpl {
+    pub conn: DbConnection<Peace>,
+}
+
+impl CommentsRepositoryImpl {
+    pub fn new(conn: DbConnection<Peace>) -> CommentsRepositoryImpl {
+        Self { conn }
+    }
+
+    pub fn into_service(self) -> DynCommentsRepository {
+        Arc::new(self) as DynCommentsRepository
+    }
+}
+
+#[async_trait]
+impl CommentsRepository for CommentsRepositoryImpl {
+    async fn get_comments(
+        &self,
+        beatmap_id: i32,
+        beatmapset_id: i32,
+        score_id: i64,
+    ) -> Result<Vec<comments::Model>, DbErr> {
+        // Postgres enums can not be compared with text directly
+        let target = || {
+            Expr::expr(
+                Expr::col(comments::Column::Target).as_enum(Alias::new("text")),
+            )
+        };
+
+        comments::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(target().eq(CommentTarget::Beatmap))
+                            .add(comments::Column::TargetId.eq(beatmap_id)),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(target().is_in([
+                                CommentTarget::Beatmapset,
+                                CommentTarget::Song,
+                            ]))
+                            .add(comments::Column::TargetId.eq(beatmapset_id)),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(target().eq(CommentTarget::Replay))
+                            .add(comments::Column::TargetId.eq(score_id)),
+                    ),
+            )
+            .order_by_asc(comments::Column::Time)
+            .all(self.conn.as_ref())
+            .await
+    }
+
+    async fn create_comment(
+        &self,
+        comment: CreateComment,
+    ) -> Result<comments::Model, DbErr> {
+        let CreateComment {
+            user_id,
+            target,
+            target_id,
+            time,
+            comment,
+            colour,
+            privileges,
+        } = comment;
+
+        comments::ActiveModel {
+            user_id: Set(user_id),
+            target: Set(target),
+            target_id: Set(target_id),
+            time: Set(time),
+            comment: Set(comment),
+            colour: Set(colour),
+            privileges: Set(privileges),
+            ..Default::default()
+        }
+        .insert(self.conn.as_ref())
+        .await
+    }
+}
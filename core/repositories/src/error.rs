@@ -13,3 +13,31 @@ impl From<DbErr> for GetUserError {
         Self::DbErr(err.to_string())
     }
 }
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+pub enum GetBeatmapError {
+    #[error("beatmap not exists")]
+    BeatmapNotExists,
+    #[error("database err: {0}")]
+    DbErr(String),
+}
+
+impl From<DbErr> for GetBeatmapError {
+    fn from(err: DbErr) -> Self {
+        Self::DbErr(err.to_string())
+    }
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+pub enum AddFavouriteBeatmapError {
+    #[error("favourite beatmaps limit exceeded ({0})")]
+    LimitExceeded(u64),
+    #[error("database err: {0}")]
+    DbErr(String),
+}
+
+impl From<DbErr> for AddFavouriteBeatmapError {
+    fn from(err: DbErr) -> Self {
+        Self::DbErr(err.to_string())
+    }
+}
@@ -0,0 +1,205 @@
+use peace_db::{
+    peace::{entity::error_reports, Peace},
+    prelude::DateTimeWithTimeZone,
+    sea_query::Expr,
+    *,
+};
+use std::sync::Arc;
+
+pub type DynErrorReportsRepository =
+    Arc<dyn ErrorReportsRepository + Send + Sync>;
+
+#[derive(Debug, Default, Clone)]
+pub struct CreateErrorReport {
+    pub user_id: Option<i32>,
+    pub username: Option<String>,
+    pub client_version: String,
+    pub exe_hash: Option<String>,
+    pub osu_mode: Option<String>,
+    pub game_mode: Option<String>,
+    pub game_time: Option<i32>,
+    pub audio_time: Option<i32>,
+    pub culture: Option<String>,
+    pub beatmap_id: Option<i32>,
+    pub beatmap_md5: Option<String>,
+    /// md5 of the exception and the top of its stacktrace, used to group
+    /// reports of the same crash.
+    pub signature: String,
+    pub exception: String,
+    pub stacktrace: String,
+    pub feedback: Option<String>,
+    pub config: Option<String>,
+    pub soft: bool,
+    pub beatmap_count: Option<i32>,
+    pub compatibility: bool,
+    pub ram: Option<i64>,
+}
+
+/// Reports of the same crash (signature) on the same client build.
+#[derive(Debug, Clone)]
+pub struct ErrorReportGroup {
+    pub signature: String,
+    pub exception: String,
+    pub client_version: String,
+    pub reports: i64,
+    pub users: i64,
+    pub last_reported_at: DateTimeWithTimeZone,
+}
+
+/// `(signature, exception, client_version, reports, users, last_reported_at)`
+type ErrorReportGroupRow =
+    (String, String, String, i64, i64, DateTimeWithTimeZone);
+
+#[derive(Debug, Default, Clone)]
+pub struct QueryErrorReportGroups {
+    pub client_version: Option<String>,
+    pub offset: u64,
+    pub limit: u64,
+}
+
+#[async_trait]
+pub trait ErrorReportsRepository {
+    async fn create_error_report(
+        &self,
+        report: CreateErrorReport,
+    ) -> Result<error_reports::Model, DbErr>;
+
+    /// Returns the report groups, most reported first.
+    async fn get_error_report_groups(
+        &self,
+        query: QueryErrorReportGroups,
+    ) -> Result<Vec<ErrorReportGroup>, DbErr>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ErrorReportsRepositoryImpl {
+    pub conn: DbConnection<Peace>,
+}
+
+impl ErrorReportsRepositoryImpl {
+    pub fn new(conn: DbConnection<Peace>) -> ErrorReportsRepositoryImpl {
+        Self { conn }
+    }
+
+    pub fn into_service(self) -> DynErrorReportsRepository {
+        Arc::new(self) as DynErrorReportsRepository
+    }
+}
+
+#[async_trait]
+impl ErrorReportsRepository for ErrorReportsRepositoryImpl {
+    async fn create_error_report(
+        &self,
+        report: CreateErrorReport,
+    ) -> Result<error_reports::Model, DbErr> {
+        let CreateErrorReport {
+            user_id,
+            username,
+            client_version,
+            exe_hash,
+            osu_mode,
+            game_mode,
+            game_time,
+            audio_time,
+            culture,
+            beatmap_id,
+            beatmap_md5,
+            signature,
+            exception,
+            stacktrace,
+            feedback,
+            config,
+            soft,
+            beatmap_count,
+            compatibility,
+            ram,
+        } = report;
+
+        error_reports::ActiveModel {
+            user_id: Set(user_id),
+            username: Set(username),
+            client_version: Set(client_version),
+            exe_hash: Set(exe_hash),
+            osu_mode: Set(osu_mode),
+            game_mode: Set(game_mode),
+            game_time: Set(game_time),
+            audio_time: Set(audio_time),
+            culture: Set(culture),
+            beatmap_id: Set(beatmap_id),
+            beatmap_md5: Set(beatmap_md5),
+            signature: Set(signature),
+            exception: Set(exception),
+            stacktrace: Set(stacktrace),
+            feedback: Set(feedback),
+            config: Set(config),
+            soft: Set(soft),
+            beatmap_count: Set(beatmap_count),
+            compatibility: Set(compatibility),
+            ram: Set(ram),
+            ..Default::default()
+        }
+        .insert(self.conn.as_ref())
+        .await
+    }
+
+    async fn get_error_report_groups(
+        &self,
+        query: QueryErrorReportGroups,
+    ) -> Result<Vec<ErrorReportGroup>, DbErr> {
+        let QueryErrorReportGroups { client_version, offset, limit } = query;
+
+        let groups = error_reports::Entity::find()
+            .select_only()
+            .column(error_reports::Column::Signature)
+            .column_as(
+                Expr::col(error_reports::Column::Exception).max(),
+                "exception",
+            )
+            .column(error_reports::Column::ClientVersion)
+            .column_as(Expr::col(error_reports::Column::Id).count(), "reports")
+            .column_as(
+                Expr::cust_with_expr(
+                    "COUNT(DISTINCT $1)",
+                    Expr::col(error_reports::Column::UserId),
+                ),
+                "users",
+            )
+            .column_as(
+                Expr::col(error_reports::Column::CreatedAt).max(),
+                "last_reported_at",
+            )
+            .filter(Condition::all().add_option(client_version.map(
+                |version| error_reports::Column::ClientVersion.eq(version),
+            )))
+            .group_by(error_reports::Column::Signature)
+            .group_by(error_reports::Column::ClientVersion)
+            .order_by_desc(Expr::col(error_reports::Column::Id).count())
+            .order_by_desc(Expr::col(error_reports::Column::CreatedAt).max())
+            .offset(offset)
+            .limit(limit)
+            .into_tuple::<ErrorReportGroupRow>()
+            .all(self.conn.as_ref())
+            .await?;
+
+        Ok(groups
+            .into_iter()
+            .map(
+                |(
+                    signature,
+                    exception,
+                    client_version,
+                    reports,
+                    users,
+                    last_reported_at,
+                )| ErrorReportGroup {
+                    signature,
+                    exception,
+                    client_version,
+                    reports,
+                    users,
+                    last_reported_at,
+                },
+            )
+            .collect())
+    }
+}
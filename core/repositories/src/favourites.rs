@@ -0,0 +1,117 @@
+use crate::AddFavouriteBeatmapError;
+use peace_db::{
+    peace::{
+        entity::{favourite_beatmaps, users},
+        Peace,
+    },
+    sea_query::OnConflict,
+    *,
+};
+use std::sync::Arc;
+
+pub type DynFavouritesRepository = Arc<dyn FavouritesRepository + Send + Sync>;
+
+#[async_trait]
+pub trait FavouritesRepository {
+    /// Returns the favourite beatmapset ids of the user (oldest first).
+    async fn get_favourite_beatmapsets(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<i32>, DbErr>;
+
+    /// Adding an already favourited beatmapset is a no-op.
+    async fn add_favourite_beatmapset(
+        &self,
+        user_id: i32,
+        beatmapset_id: i32,
+        limit: u64,
+    ) -> Result<(), AddFavouriteBeatmapError>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FavouritesRepositoryImpl {
+    pub conn: DbConnection<Peace>,
+}
+
+impl FavouritesRepositoryImpl {
+    pub fn new(conn: DbConnection<Peace>) -> FavouritesRepositoryImpl {
+        Self { conn }
+    }
+
+    pub fn into_service(self) -> DynFavouritesRepository {
+        Arc::new(self) as DynFavouritesRepository
+    }
+}
+
+#[async_trait]
+impl FavouritesRepository for FavouritesRepositoryImpl {
+    async fn get_favourite_beatmapsets(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<i32>, DbErr> {
+        favourite_beatmaps::Entity::find()
+            .select_only()
+            .column(favourite_beatmaps::Column::BeatmapsetId)
+            .filter(favourite_beatmaps::Column::UserId.eq(user_id))
+            .order_by_asc(favourite_beatmaps::Column::CreatedAt)
+            .into_tuple::<i32>()
+            .all(self.conn.as_ref())
+            .await
+    }
+
+    async fn add_favourite_beatmapset(
+        &self,
+        user_id: i32,
+        beatmapset_id: i32,
+        limit: u64,
+    ) -> Result<(), AddFavouriteBeatmapError> {
+        let txn = self.conn.begin().await?;
+
+        // serializes the adds of the user, so the count below stays valid
+        // until the insert is committed
+        users::Entity::find_by_id(user_id)
+            .select_only()
+            .column(users::Column::Id)
+            .lock_exclusive()
+            .into_tuple::<i32>()
+            .one(&txn)
+            .await?;
+
+        if favourite_beatmaps::Entity::find_by_id((user_id, beatmapset_id))
+            .one(&txn)
+            .await?
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        if favourite_beatmaps::Entity::find()
+            .filter(favourite_beatmaps::Column::UserId.eq(user_id))
+            .count(&txn)
+            .await?
+            >= limit
+        {
+            return Err(AddFavouriteBeatmapError::LimitExceeded(limit));
+        }
+
+        favourite_beatmaps::Entity::insert(favourite_beatmaps::ActiveModel {
+            user_id: Set(user_id),
+            beatmapset_id: Set(beatmapset_id),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([
+                favourite_beatmaps::Column::UserId,
+                favourite_beatmaps::Column::BeatmapsetId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+use domain_bancho::GameMode as BanchoGameMode;
+use peace_db::{
+    peace::{
+        entity::{
+            beatmaps, leaderboard_fruits, leaderboard_fruits_relax,
+            leaderboard_mania, leaderboard_standard,
+            leaderboard_standard_autopilot, leaderboard_standard_relax,
+            leaderboard_taiko, leaderboard_taiko_relax, score_pp_fruits,
+            score_pp_fruits_relax, score_pp_mania, score_pp_standard,
+            score_pp_standard_autopilot, score_pp_standard_relax,
+            score_pp_taiko, score_pp_taiko_relax, scores_fruits,
+            scores_fruits_relax, scores_mania, scores_standard,
+            scores_standard_autopilot, scores_standard_relax, scores_taiko,
+            scores_taiko_relax,
+            sea_orm_active_enums::{
+                PpVersion, RankingType, ScoreStatus, ScoreVersion,
+            },
+            users,
+        },
+        Peace,
+    },
+    sea_query::{Alias, Expr, OnConflict},
+    *,
+};
+use std::{collections::HashMap, sync::Arc};
+
+pub type DynLeaderboardsRepository =
+    Arc<dyn LeaderboardsRepository + Send + Sync>;
+
+/// Expands `$query!($scores, $score_pp, $leaderboard, ...)` with the tables
+/// of the game mode, score v2 scores are ranked on the osu!standard
+/// leaderboard with the `score_v2` ranking type.
+macro_rules! with_leaderboard_tables {
+    ($mode: expr, $query: ident!($($args: tt)*)) => {
+        match $mode {
+            BanchoGameMode::Standard | BanchoGameMode::StandardScoreV2 => {
+                $query!(
+                    scores_standard,
+                    score_pp_standard,
+                    leaderboard_standard,
+                    $($args)*
+                )
+            },
+            BanchoGameMode::StandardRelax => $query!(
+                scores_standard_relax,
+                score_pp_standard_relax,
+                leaderboard_standard_relax,
+                $($args)*
+            ),
+            BanchoGameMode::StandardAutopilot => $query!(
+                scores_standard_autopilot,
+                score_pp_standard_autopilot,
+                leaderboard_standard_autopilot,
+                $($args)*
+            ),
+            BanchoGameMode::Taiko => $query!(
+                scores_taiko,
+                score_pp_taiko,
+                leaderboard_taiko,
+                $($args)*
+            ),
+            BanchoGameMode::TaikoRelax => $query!(
+                scores_taiko_relax,
+                score_pp_taiko_relax,
+                leaderboard_taiko_relax,
+                $($args)*
+            ),
+            BanchoGameMode::Fruits => $query!(
+                scores_fruits,
+                score_pp_fruits,
+                leaderboard_fruits,
+                $($args)*
+            ),
+            BanchoGameMode::FruitsRelax => $query!(
+                scores_fruits_relax,
+                score_pp_fruits_relax,
+                leaderboard_fruits_relax,
+                $($args)*
+            ),
+            BanchoGameMode::Mania => $query!(
+                scores_mania,
+                score_pp_mania,
+                leaderboard_mania,
+                $($args)*
+            ),
+        }
+    };
+}
+
+/// The ranking types with leaderboard entries.
+///
+/// `pp_v2` is not ranked: the performance service only calculates and stores
+/// v1 pp, so there would never be a score to rank. Add it here once v2 pp
+/// are written to the `score_pp_*` tables.
+const RANKED_TYPES: [RankingType; 3] =
+    [RankingType::PpV1, RankingType::ScoreV1, RankingType::ScoreV2];
+
+/// Select the `(map_md5, score_id)` of the user's best passed score on each
+/// of the beatmaps for the ranking type in one query, the older score wins a
+/// tie.
+macro_rules! best_scores_query {
+    (
+        $scores: ident,
+        $score_pp: ident,
+        $user_id: expr,
+        $map_md5s: expr,
+        $ranking_type: expr,
+        $conn: expr
+    ) => {{
+        let select = $scores::Entity::find()
+            .select_only()
+            .column($scores::Column::MapMd5)
+            .column($scores::Column::Id)
+            .distinct_on([($scores::Entity, $scores::Column::MapMd5)])
+            .filter($scores::Column::UserId.eq($user_id))
+            .filter($scores::Column::MapMd5.is_in($map_md5s))
+            // Postgres enums can not be compared with text directly
+            .filter(
+                Expr::expr(
+                    Expr::col(($scores::Entity, $scores::Column::Status))
+                        .as_enum(Alias::new("text")),
+                )
+                .ne(ScoreStatus::Failed),
+            )
+            // `DISTINCT ON` keeps the first row of each beatmap
+            .order_by_asc($scores::Column::MapMd5);
+
+        let select = match $ranking_type {
+            RankingType::ScoreV1 | RankingType::ScoreV2 => select
+                .filter(
+                    Expr::expr(
+                        Expr::col((
+                            $scores::Entity,
+                            $scores::Column::ScoreVersion,
+                        ))
+                        .as_enum(Alias::new("text")),
+                    )
+                    .eq(score_version_of(&$ranking_type)),
+                )
+                .order_by_desc($scores::Column::Score),
+            RankingType::PpV1 | RankingType::PpV2 => select
+                .join(
+                    JoinType::InnerJoin,
+                    <$scores::Entity as Related<$score_pp::Entity>>::to(),
+                )
+                .filter(
+                    Expr::expr(
+                        Expr::col((
+                            $score_pp::Entity,
+                            $score_pp::Column::PpVersion,
+                        ))
+                        .as_enum(Alias::new("text")),
+                    )
+                    .eq(pp_version_of(&$ranking_type)),
+                )
+                .order_by_desc(Expr::col((
+                    $score_pp::Entity,
+                    $score_pp::Column::Pp,
+                ))),
+        };
+
+        select
+            .order_by_asc($scores::Column::Id)
+            .into_tuple::<(String, i64)>()
+            .all($conn)
+            .await?
+    }};
+}
+
+macro_rules! update_leaderboards_query {
+    (
+        $scores: ident,
+        $score_pp: ident,
+        $leaderboard: ident,
+        $user_id: expr,
+        $beatmap_md5s: expr,
+        $conn: expr
+    ) => {{
+        let rebuild = $beatmap_md5s.is_none();
+        let beatmap_md5s = match $beatmap_md5s {
+            Some(beatmap_md5s) => beatmap_md5s,
+            None => {
+                $scores::Entity::find()
+                    .select_only()
+                    .column($scores::Column::MapMd5)
+                    .distinct()
+                    .filter($scores::Column::UserId.eq($user_id))
+                    .into_tuple::<String>()
+                    .all($conn)
+                    .await?
+            },
+        };
+
+        // Scores of beatmaps unknown to the server can not be ranked
+        let beatmaps = beatmaps::Entity::find()
+            .select_only()
+            .column(beatmaps::Column::Md5)
+            .column(beatmaps::Column::Bid)
+            .filter(beatmaps::Column::Md5.is_in(beatmap_md5s))
+            .into_tuple::<(String, i32)>()
+            .all($conn)
+            .await?
+            .into_iter()
+            .collect::<HashMap<String, i32>>();
+
+        if rebuild {
+            $leaderboard::Entity::delete_many()
+                .filter($leaderboard::Column::UserId.eq($user_id))
+                .filter(
+                    $leaderboard::Column::BeatmapId
+                        .is_not_in(beatmaps.values().copied()),
+                )
+                .exec($conn)
+                .await?;
+        }
+
+        if !beatmaps.is_empty() {
+            for ranking_type in RANKED_TYPES {
+                let entries = best_scores_query!(
+                    $scores,
+                    $score_pp,
+                    $user_id,
+                    beatmaps.keys().cloned(),
+                    ranking_type,
+                    $conn
+                )
+                .into_iter()
+                .filter_map(|(map_md5, score_id)| {
+                    beatmaps.get(&map_md5).map(|bid| (*bid, score_id))
+                })
+                .collect::<HashMap<i32, i64>>();
+
+                // Beatmaps without any passed score left
+                $leaderboard::Entity::delete_many()
+                    .filter($leaderboard::Column::UserId.eq($user_id))
+                    .filter(
+                        $leaderboard::Column::BeatmapId.is_in(
+                            beatmaps
+                                .values()
+                                .filter(|bid| !entries.contains_key(bid))
+                                .copied(),
+                        ),
+                    )
+                    .filter(
+                        Expr::expr(
+                            Expr::col($leaderboard::Column::RankingType)
+                                .as_enum(Alias::new("text")),
+                        )
+                        .eq(ranking_type.clone()),
+                    )
+                    .exec($conn)
+                    .await?;
+
+                if entries.is_empty() {
+                    continue;
+                }
+
+                $leaderboard::Entity::insert_many(entries.into_iter().map(
+                    |(beatmap_id, score_id)| $leaderboard::ActiveModel {
+                        beatmap_id: Set(beatmap_id),
+                        ranking_type: Set(ranking_type.clone()),
+                        user_id: Set($user_id),
+                        score_id: Set(score_id),
+                    },
+                ))
+                .on_conflict(
+                    OnConflict::columns([
+                        $leaderboard::Column::BeatmapId,
+                        $leaderboard::Column::RankingType,
+                        $leaderboard::Column::UserId,
+                    ])
+                    .update_column($leaderboard::Column::ScoreId)
+                    .to_owned(),
+                )
+                .exec_without_returning($conn)
+                .await?;
+            }
+        }
+
+        beatmaps.len()
+    }};
+}
+
+#[inline]
+fn score_version_of(ranking_type: &RankingType) -> ScoreVersion {
+    match ranking_type {
+        RankingType::ScoreV2 => ScoreVersion::V2,
+        _ => ScoreVersion::V1,
+    }
+}
+
+#[inline]
+fn pp_version_of(ranking_type: &RankingType) -> PpVersion {
+    match ranking_type {
+        RankingType::PpV2 => PpVersion::V2,
+        _ => PpVersion::V1,
+    }
+}
+
+#[async_trait]
+pub trait LeaderboardsRepository {
+    /// Sets the user's entries of every ranked type to their best passed
+    /// score on each of the beatmaps, entries without any score left are
+    /// removed. `pp_v2` has no entries until v2 pp are calculated.
+    ///
+    /// `None` rebuilds every beatmap the user has played and drops the
+    /// entries of the others. Returns the count of updated beatmaps.
+    ///
+    /// The user's row is locked until the update is committed, concurrent
+    /// updates of the same user are applied one after another.
+    async fn update_user_leaderboards(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+        beatmap_md5s: Option<Vec<String>>,
+    ) -> Result<usize, DbErr>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct LeaderboardsRepositoryImpl {
+    pub conn: DbConnection<Peace>,
+}
+
+impl LeaderboardsRepositoryImpl {
+    pub fn new(conn: DbConnection<Peace>) -> LeaderboardsRepositoryImpl {
+        Self { conn }
+    }
+
+    pub fn into_service(self) -> DynLeaderboardsRepository {
+        Arc::new(self) as DynLeaderboardsRepository
+    }
+}
+
+#[async_trait]
+impl LeaderboardsRepository for LeaderboardsRepositoryImpl {
+    async fn update_user_leaderboards(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+        beatmap_md5s: Option<Vec<String>>,
+    ) -> Result<usize, DbErr> {
+        let txn = self.conn.begin().await?;
+
+        users::Entity::find_by_id(user_id)
+            .select_only()
+            .column(users::Column::Id)
+            .lock_exclusive()
+            .into_tuple::<i32>()
+            .one(&txn)
+            .await?;
+
+        let updated = with_leaderboard_tables!(
+            mode,
+            update_leaderboards_query!(user_id, beatmap_md5s, &txn)
+        );
+
+        txn.commit().await?;
+
+        Ok(updated)
+    }
+}
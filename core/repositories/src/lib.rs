@@ -2,7 +2,18 @@
 #[macro_use]
 extern crate peace_logs;
 
+#[macro_use]
+extern crate serde;
+
+pub mod beatmaps;
+pub mod chat;
+pub mod comments;
 pub mod error;
+pub mod error_reports;
+pub mod favourites;
+pub mod leaderboards;
+pub mod scores;
+pub mod user_stats;
 pub mod users;
 
 pub use error::*;
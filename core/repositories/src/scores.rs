@@ -0,0 +1,326 @@
+use domain_bancho::GameMode as BanchoGameMode;
+use peace_db::{
+    peace::{
+        entity::{
+            score_pp_fruits, score_pp_fruits_relax, score_pp_mania,
+            score_pp_standard, score_pp_standard_autopilot,
+            score_pp_standard_relax, score_pp_taiko, score_pp_taiko_relax,
+            scores_fruits, scores_fruits_relax, scores_mania, scores_standard,
+            scores_standard_autopilot, scores_standard_relax, scores_taiko,
+            scores_taiko_relax,
+            sea_orm_active_enums::{
+                GameMode, PpVersion, ScoreGrade, ScoreStatus,
+            },
+        },
+        Peace,
+    },
+    prelude::{DateTimeWithTimeZone, Decimal, Json},
+    sea_query::{Alias, Expr, OnConflict},
+    *,
+};
+use std::{collections::HashMap, sync::Arc};
+
+pub type DynScoresRepository = Arc<dyn ScoresRepository + Send + Sync>;
+
+/// Columns shared by every `scores_*` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreRow {
+    pub id: i64,
+    pub user_id: i32,
+    pub map_md5: String,
+    pub score: i32,
+    pub accuracy: Decimal,
+    pub combo: i32,
+    pub mods: i32,
+    pub n300: i32,
+    pub n100: i32,
+    pub n50: i32,
+    pub miss: i32,
+    pub geki: i32,
+    pub katu: i32,
+    pub playtime: i32,
+    pub perfect: bool,
+    pub status: ScoreStatus,
+    pub grade: ScoreGrade,
+    pub create_at: DateTimeWithTimeZone,
+}
+
+macro_rules! impl_from_score_model {
+    ($($scores: ident),+) => {
+        $(impl From<$scores::Model> for ScoreRow {
+            fn from(model: $scores::Model) -> Self {
+                Self {
+                    id: model.id,
+                    user_id: model.user_id,
+                    map_md5: model.map_md5,
+                    score: model.score,
+                    accuracy: model.accuracy,
+                    combo: model.combo,
+                    mods: model.mods,
+                    n300: model.n300,
+                    n100: model.n100,
+                    n50: model.n50,
+                    miss: model.miss,
+                    geki: model.geki,
+                    katu: model.katu,
+                    playtime: model.playtime,
+                    perfect: model.perfect,
+                    status: model.status,
+                    grade: model.grade,
+                    create_at: model.create_at,
+                }
+            }
+        })+
+    };
+}
+
+impl_from_score_model!(
+    scores_standard,
+    scores_standard_relax,
+    scores_standard_autopilot,
+    scores_taiko,
+    scores_taiko_relax,
+    scores_fruits,
+    scores_fruits_relax,
+    scores_mania
+);
+
+/// Expands `$query!($scores, $score_pp, ...)` with the tables of the game
+/// mode, relax and autopilot scores are stored apart while score v2 scores
+/// share the tables of osu!standard.
+macro_rules! with_score_tables {
+    ($mode: expr, $query: ident!($($args: tt)*)) => {
+        match $mode {
+            BanchoGameMode::Standard | BanchoGameMode::StandardScoreV2 => {
+                $query!(scores_standard, score_pp_standard, $($args)*)
+            },
+            BanchoGameMode::StandardRelax => $query!(
+                scores_standard_relax,
+                score_pp_standard_relax,
+                $($args)*
+            ),
+            BanchoGameMode::StandardAutopilot => $query!(
+                scores_standard_autopilot,
+                score_pp_standard_autopilot,
+                $($args)*
+            ),
+            BanchoGameMode::Taiko => {
+                $query!(scores_taiko, score_pp_taiko, $($args)*)
+            },
+            BanchoGameMode::TaikoRelax => {
+                $query!(scores_taiko_relax, score_pp_taiko_relax, $($args)*)
+            },
+            BanchoGameMode::Fruits => {
+                $query!(scores_fruits, score_pp_fruits, $($args)*)
+            },
+            BanchoGameMode::FruitsRelax => {
+                $query!(scores_fruits_relax, score_pp_fruits_relax, $($args)*)
+            },
+            BanchoGameMode::Mania => {
+                $query!(scores_mania, score_pp_mania, $($args)*)
+            },
+        }
+    };
+}
+
+macro_rules! get_score_query {
+    ($scores: ident, $score_pp: ident, $score_id: expr, $conn: expr) => {
+        $scores::Entity::find_by_id($score_id)
+            .one($conn)
+            .await?
+            .map(ScoreRow::from)
+    };
+}
+
+macro_rules! user_score_ids_query {
+    ($scores: ident, $score_pp: ident, $user_id: expr, $conn: expr) => {
+        $scores::Entity::find()
+            .select_only()
+            .column($scores::Column::Id)
+            .filter($scores::Column::UserId.eq($user_id))
+            .order_by_asc($scores::Column::Id)
+            .into_tuple::<i64>()
+            .all($conn)
+            .await?
+    };
+}
+
+macro_rules! save_score_pp_query {
+    (
+        $scores: ident,
+        $score_pp: ident,
+        $score_id: expr,
+        $pp_version: expr,
+        $pp: expr,
+        $raw_pp: expr,
+        $conn: expr
+    ) => {
+        $score_pp::Entity::insert($score_pp::ActiveModel {
+            score_id: Set($score_id),
+            pp_version: Set($pp_version),
+            pp: Set($pp),
+            raw_pp: Set($raw_pp),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                $score_pp::Column::ScoreId,
+                $score_pp::Column::PpVersion,
+            ])
+            .update_columns([$score_pp::Column::Pp, $score_pp::Column::RawPp])
+            .to_owned(),
+        )
+        .exec_without_returning($conn)
+        .await?
+    };
+}
+
+/// Select `(map_md5, grade)` of the user's best (`High`) scores on the given
+/// beatmaps from one of the `scores_*` tables.
+macro_rules! best_grades_query {
+    ($scores: ident, $user_id: expr, $beatmap_md5s: expr) => {
+        $scores::Entity::find()
+            .select_only()
+            .column($scores::Column::MapMd5)
+            .column($scores::Column::Grade)
+            .filter($scores::Column::UserId.eq($user_id))
+            .filter($scores::Column::MapMd5.is_in($beatmap_md5s))
+            // Postgres enums can not be compared with text directly
+            .filter(
+                Expr::expr(
+                    Expr::col($scores::Column::Status)
+                        .as_enum(Alias::new("text")),
+                )
+                .eq(ScoreStatus::High),
+            )
+            .into_tuple::<(String, ScoreGrade)>()
+    };
+}
+
+#[async_trait]
+pub trait ScoresRepository {
+    /// Returns the grades of the user's best scores keyed by beatmap md5,
+    /// beatmaps without a score are not included.
+    async fn get_user_best_grades(
+        &self,
+        user_id: i32,
+        game_mode: GameMode,
+        beatmap_md5s: Vec<String>,
+    ) -> Result<HashMap<String, ScoreGrade>, DbErr>;
+
+    async fn get_score(
+        &self,
+        mode: BanchoGameMode,
+        score_id: i64,
+    ) -> Result<Option<ScoreRow>, DbErr>;
+
+    /// Returns the ids of all the user's scores in the table of `mode`.
+    async fn get_user_score_ids(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+    ) -> Result<Vec<i64>, DbErr>;
+
+    /// Inserts or replaces the pp of the score for the `pp_version`.
+    async fn save_score_pp(
+        &self,
+        mode: BanchoGameMode,
+        score_id: i64,
+        pp_version: PpVersion,
+        pp: f64,
+        raw_pp: Option<Json>,
+    ) -> Result<(), DbErr>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ScoresRepositoryImpl {
+    pub conn: DbConnection<Peace>,
+}
+
+impl ScoresRepositoryImpl {
+    pub fn new(conn: DbConnection<Peace>) -> ScoresRepositoryImpl {
+        Self { conn }
+    }
+
+    pub fn into_service(self) -> DynScoresRepository {
+        Arc::new(self) as DynScoresRepository
+    }
+}
+
+#[async_trait]
+impl ScoresRepository for ScoresRepositoryImpl {
+    async fn get_user_best_grades(
+        &self,
+        user_id: i32,
+        game_mode: GameMode,
+        beatmap_md5s: Vec<String>,
+    ) -> Result<HashMap<String, ScoreGrade>, DbErr> {
+        if beatmap_md5s.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let conn = self.conn.as_ref();
+
+        let grades = match game_mode {
+            GameMode::Standard => {
+                best_grades_query!(scores_standard, user_id, beatmap_md5s)
+                    .all(conn)
+                    .await?
+            },
+            GameMode::Taiko => {
+                best_grades_query!(scores_taiko, user_id, beatmap_md5s)
+                    .all(conn)
+                    .await?
+            },
+            GameMode::Fruits => {
+                best_grades_query!(scores_fruits, user_id, beatmap_md5s)
+                    .all(conn)
+                    .await?
+            },
+            GameMode::Mania => {
+                best_grades_query!(scores_mania, user_id, beatmap_md5s)
+                    .all(conn)
+                    .await?
+            },
+        };
+
+        Ok(grades.into_iter().collect())
+    }
+    async fn get_score(
+        &self,
+        mode: BanchoGameMode,
+        score_id: i64,
+    ) -> Result<Option<ScoreRow>, DbErr> {
+        let conn = self.conn.as_ref();
+
+        Ok(with_score_tables!(mode, get_score_query!(score_id, conn)))
+    }
+
+    async fn get_user_score_ids(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+    ) -> Result<Vec<i64>, DbErr> {
+        let conn = self.conn.as_ref();
+
+        Ok(with_score_tables!(mode, user_score_ids_query!(user_id, conn)))
+    }
+
+    async fn save_score_pp(
+        &self,
+        mode: BanchoGameMode,
+        score_id: i64,
+        pp_version: PpVersion,
+        pp: f64,
+        raw_pp: Option<Json>,
+    ) -> Result<(), DbErr> {
+        let conn = self.conn.as_ref();
+        let pp = Decimal::from_f64_retain(pp).unwrap_or_default().round_dp(2);
+
+        with_score_tables!(
+            mode,
+            save_score_pp_query!(score_id, pp_version, pp, raw_pp, conn)
+        );
+
+        Ok(())
+    }
+}
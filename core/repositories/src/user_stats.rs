@@ -0,0 +1,578 @@
+use domain_bancho::GameMode as BanchoGameMode;
+use peace_db::{
+    peace::{
+        entity::{
+            beatmaps, score_pp_fruits, score_pp_fruits_relax, score_pp_mania,
+            score_pp_standard, score_pp_standard_autopilot,
+            score_pp_standard_relax, score_pp_taiko, score_pp_taiko_relax,
+            scores_fruits, scores_fruits_relax, scores_mania, scores_standard,
+            scores_standard_autopilot, scores_standard_relax, scores_taiko,
+            scores_taiko_relax,
+            sea_orm_active_enums::{
+                PpVersion, RankStatus, ScoreStatus, ScoreVersion,
+            },
+            user_pp_fruits, user_pp_fruits_relax, user_pp_mania,
+            user_pp_standard, user_pp_standard_autopilot,
+            user_pp_standard_relax, user_pp_taiko, user_pp_taiko_relax,
+            user_stats_fruits, user_stats_fruits_relax, user_stats_mania,
+            user_stats_standard, user_stats_standard_autopilot,
+            user_stats_standard_relax, user_stats_standard_score_v2,
+            user_stats_taiko, user_stats_taiko_relax,
+        },
+        Peace,
+    },
+    prelude::{Decimal, Json},
+    sea_query::{Alias, CaseStatement, Expr, Func, OnConflict, Query},
+    *,
+};
+use std::sync::Arc;
+
+pub type DynUserStatsRepository = Arc<dyn UserStatsRepository + Send + Sync>;
+
+/// Totals of all the user's scores of a game mode, failed ones included.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ScoreTotals {
+    pub playcount: i64,
+    pub total_score: Option<i64>,
+    pub total_hits: Option<i64>,
+    pub max_combo: Option<i32>,
+    pub total_seconds_played: Option<i64>,
+    pub count300: Option<i64>,
+    pub count100: Option<i64>,
+    pub count50: Option<i64>,
+    pub count_miss: Option<i64>,
+    pub count_failed: Option<i64>,
+}
+
+/// A best score on a ranked beatmap, which is weighted into the user's pp.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedScorePp {
+    pub pp: Decimal,
+    pub accuracy: Decimal,
+}
+
+impl FromQueryResult for ScoreTotals {
+    fn from_query_result(res: &QueryResult, pre: &str) -> Result<Self, DbErr> {
+        Ok(Self {
+            playcount: res.try_get(pre, "playcount")?,
+            total_score: res.try_get(pre, "total_score")?,
+            total_hits: res.try_get(pre, "total_hits")?,
+            max_combo: res.try_get(pre, "max_combo")?,
+            total_seconds_played: res.try_get(pre, "total_seconds_played")?,
+            count300: res.try_get(pre, "count300")?,
+            count100: res.try_get(pre, "count100")?,
+            count50: res.try_get(pre, "count50")?,
+            count_miss: res.try_get(pre, "count_miss")?,
+            count_failed: res.try_get(pre, "count_failed")?,
+        })
+    }
+}
+
+impl FromQueryResult for RankedScorePp {
+    fn from_query_result(res: &QueryResult, pre: &str) -> Result<Self, DbErr> {
+        Ok(Self {
+            pp: res.try_get(pre, "pp")?,
+            accuracy: res.try_get(pre, "accuracy")?,
+        })
+    }
+}
+
+/// Columns of the `user_stats_*` tables written by the recalculation,
+/// `count_quit` is kept as it is.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UserModeStats {
+    pub total_score: i64,
+    pub ranked_score: i64,
+    pub playcount: i32,
+    pub total_hits: i32,
+    pub accuracy: f64,
+    pub max_combo: i32,
+    pub total_seconds_played: i32,
+    pub count300: i32,
+    pub count100: i32,
+    pub count50: i32,
+    pub count_miss: i32,
+    pub count_failed: i32,
+}
+
+/// Expands `$query!($scores, $score_pp, $user_stats, $version, ...)` with the
+/// tables of the game mode.
+///
+/// osu!standard and score v2 scores share `scores_standard` and are told
+/// apart by `$version`, the other tables hold every score version.
+macro_rules! with_user_stats_tables {
+    ($mode: expr, $query: ident!($($args: tt)*)) => {
+        match $mode {
+            BanchoGameMode::Standard => $query!(
+                scores_standard,
+                score_pp_standard,
+                user_stats_standard,
+                Some(ScoreVersion::V1),
+                $($args)*
+            ),
+            BanchoGameMode::StandardScoreV2 => $query!(
+                scores_standard,
+                score_pp_standard,
+                user_stats_standard_score_v2,
+                Some(ScoreVersion::V2),
+                $($args)*
+            ),
+            BanchoGameMode::StandardRelax => $query!(
+                scores_standard_relax,
+                score_pp_standard_relax,
+                user_stats_standard_relax,
+                None,
+                $($args)*
+            ),
+            BanchoGameMode::StandardAutopilot => $query!(
+                scores_standard_autopilot,
+                score_pp_standard_autopilot,
+                user_stats_standard_autopilot,
+                None,
+                $($args)*
+            ),
+            BanchoGameMode::Taiko => $query!(
+                scores_taiko,
+                score_pp_taiko,
+                user_stats_taiko,
+                None,
+                $($args)*
+            ),
+            BanchoGameMode::TaikoRelax => $query!(
+                scores_taiko_relax,
+                score_pp_taiko_relax,
+                user_stats_taiko_relax,
+                None,
+                $($args)*
+            ),
+            BanchoGameMode::Fruits => $query!(
+                scores_fruits,
+                score_pp_fruits,
+                user_stats_fruits,
+                None,
+                $($args)*
+            ),
+            BanchoGameMode::FruitsRelax => $query!(
+                scores_fruits_relax,
+                score_pp_fruits_relax,
+                user_stats_fruits_relax,
+                None,
+                $($args)*
+            ),
+            BanchoGameMode::Mania => $query!(
+                scores_mania,
+                score_pp_mania,
+                user_stats_mania,
+                None,
+                $($args)*
+            ),
+        }
+    };
+}
+
+/// `WHERE user_id = $user_id [AND score_version = $version]`
+macro_rules! user_scores_condition {
+    ($scores: ident, $version: expr, $user_id: expr) => {
+        Condition::all()
+            .add(
+                Expr::col(($scores::Entity, $scores::Column::UserId))
+                    .eq($user_id),
+            )
+            .add_option($version.map(|version: ScoreVersion| {
+                // Postgres enums can not be compared with text directly
+                Expr::expr(
+                    Expr::col(($scores::Entity, $scores::Column::ScoreVersion))
+                        .as_enum(Alias::new("text")),
+                )
+                .eq(version)
+            }))
+    };
+}
+
+/// The user's best (`High`) scores on ranked or approved beatmaps.
+macro_rules! ranked_best_scores_condition {
+    ($scores: ident, $version: expr, $user_id: expr) => {
+        user_scores_condition!($scores, $version, $user_id)
+            .add(
+                Expr::expr(
+                    Expr::col(($scores::Entity, $scores::Column::Status))
+                        .as_enum(Alias::new("text")),
+                )
+                .eq(ScoreStatus::High),
+            )
+            .add(
+                Expr::col(($scores::Entity, $scores::Column::MapMd5))
+                    .in_subquery(
+                        Query::select()
+                            .column(beatmaps::Column::Md5)
+                            .from(beatmaps::Entity)
+                            .and_where(
+                                Expr::expr(
+                                    Expr::col(beatmaps::Column::RankStatus)
+                                        .as_enum(Alias::new("text")),
+                                )
+                                .is_in([
+                                    RankStatus::Ranked,
+                                    RankStatus::Approved,
+                                ]),
+                            )
+                            .to_owned(),
+                    ),
+            )
+    };
+}
+
+macro_rules! score_totals_query {
+    (
+        $scores: ident,
+        $score_pp: ident,
+        $user_stats: ident,
+        $version: expr,
+        $user_id: expr,
+        $conn: expr
+    ) => {
+        $scores::Entity::find()
+            .select_only()
+            .column_as(Expr::col($scores::Column::Id).count(), "playcount")
+            .column_as(Expr::col($scores::Column::Score).sum(), "total_score")
+            .column_as(
+                Expr::expr(Func::sum(
+                    Expr::col($scores::Column::N300)
+                        .add(Expr::col($scores::Column::N100))
+                        .add(Expr::col($scores::Column::N50)),
+                )),
+                "total_hits",
+            )
+            .column_as(Expr::col($scores::Column::Combo).max(), "max_combo")
+            .column_as(
+                Expr::col($scores::Column::Playtime).sum(),
+                "total_seconds_played",
+            )
+            .column_as(Expr::col($scores::Column::N300).sum(), "count300")
+            .column_as(Expr::col($scores::Column::N100).sum(), "count100")
+            .column_as(Expr::col($scores::Column::N50).sum(), "count50")
+            .column_as(Expr::col($scores::Column::Miss).sum(), "count_miss")
+            .column_as(
+                Expr::expr(Func::sum(
+                    CaseStatement::new()
+                        .case(
+                            Expr::expr(
+                                Expr::col($scores::Column::Status)
+                                    .as_enum(Alias::new("text")),
+                            )
+                            .eq(ScoreStatus::Failed),
+                            1,
+                        )
+                        .finally(0),
+                )),
+                "count_failed",
+            )
+            .filter(user_scores_condition!($scores, $version, $user_id))
+            .into_model::<ScoreTotals>()
+            .one($conn)
+            .await?
+            .unwrap_or_default()
+    };
+}
+
+macro_rules! ranked_scores_pp_query {
+    (
+        $scores: ident,
+        $score_pp: ident,
+        $user_stats: ident,
+        $version: expr,
+        $user_id: expr,
+        $pp_version: expr,
+        $limit: expr,
+        $conn: expr
+    ) => {
+        $scores::Entity::find()
+            .select_only()
+            .column($scores::Column::Accuracy)
+            .column_as(
+                Expr::col(($score_pp::Entity, $score_pp::Column::Pp)),
+                "pp",
+            )
+            .join(
+                JoinType::InnerJoin,
+                <$scores::Entity as Related<$score_pp::Entity>>::to(),
+            )
+            .filter(ranked_best_scores_condition!($scores, $version, $user_id))
+            .filter(
+                Expr::expr(
+                    Expr::col((
+                        $score_pp::Entity,
+                        $score_pp::Column::PpVersion,
+                    ))
+                    .as_enum(Alias::new("text")),
+                )
+                .eq($pp_version),
+            )
+            .order_by_desc(Expr::col((
+                $score_pp::Entity,
+                $score_pp::Column::Pp,
+            )))
+            .limit($limit)
+            .into_model::<RankedScorePp>()
+            .all($conn)
+            .await?
+    };
+}
+
+macro_rules! ranked_score_query {
+    (
+        $scores: ident,
+        $score_pp: ident,
+        $user_stats: ident,
+        $version: expr,
+        $user_id: expr,
+        $conn: expr
+    ) => {
+        $scores::Entity::find()
+            .select_only()
+            .column_as(Expr::col($scores::Column::Id).count(), "count")
+            .column_as(Expr::col($scores::Column::Score).sum(), "ranked_score")
+            .filter(ranked_best_scores_condition!($scores, $version, $user_id))
+            .into_tuple::<(i64, Option<i64>)>()
+            .one($conn)
+            .await?
+            .map(|(count, ranked_score)| {
+                (count, ranked_score.unwrap_or_default())
+            })
+            .unwrap_or_default()
+    };
+}
+
+macro_rules! save_user_stats_query {
+    (
+        $scores: ident,
+        $score_pp: ident,
+        $user_stats: ident,
+        $version: expr,
+        $user_id: expr,
+        $stats: expr,
+        $conn: expr
+    ) => {
+        $user_stats::Entity::insert($user_stats::ActiveModel {
+            user_id: Set($user_id),
+            total_score: Set($stats.total_score),
+            ranked_score: Set($stats.ranked_score),
+            playcount: Set($stats.playcount),
+            total_hits: Set($stats.total_hits),
+            accuracy: Set(Decimal::from_f64_retain($stats.accuracy)
+                .unwrap_or_default()
+                .round_dp(2)),
+            max_combo: Set($stats.max_combo),
+            total_seconds_played: Set($stats.total_seconds_played),
+            count300: Set($stats.count300),
+            count100: Set($stats.count100),
+            count50: Set($stats.count50),
+            count_miss: Set($stats.count_miss),
+            count_failed: Set($stats.count_failed),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::column($user_stats::Column::UserId)
+                .update_columns([
+                    $user_stats::Column::TotalScore,
+                    $user_stats::Column::RankedScore,
+                    $user_stats::Column::Playcount,
+                    $user_stats::Column::TotalHits,
+                    $user_stats::Column::Accuracy,
+                    $user_stats::Column::MaxCombo,
+                    $user_stats::Column::TotalSecondsPlayed,
+                    $user_stats::Column::Count300,
+                    $user_stats::Column::Count100,
+                    $user_stats::Column::Count50,
+                    $user_stats::Column::CountMiss,
+                    $user_stats::Column::CountFailed,
+                ])
+                .value(
+                    $user_stats::Column::UpdatedAt,
+                    Expr::current_timestamp(),
+                )
+                .to_owned(),
+        )
+        .exec_without_returning($conn)
+        .await?
+    };
+}
+
+macro_rules! save_user_pp_query {
+    (
+        $user_pp: ident,
+        $user_id: expr,
+        $pp_version: expr,
+        $pp: expr,
+        $raw_pp: expr,
+        $conn: expr
+    ) => {
+        $user_pp::Entity::insert($user_pp::ActiveModel {
+            user_id: Set($user_id),
+            pp_version: Set($pp_version),
+            pp: Set($pp),
+            raw_pp: Set($raw_pp),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                $user_pp::Column::UserId,
+                $user_pp::Column::PpVersion,
+            ])
+            .update_columns([$user_pp::Column::Pp, $user_pp::Column::RawPp])
+            .to_owned(),
+        )
+        .exec_without_returning($conn)
+        .await?
+    };
+}
+
+#[async_trait]
+pub trait UserStatsRepository {
+    async fn get_score_totals(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+    ) -> Result<ScoreTotals, DbErr>;
+
+    /// Returns the pp and accuracy of the user's best scores on ranked
+    /// beatmaps, the highest pp first.
+    async fn get_ranked_scores_pp(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+        pp_version: PpVersion,
+        limit: u64,
+    ) -> Result<Vec<RankedScorePp>, DbErr>;
+
+    /// Returns the count and the summed score of the user's best scores on
+    /// ranked beatmaps.
+    async fn get_ranked_score(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+    ) -> Result<(i64, i64), DbErr>;
+
+    async fn save_user_stats(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+        stats: UserModeStats,
+    ) -> Result<(), DbErr>;
+
+    /// Inserts or replaces the pp of the user for the `pp_version`.
+    ///
+    /// Score v2 has no pp table, nothing is saved for it.
+    async fn save_user_pp(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+        pp_version: PpVersion,
+        pp: f64,
+        raw_pp: Option<Json>,
+    ) -> Result<(), DbErr>;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct UserStatsRepositoryImpl {
+    pub conn: DbConnection<Peace>,
+}
+
+impl UserStatsRepositoryImpl {
+    pub fn new(conn: DbConnection<Peace>) -> UserStatsRepositoryImpl {
+        Self { conn }
+    }
+
+    pub fn into_service(self) -> DynUserStatsRepository {
+        Arc::new(self) as DynUserStatsRepository
+    }
+}
+
+#[async_trait]
+impl UserStatsRepository for UserStatsRepositoryImpl {
+    async fn get_score_totals(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+    ) -> Result<ScoreTotals, DbErr> {
+        let conn = self.conn.as_ref();
+
+        Ok(with_user_stats_tables!(mode, score_totals_query!(user_id, conn)))
+    }
+
+    async fn get_ranked_scores_pp(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+        pp_version: PpVersion,
+        limit: u64,
+    ) -> Result<Vec<RankedScorePp>, DbErr> {
+        let conn = self.conn.as_ref();
+
+        Ok(with_user_stats_tables!(
+            mode,
+            ranked_scores_pp_query!(user_id, pp_version, limit, conn)
+        ))
+    }
+
+    async fn get_ranked_score(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+    ) -> Result<(i64, i64), DbErr> {
+        let conn = self.conn.as_ref();
+
+        Ok(with_user_stats_tables!(mode, ranked_score_query!(user_id, conn)))
+    }
+
+    async fn save_user_stats(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+        stats: UserModeStats,
+    ) -> Result<(), DbErr> {
+        let conn = self.conn.as_ref();
+
+        with_user_stats_tables!(
+            mode,
+            save_user_stats_query!(user_id, stats, conn)
+        );
+
+        Ok(())
+    }
+
+    async fn save_user_pp(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+        pp_version: PpVersion,
+        pp: f64,
+        raw_pp: Option<Json>,
+    ) -> Result<(), DbErr> {
+        let conn = self.conn.as_ref();
+        let pp = Decimal::from_f64_retain(pp).unwrap_or_default().round_dp(2);
+
+        macro_rules! save {
+            ($user_pp: ident) => {
+                save_user_pp_query!(
+                    $user_pp, user_id, pp_version, pp, raw_pp, conn
+                )
+            };
+        }
+
+        match mode {
+            BanchoGameMode::Standard => save!(user_pp_standard),
+            BanchoGameMode::StandardRelax => save!(user_pp_standard_relax),
+            BanchoGameMode::StandardAutopilot => {
+                save!(user_pp_standard_autopilot)
+            },
+            BanchoGameMode::Taiko => save!(user_pp_taiko),
+            BanchoGameMode::TaikoRelax => save!(user_pp_taiko_relax),
+            BanchoGameMode::Fruits => save!(user_pp_fruits),
+            BanchoGameMode::FruitsRelax => save!(user_pp_fruits_relax),
+            BanchoGameMode::Mania => save!(user_pp_mania),
+            BanchoGameMode::StandardScoreV2 => return Ok(()),
+        };
+
+        Ok(())
+    }
+}
@@ -1,7 +1,13 @@
 use crate::GetUserError;
-use domain_users::{CreateUser, UsernameAscii, UsernameSafe, UsernameUnicode};
+use domain_users::{
+    CreateUser, Email, UsernameAscii, UsernameSafe, UsernameUnicode,
+};
 use peace_db::{
-    peace::{entity::users, Peace},
+    peace::{
+        entity::{followers, privileges, user_blocks, user_privileges, users},
+        Peace,
+    },
+    sea_query::OnConflict,
     *,
 };
 use std::sync::Arc;
@@ -32,6 +38,11 @@ pub trait UsersRepository {
         username_unicode: &str,
     ) -> Result<users::Model, GetUserError>;
 
+    async fn get_user_by_email(
+        &self,
+        email: &Email,
+    ) -> Result<users::Model, GetUserError>;
+
     async fn create_user(
         &self,
         creat_user: CreateUser,
@@ -44,6 +55,61 @@ pub trait UsersRepository {
         username_unicode: Option<UsernameSafe>,
         password: String,
     ) -> Result<InsertResult<users::ActiveModel>, DbErr>;
+
+    /// Returns the ids of the users after `after_user_id` in ascending order,
+    /// used to walk through every user page by page.
+    async fn get_user_ids_after(
+        &self,
+        after_user_id: i32,
+        limit: u64,
+    ) -> Result<Vec<i32>, DbErr>;
+
+    /// The priority of the highest privilege granted to the user, `0` if the
+    /// user has none.
+    async fn get_user_privilege_level(
+        &self,
+        user_id: i32,
+    ) -> Result<i32, DbErr>;
+
+    /// The name of the highest privilege granted to the user, e.g.
+    /// `supporter`.
+    async fn get_user_privilege_name(
+        &self,
+        user_id: i32,
+    ) -> Result<Option<String>, DbErr>;
+
+    /// Whether `friend_id` is on the friend list of the user.
+    async fn is_friend(
+        &self,
+        user_id: i32,
+        friend_id: i32,
+    ) -> Result<bool, DbErr>;
+
+    /// Returns `false` if the user was blocked already.
+    async fn block_user(
+        &self,
+        user_id: i32,
+        block_id: i32,
+    ) -> Result<bool, DbErr>;
+
+    /// Returns `false` if the user was not blocked.
+    async fn unblock_user(
+        &self,
+        user_id: i32,
+        block_id: i32,
+    ) -> Result<bool, DbErr>;
+
+    /// Whether the user blocked `block_id`.
+    async fn is_blocked(
+        &self,
+        user_id: i32,
+        block_id: i32,
+    ) -> Result<bool, DbErr>;
+
+    async fn get_blocked_user_ids(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<i32>, DbErr>;
 }
 
 #[derive(Debug, Default, Clone)]
@@ -135,6 +201,18 @@ impl UsersRepository for UsersRepositoryImpl {
             .ok_or(GetUserError::UserNotExists)
     }
 
+    async fn get_user_by_email(
+        &self,
+        email: &Email,
+    ) -> Result<users::Model, GetUserError> {
+        users::Entity::find()
+            .filter(users::Column::Email.eq(email.as_str()))
+            .one(self.conn.as_ref())
+            .await
+            .map_err(GetUserError::from)?
+            .ok_or(GetUserError::UserNotExists)
+    }
+
     async fn create_user(
         &self,
         creat_user: CreateUser,
@@ -190,6 +268,124 @@ impl UsersRepository for UsersRepositoryImpl {
 
         todo!()
     }
+
+    async fn get_user_ids_after(
+        &self,
+        after_user_id: i32,
+        limit: u64,
+    ) -> Result<Vec<i32>, DbErr> {
+        users::Entity::find()
+            .select_only()
+            .column(users::Column::Id)
+            .filter(users::Column::Id.gt(after_user_id))
+            .order_by_asc(users::Column::Id)
+            .limit(limit)
+            .into_tuple::<i32>()
+            .all(self.conn.as_ref())
+            .await
+    }
+
+    async fn get_user_privilege_level(
+        &self,
+        user_id: i32,
+    ) -> Result<i32, DbErr> {
+        Ok(user_privileges::Entity::find()
+            .select_only()
+            .column(privileges::Column::Priority)
+            .inner_join(privileges::Entity)
+            .filter(user_privileges::Column::UserId.eq(user_id))
+            .order_by_desc(privileges::Column::Priority)
+            .into_tuple::<i16>()
+            .one(self.conn.as_ref())
+            .await?
+            .map(i32::from)
+            .unwrap_or_default())
+    }
+
+    async fn get_user_privilege_name(
+        &self,
+        user_id: i32,
+    ) -> Result<Option<String>, DbErr> {
+        user_privileges::Entity::find()
+            .select_only()
+            .column(privileges::Column::Name)
+            .inner_join(privileges::Entity)
+            .filter(user_privileges::Column::UserId.eq(user_id))
+            .order_by_desc(privileges::Column::Priority)
+            .into_tuple::<String>()
+            .one(self.conn.as_ref())
+            .await
+    }
+
+    async fn is_friend(
+        &self,
+        user_id: i32,
+        friend_id: i32,
+    ) -> Result<bool, DbErr> {
+        Ok(followers::Entity::find_by_id((user_id, friend_id))
+            .count(self.conn.as_ref())
+            .await?
+            > 0)
+    }
+
+    async fn block_user(
+        &self,
+        user_id: i32,
+        block_id: i32,
+    ) -> Result<bool, DbErr> {
+        Ok(user_blocks::Entity::insert(user_blocks::ActiveModel {
+            user_id: Set(user_id),
+            block_id: Set(block_id),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([
+                user_blocks::Column::UserId,
+                user_blocks::Column::BlockId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(self.conn.as_ref())
+        .await?
+            > 0)
+    }
+
+    async fn unblock_user(
+        &self,
+        user_id: i32,
+        block_id: i32,
+    ) -> Result<bool, DbErr> {
+        Ok(user_blocks::Entity::delete_by_id((user_id, block_id))
+            .exec(self.conn.as_ref())
+            .await?
+            .rows_affected
+            > 0)
+    }
+
+    async fn is_blocked(
+        &self,
+        user_id: i32,
+        block_id: i32,
+    ) -> Result<bool, DbErr> {
+        Ok(user_blocks::Entity::find_by_id((user_id, block_id))
+            .count(self.conn.as_ref())
+            .await?
+            > 0)
+    }
+
+    async fn get_blocked_user_ids(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<i32>, DbErr> {
+        user_blocks::Entity::find()
+            .select_only()
+            .column(user_blocks::Column::BlockId)
+            .filter(user_blocks::Column::UserId.eq(user_id))
+            .into_tuple::<i32>()
+            .all(self.conn.as_ref())
+            .await
+    }
 }
 
 #[cfg(test)]
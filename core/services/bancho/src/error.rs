@@ -1,9 +1,14 @@
+use crate::difficulty::DifficultyError;
 use bancho_packets::PacketId;
 use core_bancho_state::BanchoStateError;
 use core_chat::ChatError;
 use domain_users::PasswordError;
+use infra_beatmaps::BeatmapFileError;
+use peace_db::DbErr;
 use peace_pb::ConvertError;
-use peace_repositories::GetUserError;
+use peace_repositories::{
+    AddFavouriteBeatmapError, GetBeatmapError, GetUserError,
+};
 use peace_rpc_error::{RpcError, TonicError};
 use tonic::Status;
 
@@ -45,10 +50,51 @@ pub enum BanchoServiceError {
     ChatError(#[from] ChatError),
     #[error(transparent)]
     ConvertError(#[from] ConvertError),
+    #[error(transparent)]
+    GetBeatmapError(#[from] GetBeatmapError),
+    #[error(transparent)]
+    AddFavouriteBeatmapError(#[from] AddFavouriteBeatmapError),
+    #[error("too many registrations from your ip, please try again later")]
+    RegistrationRateLimited,
+    #[error("registration is not available in your region")]
+    RegistrationNotAllowed,
+    #[error("beatmap file not exists")]
+    BeatmapFileNotExists,
+    #[error("beatmap file err: {0}")]
+    BeatmapFileError(String),
+    #[error("not in a multiplayer match")]
+    NotInMatch,
+    #[error("invalid ruleset: {0}")]
+    InvalidRuleset(i32),
+    #[error("difficulty calculation err: {0}")]
+    DifficultyError(String),
+    #[error("database err: {0}")]
+    DbErr(String),
     #[error("TonicError: {0}")]
     TonicError(String),
 }
 
+impl From<DbErr> for BanchoServiceError {
+    fn from(err: DbErr) -> Self {
+        Self::DbErr(err.to_string())
+    }
+}
+
+impl From<BeatmapFileError> for BanchoServiceError {
+    fn from(err: BeatmapFileError) -> Self {
+        Self::BeatmapFileError(err.to_string())
+    }
+}
+
+impl From<DifficultyError> for BanchoServiceError {
+    fn from(err: DifficultyError) -> Self {
+        match err {
+            DifficultyError::InvalidRuleset(id) => Self::InvalidRuleset(id),
+            err => Self::DifficultyError(err.to_string()),
+        }
+    }
+}
+
 impl TonicError for BanchoServiceError {
     fn tonic_error(s: Status) -> Self {
         Self::TonicError(s.message().to_owned())
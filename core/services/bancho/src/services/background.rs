@@ -3,6 +3,7 @@ use crate::{
     PasswordBackgroundService, PasswordCacheStore,
 };
 use async_trait::async_trait;
+use clap::Parser;
 use clap_serde_derive::ClapSerde;
 use std::{
     sync::Arc,
@@ -0,0 +1,46 @@
+use super::osu_direct;
+use peace_db::peace::entity::{
+    beatmaps,
+    sea_orm_active_enums::{GameMode, ScoreGrade},
+};
+
+/// Modes shown by the song select, in the order the client expects the
+/// grades.
+pub const BEATMAP_INFO_MODES: [GameMode; 4] =
+    [GameMode::Standard, GameMode::Taiko, GameMode::Fruits, GameMode::Mania];
+
+/// `N` means the user has no score on the beatmap.
+#[inline]
+pub fn score_grade_to_client(grade: Option<&ScoreGrade>) -> &'static str {
+    match grade {
+        Some(ScoreGrade::Xh) => "XH",
+        Some(ScoreGrade::Sh) => "SH",
+        Some(ScoreGrade::X) => "X",
+        Some(ScoreGrade::S) => "S",
+        Some(ScoreGrade::A) => "A",
+        Some(ScoreGrade::B) => "B",
+        Some(ScoreGrade::C) => "C",
+        Some(ScoreGrade::D) => "D",
+        Some(ScoreGrade::F) => "F",
+        None => "N",
+    }
+}
+
+/// `{index}|{bid}|{sid}|{md5}|{status}|{std}|{taiko}|{ctb}|{mania}`
+///
+/// `index` is the position of the beatmap in the requested file names, `-1`
+/// if it was requested by id.
+pub fn format_beatmap_info(
+    index: i32,
+    beatmap: &beatmaps::Model,
+    grades: [&str; 4],
+) -> String {
+    format!(
+        "{index}|{}|{}|{}|{}|{}",
+        beatmap.bid,
+        beatmap.sid,
+        beatmap.md5,
+        osu_direct::rank_status_val(&beatmap.rank_status),
+        grades.join("|")
+    )
+}
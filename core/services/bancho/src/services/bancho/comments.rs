@@ -0,0 +1,64 @@
+use domain_bancho::BanchoPrivileges;
+use peace_db::peace::entity::{comments, sea_orm_active_enums::CommentTarget};
+
+/// `target` param sent by the client when posting a comment.
+#[inline]
+pub fn comment_target_from_client(target: &str) -> Option<CommentTarget> {
+    match target {
+        "map" => Some(CommentTarget::Beatmap),
+        "song" => Some(CommentTarget::Song),
+        "replay" => Some(CommentTarget::Replay),
+        _ => None,
+    }
+}
+
+/// The client only knows `map`, `song` and `replay` comments.
+#[inline]
+pub fn comment_target_to_client(target: &CommentTarget) -> &'static str {
+    match target {
+        CommentTarget::Beatmap => "map",
+        CommentTarget::Beatmapset | CommentTarget::Song => "song",
+        CommentTarget::Replay => "replay",
+    }
+}
+
+/// Accepts `RRGGBB` hex colours (with or without `#`), only supporters are
+/// allowed to use them.
+pub fn comment_colour(
+    colour: Option<&str>,
+    privileges: BanchoPrivileges,
+) -> Option<String> {
+    let colour = colour?.trim_start_matches('#');
+
+    (privileges.contains(BanchoPrivileges::Supporter)
+        && colour.len() == 6
+        && colour.chars().all(|c| c.is_ascii_hexdigit()))
+    .then(|| colour.to_uppercase())
+}
+
+/// `{time}\t{target}\t{format}\t{comment}`
+pub fn format_comment(comment: &comments::Model) -> String {
+    let privileges = BanchoPrivileges::from(comment.privileges);
+
+    let mut format = if privileges.intersects(
+        BanchoPrivileges::Moderator | BanchoPrivileges::Administrator,
+    ) {
+        "bat".to_owned()
+    } else if privileges.contains(BanchoPrivileges::Supporter) {
+        "supporter".to_owned()
+    } else {
+        String::new()
+    };
+
+    if let Some(colour) = comment.colour.as_deref() {
+        format.push('|');
+        format.push_str(colour);
+    }
+
+    format!(
+        "{}\t{}\t{format}\t{}",
+        comment.time,
+        comment_target_to_client(&comment.target),
+        comment.comment
+    )
+}
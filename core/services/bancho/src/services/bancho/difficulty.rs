@@ -0,0 +1,38 @@
+use peace_performance::{
+    Beatmap, CalculateError, DifficultyAttributes, GameMode, ParseError,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DifficultyError {
+    #[error("invalid ruleset: {0}")]
+    InvalidRuleset(i32),
+    #[error("invalid beatmap file: {0}")]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Calculate(#[from] CalculateError),
+    #[error("difficulty calculation failed: {0}")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+/// Parses the `.osu` file and calculates its difficulty on a blocking thread,
+/// large beatmaps can take a while.
+///
+/// `ruleset_id` defaults to the ruleset of the beatmap.
+pub async fn calculate_difficulty(
+    content: Vec<u8>,
+    ruleset_id: Option<i32>,
+    mods: u32,
+) -> Result<DifficultyAttributes, DifficultyError> {
+    let mode = ruleset_id
+        .map(|id| {
+            GameMode::from_id(id).ok_or(DifficultyError::InvalidRuleset(id))
+        })
+        .transpose()?;
+
+    tokio::task::spawn_blocking(move || {
+        let map = Beatmap::parse(&content)?;
+
+        Ok(peace_performance::calculate(&map, mode, mods)?)
+    })
+    .await?
+}
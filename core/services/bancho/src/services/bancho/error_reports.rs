@@ -0,0 +1,27 @@
+/// Stack frames used by [`error_report_signature`].
+const SIGNATURE_STACK_FRAMES: usize = 3;
+
+/// Error report groups returned per page by default.
+pub const DEFAULT_ERROR_REPORT_GROUPS_LIMIT: u64 = 50;
+pub const MAX_ERROR_REPORT_GROUPS_LIMIT: u64 = 500;
+
+/// Exception messages often contain file paths or beatmap names, so only the
+/// exception type and the top stack frames make up the signature.
+pub fn error_report_signature(exception: &str, stacktrace: &str) -> String {
+    let exception_type = exception
+        .lines()
+        .next()
+        .and_then(|line| line.split(':').next())
+        .unwrap_or_default()
+        .trim();
+
+    let frames = stacktrace
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .take(SIGNATURE_STACK_FRAMES)
+        .collect::<Vec<&str>>()
+        .join("\n");
+
+    format!("{:x}", md5::compute(format!("{exception_type}\n{frames}")))
+}
@@ -0,0 +1,83 @@
+use bancho_packets::server;
+use clap::Parser;
+use clap_serde_derive::ClapSerde;
+use pb_bancho::{MainMenuIcon, MenuContent};
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Parser, ClapSerde, Serialize, Deserialize)]
+pub struct CliBanchoMenuConfigs {
+    /// Image urls of the seasonal backgrounds shown in the main menu,
+    /// served by `/web/osu-getseasonal.php`.
+    #[default(Vec::new())]
+    #[arg(long, value_delimiter = ',')]
+    pub seasonal_backgrounds: Vec<String>,
+
+    /// Image url of the main menu icon, sent to the client at login.
+    #[arg(long)]
+    pub main_menu_icon_image_url: Option<String>,
+
+    /// Url opened when the main menu icon is clicked.
+    #[arg(long)]
+    pub main_menu_icon_link_url: Option<String>,
+
+    /// JSON answered to `/web/check-updates.php`, an empty manifest tells
+    /// the client that it is up to date.
+    #[default("[]".to_owned())]
+    #[arg(long, default_value = "[]")]
+    pub check_updates_manifest: String,
+}
+
+/// Seasonal backgrounds and main menu icon, can be replaced at runtime with
+/// `UpdateMenuContent`.
+#[derive(Debug, Default)]
+pub struct ReloadableMenuContent {
+    pub content: RwLock<MenuContent>,
+}
+
+impl ReloadableMenuContent {
+    #[inline]
+    pub fn new(content: MenuContent) -> Self {
+        Self { content: RwLock::new(content) }
+    }
+
+    #[inline]
+    pub fn with_cfg(cfg: &CliBanchoMenuConfigs) -> Self {
+        Self::new(MenuContent {
+            seasonal_backgrounds: cfg.seasonal_backgrounds.clone(),
+            main_menu_icon: cfg.main_menu_icon_image_url.as_ref().map(
+                |image_url| MainMenuIcon {
+                    image_url: image_url.to_owned(),
+                    link_url: cfg
+                        .main_menu_icon_link_url
+                        .to_owned()
+                        .unwrap_or_default(),
+                },
+            ),
+        })
+    }
+
+    #[inline]
+    pub async fn get(&self) -> MenuContent {
+        self.content.read().await.clone()
+    }
+
+    #[inline]
+    pub async fn replace(&self, content: MenuContent) -> MenuContent {
+        std::mem::replace(&mut *self.content.write().await, content)
+    }
+
+    /// JSON array of image urls, the format expected by the client.
+    pub async fn seasonal_backgrounds_json(&self) -> String {
+        serde_json::to_string(&self.content.read().await.seasonal_backgrounds)
+            .unwrap_or_else(|_| "[]".to_owned())
+    }
+
+    pub async fn main_menu_icon_packet(&self) -> Option<Vec<u8>> {
+        self.content.read().await.main_menu_icon.as_ref().map(|icon| {
+            server::MainMenuIcon::pack(
+                icon.image_url.as_str().into(),
+                icon.link_url.as_str().into(),
+            )
+        })
+    }
+}
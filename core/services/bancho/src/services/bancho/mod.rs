@@ -1,5 +1,16 @@
+pub mod beatmap_info;
+pub mod comments;
+pub mod difficulty;
+pub mod error_reports;
+pub mod menu;
+pub mod osu_direct;
 pub mod packet_processor;
+pub mod registration;
 pub mod service;
 
+pub use difficulty::{calculate_difficulty, DifficultyError};
+pub use menu::{CliBanchoMenuConfigs, ReloadableMenuContent};
+pub use osu_direct::{BeatmapMirror, CliBeatmapMirrorConfigs};
 pub use packet_processor::*;
+pub use registration::{CliBanchoRegistrationConfigs, RegistrationGuard};
 pub use service::*;
@@ -0,0 +1,315 @@
+use clap::Parser;
+use clap_serde_derive::ClapSerde;
+use domain_bancho::OsuDirectRankStatus;
+use peace_db::peace::entity::{
+    beatmaps,
+    sea_orm_active_enums::{GameMode, RankStatus},
+};
+use std::time::Duration;
+
+/// osu!direct always requests 100 beatmapsets per page.
+pub const OSU_DIRECT_PAGE_SIZE: u64 = 100;
+
+/// Queries sent by the sorting buttons of the osu!direct panel, they are not
+/// real keywords.
+const OSU_DIRECT_SPECIAL_QUERIES: [&str; 3] =
+    ["Newest", "Top Rated", "Most Played"];
+
+const LAST_UPDATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+#[derive(Debug, Clone, Parser, ClapSerde, Serialize, Deserialize)]
+pub struct CliBeatmapMirrorConfigs {
+    /// Base url of an osu!direct compatible beatmap mirror, used when the
+    /// local `beatmaps` table has no results.
+    #[arg(long)]
+    pub beatmap_mirror_url: Option<String>,
+
+    #[default(10)]
+    #[arg(long, default_value = "10")]
+    pub beatmap_mirror_timeout_secs: u64,
+}
+
+/// Remote mirror that speaks the osu!direct text protocol
+/// (`/web/osu-search.php` and `/web/osu-search-set.php`).
+#[derive(Debug, Clone)]
+pub struct BeatmapMirror {
+    pub base_url: String,
+    pub client: reqwest::Client,
+}
+
+impl BeatmapMirror {
+    #[inline]
+    pub fn new(base_url: &str, timeout: Duration) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    #[inline]
+    pub fn with_cfg(cfg: &CliBeatmapMirrorConfigs) -> Option<Self> {
+        cfg.beatmap_mirror_url.as_deref().map(|url| {
+            Self::new(url, Duration::from_secs(cfg.beatmap_mirror_timeout_secs))
+        })
+    }
+
+    #[inline]
+    pub async fn osu_search(
+        &self,
+        params: &[(&str, String)],
+    ) -> Result<String, reqwest::Error> {
+        self.get("/web/osu-search.php", params).await
+    }
+
+    #[inline]
+    pub async fn osu_search_set(
+        &self,
+        params: &[(&str, String)],
+    ) -> Result<String, reqwest::Error> {
+        self.get("/web/osu-search-set.php", params).await
+    }
+
+    async fn get(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<String, reqwest::Error> {
+        self.client
+            .get(format!("{}{}", self.base_url, path))
+            .query(params)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await
+    }
+}
+
+/// Splits the osu!direct query into keywords, the special queries of the
+/// sorting buttons are treated as empty.
+pub fn parse_keywords(query: &str) -> Vec<String> {
+    let query = query.trim();
+
+    if OSU_DIRECT_SPECIAL_QUERIES.contains(&query) {
+        return Vec::new();
+    }
+
+    query.split_whitespace().map(|keyword| keyword.to_owned()).collect()
+}
+
+pub fn rank_status_filter(
+    status: OsuDirectRankStatus,
+) -> Option<Vec<RankStatus>> {
+    match status {
+        OsuDirectRankStatus::Ranked | OsuDirectRankStatus::RankedPlayed => {
+            Some(vec![RankStatus::Ranked, RankStatus::Approved])
+        },
+        OsuDirectRankStatus::Pending => {
+            Some(vec![RankStatus::Pending, RankStatus::Wip])
+        },
+        OsuDirectRankStatus::Qualified => Some(vec![RankStatus::Qualified]),
+        OsuDirectRankStatus::Graveyard => Some(vec![RankStatus::Graveyard]),
+        OsuDirectRankStatus::Loved => Some(vec![RankStatus::Loved]),
+        OsuDirectRankStatus::All => None,
+    }
+}
+
+/// Ranked status as displayed by the client (same as osu!api v1).
+#[inline]
+pub fn rank_status_val(status: &RankStatus) -> i32 {
+    match status {
+        RankStatus::Graveyard => -2,
+        RankStatus::Wip => -1,
+        RankStatus::Pending => 0,
+        RankStatus::Ranked => 1,
+        RankStatus::Approved => 2,
+        RankStatus::Qualified => 3,
+        RankStatus::Loved => 4,
+    }
+}
+
+#[inline]
+pub fn game_mode_val(mode: &GameMode) -> i32 {
+    match mode {
+        GameMode::Standard => 0,
+        GameMode::Taiko => 1,
+        GameMode::Fruits => 2,
+        GameMode::Mania => 3,
+    }
+}
+
+/// `-1` (or any unknown value) means all modes.
+#[inline]
+pub fn game_mode_from_val(mode: i32) -> Option<GameMode> {
+    match mode {
+        0 => Some(GameMode::Standard),
+        1 => Some(GameMode::Taiko),
+        2 => Some(GameMode::Fruits),
+        3 => Some(GameMode::Mania),
+        _ => None,
+    }
+}
+
+/// Format the search results, the first line is the amount of beatmapsets
+/// (`101` tells the client there are more pages).
+pub fn format_search_result(beatmapsets: &[Vec<beatmaps::Model>]) -> String {
+    let amount = if beatmapsets.len() as u64 >= OSU_DIRECT_PAGE_SIZE {
+        OSU_DIRECT_PAGE_SIZE as usize + 1
+    } else {
+        beatmapsets.len()
+    };
+
+    let mut result = amount.to_string();
+    for beatmapset in beatmapsets.iter().filter(|set| !set.is_empty()) {
+        result.push('\n');
+        result.push_str(&format_beatmapset(beatmapset));
+    }
+
+    result
+}
+
+/// `{sid}.osz|{artist}|{title}|{mapper}|{status}|10.0|{last_update}|{sid}|0|{video}|0|0|0|{diffs}`
+pub fn format_beatmapset(beatmapset: &[beatmaps::Model]) -> String {
+    let diffs = beatmapset
+        .iter()
+        .map(|b| {
+            format!(
+                "[{:.2}⭐] {} {{cs: {} / od: {} / ar: {} / hp: {}}}@{}",
+                b.stars,
+                b.diff_name,
+                b.cs,
+                b.od,
+                b.ar,
+                b.hp,
+                game_mode_val(&b.game_mode)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+
+    format!("{}|{diffs}", format_beatmapset_info(&beatmapset[0]))
+}
+
+/// `{sid}.osz|{artist}|{title}|{mapper}|{status}|10.0|{last_update}|{sid}|0|{video}|0|0|0`
+pub fn format_beatmapset_info(beatmap: &beatmaps::Model) -> String {
+    format!(
+        "{sid}.osz|{}|{}|{}|{}|10.0|{}|{sid}|0|{}|0|0|0",
+        beatmap.artist,
+        beatmap.title,
+        beatmap.mapper_name,
+        rank_status_val(&beatmap.rank_status),
+        beatmap.last_update.format(LAST_UPDATE_FORMAT),
+        beatmap.video.unwrap_or_default() as u8,
+        sid = beatmap.sid,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone};
+    use peace_db::prelude::Decimal;
+
+    fn beatmap(bid: i32, sid: i32) -> beatmaps::Model {
+        let time = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2023, 1, 2, 3, 4, 5)
+            .unwrap();
+
+        beatmaps::Model {
+            bid,
+            sid,
+            md5: format!("{bid:032}"),
+            title: "Title".to_owned(),
+            file_name: format!("Artist - Title (Mapper) [Diff {bid}].osu"),
+            artist: "Artist".to_owned(),
+            diff_name: format!("Diff {bid}"),
+            origin_server: "bancho".to_owned(),
+            mapper_name: "Mapper".to_owned(),
+            mapper_id: "1".to_owned(),
+            rank_status: RankStatus::Ranked,
+            game_mode: GameMode::Standard,
+            stars: Decimal::new(512, 2),
+            bpm: Decimal::new(180, 0),
+            cs: Decimal::new(4, 0),
+            od: Decimal::new(8, 0),
+            ar: Decimal::new(9, 0),
+            hp: Decimal::new(5, 0),
+            length: 120,
+            length_drain: 110,
+            source: None,
+            tags: None,
+            genre_id: None,
+            language_id: None,
+            storyboard: None,
+            video: Some(true),
+            object_count: None,
+            slider_count: None,
+            spinner_count: None,
+            max_combo: None,
+            immutable: false,
+            last_update: time,
+            upload_time: time,
+            approved_time: None,
+            updated_at: time,
+        }
+    }
+
+    #[test]
+    fn parse_keywords_of_queries() {
+        assert_eq!(parse_keywords("  hello   world "), ["hello", "world"]);
+        assert!(parse_keywords("").is_empty());
+        assert!(parse_keywords("Newest").is_empty());
+        assert!(parse_keywords(" Top Rated ").is_empty());
+        assert!(parse_keywords("Most Played").is_empty());
+    }
+
+    #[test]
+    fn rank_status_filters() {
+        assert_eq!(
+            rank_status_filter(OsuDirectRankStatus::Ranked),
+            Some(vec![RankStatus::Ranked, RankStatus::Approved])
+        );
+        assert_eq!(
+            rank_status_filter(OsuDirectRankStatus::RankedPlayed),
+            Some(vec![RankStatus::Ranked, RankStatus::Approved])
+        );
+        assert_eq!(
+            rank_status_filter(OsuDirectRankStatus::Pending),
+            Some(vec![RankStatus::Pending, RankStatus::Wip])
+        );
+        assert_eq!(
+            rank_status_filter(OsuDirectRankStatus::Loved),
+            Some(vec![RankStatus::Loved])
+        );
+        assert_eq!(rank_status_filter(OsuDirectRankStatus::All), None);
+    }
+
+    #[test]
+    fn format_search_result_of_beatmapsets() {
+        assert_eq!(format_search_result(&[]), "0");
+
+        let result = format_search_result(&[vec![beatmap(1, 10)]]);
+        assert_eq!(
+            result,
+            "1\n10.osz|Artist|Title|Mapper|1|10.0|2023-01-02T03:04:05|10|0|1|0|0|0|\
+             [5.12⭐] Diff 1 {cs: 4 / od: 8 / ar: 9 / hp: 5}@0"
+        );
+    }
+
+    #[test]
+    fn format_search_result_of_full_page() {
+        let beatmapsets = (0..OSU_DIRECT_PAGE_SIZE as i32)
+            .map(|sid| vec![beatmap(sid, sid)])
+            .collect::<Vec<_>>();
+
+        let result = format_search_result(&beatmapsets);
+        let mut lines = result.lines();
+
+        assert_eq!(lines.next(), Some("101"));
+        assert_eq!(lines.count(), OSU_DIRECT_PAGE_SIZE as usize);
+    }
+}
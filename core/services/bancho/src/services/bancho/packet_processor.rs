@@ -65,20 +65,8 @@ impl<'a> ProcessSendPublicMessage for PacketProcessor<'a> {
     async fn send_public_message(
         &self,
     ) -> Result<HandleCompleted, ProcessBanchoPacketError> {
-        #[allow(unused_mut)]
-        let mut chat_message = read_chat_message(self.packet.payload)?;
-
-        match chat_message.target.as_str() {
-            "#spectator" => {
-                // TODO: spectator chat
-                todo!("get user's current #spectator channel id")
-            },
-            "#multiplayer" => {
-                // TODO: multiplayer chat
-                todo!("get user's current #multiplayer channel id")
-            },
-            _ => {},
-        };
+        // `#spectator` and `#multiplayer` are resolved by the chat service
+        let chat_message = read_chat_message(self.packet.payload)?;
 
         let request = SendMessageRequest {
             sender: Some(UserQuery::UserId(self.user_id).into()),
@@ -346,3 +334,65 @@ impl<'a> ProcessUserPresenceRequest for PacketProcessor<'a> {
         Ok(HandleCompleted::default())
     }
 }
+
+#[async_trait]
+impl<'a> ProcessSpectateStart for PacketProcessor<'a> {
+    #[inline]
+    async fn spectate_start(
+        &self,
+    ) -> Result<HandleCompleted, ProcessBanchoPacketError> {
+        let host_id = PayloadReader::new(
+            self.packet
+                .payload
+                .ok_or(ProcessBanchoPacketError::PacketPayloadNotExists)?,
+        )
+        .read::<i32>()
+        .ok_or(ProcessBanchoPacketError::InvalidPacketPayload)?;
+
+        Ok(self
+            .bancho_service
+            .spectate_start(SpectateStartRequest {
+                user_id: self.user_id,
+                host_id,
+            })
+            .await?)
+    }
+}
+
+#[async_trait]
+impl<'a> ProcessSpectateStop for PacketProcessor<'a> {
+    #[inline]
+    async fn spectate_stop(
+        &self,
+    ) -> Result<HandleCompleted, ProcessBanchoPacketError> {
+        self.bancho_service
+            .spectate_stop(UserQuery::UserId(self.user_id))
+            .await?;
+
+        Ok(HandleCompleted::default())
+    }
+}
+
+#[async_trait]
+impl<'a> ProcessMatchInvite for PacketProcessor<'a> {
+    #[inline]
+    async fn match_invite(
+        &self,
+    ) -> Result<HandleCompleted, ProcessBanchoPacketError> {
+        let target_id = PayloadReader::new(
+            self.packet
+                .payload
+                .ok_or(ProcessBanchoPacketError::PacketPayloadNotExists)?,
+        )
+        .read::<i32>()
+        .ok_or(ProcessBanchoPacketError::InvalidPacketPayload)?;
+
+        Ok(self
+            .bancho_service
+            .match_invite(MatchInviteRequest {
+                user_id: self.user_id,
+                target_id,
+            })
+            .await?)
+    }
+}
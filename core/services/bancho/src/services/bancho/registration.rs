@@ -0,0 +1,242 @@
+use clap::Parser;
+use clap_serde_derive::ClapSerde;
+use domain_users::{Email, UsernameAscii};
+use pb_bancho::ClientRegisterResponse;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+pub const USERNAME_MIN_LENGTH: usize = 2;
+pub const USERNAME_MAX_LENGTH: usize = 15;
+pub const PASSWORD_MIN_LENGTH: usize = 8;
+pub const PASSWORD_MAX_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, Parser, ClapSerde, Serialize, Deserialize)]
+pub struct CliBanchoRegistrationConfigs {
+    /// Maximum amount of accounts that can be registered from the same ip
+    /// address within `registration_rate_limit_window_secs`.
+    #[default(3)]
+    #[arg(long, default_value = "3")]
+    pub registration_rate_limit: usize,
+
+    #[default(86400)]
+    #[arg(long, default_value = "86400")]
+    pub registration_rate_limit_window_secs: u64,
+
+    /// Country codes (e.g. `US,CN`) that are not allowed to register,
+    /// requires GeoIP.
+    #[default(Vec::new())]
+    #[arg(long, value_delimiter = ',')]
+    pub registration_blocked_countries: Vec<String>,
+}
+
+/// Rate limit and GeoIP block list of the in-game registration.
+#[derive(Debug, Default)]
+pub struct RegistrationGuard {
+    pub rate_limit: usize,
+    pub rate_limit_window: Duration,
+    pub blocked_countries: HashSet<String>,
+    pub registrations: Mutex<HashMap<IpAddr, VecDeque<Instant>>>,
+}
+
+impl RegistrationGuard {
+    #[inline]
+    pub fn new(
+        rate_limit: usize,
+        rate_limit_window: Duration,
+        blocked_countries: HashSet<String>,
+    ) -> Self {
+        Self {
+            rate_limit,
+            rate_limit_window,
+            blocked_countries,
+            registrations: Mutex::default(),
+        }
+    }
+
+    #[inline]
+    pub fn with_cfg(cfg: &CliBanchoRegistrationConfigs) -> Self {
+        Self::new(
+            cfg.registration_rate_limit,
+            Duration::from_secs(cfg.registration_rate_limit_window_secs),
+            cfg.registration_blocked_countries
+                .iter()
+                .map(|code| code.trim().to_ascii_uppercase())
+                .collect(),
+        )
+    }
+
+    #[inline]
+    pub fn is_country_blocked(&self, country_code: &str) -> bool {
+        self.blocked_countries.contains(&country_code.to_ascii_uppercase())
+    }
+
+    pub async fn is_rate_limited(&self, ip: IpAddr) -> bool {
+        let mut registrations = self.registrations.lock().await;
+        self.remove_expired(&mut registrations);
+
+        registrations
+            .get(&ip)
+            .map(|records| records.len() >= self.rate_limit)
+            .unwrap_or_default()
+    }
+
+    /// Reserves a registration of the ip, returns `false` if the rate limit
+    /// has been reached. The check and the reservation share one lock, so
+    /// concurrent registrations can not exceed the limit.
+    pub async fn try_acquire(&self, ip: IpAddr) -> bool {
+        let mut registrations = self.registrations.lock().await;
+        self.remove_expired(&mut registrations);
+
+        let records = registrations.entry(ip).or_default();
+        if records.len() >= self.rate_limit {
+            return false;
+        }
+
+        records.push_back(Instant::now());
+        true
+    }
+
+    /// Gives back a reservation of the ip whose registration failed.
+    pub async fn release(&self, ip: IpAddr) {
+        let mut registrations = self.registrations.lock().await;
+
+        if let Some(records) = registrations.get_mut(&ip) {
+            records.pop_back();
+            if records.is_empty() {
+                registrations.remove(&ip);
+            }
+        }
+    }
+
+    fn remove_expired(
+        &self,
+        registrations: &mut HashMap<IpAddr, VecDeque<Instant>>,
+    ) {
+        registrations.retain(|_, records| {
+            while records
+                .front()
+                .map(|at| at.elapsed() > self.rate_limit_window)
+                .unwrap_or_default()
+            {
+                records.pop_front();
+            }
+
+            !records.is_empty()
+        });
+    }
+}
+
+pub trait RegistrationErrors {
+    fn is_ok(&self) -> bool;
+}
+
+impl RegistrationErrors for ClientRegisterResponse {
+    #[inline]
+    fn is_ok(&self) -> bool {
+        self.username_errors.is_empty()
+            && self.email_errors.is_empty()
+            && self.password_errors.is_empty()
+    }
+}
+
+pub fn validate_username(username: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if let Err(err) = UsernameAscii::new(username) {
+        errors.push(err.to_string());
+    }
+
+    let length = username.trim().chars().count();
+    if !(USERNAME_MIN_LENGTH..=USERNAME_MAX_LENGTH).contains(&length) {
+        errors.push(format!(
+            "must be {USERNAME_MIN_LENGTH} to {USERNAME_MAX_LENGTH} characters \
+             long"
+        ));
+    }
+
+    if !username.trim().chars().all(|c| {
+        c.is_ascii_alphanumeric() || matches!(c, ' ' | '_' | '-' | '[' | ']')
+    }) {
+        errors.push("contains invalid characters".into());
+    }
+
+    errors
+}
+
+pub fn validate_email(email: &str) -> (Option<Email>, Vec<String>) {
+    match Email::new(email) {
+        Ok(email) => (Some(email), Vec::new()),
+        Err(err) => (None, vec![err.to_string()]),
+    }
+}
+
+pub fn validate_password(password: &str, username: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let length = password.chars().count();
+    if !(PASSWORD_MIN_LENGTH..=PASSWORD_MAX_LENGTH).contains(&length) {
+        errors.push(format!(
+            "must be {PASSWORD_MIN_LENGTH} to {PASSWORD_MAX_LENGTH} characters \
+             long"
+        ));
+    }
+
+    if password.chars().collect::<HashSet<char>>().len() <= 3 {
+        errors.push("must have more than 3 unique characters".into());
+    }
+
+    if !username.trim().is_empty()
+        && password.to_lowercase().contains(&username.trim().to_lowercase())
+    {
+        errors.push("must not contain your username".into());
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_registrations_are_rate_limited() {
+        let guard =
+            RegistrationGuard::new(2, Duration::from_secs(60), HashSet::new());
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        let acquired = tokio::join!(
+            guard.try_acquire(ip),
+            guard.try_acquire(ip),
+            guard.try_acquire(ip),
+            guard.try_acquire(ip),
+        );
+
+        assert_eq!(
+            [acquired.0, acquired.1, acquired.2, acquired.3]
+                .into_iter()
+                .filter(|ok| *ok)
+                .count(),
+            2
+        );
+        assert!(guard.is_rate_limited(ip).await);
+        assert!(guard.try_acquire(IpAddr::from([127, 0, 0, 2])).await);
+    }
+
+    #[tokio::test]
+    async fn released_registrations_are_not_counted() {
+        let guard =
+            RegistrationGuard::new(1, Duration::from_secs(60), HashSet::new());
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        assert!(guard.try_acquire(ip).await);
+        guard.release(ip).await;
+
+        assert!(!guard.is_rate_limited(ip).await);
+        assert!(guard.try_acquire(ip).await);
+        assert!(!guard.try_acquire(ip).await);
+    }
+}
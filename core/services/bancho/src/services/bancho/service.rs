@@ -1,18 +1,47 @@
-use crate::*;
+use crate::{registration::RegistrationErrors, *};
 use bancho_packets::{server, Packet, PacketBuilder, PacketId, PacketReader};
 use core_bancho_state::DynBanchoStateService;
-use core_chat::DynChatService;
+use core_chat::{ChatError, DynChatService};
 use core_geoip::DynGeoipService;
-use domain_bancho::BanchoCountryCode;
+use domain_bancho::{BanchoCountryCode, BanchoPrivileges, OsuDirectRankStatus};
 use domain_chat::Platform;
+use domain_users::{CreateUser, Password, UsernameAscii};
+use infra_beatmaps::{BeatmapFileContent, BeatmapFileStorage};
 use infra_services::{FromRpcClient, IntoService, RpcClient};
+use num_traits::FromPrimitive;
 use pb_bancho::{bancho_rpc_client::BanchoRpcClient, *};
 use pb_bancho_state::*;
-use peace_repositories::users::DynUsersRepository;
-use std::{net::IpAddr, sync::Arc, time::Instant};
+use pb_chat::{
+    JoinSpectatorChannelRequest, LeaveSpectatorChannelRequest,
+    MarkMessagesReadRequest, ModerateMessageRequest, ModerateMessageResponse,
+};
+use peace_db::peace::entity::{
+    beatmaps,
+    sea_orm_active_enums::{CommentTarget, RankStatus, ScoreGrade},
+    users,
+};
+use peace_repositories::{
+    beatmaps::{DynBeatmapsRepository, SearchBeatmapsets},
+    comments::{CreateComment, DynCommentsRepository},
+    error_reports::{
+        CreateErrorReport, DynErrorReportsRepository, QueryErrorReportGroups,
+    },
+    favourites::DynFavouritesRepository,
+    scores::DynScoresRepository,
+    users::DynUsersRepository,
+    GetBeatmapError,
+};
+use std::{collections::HashMap, net::IpAddr, sync::Arc, time::Instant};
 use tonic::{async_trait, transport::Channel};
 use tools::{lazy_init, tonic_utils::RawRequest};
 
+/// Maximum amount of favourite beatmapsets per user.
+pub const MAX_FAVOURITE_BEATMAPSETS: u64 = 100;
+
+/// Range of the ratings submitted by `/web/osu-rate.php`.
+pub const MIN_BEATMAP_RATING: i16 = 1;
+pub const MAX_BEATMAP_RATING: i16 = 10;
+
 #[derive(Clone)]
 pub struct BanchoServiceImpl {
     pub users_repository: DynUsersRepository,
@@ -21,9 +50,20 @@ pub struct BanchoServiceImpl {
     pub bancho_background_service: DynBanchoBackgroundService,
     pub geoip_service: DynGeoipService,
     pub chat_service: DynChatService,
+    pub beatmaps_repository: DynBeatmapsRepository,
+    pub favourites_repository: DynFavouritesRepository,
+    pub comments_repository: DynCommentsRepository,
+    pub error_reports_repository: DynErrorReportsRepository,
+    pub scores_repository: DynScoresRepository,
+    pub beatmap_mirror: Option<BeatmapMirror>,
+    pub registration_guard: Arc<RegistrationGuard>,
+    pub menu_content: Arc<ReloadableMenuContent>,
+    pub beatmap_file_storage: BeatmapFileStorage,
+    pub check_updates_manifest: String,
 }
 
 impl BanchoServiceImpl {
+    #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn new(
         users_repository: DynUsersRepository,
@@ -32,6 +72,16 @@ impl BanchoServiceImpl {
         bancho_background_service: DynBanchoBackgroundService,
         geoip_service: DynGeoipService,
         chat_service: DynChatService,
+        beatmaps_repository: DynBeatmapsRepository,
+        favourites_repository: DynFavouritesRepository,
+        comments_repository: DynCommentsRepository,
+        error_reports_repository: DynErrorReportsRepository,
+        scores_repository: DynScoresRepository,
+        beatmap_mirror: Option<BeatmapMirror>,
+        registration_guard: RegistrationGuard,
+        menu_content: ReloadableMenuContent,
+        beatmap_file_storage: BeatmapFileStorage,
+        check_updates_manifest: String,
     ) -> Self {
         Self {
             users_repository,
@@ -40,8 +90,96 @@ impl BanchoServiceImpl {
             bancho_background_service,
             geoip_service,
             chat_service,
+            beatmaps_repository,
+            favourites_repository,
+            comments_repository,
+            error_reports_repository,
+            scores_repository,
+            beatmap_mirror,
+            registration_guard: Arc::new(registration_guard),
+            menu_content: Arc::new(menu_content),
+            beatmap_file_storage,
+            check_updates_manifest,
         }
     }
+
+    /// Verify the credentials sent with osu! web requests (`u` and `h`).
+    #[inline]
+    pub async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<users::Model, BanchoServiceError> {
+        let user = self
+            .users_repository
+            .get_user(None, Some(username), Some(username))
+            .await?;
+
+        let () = self
+            .password_service
+            .verify_password(user.password.as_str(), password)
+            .await?;
+
+        Ok(user)
+    }
+
+    /// Client privileges of the user, derived from the same privilege as the
+    /// level that channel permissions are checked against.
+    #[inline]
+    pub async fn bancho_privileges_of(
+        &self,
+        user_id: i32,
+    ) -> Result<BanchoPrivileges, BanchoServiceError> {
+        Ok(self
+            .users_repository
+            .get_user_privilege_name(user_id)
+            .await?
+            .as_deref()
+            .map(BanchoPrivileges::from_privilege_name)
+            .unwrap_or_default())
+    }
+
+    /// Returns the `.osu` file of the beatmap. Whenever a new version of the
+    /// file is downloaded, the stored star rating is recalculated from it.
+    pub async fn beatmap_file(
+        &self,
+        beatmap: &beatmaps::Model,
+    ) -> Result<Vec<u8>, BanchoServiceError> {
+        const LOG_TARGET: &str = "core_bancho::beatmap_file";
+
+        let BeatmapFileContent { content, downloaded } = self
+            .beatmap_file_storage
+            .get(beatmap.bid, &beatmap.md5)
+            .await?
+            .ok_or(BanchoServiceError::BeatmapFileNotExists)?;
+
+        if downloaded {
+            let refreshed =
+                match calculate_difficulty(content.clone(), None, 0).await {
+                    Ok(attrs) => self
+                        .beatmaps_repository
+                        .update_beatmap_difficulty(
+                            beatmap.bid,
+                            &beatmap.md5,
+                            attrs.stars(),
+                            attrs.max_combo() as i32,
+                        )
+                        .await
+                        .map_err(BanchoServiceError::from),
+                    Err(err) => Err(err.into()),
+                };
+
+            if let Err(err) = refreshed {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to refresh difficulty of beatmap <{}>: {err}",
+                    beatmap.bid
+                );
+            }
+        }
+
+        Ok(content)
+    }
 }
 
 impl BanchoService for BanchoServiceImpl {}
@@ -139,6 +277,15 @@ impl Login for BanchoServiceImpl {
             .verify_password(user.password.as_str(), password.as_str())
             .await?;
 
+        #[cfg(feature = "bancho-mock-test")]
+        let (privileges, bancho_privileges) = (1, BanchoPrivileges::Normal);
+
+        #[cfg(not(feature = "bancho-mock-test"))]
+        let (privileges, bancho_privileges) = (
+            self.users_repository.get_user_privilege_level(user.id).await?,
+            self.bancho_privileges_of(user.id).await?,
+        );
+
         let geoip_data =
             self.geoip_service.lookup_with_ip_address(client_ip).await.ok();
 
@@ -153,12 +300,12 @@ impl Login for BanchoServiceImpl {
                 user_id: user.id,
                 username: user.name.to_owned(),
                 username_unicode: user.name_unicode.to_owned(),
-                privileges: 1, // todo
+                privileges,
                 client_version,
                 utc_offset,
                 display_city,
                 only_friend_pm_allowed,
-                bancho_privileges: 1, // todo
+                bancho_privileges: bancho_privileges.bits(),
                 connection_info: Some(ConnectionInfo {
                     ip: client_ip.to_string(),
                     geoip_data: geoip_data.map(|g| g.into()),
@@ -173,8 +320,9 @@ impl Login for BanchoServiceImpl {
                 user_id: user.id,
                 username: user.name.to_owned(),
                 username_unicode: user.name_unicode,
-                privileges: 1, // todo
+                privileges,
                 platforms: Platform::Bancho.bits(),
+                only_friend_pm_allowed,
             })
             .await
         {
@@ -184,13 +332,19 @@ impl Login for BanchoServiceImpl {
             )
         }
 
-        let packet_builder = PacketBuilder::new()
+        let mut packet_builder = PacketBuilder::new()
             .add(server::ProtocolVersion::new(19))
             .add(server::LoginReply::success(user.id))
-            .add(server::BanchoPrivileges::new(1))
+            .add(server::BanchoPrivileges::new(bancho_privileges.bits()))
             .add(server::SilenceEnd::new(0)) // todo
             .add(server::FriendsList::new(&[]));
 
+        if let Some(main_menu_icon) =
+            self.menu_content.main_menu_icon_packet().await
+        {
+            packet_builder.add_ref(main_menu_icon);
+        }
+
         info!(
             target: LOG_TARGET,
             "Logged in: {} [{}] ({}), time spent: {:?}",
@@ -208,6 +362,118 @@ impl Login for BanchoServiceImpl {
         })
     }
 }
+#[async_trait]
+impl ClientRegister for BanchoServiceImpl {
+    async fn client_register(
+        &self,
+        client_ip: IpAddr,
+        request: ClientRegisterRequest,
+    ) -> Result<ClientRegisterResponse, BanchoServiceError> {
+        const LOG_TARGET: &str = "core_bancho::client_register";
+
+        let ClientRegisterRequest { username, email, password, check } =
+            request;
+
+        if self.registration_guard.is_rate_limited(client_ip).await {
+            return Err(BanchoServiceError::RegistrationRateLimited);
+        }
+
+        let geoip_data =
+            self.geoip_service.lookup_with_ip_address(client_ip).await.ok();
+
+        if let Some(geoip_data) = geoip_data.as_ref() {
+            if self
+                .registration_guard
+                .is_country_blocked(&geoip_data.country.code)
+            {
+                info!(
+                    target: LOG_TARGET,
+                    "Registration blocked: {username} ({client_ip}, {})",
+                    geoip_data.country.code
+                );
+                return Err(BanchoServiceError::RegistrationNotAllowed);
+            }
+        }
+
+        let mut response = ClientRegisterResponse {
+            username_errors: registration::validate_username(&username),
+            password_errors: registration::validate_password(
+                &password, &username,
+            ),
+            ..Default::default()
+        };
+
+        let (email, email_errors) = registration::validate_email(&email);
+        response.email_errors = email_errors;
+
+        if response.username_errors.is_empty()
+            && self
+                .users_repository
+                .get_user_by_username(username.trim())
+                .await
+                .is_ok()
+        {
+            response.username_errors.push("already taken".into());
+        }
+
+        if let Some(email) = email.as_ref() {
+            if self.users_repository.get_user_by_email(email).await.is_ok() {
+                response.email_errors.push("already in use".into());
+            }
+        }
+
+        // `check=1` is sent while the user is still filling the form
+        if !response.is_ok() || check {
+            return Ok(response);
+        }
+
+        let (Ok(name), Some(email)) =
+            (UsernameAscii::new(username.trim()), email)
+        else {
+            return Ok(response);
+        };
+
+        if !self.registration_guard.try_acquire(client_ip).await {
+            return Err(BanchoServiceError::RegistrationRateLimited);
+        }
+
+        let created = async {
+            // Logins send the md5 of the password, so that is what gets hashed
+            let password = Password::hash_password(format!(
+                "{:x}",
+                md5::compute(password.as_bytes())
+            ))?;
+
+            self.users_repository
+                .create_user(CreateUser {
+                    name,
+                    name_unicode: None,
+                    password,
+                    email,
+                    country: geoip_data.map(|d| d.country.code),
+                })
+                .await?;
+
+            Ok::<_, BanchoServiceError>(())
+        }
+        .await;
+
+        // Only created accounts count towards the rate limit
+        if created.is_err() {
+            self.registration_guard.release(client_ip).await;
+        }
+        created?;
+
+        info!(
+            target: LOG_TARGET,
+            "Registered: {} ({client_ip})",
+            username.trim()
+        );
+
+        Ok(response)
+    }
+}
+
 #[async_trait]
 impl BatchProcessPackets for BanchoServiceImpl {
     async fn batch_process_bancho_packets(
@@ -310,17 +576,20 @@ impl ProcessPackets for BanchoServiceImpl {
                 processor.user_presence_request().await?
             },
             // Spectate
-            PacketId::OSU_SPECTATE_START => todo!(),
-            PacketId::OSU_SPECTATE_STOP => todo!(),
+            PacketId::OSU_SPECTATE_START => processor.spectate_start().await?,
+            PacketId::OSU_SPECTATE_STOP => processor.spectate_stop().await?,
             PacketId::OSU_SPECTATE_CANT => todo!(),
             PacketId::OSU_SPECTATE_FRAMES => todo!(),
             // Multiplayer
             PacketId::OSU_USER_PART_LOBBY => todo!(),
             PacketId::OSU_USER_JOIN_LOBBY => todo!(),
-            PacketId::OSU_USER_PART_MATCH => todo!(),
+            // Matches are not tracked yet, creating or joining one fails
+            // and there is nothing to leave
+            PacketId::OSU_USER_PART_MATCH => HandleCompleted::default(),
             PacketId::OSU_USER_MATCH_READY => todo!(),
-            PacketId::OSU_USER_CREATE_MATCH => todo!(),
-            PacketId::OSU_USER_JOIN_MATCH => todo!(),
+            PacketId::OSU_USER_CREATE_MATCH | PacketId::OSU_USER_JOIN_MATCH => {
+                HandleCompleted { packets: Some(server::MatchJoinFail::pack()) }
+            },
             PacketId::OSU_MATCH_START => todo!(),
             PacketId::OSU_MATCH_COMPLETE => todo!(),
             PacketId::OSU_MATCH_LOAD_COMPLETE => todo!(),
@@ -336,7 +605,7 @@ impl ProcessPackets for BanchoServiceImpl {
             PacketId::OSU_MATCH_SCORE_UPDATE => todo!(),
             PacketId::OSU_MATCH_CHANGE_MODS => todo!(),
             PacketId::OSU_MATCH_TRANSFER_HOST => todo!(),
-            PacketId::OSU_MATCH_INVITE => todo!(),
+            PacketId::OSU_MATCH_INVITE => processor.match_invite().await?,
             PacketId::OSU_MATCH_CHANGE_PASSWORD => todo!(),
             // Tournament
             PacketId::OSU_TOURNAMENT_MATCH_INFO_REQUEST => todo!(),
@@ -468,9 +737,21 @@ impl ToggleBlockNonFriendDms for BanchoServiceImpl {
         &self,
         request: ToggleBlockNonFriendDmsRequest,
     ) -> Result<HandleCompleted, BanchoServiceError> {
-        println!("Got a request: {:?}", request);
+        let ToggleBlockNonFriendDmsRequest { user_id, toggle } = request;
 
-        // todo chat service
+        self.bancho_state_service
+            .update_only_friend_pm_allowed(UpdateOnlyFriendPmAllowedRequest {
+                user_query: Some(UserQuery::UserId(user_id).into()),
+                only_friend_pm_allowed: toggle,
+            })
+            .await?;
+
+        self.chat_service
+            .update_only_friend_pm_allowed(UpdateOnlyFriendPmAllowedRequest {
+                user_query: Some(UserQuery::UserId(user_id).into()),
+                only_friend_pm_allowed: toggle,
+            })
+            .await?;
 
         Ok(HandleCompleted::default())
     }
@@ -513,13 +794,41 @@ impl RequestPresence for BanchoServiceImpl {
     }
 }
 
+#[async_trait]
+impl SpectateStart for BanchoServiceImpl {
+    async fn spectate_start(
+        &self,
+        request: SpectateStartRequest,
+    ) -> Result<HandleCompleted, BanchoServiceError> {
+        let SpectateStartRequest { user_id, host_id } = request;
+
+        // Spectating a host who blocked the user fails silently.
+        if self.users_repository.is_blocked(host_id, user_id).await? {
+            return Ok(HandleCompleted::default());
+        }
+
+        self.chat_service
+            .join_spectator_channel(JoinSpectatorChannelRequest {
+                host_id,
+                user_query: Some(UserQuery::UserId(user_id).into()),
+            })
+            .await?;
+
+        Ok(HandleCompleted::default())
+    }
+}
+
 #[async_trait]
 impl SpectateStop for BanchoServiceImpl {
     async fn spectate_stop(
         &self,
         user_query: UserQuery,
     ) -> Result<HandleCompleted, BanchoServiceError> {
-        println!("Got a request: {:?}", user_query);
+        self.chat_service
+            .leave_spectator_channel(LeaveSpectatorChannelRequest {
+                user_query: Some(user_query.into()),
+            })
+            .await?;
 
         Ok(HandleCompleted::default())
     }
@@ -561,6 +870,650 @@ impl LobbyJoin for BanchoServiceImpl {
     }
 }
 
+#[async_trait]
+impl MatchInvite for BanchoServiceImpl {
+    async fn match_invite(
+        &self,
+        _request: MatchInviteRequest,
+    ) -> Result<HandleCompleted, BanchoServiceError> {
+        // todo: multiplayer matches are not tracked yet, so there is no
+        // match to invite into.
+        Err(BanchoServiceError::NotInMatch)
+    }
+}
+
+#[async_trait]
+impl OsuSearch for BanchoServiceImpl {
+    async fn osu_search(
+        &self,
+        request: OsuSearchRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        const LOG_TARGET: &str = "bancho::osu_search";
+
+        let OsuSearchRequest {
+            username,
+            password,
+            ranked_status,
+            query,
+            mode,
+            page,
+        } = request;
+
+        self.authenticate(&username, &password).await?;
+
+        let beatmapsets = self
+            .beatmaps_repository
+            .search_beatmapsets(SearchBeatmapsets {
+                keywords: osu_direct::parse_keywords(&query),
+                rank_status: osu_direct::rank_status_filter(
+                    OsuDirectRankStatus::from_i32(ranked_status)
+                        .unwrap_or_default(),
+                ),
+                game_mode: osu_direct::game_mode_from_val(mode),
+                offset: page.max(0) as u64 * osu_direct::OSU_DIRECT_PAGE_SIZE,
+                limit: osu_direct::OSU_DIRECT_PAGE_SIZE,
+            })
+            .await?;
+
+        if beatmapsets.is_empty() {
+            if let Some(mirror) = self.beatmap_mirror.as_ref() {
+                match mirror
+                    .osu_search(&[
+                        ("r", ranked_status.to_string()),
+                        ("q", query),
+                        ("m", mode.to_string()),
+                        ("p", page.to_string()),
+                    ])
+                    .await
+                {
+                    Ok(body) => return Ok(HttpResponse { body }),
+                    Err(err) => {
+                        warn!(target: LOG_TARGET, "Mirror request failed: {err}")
+                    },
+                }
+            }
+        }
+
+        Ok(HttpResponse {
+            body: osu_direct::format_search_result(&beatmapsets),
+        })
+    }
+}
+
+#[async_trait]
+impl OsuSearchSet for BanchoServiceImpl {
+    async fn osu_search_set(
+        &self,
+        request: OsuSearchSetRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        const LOG_TARGET: &str = "bancho::osu_search_set";
+
+        let OsuSearchSetRequest {
+            username,
+            password,
+            beatmapset_id,
+            beatmap_id,
+            beatmap_md5,
+        } = request;
+
+        self.authenticate(&username, &password).await?;
+
+        let beatmap = self
+            .beatmaps_repository
+            .get_beatmap(beatmap_id, beatmapset_id, beatmap_md5.as_deref())
+            .await;
+
+        match beatmap {
+            Ok(beatmap) => Ok(HttpResponse {
+                body: osu_direct::format_beatmapset_info(&beatmap),
+            }),
+            Err(GetBeatmapError::BeatmapNotExists) => {
+                if let Some(mirror) = self.beatmap_mirror.as_ref() {
+                    let params = [
+                        beatmapset_id.map(|sid| ("s", sid.to_string())),
+                        beatmap_id.map(|bid| ("b", bid.to_string())),
+                        beatmap_md5.map(|md5| ("c", md5)),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<(&str, String)>>();
+
+                    match mirror.osu_search_set(&params).await {
+                        Ok(body) => return Ok(HttpResponse { body }),
+                        Err(err) => {
+                            warn!(
+                                target: LOG_TARGET,
+                                "Mirror request failed: {err}"
+                            )
+                        },
+                    }
+                }
+
+                Ok(HttpResponse::default())
+            },
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl OsuGetBeatmapInfo for BanchoServiceImpl {
+    async fn osu_getbeatmapinfo(
+        &self,
+        request: OsuGetBeatmapInfoRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        let OsuGetBeatmapInfoRequest {
+            username,
+            password,
+            filenames,
+            beatmap_ids,
+        } = request;
+
+        let user = self.authenticate(&username, &password).await?;
+
+        let beatmaps = self
+            .beatmaps_repository
+            .get_beatmaps_by_file_names_or_ids(filenames.clone(), beatmap_ids)
+            .await?;
+
+        let beatmap_md5s =
+            beatmaps.iter().map(|b| b.md5.to_owned()).collect::<Vec<_>>();
+
+        // One query per mode for all of the requested beatmaps
+        let [standard, taiko, fruits, mania] = beatmap_info::BEATMAP_INFO_MODES
+            .map(|mode| {
+                self.scores_repository.get_user_best_grades(
+                    user.id,
+                    mode,
+                    beatmap_md5s.clone(),
+                )
+            });
+        let grades = tokio::try_join!(standard, taiko, fruits, mania)?;
+
+        let filename_indexes = filenames
+            .iter()
+            .enumerate()
+            .map(|(index, filename)| (filename.as_str(), index as i32))
+            .collect::<HashMap<&str, i32>>();
+
+        Ok(HttpResponse {
+            body: beatmaps
+                .iter()
+                .map(|beatmap| {
+                    let grade = |grades: &HashMap<String, ScoreGrade>| {
+                        beatmap_info::score_grade_to_client(
+                            grades.get(&beatmap.md5),
+                        )
+                    };
+
+                    beatmap_info::format_beatmap_info(
+                        filename_indexes
+                            .get(beatmap.file_name.as_str())
+                            .copied()
+                            .unwrap_or(-1),
+                        beatmap,
+                        [
+                            grade(&grades.0),
+                            grade(&grades.1),
+                            grade(&grades.2),
+                            grade(&grades.3),
+                        ],
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+        })
+    }
+}
+
+#[async_trait]
+impl OsuGetFavourites for BanchoServiceImpl {
+    async fn osu_getfavourites(
+        &self,
+        request: OsuGetFavouritesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        let OsuGetFavouritesRequest { username, password } = request;
+
+        let user = self.authenticate(&username, &password).await?;
+
+        let beatmapset_ids = self
+            .favourites_repository
+            .get_favourite_beatmapsets(user.id)
+            .await?;
+
+        Ok(HttpResponse {
+            body: beatmapset_ids
+                .into_iter()
+                .map(|sid| sid.to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        })
+    }
+}
+
+#[async_trait]
+impl OsuAddFavourite for BanchoServiceImpl {
+    async fn osu_addfavourite(
+        &self,
+        request: OsuAddFavouriteRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        let OsuAddFavouriteRequest { username, password, beatmapset_id } =
+            request;
+
+        let user = self.authenticate(&username, &password).await?;
+
+        let () = self
+            .favourites_repository
+            .add_favourite_beatmapset(
+                user.id,
+                beatmapset_id,
+                MAX_FAVOURITE_BEATMAPSETS,
+            )
+            .await?;
+
+        Ok(HttpResponse::default())
+    }
+}
+
+#[async_trait]
+impl OsuRate for BanchoServiceImpl {
+    async fn osu_rate(
+        &self,
+        request: OsuRateRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        let OsuRateRequest { username, password, beatmap_md5, vote } = request;
+
+        let user = self.authenticate(&username, &password).await?;
+
+        let beatmap = match self
+            .beatmaps_repository
+            .get_beatmap(None, None, Some(&beatmap_md5))
+            .await
+        {
+            Ok(beatmap) => beatmap,
+            Err(GetBeatmapError::BeatmapNotExists) => {
+                return Ok(HttpResponse { body: "no exist".into() })
+            },
+            Err(err) => return Err(err.into()),
+        };
+
+        if !matches!(
+            beatmap.rank_status,
+            RankStatus::Ranked
+                | RankStatus::Approved
+                | RankStatus::Qualified
+                | RankStatus::Loved
+        ) {
+            return Ok(HttpResponse { body: "not ranked".into() });
+        }
+
+        match vote {
+            Some(vote) => {
+                self.beatmaps_repository
+                    .rate_beatmap(
+                        user.id,
+                        &beatmap.md5,
+                        vote.clamp(
+                            MIN_BEATMAP_RATING as i32,
+                            MAX_BEATMAP_RATING as i32,
+                        ) as i16,
+                    )
+                    .await?;
+            },
+            None => {
+                let rating = self
+                    .beatmaps_repository
+                    .get_beatmap_rating(user.id, &beatmap.md5)
+                    .await?;
+
+                // Tell the client that the user can rate this beatmap
+                if rating.is_none() {
+                    return Ok(HttpResponse { body: "ok".into() });
+                }
+            },
+        }
+
+        let average = self
+            .beatmaps_repository
+            .get_beatmap_average_rating(&beatmap.md5)
+            .await?
+            .unwrap_or_default();
+
+        Ok(HttpResponse { body: format!("alreadyvoted\n{average:.2}") })
+    }
+}
+
+#[async_trait]
+impl OsuMarkAsRead for BanchoServiceImpl {
+    async fn osu_mark_as_read(
+        &self,
+        request: OsuMarkAsReadRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        let OsuMarkAsReadRequest { username, password, channel } = request;
+
+        let user = self.authenticate(&username, &password).await?;
+
+        // public channels have no unread messages
+        if !channel.starts_with('#') {
+            self.chat_service
+                .mark_messages_read(MarkMessagesReadRequest {
+                    user_id: user.id,
+                    sender: Some(UserQuery::Username(channel).into()),
+                })
+                .await?;
+        }
+
+        Ok(HttpResponse::default())
+    }
+}
+
+#[async_trait]
+impl OsuComment for BanchoServiceImpl {
+    async fn osu_comment(
+        &self,
+        request: OsuCommentRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        const LOG_TARGET: &str = "bancho::osu_comment";
+
+        let OsuCommentRequest {
+            username,
+            password,
+            beatmap_id,
+            beatmapset_id,
+            score_id,
+            action,
+            target,
+            colour,
+            start_time,
+            comment,
+            ..
+        } = request;
+
+        let user = self.authenticate(&username, &password).await?;
+
+        match action.as_str() {
+            "get" => {
+                let comments = self
+                    .comments_repository
+                    .get_comments(beatmap_id, beatmapset_id, score_id)
+                    .await?;
+
+                Ok(HttpResponse {
+                    body: comments
+                        .iter()
+                        .map(comments::format_comment)
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                })
+            },
+            "post" => {
+                let (Some(target), Some(time), Some(comment)) = (
+                    target
+                        .as_deref()
+                        .and_then(comments::comment_target_from_client),
+                    start_time,
+                    comment,
+                ) else {
+                    return Ok(HttpResponse::default());
+                };
+
+                let target_id = match target {
+                    CommentTarget::Beatmap => beatmap_id as i64,
+                    CommentTarget::Replay => score_id,
+                    CommentTarget::Beatmapset | CommentTarget::Song => {
+                        beatmapset_id as i64
+                    },
+                };
+
+                // Comments go through the same moderation as chat messages
+                let comment = match self
+                    .chat_service
+                    .moderate_message(ModerateMessageRequest {
+                        user_id: user.id,
+                        message: comment,
+                    })
+                    .await
+                {
+                    Ok(ModerateMessageResponse { message }) => message,
+                    Err(ChatError::MessageRejected(reason)) => {
+                        info!(
+                            target: LOG_TARGET,
+                            "Comment from {}({}) rejected: {reason}",
+                            user.name,
+                            user.id
+                        );
+                        return Ok(HttpResponse::default());
+                    },
+                    Err(err) => return Err(err.into()),
+                };
+
+                let privileges = self.bancho_privileges_of(user.id).await?;
+
+                self.comments_repository
+                    .create_comment(CreateComment {
+                        user_id: user.id,
+                        target,
+                        target_id,
+                        time,
+                        comment,
+                        colour: comments::comment_colour(
+                            colour.as_deref(),
+                            privileges,
+                        ),
+                        privileges: privileges.bits(),
+                    })
+                    .await?;
+
+                Ok(HttpResponse::default())
+            },
+            _ => Ok(HttpResponse::default()),
+        }
+    }
+}
+
+#[async_trait]
+impl OsuError for BanchoServiceImpl {
+    async fn osu_error(
+        &self,
+        request: OsuErrorRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        const LOG_TARGET: &str = "bancho::osu_error";
+
+        let OsuErrorRequest {
+            username,
+            password,
+            client_version,
+            exe_hash,
+            osu_mode,
+            game_mode,
+            game_time,
+            audio_time,
+            culture,
+            beatmap_id,
+            beatmap_md5,
+            exception,
+            stacktrace,
+            feedback,
+            config,
+            soft,
+            beatmap_count,
+            compatibility,
+            ram,
+        } = request;
+
+        // Reports can be sent before logging in, they are kept anyway
+        let user_id = match (username.as_deref(), password.as_deref()) {
+            (Some(username), Some(password)) => {
+                self.authenticate(username, password).await.ok().map(|u| u.id)
+            },
+            _ => None,
+        };
+
+        let signature =
+            error_reports::error_report_signature(&exception, &stacktrace);
+
+        info!(
+            target: LOG_TARGET,
+            "Error report from {} [{client_version}]: {signature}",
+            username.as_deref().unwrap_or("unknown")
+        );
+
+        self.error_reports_repository
+            .create_error_report(CreateErrorReport {
+                user_id,
+                username,
+                client_version,
+                exe_hash,
+                osu_mode,
+                game_mode,
+                game_time,
+                audio_time,
+                culture,
+                beatmap_id,
+                beatmap_md5,
+                signature,
+                exception,
+                stacktrace,
+                feedback,
+                config,
+                soft,
+                beatmap_count,
+                compatibility,
+                ram,
+            })
+            .await?;
+
+        Ok(HttpResponse::default())
+    }
+}
+
+#[async_trait]
+impl OsuGetSeasonal for BanchoServiceImpl {
+    async fn osu_get_seasonal(
+        &self,
+        _request: OsuGetSeasonalRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(HttpResponse {
+            body: self.menu_content.seasonal_backgrounds_json().await,
+        })
+    }
+}
+
+#[async_trait]
+impl OsuGetBeatmapFile for BanchoServiceImpl {
+    async fn osu_get_beatmap_file(
+        &self,
+        request: OsuGetBeatmapFileRequest,
+    ) -> Result<BeatmapFile, BanchoServiceError> {
+        let beatmap = self
+            .beatmaps_repository
+            .get_beatmap_by_file_name(&request.file_name)
+            .await?;
+
+        let content = self.beatmap_file(&beatmap).await?;
+
+        Ok(BeatmapFile { content })
+    }
+}
+
+#[async_trait]
+impl OsuCheckUpdates for BanchoServiceImpl {
+    async fn osu_check_updates(
+        &self,
+        _request: OsuCheckUpdatesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(HttpResponse { body: self.check_updates_manifest.to_owned() })
+    }
+}
+
+#[async_trait]
+impl GetDifficultyRating for BanchoServiceImpl {
+    async fn get_difficulty_rating(
+        &self,
+        request: DifficultyRatingRequest,
+    ) -> Result<DifficultyRating, BanchoServiceError> {
+        let DifficultyRatingRequest { beatmap_id, ruleset_id, mods } = request;
+
+        let beatmap = self
+            .beatmaps_repository
+            .get_beatmap(Some(beatmap_id), None, None)
+            .await?;
+
+        let content = self.beatmap_file(&beatmap).await?;
+        let attrs = calculate_difficulty(content, ruleset_id, mods).await?;
+
+        Ok(DifficultyRating {
+            star_rating: attrs.stars(),
+            max_combo: attrs.max_combo() as i32,
+        })
+    }
+}
+
+#[async_trait]
+impl GetErrorReportGroups for BanchoServiceImpl {
+    async fn get_error_report_groups(
+        &self,
+        request: GetErrorReportGroupsRequest,
+    ) -> Result<ErrorReportGroups, BanchoServiceError> {
+        let GetErrorReportGroupsRequest { client_version, offset, limit } =
+            request;
+
+        let limit = match limit {
+            0 => error_reports::DEFAULT_ERROR_REPORT_GROUPS_LIMIT,
+            limit => limit.min(error_reports::MAX_ERROR_REPORT_GROUPS_LIMIT),
+        };
+
+        let groups = self
+            .error_reports_repository
+            .get_error_report_groups(QueryErrorReportGroups {
+                client_version,
+                offset,
+                limit,
+            })
+            .await?;
+
+        Ok(ErrorReportGroups {
+            groups: groups
+                .into_iter()
+                .map(|group| ErrorReportGroup {
+                    signature: group.signature,
+                    exception: group.exception,
+                    client_version: group.client_version,
+                    reports: group.reports,
+                    users: group.users,
+                    last_reported_at: group.last_reported_at.to_rfc3339(),
+                })
+                .collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl MenuContentAdmin for BanchoServiceImpl {
+    async fn get_menu_content(
+        &self,
+        _request: GetMenuContentRequest,
+    ) -> Result<MenuContent, BanchoServiceError> {
+        Ok(self.menu_content.get().await)
+    }
+
+    async fn update_menu_content(
+        &self,
+        request: MenuContent,
+    ) -> Result<MenuContent, BanchoServiceError> {
+        const LOG_TARGET: &str = "core_bancho::update_menu_content";
+
+        info!(
+            target: LOG_TARGET,
+            "Menu content updated: {} seasonal backgrounds, icon: {:?}",
+            request.seasonal_backgrounds.len(),
+            request.main_menu_icon.as_ref().map(|icon| &icon.image_url)
+        );
+
+        Ok(self.menu_content.replace(request).await)
+    }
+}
+
 #[derive(Clone)]
 pub struct BanchoServiceRemote(BanchoRpcClient<Channel>);
 
@@ -602,6 +1555,21 @@ impl Login for BanchoServiceRemote {
             .into_inner())
     }
 }
+#[async_trait]
+impl ClientRegister for BanchoServiceRemote {
+    async fn client_register(
+        &self,
+        client_ip: IpAddr,
+        request: ClientRegisterRequest,
+    ) -> Result<ClientRegisterResponse, BanchoServiceError> {
+        Ok(self
+            .client()
+            .client_register(RawRequest::add_client_ip(request, client_ip))
+            .await?
+            .into_inner())
+    }
+}
+
 #[async_trait]
 impl BatchProcessPackets for BanchoServiceRemote {
     async fn batch_process_bancho_packets(
@@ -733,6 +1701,16 @@ impl RequestPresence for BanchoServiceRemote {
     }
 }
 
+#[async_trait]
+impl SpectateStart for BanchoServiceRemote {
+    async fn spectate_start(
+        &self,
+        request: SpectateStartRequest,
+    ) -> Result<HandleCompleted, BanchoServiceError> {
+        Ok(self.client().spectate_start(request).await?.into_inner())
+    }
+}
+
 #[async_trait]
 impl SpectateStop for BanchoServiceRemote {
     async fn spectate_stop(
@@ -788,3 +1766,170 @@ impl LobbyJoin for BanchoServiceRemote {
             .into_inner())
     }
 }
+
+#[async_trait]
+impl MatchInvite for BanchoServiceRemote {
+    async fn match_invite(
+        &self,
+        request: MatchInviteRequest,
+    ) -> Result<HandleCompleted, BanchoServiceError> {
+        Ok(self.client().match_invite(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuSearch for BanchoServiceRemote {
+    async fn osu_search(
+        &self,
+        request: OsuSearchRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_search(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuSearchSet for BanchoServiceRemote {
+    async fn osu_search_set(
+        &self,
+        request: OsuSearchSetRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_search_set(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuGetBeatmapInfo for BanchoServiceRemote {
+    async fn osu_getbeatmapinfo(
+        &self,
+        request: OsuGetBeatmapInfoRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_get_beatmap_info(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuGetFavourites for BanchoServiceRemote {
+    async fn osu_getfavourites(
+        &self,
+        request: OsuGetFavouritesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_get_favourites(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuAddFavourite for BanchoServiceRemote {
+    async fn osu_addfavourite(
+        &self,
+        request: OsuAddFavouriteRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_add_favourite(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuRate for BanchoServiceRemote {
+    async fn osu_rate(
+        &self,
+        request: OsuRateRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_rate(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuMarkAsRead for BanchoServiceRemote {
+    async fn osu_mark_as_read(
+        &self,
+        request: OsuMarkAsReadRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_mark_as_read(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuComment for BanchoServiceRemote {
+    async fn osu_comment(
+        &self,
+        request: OsuCommentRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_comment(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuError for BanchoServiceRemote {
+    async fn osu_error(
+        &self,
+        request: OsuErrorRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_error(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuGetSeasonal for BanchoServiceRemote {
+    async fn osu_get_seasonal(
+        &self,
+        request: OsuGetSeasonalRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_get_seasonal(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuGetBeatmapFile for BanchoServiceRemote {
+    async fn osu_get_beatmap_file(
+        &self,
+        request: OsuGetBeatmapFileRequest,
+    ) -> Result<BeatmapFile, BanchoServiceError> {
+        Ok(self.client().osu_get_beatmap_file(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl OsuCheckUpdates for BanchoServiceRemote {
+    async fn osu_check_updates(
+        &self,
+        request: OsuCheckUpdatesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        Ok(self.client().osu_check_updates(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl GetDifficultyRating for BanchoServiceRemote {
+    async fn get_difficulty_rating(
+        &self,
+        request: DifficultyRatingRequest,
+    ) -> Result<DifficultyRating, BanchoServiceError> {
+        Ok(self.client().get_difficulty_rating(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl GetErrorReportGroups for BanchoServiceRemote {
+    async fn get_error_report_groups(
+        &self,
+        request: GetErrorReportGroupsRequest,
+    ) -> Result<ErrorReportGroups, BanchoServiceError> {
+        Ok(self.client().get_error_report_groups(request).await?.into_inner())
+    }
+}
+
+#[async_trait]
+impl MenuContentAdmin for BanchoServiceRemote {
+    async fn get_menu_content(
+        &self,
+        request: GetMenuContentRequest,
+    ) -> Result<MenuContent, BanchoServiceError> {
+        Ok(self.client().get_menu_content(request).await?.into_inner())
+    }
+
+    async fn update_menu_content(
+        &self,
+        request: MenuContent,
+    ) -> Result<MenuContent, BanchoServiceError> {
+        Ok(self.client().update_menu_content(request).await?.into_inner())
+    }
+}
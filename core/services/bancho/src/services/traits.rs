@@ -45,6 +45,7 @@ pub trait BanchoBackgroundService: PasswordBackgroundService {
 
 pub trait BanchoService:
     Login
+    + ClientRegister
     + BatchProcessPackets
     + ProcessPackets
     + ClientPing
@@ -56,10 +57,27 @@ pub trait BanchoService:
     + ToggleBlockNonFriendDms
     + UserLogout
     + RequestPresence
+    + SpectateStart
     + SpectateStop
     + SpectateCant
     + LobbyPart
     + LobbyJoin
+    + MatchInvite
+    + OsuSearch
+    + OsuSearchSet
+    + OsuGetBeatmapInfo
+    + OsuGetFavourites
+    + OsuAddFavourite
+    + OsuRate
+    + OsuComment
+    + OsuMarkAsRead
+    + OsuError
+    + OsuGetSeasonal
+    + OsuGetBeatmapFile
+    + OsuCheckUpdates
+    + GetDifficultyRating
+    + GetErrorReportGroups
+    + MenuContentAdmin
 {
 }
 
@@ -72,6 +90,15 @@ pub trait Login {
     ) -> Result<LoginSuccess, BanchoServiceError>;
 }
 
+#[async_trait]
+pub trait ClientRegister {
+    async fn client_register(
+        &self,
+        client_ip: IpAddr,
+        request: ClientRegisterRequest,
+    ) -> Result<ClientRegisterResponse, BanchoServiceError>;
+}
+
 #[async_trait]
 pub trait BatchProcessPackets {
     async fn batch_process_bancho_packets(
@@ -158,6 +185,14 @@ pub trait RequestPresence {
     ) -> Result<HandleCompleted, BanchoServiceError>;
 }
 
+#[async_trait]
+pub trait SpectateStart {
+    async fn spectate_start(
+        &self,
+        request: SpectateStartRequest,
+    ) -> Result<HandleCompleted, BanchoServiceError>;
+}
+
 #[async_trait]
 pub trait SpectateStop {
     async fn spectate_stop(
@@ -190,6 +225,141 @@ pub trait LobbyJoin {
     ) -> Result<HandleCompleted, BanchoServiceError>;
 }
 
+#[async_trait]
+pub trait MatchInvite {
+    async fn match_invite(
+        &self,
+        request: MatchInviteRequest,
+    ) -> Result<HandleCompleted, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuSearch {
+    async fn osu_search(
+        &self,
+        request: OsuSearchRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuSearchSet {
+    async fn osu_search_set(
+        &self,
+        request: OsuSearchSetRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuGetBeatmapInfo {
+    async fn osu_getbeatmapinfo(
+        &self,
+        request: OsuGetBeatmapInfoRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuGetFavourites {
+    async fn osu_getfavourites(
+        &self,
+        request: OsuGetFavouritesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuAddFavourite {
+    async fn osu_addfavourite(
+        &self,
+        request: OsuAddFavouriteRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuRate {
+    async fn osu_rate(
+        &self,
+        request: OsuRateRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuComment {
+    async fn osu_comment(
+        &self,
+        request: OsuCommentRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuMarkAsRead {
+    async fn osu_mark_as_read(
+        &self,
+        request: OsuMarkAsReadRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuError {
+    async fn osu_error(
+        &self,
+        request: OsuErrorRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuGetSeasonal {
+    async fn osu_get_seasonal(
+        &self,
+        request: OsuGetSeasonalRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuGetBeatmapFile {
+    async fn osu_get_beatmap_file(
+        &self,
+        request: OsuGetBeatmapFileRequest,
+    ) -> Result<BeatmapFile, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait OsuCheckUpdates {
+    async fn osu_check_updates(
+        &self,
+        request: OsuCheckUpdatesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait GetDifficultyRating {
+    async fn get_difficulty_rating(
+        &self,
+        request: DifficultyRatingRequest,
+    ) -> Result<DifficultyRating, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait GetErrorReportGroups {
+    async fn get_error_report_groups(
+        &self,
+        request: GetErrorReportGroupsRequest,
+    ) -> Result<ErrorReportGroups, BanchoServiceError>;
+}
+
+#[async_trait]
+pub trait MenuContentAdmin {
+    async fn get_menu_content(
+        &self,
+        request: GetMenuContentRequest,
+    ) -> Result<MenuContent, BanchoServiceError>;
+
+    /// Replaces the seasonal backgrounds and the main menu icon, returns the
+    /// previous content.
+    async fn update_menu_content(
+        &self,
+        request: MenuContent,
+    ) -> Result<MenuContent, BanchoServiceError>;
+}
+
 pub trait BanchoPacketProcessor:
     ProcessSendPublicMessage
     + ProcessSendPrivateMessage
@@ -203,6 +373,9 @@ pub trait BanchoPacketProcessor:
     + ProcessUserToggleBlockNonFriendDms
     + ProcessUserLogout
     + ProcessUserPresenceRequest
+    + ProcessSpectateStart
+    + ProcessSpectateStop
+    + ProcessMatchInvite
 {
 }
 
@@ -289,3 +462,24 @@ pub trait ProcessUserPresenceRequest {
         &self,
     ) -> Result<HandleCompleted, ProcessBanchoPacketError>;
 }
+
+#[async_trait]
+pub trait ProcessSpectateStart {
+    async fn spectate_start(
+        &self,
+    ) -> Result<HandleCompleted, ProcessBanchoPacketError>;
+}
+
+#[async_trait]
+pub trait ProcessSpectateStop {
+    async fn spectate_stop(
+        &self,
+    ) -> Result<HandleCompleted, ProcessBanchoPacketError>;
+}
+
+#[async_trait]
+pub trait ProcessMatchInvite {
+    async fn match_invite(
+        &self,
+    ) -> Result<HandleCompleted, ProcessBanchoPacketError>;
+}
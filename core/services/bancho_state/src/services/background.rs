@@ -3,6 +3,7 @@ use crate::{
     NotifyMessagesCleaner, UserSessionsCleaner,
 };
 use async_trait::async_trait;
+use clap::Parser;
 use clap_serde_derive::ClapSerde;
 use peace_unique_id::Ulid;
 use std::{
@@ -257,6 +257,33 @@ impl UpdatePresenceFilter for BanchoStateServiceImpl {
     }
 }
 
+#[async_trait]
+impl UpdateOnlyFriendPmAllowed for BanchoStateServiceImpl {
+    async fn update_only_friend_pm_allowed(
+        &self,
+        request: UpdateOnlyFriendPmAllowedRequest,
+    ) -> Result<ExecSuccess, BanchoStateError> {
+        let UpdateOnlyFriendPmAllowedRequest {
+            user_query,
+            only_friend_pm_allowed,
+        } = request;
+
+        let query = user_query
+            .ok_or(BanchoStateError::InvalidArgument)?
+            .into_user_query()?;
+
+        let session = self
+            .user_sessions_service
+            .get(&query)
+            .await
+            .ok_or(BanchoStateError::SessionNotExists)?;
+
+        session.extends.only_friend_pm_allowed.set(only_friend_pm_allowed);
+
+        Ok(ExecSuccess::default())
+    }
+}
+
 #[async_trait]
 impl BatchSendPresences for BanchoStateServiceImpl {
     async fn batch_send_presences(
@@ -241,6 +241,20 @@ impl UpdatePresenceFilter for BanchoStateServiceRemote {
     }
 }
 
+#[async_trait]
+impl UpdateOnlyFriendPmAllowed for BanchoStateServiceRemote {
+    async fn update_only_friend_pm_allowed(
+        &self,
+        request: UpdateOnlyFriendPmAllowedRequest,
+    ) -> Result<ExecSuccess, BanchoStateError> {
+        Ok(self
+            .client()
+            .update_only_friend_pm_allowed(request)
+            .await?
+            .into_inner())
+    }
+}
+
 #[async_trait]
 impl UpdateUserBanchoStatus for BanchoStateServiceRemote {
     async fn update_user_bancho_status(
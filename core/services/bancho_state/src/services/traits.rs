@@ -143,7 +143,7 @@ pub trait UserSessionsCreate: UserSessionsStore + NotifyMessagesQueue {
             bancho_packets::server::UserPresenceSingle::pack(session.user_id)
                 .into(),
             [session.user_id],
-            Some(Arc::new(move |_| weak.upgrade().is_some())),
+            Some(Arc::new(move |_, _| weak.upgrade().is_some())),
         );
 
         let online_users = {
@@ -202,6 +202,7 @@ pub trait UserSessionsExists: UserSessionsStore {
 pub trait BanchoStateService:
     UpdateUserBanchoStatus
     + UpdatePresenceFilter
+    + UpdateOnlyFriendPmAllowed
     + BatchSendPresences
     + SendAllPresences
     + BatchSendUserStatsPacket
@@ -239,6 +240,14 @@ pub trait UpdatePresenceFilter {
     ) -> Result<ExecSuccess, BanchoStateError>;
 }
 
+#[async_trait]
+pub trait UpdateOnlyFriendPmAllowed {
+    async fn update_only_friend_pm_allowed(
+        &self,
+        request: UpdateOnlyFriendPmAllowedRequest,
+    ) -> Result<ExecSuccess, BanchoStateError>;
+}
+
 #[async_trait]
 pub trait BatchSendPresences {
     async fn batch_send_presences(
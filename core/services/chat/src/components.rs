@@ -1,4 +1,4 @@
-use crate::{BanchoMessageData, BanchoMessageQueue};
+use crate::{BanchoMessageData, BanchoMessageQueue, RecentMessage};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use clap_serde_derive::ClapSerde;
@@ -7,19 +7,25 @@ use infra_packets::{Packet, PacketsQueue};
 use infra_users::{
     BaseSession, BaseSessionData, CreateSessionDto, UserIndexes, UserStore,
 };
-use pb_chat::ChannelQuery;
+use pb_chat::{ChannelInfo, ChannelQuery};
+use peace_message_queue::MessageValidator;
 use peace_snapshot::{cli_snapshot_config, CreateSnapshot, SnapshotType};
 use peace_unique_id::Ulid;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     ops::{Deref, DerefMut},
     sync::{Arc, Weak},
 };
 use tokio::sync::{Mutex, RwLock};
 use tools::atomic::{
-    Atomic, AtomicOperation, AtomicOption, AtomicValue, Usize, U32,
+    Atomic, AtomicOperation, AtomicOption, AtomicValue, Bool, Usize, U32,
 };
 
+/// Bancho clients name the spectator and multiplayer channel of the user
+/// by these aliases.
+pub const SPECTATOR_CHANNEL_ALIAS: &str = "#spectator";
+pub const MULTIPLAYER_CHANNEL_ALIAS: &str = "#multiplayer";
+
 pub type SessionIndexes = UserIndexes<ChatSession>;
 pub type UserSessions = UserStore<ChatSession>;
 
@@ -156,6 +162,11 @@ pub struct ChatSessionExtend {
     pub bancho_ext: AtomicOption<BanchoChatExt>,
     pub joined_channels: RwLock<HashMap<u64, Arc<JoinedChannel>>>,
     pub channel_count: U32,
+    pub recent_message: Mutex<RecentMessage>,
+    /// Private messages are only accepted from friends.
+    pub only_friend_pm_allowed: Bool,
+    /// Users blocked by the user, loaded on login.
+    pub blocked_users: Atomic<HashSet<i32>>,
 }
 
 impl From<ChatSessionExtendData> for ChatSessionExtend {
@@ -177,6 +188,9 @@ impl From<ChatSessionExtendData> for ChatSessionExtend {
                 }),
             )),
             channel_count,
+            recent_message: Mutex::default(),
+            only_friend_pm_allowed: data.only_friend_pm_allowed.into(),
+            blocked_users: HashSet::from_iter(data.blocked_users).into(),
         }
     }
 }
@@ -195,6 +209,9 @@ impl ChatSessionExtend {
             bancho_ext: bancho_ext.into(),
             joined_channels: RwLock::new(joined_channels),
             channel_count: U32::from(channel_count as u32),
+            recent_message: Mutex::default(),
+            only_friend_pm_allowed: Bool::default(),
+            blocked_users: Atomic::default(),
         }
     }
 
@@ -212,6 +229,38 @@ impl ChatSessionExtend {
 
         channels
     }
+
+    #[inline]
+    pub fn is_blocking(&self, user_id: i32) -> bool {
+        self.blocked_users.load().contains(&user_id)
+    }
+
+    #[inline]
+    pub fn set_blocked(&self, user_id: i32, blocked: bool) {
+        self.blocked_users.rcu(|blocked_users| {
+            let mut blocked_users = HashSet::clone(blocked_users);
+            if blocked {
+                blocked_users.insert(user_id);
+            } else {
+                blocked_users.remove(&user_id);
+            }
+
+            blocked_users
+        });
+    }
+
+    pub async fn joined_channels_of_type(
+        &self,
+        channel_type: ChannelType,
+    ) -> Vec<Arc<Channel>> {
+        self.joined_channels
+            .read()
+            .await
+            .values()
+            .filter_map(|joined| joined.ptr.load().upgrade())
+            .filter(|channel| channel.channel_type == channel_type)
+            .collect()
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -219,6 +268,10 @@ pub struct ChatSessionExtendData {
     pub platforms: i32,
     pub bancho_ext: Option<BanchoChatExtData>,
     pub joined_channels: Vec<JoinedChannelData>,
+    #[serde(default)]
+    pub only_friend_pm_allowed: bool,
+    #[serde(default)]
+    pub blocked_users: Vec<i32>,
 }
 
 #[async_trait]
@@ -231,6 +284,8 @@ impl CreateSnapshot<ChatSessionExtendData> for ChatSessionExtend {
                 None => None,
             },
             joined_channels: self.collect_joined_channels().await,
+            only_friend_pm_allowed: self.only_friend_pm_allowed.val(),
+            blocked_users: self.blocked_users.load().iter().copied().collect(),
         }
     }
 }
@@ -286,6 +341,57 @@ impl ChannelIndexes {
 
         removed
     }
+
+    pub fn rename_channel(&mut self, channel: &Arc<Channel>, new_name: String) {
+        self.channel_name.remove(channel.name.load().as_str());
+        channel.name.set(new_name.clone().into());
+        self.channel_name.insert(new_name, channel.clone());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelHandle {
+    Join,
+    SendMessage,
+    KickUser,
+    MuteUser,
+}
+
+/// Privilege levels users need to act in a channel, loaded from the
+/// `channel_privileges` table.
+///
+/// Without a rule everyone may join and send messages, while kicking and
+/// muting members is denied.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct ChannelPrivileges {
+    pub join: Option<i32>,
+    pub send_message: Option<i32>,
+    pub kick_user: Option<i32>,
+    pub mute_user: Option<i32>,
+}
+
+impl ChannelPrivileges {
+    #[inline]
+    pub fn set(&mut self, handle: ChannelHandle, required_level: i32) {
+        *match handle {
+            ChannelHandle::Join => &mut self.join,
+            ChannelHandle::SendMessage => &mut self.send_message,
+            ChannelHandle::KickUser => &mut self.kick_user,
+            ChannelHandle::MuteUser => &mut self.mute_user,
+        } = Some(required_level);
+    }
+
+    #[inline]
+    pub fn is_permitted(&self, handle: ChannelHandle, level: i32) -> bool {
+        let (required_level, default) = match handle {
+            ChannelHandle::Join => (self.join, true),
+            ChannelHandle::SendMessage => (self.send_message, true),
+            ChannelHandle::KickUser => (self.kick_user, false),
+            ChannelHandle::MuteUser => (self.mute_user, false),
+        };
+
+        required_level.map(|required| level >= required).unwrap_or(default)
+    }
 }
 
 #[derive(Debug, Default)]
@@ -294,9 +400,16 @@ pub struct Channel {
     pub name: Atomic<String>,
     pub channel_type: ChannelType,
     pub description: AtomicOption<String>,
+    pub icon: AtomicOption<String>,
+    pub auto_join: Bool,
+    pub privileges: Atomic<ChannelPrivileges>,
+    /// The owner of private and group channels.
+    pub owner_id: AtomicOption<i32>,
 
     pub users: Arc<RwLock<HashMap<i32, Option<Weak<ChatSession>>>>>,
     pub user_count: U32,
+    /// Users muted in the channel until the time.
+    pub muted_users: RwLock<HashMap<i32, DateTime<Utc>>>,
 
     pub min_msg_index: AtomicOption<Ulid>,
     pub message_queue: Arc<BanchoMessageQueue>,
@@ -326,8 +439,13 @@ impl Channel {
             name: name.into(),
             channel_type,
             description: description.into(),
+            icon: None.into(),
+            auto_join: Bool::default(),
+            privileges: Atomic::default(),
+            owner_id: None.into(),
             users: Arc::new(users.into()),
             user_count: user_count.into(),
+            muted_users: RwLock::default(),
             min_msg_index: None.into(),
             message_queue: Arc::new(BanchoMessageQueue::default()),
             created_at: Utc::now(),
@@ -338,12 +456,17 @@ impl Channel {
     pub async fn join(session: &Arc<ChatSession>, channel: &Arc<Channel>) {
         const LOG_TARGET: &str = "chat::channel::join";
 
-        channel.users.write().await.entry(session.user_id).or_insert_with(
-            || {
+        // members of private and group channels are added while offline
+        channel
+            .users
+            .write()
+            .await
+            .entry(session.user_id)
+            .and_modify(|ptr| *ptr = Some(Arc::downgrade(session)))
+            .or_insert_with(|| {
                 channel.user_count.add(1);
                 Some(Arc::downgrade(session))
-            },
-        );
+            });
 
         session
             .extends
@@ -415,10 +538,212 @@ impl Channel {
         );
     }
 
+    /// Closes the channel for the session, the user stays a member of the
+    /// private or group channel and may join it again.
+    pub async fn part(session: &Arc<ChatSession>, channel: &Arc<Channel>) {
+        if let Some(ptr) = channel.users.write().await.get_mut(&session.user_id)
+        {
+            *ptr = None;
+        }
+
+        if session
+            .extends
+            .joined_channels
+            .write()
+            .await
+            .remove(&channel.id)
+            .is_some()
+        {
+            session.extends.channel_count.sub(1);
+        }
+
+        // notify to user's bancho client if possible
+        if let Some(bancho_ext) = session.extends.bancho_ext.load().as_ref() {
+            bancho_ext
+                .packets_queue
+                .push_packet(channel.kick_packets().into())
+                .await;
+        }
+    }
+
+    /// Adds a member which is not online to the channel.
+    #[inline]
+    pub async fn add_member(&self, user_id: i32) {
+        self.users.write().await.entry(user_id).or_insert_with(|| {
+            self.user_count.add(1);
+            None
+        });
+    }
+
+    /// Removes a member which is not online from the channel.
+    #[inline]
+    pub async fn remove_member(&self, user_id: i32) -> bool {
+        let removed = self.users.write().await.remove(&user_id).is_some();
+        if removed {
+            self.user_count.sub(1);
+        }
+
+        removed
+    }
+
+    /// Online members blocking the user.
+    pub async fn members_blocking(&self, user_id: i32) -> HashSet<i32> {
+        self.users
+            .read()
+            .await
+            .iter()
+            .filter(|(_, session)| {
+                session
+                    .as_ref()
+                    .and_then(Weak::upgrade)
+                    .is_some_and(|session| session.extends.is_blocking(user_id))
+            })
+            .map(|(member_id, _)| *member_id)
+            .collect()
+    }
+
+    /// Hides the messages of the sender from the members blocking it,
+    /// `None` if no member does.
+    pub async fn blocking_validator(
+        &self,
+        sender_id: i32,
+    ) -> Option<MessageValidator<Packet, i32>> {
+        let blocking = self.members_blocking(sender_id).await;
+        if blocking.is_empty() {
+            return None;
+        }
+
+        Some(Arc::new(move |_, reader| {
+            !reader.is_some_and(|reader| blocking.contains(reader))
+        }))
+    }
+
+    /// Members of private and group channels are kept in the database.
+    #[inline]
+    pub fn has_persistent_members(&self) -> bool {
+        matches!(self.channel_type, ChannelType::Private | ChannelType::Group)
+    }
+
+    /// Public channels may be joined by anyone permitted, other channels
+    /// only by the users added to them.
+    #[inline]
+    pub fn is_joinable(&self, privileges: i32) -> bool {
+        self.channel_type == ChannelType::Public
+            && self.is_permitted(ChannelHandle::Join, privileges)
+    }
+
+    #[inline]
+    pub fn is_permitted(&self, handle: ChannelHandle, privileges: i32) -> bool {
+        self.privileges.load().is_permitted(handle, privileges)
+    }
+
+    #[inline]
+    pub async fn is_member(&self, user_id: i32) -> bool {
+        self.users.read().await.contains_key(&user_id)
+    }
+
+    #[inline]
+    pub async fn is_muted(&self, user_id: i32) -> bool {
+        self.muted_users
+            .read()
+            .await
+            .get(&user_id)
+            .map(|until| *until > Utc::now())
+            .unwrap_or_default()
+    }
+
+    /// Mutes the user until the time, unmutes if `None`.
+    #[inline]
+    pub async fn mute(&self, user_id: i32, until: Option<DateTime<Utc>>) {
+        let mut muted_users = self.muted_users.write().await;
+        match until {
+            Some(until) => muted_users.insert(user_id, until),
+            None => muted_users.remove(&user_id),
+        };
+    }
+
+    /// The name bancho clients know the channel by, spectator and
+    /// multiplayer channels are shown by their aliases.
+    #[inline]
+    pub fn bancho_name(&self) -> String {
+        match self.channel_type {
+            ChannelType::Spectaor => SPECTATOR_CHANNEL_ALIAS.to_owned(),
+            ChannelType::Multiplayer => MULTIPLAYER_CHANNEL_ALIAS.to_owned(),
+            _ => self.name.to_string(),
+        }
+    }
+
+    /// Spectator and multiplayer channels are created on demand and
+    /// destroyed once deserted.
+    #[inline]
+    pub fn is_dynamic(&self) -> bool {
+        matches!(
+            self.channel_type,
+            ChannelType::Spectaor | ChannelType::Multiplayer
+        )
+    }
+
+    /// Channel names start with `#` and contain no whitespaces, as bancho
+    /// clients expect.
+    #[inline]
+    pub fn is_valid_name(name: &str) -> bool {
+        name.len() > 1
+            && name.starts_with('#')
+            && !name.contains(char::is_whitespace)
+    }
+
+    /// Removes every online member from the channel, e.g. before it is
+    /// deleted.
+    pub async fn remove_all(channel: &Arc<Channel>) {
+        let sessions = channel
+            .users
+            .read()
+            .await
+            .values()
+            .filter_map(|session| session.as_ref().and_then(Weak::upgrade))
+            .collect::<Vec<_>>();
+
+        for session in sessions {
+            Channel::remove(&session, channel).await;
+        }
+    }
+
+    #[inline]
+    pub fn update_info(
+        &self,
+        description: Option<String>,
+        icon: Option<String>,
+        auto_join: bool,
+    ) {
+        self.description.set(description.map(Arc::new));
+        self.icon.set(icon.map(Arc::new));
+        self.auto_join.set(auto_join);
+        self.updated_at.set(Utc::now().into());
+    }
+
+    #[inline]
+    pub fn to_channel_info(&self) -> ChannelInfo {
+        ChannelInfo {
+            id: self.id,
+            name: self.name.to_string(),
+            channel_type: self.channel_type as i32,
+            description: self
+                .description
+                .load()
+                .as_deref()
+                .map(|s| s.to_string()),
+            online_users: self.user_count.val(),
+            users: None,
+            icon: self.icon.load().as_deref().map(|s| s.to_string()),
+            auto_join: self.auto_join.val(),
+            owner_id: self.owner_id.load().as_deref().copied(),
+        }
+    }
+
     #[inline]
     pub fn info_packets(&self) -> Vec<u8> {
         bancho_packets::server::ChannelInfo::pack(
-            self.name.load().as_ref().into(),
+            self.bancho_name().into(),
             self.description
                 .load()
                 .as_deref()
@@ -430,17 +755,27 @@ impl Channel {
     }
 
     #[inline]
-    pub fn join_packets(&self) -> Vec<u8> {
-        bancho_packets::server::ChannelJoin::pack(
-            self.name.load().as_ref().into(),
+    pub fn auto_join_packets(&self) -> Vec<u8> {
+        bancho_packets::server::ChannelAutoJoin::pack(
+            self.bancho_name().into(),
+            self.description
+                .load()
+                .as_deref()
+                .map(|s| s.to_owned())
+                .unwrap_or_default()
+                .into(),
+            self.user_count.val() as i16,
         )
     }
 
+    #[inline]
+    pub fn join_packets(&self) -> Vec<u8> {
+        bancho_packets::server::ChannelJoin::pack(self.bancho_name().into())
+    }
+
     #[inline]
     pub fn kick_packets(&self) -> Vec<u8> {
-        bancho_packets::server::ChannelKick::pack(
-            self.name.load().as_ref().into(),
-        )
+        bancho_packets::server::ChannelKick::pack(self.bancho_name().into())
     }
 }
 
@@ -450,7 +785,17 @@ pub struct ChannelData {
     pub name: String,
     pub channel_type: ChannelType,
     pub description: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub auto_join: bool,
+    #[serde(default)]
+    pub privileges: ChannelPrivileges,
+    #[serde(default)]
+    pub owner_id: Option<i32>,
     pub users: Vec<i32>,
+    #[serde(default)]
+    pub muted_users: HashMap<i32, DateTime<Utc>>,
     pub min_msg_index: Option<Ulid>,
     pub message_queue: Vec<BanchoMessageData>,
     pub created_at: DateTime<Utc>,
@@ -468,7 +813,12 @@ impl ChannelData {
                 .load()
                 .as_deref()
                 .map(|s| s.to_string()),
+            icon: ch.icon.load().as_deref().map(|s| s.to_string()),
+            auto_join: ch.auto_join.val(),
+            privileges: *ch.privileges.load().as_ref(),
+            owner_id: ch.owner_id.load().as_deref().copied(),
             users: ch.users.read().await.keys().copied().collect(),
+            muted_users: ch.muted_users.read().await.clone(),
             min_msg_index: ch.min_msg_index.load().as_deref().copied(),
             message_queue: ch
                 .message_queue
@@ -477,7 +827,7 @@ impl ChannelData {
                 .create_snapshot()
                 .await,
             created_at: ch.created_at,
-            updated_at: ch.updated_at.load().as_ref().clone(),
+            updated_at: *ch.updated_at.load().as_ref(),
         }
     }
 }
@@ -545,6 +895,26 @@ impl Channels {
         self.len.add(1);
     }
 
+    /// Gets the channel, or creates it if it does not exist yet.
+    #[inline]
+    pub async fn get_or_create_channel(
+        &self,
+        channel_id: u64,
+        create: impl FnOnce() -> Channel,
+    ) -> Arc<Channel> {
+        let mut indexes = self.write().await;
+        if let Some(channel) = self
+            .get_channel_inner(&indexes, &ChannelQuery::ChannelId(channel_id))
+        {
+            return channel;
+        }
+
+        let channel = Arc::new(create());
+        self.create_channel_inner(&mut indexes, channel.clone(), false);
+
+        channel
+    }
+
     #[inline]
     pub async fn remove_channel(
         &self,
@@ -1,3 +1,4 @@
+use peace_db::DbErr;
 use peace_pb::ConvertError;
 use peace_repositories::GetUserError;
 use peace_rpc_error::{RpcError, TonicError};
@@ -11,16 +12,40 @@ pub enum ChatError {
     InvalidArgument,
     #[error("chat session not exists")]
     SessionNotExists,
+    #[error("not logged in from bancho")]
+    NotBanchoSession,
     #[error("channel not exists")]
     ChannelNotExists,
+    #[error("channel already exists")]
+    ChannelExists,
+    #[error("invalid channel name")]
+    InvalidChannelName,
+    #[error("permission denied")]
+    PermissionDenied,
+    #[error("not in channel")]
+    NotInChannel,
+    #[error("muted in channel")]
+    Muted,
+    #[error("the user only accepts messages from friends")]
+    DmBlocked,
+    #[error("message rejected: {0}")]
+    MessageRejected(String),
     #[error(transparent)]
     ConvertError(#[from] ConvertError),
+    #[error("database err: {0}")]
+    DbErr(String),
     #[error("bancho state error: {0}")]
     BanchoStateError(String),
     #[error("TonicError: {0}")]
     TonicError(String),
 }
 
+impl From<DbErr> for ChatError {
+    fn from(err: DbErr) -> Self {
+        Self::DbErr(err.to_string())
+    }
+}
+
 impl TonicError for ChatError {
     fn tonic_error(s: Status) -> Self {
         Self::TonicError(s.message().to_owned())
@@ -0,0 +1,292 @@
+use crate::{moderation, ChatError, ChatSession};
+use chrono::{Duration, Utc};
+use clap::Parser;
+use clap_serde_derive::ClapSerde;
+use pb_chat::{ChatFilterRules, WordFilter};
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Parser, ClapSerde, Serialize, Deserialize)]
+pub struct CliChatFilterConfigs {
+    /// Words filtered out of messages, `word=replacement` replaces the word,
+    /// a bare `word` rejects the message.
+    #[default(Vec::new())]
+    #[arg(long, value_delimiter = ',')]
+    pub chat_word_filters: Vec<String>,
+
+    /// Rejects links to hosts not in `chat_allowed_link_hosts`.
+    #[arg(long)]
+    pub chat_link_allow_list: bool,
+
+    /// Hosts links may point to, subdomains included.
+    #[default(Vec::new())]
+    #[arg(long, value_delimiter = ',')]
+    pub chat_allowed_link_hosts: Vec<String>,
+
+    /// In chars, 0 for the default limit.
+    #[default(0)]
+    #[arg(long, default_value = "0")]
+    pub chat_max_message_length: u32,
+
+    /// Rejects a message sent more times in a row within
+    /// `chat_repeat_window_secs`, 0 disables.
+    #[default(0)]
+    #[arg(long, default_value = "0")]
+    pub chat_max_repeated_messages: u32,
+
+    #[default(10)]
+    #[arg(long, default_value = "10")]
+    pub chat_repeat_window_secs: u64,
+
+    /// Rewrites markdown links and `[www.example.com text]` into osu! links.
+    #[default(true)]
+    #[arg(long, default_value = "true")]
+    pub chat_rewrite_links: bool,
+}
+
+/// The last message of a user, for the repeated message detection.
+#[derive(Debug, Default)]
+pub struct RecentMessage {
+    pub content: String,
+    pub count: u32,
+    pub since: chrono::DateTime<Utc>,
+}
+
+/// Filter chain of the chat messages, the rules can be replaced at runtime
+/// with `UpdateChatFilterRules`.
+#[derive(Debug, Default)]
+pub struct ChatFilters {
+    pub rules: RwLock<ChatFilterRules>,
+}
+
+impl ChatFilters {
+    #[inline]
+    pub fn new(rules: ChatFilterRules) -> Self {
+        Self { rules: RwLock::new(rules) }
+    }
+
+    #[inline]
+    pub fn with_cfg(cfg: &CliChatFilterConfigs) -> Self {
+        Self::new(ChatFilterRules {
+            word_filters: cfg
+                .chat_word_filters
+                .iter()
+                .map(|filter| match filter.split_once('=') {
+                    Some((word, replacement)) => WordFilter {
+                        word: word.to_owned(),
+                        replacement: Some(replacement.to_owned()),
+                    },
+                    None => WordFilter {
+                        word: filter.to_owned(),
+                        replacement: None,
+                    },
+                })
+                .collect(),
+            link_allow_list: cfg.chat_link_allow_list,
+            allowed_link_hosts: cfg.chat_allowed_link_hosts.clone(),
+            max_message_length: cfg.chat_max_message_length,
+            max_repeated_messages: cfg.chat_max_repeated_messages,
+            repeat_window_secs: cfg.chat_repeat_window_secs,
+            rewrite_links: cfg.chat_rewrite_links,
+        })
+    }
+
+    #[inline]
+    pub async fn get(&self) -> ChatFilterRules {
+        self.rules.read().await.clone()
+    }
+
+    #[inline]
+    pub async fn replace(&self, rules: ChatFilterRules) -> ChatFilterRules {
+        std::mem::replace(&mut *self.rules.write().await, rules)
+    }
+
+    /// Runs the message through the filter chain and returns the content
+    /// that should be delivered. Repeated messages are only detected with a
+    /// sender.
+    pub async fn filter(
+        &self,
+        message: &str,
+        sender: Option<&ChatSession>,
+    ) -> Result<String, ChatError> {
+        let rules = self.rules.read().await;
+
+        let mut message = moderation::moderate_message(message)?;
+
+        if rules.max_message_length > 0
+            && message.chars().count() > rules.max_message_length as usize
+        {
+            return Err(ChatError::MessageRejected("message too long".into()));
+        }
+
+        message = filter_words(&rules.word_filters, message)?;
+
+        if rules.rewrite_links {
+            message = rewrite_links(&message);
+        }
+
+        if rules.link_allow_list {
+            if let Some(host) = link_hosts(&message)
+                .find(|host| !is_allowed_host(&rules.allowed_link_hosts, host))
+            {
+                return Err(ChatError::MessageRejected(format!(
+                    "links to {host} are not allowed"
+                )));
+            }
+        }
+
+        if let Some(sender) = sender {
+            check_repeated(&rules, sender, &message).await?;
+        }
+
+        Ok(message)
+    }
+}
+
+/// Replaces or rejects the filtered words, matched ignoring ASCII case.
+pub fn filter_words(
+    word_filters: &[WordFilter],
+    mut message: String,
+) -> Result<String, ChatError> {
+    for WordFilter { word, replacement } in word_filters {
+        if word.is_empty() {
+            continue;
+        }
+
+        // ASCII lowercasing keeps the byte offsets of the message
+        let word = word.to_ascii_lowercase();
+        let lowercase = message.to_ascii_lowercase();
+        if !lowercase.contains(&word) {
+            continue;
+        }
+
+        let replacement = replacement.as_deref().ok_or_else(|| {
+            ChatError::MessageRejected("message contains a blocked word".into())
+        })?;
+
+        let mut filtered = String::with_capacity(message.len());
+        let mut last = 0;
+        for (start, _) in lowercase.match_indices(&word) {
+            filtered.push_str(&message[last..start]);
+            filtered.push_str(replacement);
+            last = start + word.len();
+        }
+        filtered.push_str(&message[last..]);
+
+        message = filtered;
+    }
+
+    Ok(message)
+}
+
+/// Rewrites markdown links `[text](url)` into osu! links `[url text]`, and
+/// adds the scheme the client needs to `[www.example.com text]`.
+pub fn rewrite_links(message: &str) -> String {
+    let mut rewritten = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(open) = rest.find('[') {
+        rewritten.push_str(&rest[..open]);
+        rest = &rest[open..];
+
+        match rewrite_link(rest) {
+            Some((link, consumed)) => {
+                rewritten.push_str(&link);
+                rest = &rest[consumed..];
+            },
+            None => {
+                rewritten.push('[');
+                rest = &rest[1..];
+            },
+        }
+    }
+    rewritten.push_str(rest);
+
+    rewritten
+}
+
+/// Rewrites the link `s` starts with, returns it with the length of the
+/// original.
+fn rewrite_link(s: &str) -> Option<(String, usize)> {
+    let close = s.find(']')?;
+    let inner = &s[1..close];
+    if inner.contains('[') {
+        return None;
+    }
+
+    if let Some(after) = s[close + 1..].strip_prefix('(') {
+        if let Some(end) = after.find(')') {
+            let url = &after[..end];
+            if !inner.is_empty()
+                && strip_scheme(url).is_some()
+                && !url.contains(char::is_whitespace)
+            {
+                return Some((format!("[{url} {inner}]"), close + end + 3));
+            }
+        }
+    }
+
+    let (url, text) = inner.split_once(' ')?;
+    let www = url.get(..4).is_some_and(|p| p.eq_ignore_ascii_case("www."));
+
+    (www && url.len() > 4 && !text.trim().is_empty())
+        .then(|| (format!("[https://{url} {text}]"), close + 1))
+}
+
+#[inline]
+fn strip_scheme(url: &str) -> Option<&str> {
+    ["https://", "http://"].into_iter().find_map(|scheme| {
+        url.get(..scheme.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+            .map(|_| &url[scheme.len()..])
+    })
+}
+
+/// Hosts of the `http(s)://` links in the message.
+pub fn link_hosts(message: &str) -> impl Iterator<Item = &str> {
+    message
+        .split(|c: char| {
+            c.is_whitespace() || matches!(c, '[' | ']' | '(' | ')')
+        })
+        .filter_map(strip_scheme)
+        .map(|rest| rest.split(['/', ':', '?', '#']).next().unwrap_or_default())
+}
+
+#[inline]
+pub fn is_allowed_host(allowed_hosts: &[String], host: &str) -> bool {
+    allowed_hosts.iter().any(|allowed| {
+        host.eq_ignore_ascii_case(allowed)
+            || host.len() > allowed.len()
+                && host.as_bytes()[host.len() - allowed.len() - 1] == b'.'
+                && host
+                    .get(host.len() - allowed.len()..)
+                    .is_some_and(|suffix| suffix.eq_ignore_ascii_case(allowed))
+    })
+}
+
+async fn check_repeated(
+    rules: &ChatFilterRules,
+    sender: &ChatSession,
+    message: &str,
+) -> Result<(), ChatError> {
+    if rules.max_repeated_messages == 0 {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let window =
+        Duration::seconds(rules.repeat_window_secs.min(i32::MAX as u64) as i64);
+
+    let mut recent = sender.extends.recent_message.lock().await;
+    if recent.content == message && now - recent.since < window {
+        recent.count += 1;
+    } else {
+        *recent =
+            RecentMessage { content: message.to_owned(), count: 1, since: now };
+    }
+
+    if recent.count > rules.max_repeated_messages {
+        return Err(ChatError::MessageRejected("repeated message".into()));
+    }
+
+    Ok(())
+}
@@ -8,12 +8,20 @@ extern crate anyhow;
 #[macro_use]
 extern crate serde;
 
+#[cfg(test)]
+mod tests;
+
 pub mod components;
 pub mod error;
+pub mod filters;
+pub mod messages;
+pub mod moderation;
 pub mod services;
 
 pub use components::*;
 pub use error::*;
+pub use filters::*;
+pub use messages::*;
 pub use services::*;
 
 pub mod rpc_config {
@@ -0,0 +1,242 @@
+use crate::{Channel, ChannelHandle};
+use chrono::Utc;
+use domain_chat::ChannelType;
+use peace_db::{
+    peace::entity::sea_orm_active_enums::{
+        ChannelHandleType, ChannelType as DbChannelType,
+    },
+    DbErr,
+};
+use peace_repositories::chat::{
+    CreateChatChannel, CreateChatMessage, DynChatRepository,
+    PRIVATE_CHANNEL_ID_BASE, SPECTATOR_CHANNEL_ID_BASE,
+};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::Mutex;
+
+pub const DEFAULT_HISTORY_LIMIT: u32 = 50;
+pub const MAX_HISTORY_LIMIT: u32 = 100;
+
+/// Batch size of the flushes outside the `chat_messages_persist` task.
+pub const FLUSH_BATCH_SIZE: usize = 500;
+
+/// Count of message ids reserved from the database at once.
+pub const MESSAGE_ID_BLOCK_SIZE: u64 = 100;
+
+/// osu! clients send `/me` messages as `\x01ACTION <message>\x01`.
+pub const ACTION_PREFIX: &str = "\x01ACTION ";
+
+/// The content of a `/me` message without its `\x01ACTION` markers, which
+/// are stored as `is_action` instead.
+#[inline]
+pub fn action_content(moderated_message: &str) -> &str {
+    match moderated_message.strip_prefix(ACTION_PREFIX) {
+        Some(content) => content.strip_suffix('\x01').unwrap_or(content),
+        None => moderated_message,
+    }
+}
+
+/// The channel of the private conversation between two users, the same for
+/// both directions.
+#[inline]
+pub fn private_channel_id(user_id: i32, target_user_id: i32) -> i64 {
+    let (a, b) = if user_id < target_user_id {
+        (user_id, target_user_id)
+    } else {
+        (target_user_id, user_id)
+    };
+
+    PRIVATE_CHANNEL_ID_BASE | ((a.max(0) as i64) << 31) | b.max(0) as i64
+}
+
+/// The channel shared by the host and the spectators.
+#[inline]
+pub fn spectator_channel_id(host_id: i32) -> u64 {
+    (SPECTATOR_CHANNEL_ID_BASE | host_id.max(0) as i64) as u64
+}
+
+#[inline]
+pub fn db_channel_type(channel_type: ChannelType) -> DbChannelType {
+    match channel_type {
+        ChannelType::Private => DbChannelType::Private,
+        ChannelType::Public => DbChannelType::Public,
+        ChannelType::Group => DbChannelType::Group,
+        ChannelType::Multiplayer => DbChannelType::Multiplayer,
+        ChannelType::Spectaor => DbChannelType::Spectaor,
+    }
+}
+
+#[inline]
+pub fn channel_type(channel_type: DbChannelType) -> ChannelType {
+    match channel_type {
+        DbChannelType::Private => ChannelType::Private,
+        DbChannelType::Public => ChannelType::Public,
+        DbChannelType::Group => ChannelType::Group,
+        DbChannelType::Multiplayer => ChannelType::Multiplayer,
+        DbChannelType::Spectaor => ChannelType::Spectaor,
+    }
+}
+
+#[inline]
+pub fn channel_handle(handle: ChannelHandleType) -> ChannelHandle {
+    match handle {
+        ChannelHandleType::Join => ChannelHandle::Join,
+        ChannelHandleType::SendMessage => ChannelHandle::SendMessage,
+        ChannelHandleType::KickUser => ChannelHandle::KickUser,
+        ChannelHandleType::MuteUser => ChannelHandle::MuteUser,
+    }
+}
+
+/// The row of a live channel, created on its first message.
+pub fn channel_row(channel: &Channel) -> CreateChatChannel {
+    CreateChatChannel {
+        id: channel.id as i64,
+        channel_type: db_channel_type(channel.channel_type),
+        name: Some(channel.name.to_string()),
+        description: channel.description.load().as_deref().cloned(),
+        user_ids: Vec::new(),
+    }
+}
+
+/// The row of a private conversation, created on its first message.
+pub fn private_channel_row(
+    user_id: i32,
+    target_user_id: i32,
+) -> CreateChatChannel {
+    CreateChatChannel {
+        id: private_channel_id(user_id, target_user_id),
+        channel_type: DbChannelType::Private,
+        name: None,
+        description: None,
+        user_ids: vec![user_id, target_user_id],
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PendingChatMessages {
+    pub channels: HashMap<i64, CreateChatChannel>,
+    pub messages: Vec<CreateChatMessage>,
+}
+
+/// Buffers the delivered messages, they are written to the database in
+/// batches by the `chat_messages_persist` background task.
+///
+/// Message ids are returned before the message is written, so they are
+/// reserved in blocks from the `chat_messages` id sequence and stay unique
+/// with other writers of the table.
+pub struct ChatMessageWriter {
+    pub chat_repository: DynChatRepository,
+    pub reserved_ids: Mutex<VecDeque<i64>>,
+    pub pending: Mutex<PendingChatMessages>,
+}
+
+impl ChatMessageWriter {
+    #[inline]
+    pub fn new(chat_repository: DynChatRepository) -> Self {
+        Self {
+            chat_repository,
+            reserved_ids: Mutex::default(),
+            pending: Mutex::new(PendingChatMessages::default()),
+        }
+    }
+
+    /// Takes the next reserved id, reserving a new block if all are used.
+    async fn next_message_id(&self) -> Result<i64, DbErr> {
+        let mut reserved_ids = self.reserved_ids.lock().await;
+
+        if reserved_ids.is_empty() {
+            reserved_ids.extend(
+                self.chat_repository
+                    .reserve_message_ids(MESSAGE_ID_BLOCK_SIZE)
+                    .await?,
+            );
+        }
+
+        reserved_ids
+            .pop_front()
+            .ok_or_else(|| DbErr::Custom("no message id reserved".to_owned()))
+    }
+
+    /// Queues the message and returns its id.
+    pub async fn push(
+        &self,
+        sender_id: i32,
+        channel: CreateChatChannel,
+        content_string: String,
+        is_action: bool,
+        unread_by: Option<i32>,
+    ) -> Result<i64, DbErr> {
+        let id = self.next_message_id().await?;
+
+        let message = CreateChatMessage {
+            id,
+            sender_id,
+            channel_id: channel.id,
+            timestamp: Utc::now().into(),
+            content_string,
+            content_html: None,
+            is_action,
+            unread_by,
+        };
+
+        let mut pending = self.pending.lock().await;
+        pending.channels.entry(channel.id).or_insert(channel);
+        pending.messages.push(message);
+
+        Ok(id)
+    }
+
+    /// Writes the queued messages, at most `batch_size` in one transaction.
+    ///
+    /// Returns the count of written messages. A failed batch is put back in
+    /// front of the queue with its channel rows, the next flush retries it.
+    pub async fn flush(&self, batch_size: usize) -> Result<usize, DbErr> {
+        let batch_size = batch_size.max(1);
+        let mut written = 0;
+
+        loop {
+            let (channels, messages) = {
+                let mut pending = self.pending.lock().await;
+                if pending.messages.is_empty() {
+                    pending.channels.clear();
+                    break;
+                }
+
+                let len = pending.messages.len().min(batch_size);
+                let messages =
+                    pending.messages.drain(..len).collect::<Vec<_>>();
+
+                // Only the rows of the channels in this batch, the others
+                // are written with their own messages
+                let mut channels = Vec::new();
+                for message in messages.iter() {
+                    if let Some(channel) =
+                        pending.channels.remove(&message.channel_id)
+                    {
+                        channels.push(channel);
+                    }
+                }
+
+                (channels, messages)
+            };
+
+            let len = messages.len();
+            if let Err(err) = self
+                .chat_repository
+                .save_messages(channels.clone(), messages.clone())
+                .await
+            {
+                let mut pending = self.pending.lock().await;
+                for channel in channels {
+                    pending.channels.entry(channel.id).or_insert(channel);
+                }
+                pending.messages.splice(..0, messages);
+
+                return Err(err);
+            }
+            written += len;
+        }
+
+        Ok(written)
+    }
+}
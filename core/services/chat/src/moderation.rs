@@ -0,0 +1,33 @@
+use crate::{ChatError, ACTION_PREFIX};
+
+/// Maximum length (in chars) of a message after moderation.
+pub const MAX_MESSAGE_LENGTH: usize = 1024;
+
+/// Moderation hook for user generated content, used by chat messages and
+/// everything else users can post (e.g. beatmap comments).
+///
+/// Returns the message that should be delivered, the `\x01` markers of
+/// `/me` messages are kept.
+pub fn moderate_message(message: &str) -> Result<String, ChatError> {
+    let (body, is_action) = match message.strip_prefix(ACTION_PREFIX) {
+        Some(body) => (body.strip_suffix('\x01').unwrap_or(body), true),
+        None => (message, false),
+    };
+
+    let body = body
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .to_owned();
+
+    if body.is_empty() {
+        return Err(ChatError::MessageRejected("empty message".into()));
+    }
+
+    if body.chars().count() > MAX_MESSAGE_LENGTH {
+        return Err(ChatError::MessageRejected("message too long".into()));
+    }
+
+    Ok(if is_action { format!("{ACTION_PREFIX}{body}\x01") } else { body })
+}
@@ -1,8 +1,9 @@
 use crate::{
-    Channel, ChatBackgroundService, ChatSession, DynChatBackgroundService,
-    DynChatService,
+    Channel, ChatBackgroundService, ChatMessageWriter, ChatSession,
+    DynChatBackgroundService, DynChatService,
 };
 use async_trait::async_trait;
+use clap::Parser;
 use clap_serde_derive::ClapSerde;
 use peace_unique_id::Ulid;
 use std::{
@@ -24,6 +25,7 @@ pub struct Tasks {
     pub user_sessions_recycle: BackgroundTaskManager,
     pub notify_messages_recycle: BackgroundTaskManager,
     pub channel_messages_recycle: BackgroundTaskManager,
+    pub chat_messages_persist: BackgroundTaskManager,
 }
 
 #[derive(Clone)]
@@ -295,6 +297,70 @@ impl ChatBackgroundServiceImpl {
             })
         }))
     }
+
+    pub fn chat_messages_persist_factory(
+        &self,
+        config: Arc<LoopBackgroundTaskConfig>,
+        batch_size: usize,
+    ) -> BackgroundTaskFactory {
+        const LOG_TARGET: &str =
+            "chat::background_tasks::chat_messages_persisting";
+
+        let message_writer = self.chat_service.message_writer().clone();
+
+        BackgroundTaskFactory::new(Arc::new(move |stop: SignalHandle| {
+            let message_writer = message_writer.clone();
+            let cfg = config.clone();
+
+            let task = {
+                let message_writer = message_writer.clone();
+                async move {
+                    loop {
+                        tokio::time::sleep(*cfg.loop_interval.load().as_ref())
+                            .await;
+                        flush_chat_messages(&message_writer, batch_size).await;
+                    }
+                }
+            };
+
+            info!(
+                target: LOG_TARGET,
+                "Service started! (sleep={:?}, batch_size={batch_size})",
+                config.loop_interval.val()
+            );
+
+            Box::pin(async move {
+                tokio::select!(
+                    _ = task => {},
+                    _ = stop.wait_signal() => {}
+                );
+                // keep the messages queued since the last loop
+                flush_chat_messages(&message_writer, batch_size).await;
+                warn!(target: LOG_TARGET, "Service stopped!");
+            })
+        }))
+    }
+}
+
+async fn flush_chat_messages(
+    message_writer: &ChatMessageWriter,
+    batch_size: usize,
+) {
+    const LOG_TARGET: &str = "chat::background_tasks::chat_messages_persisting";
+
+    let start = Instant::now();
+
+    match message_writer.flush(batch_size).await {
+        Ok(0) => {},
+        Ok(written) => debug!(
+            target: LOG_TARGET,
+            "Done in: {:?} ({written} messages written)",
+            start.elapsed()
+        ),
+        Err(err) => {
+            error!(target: LOG_TARGET, "Failed to write chat messages: {err}")
+        },
+    }
 }
 
 #[derive(Debug, Clone, Parser, ClapSerde, Serialize, Deserialize)]
@@ -314,6 +380,16 @@ pub struct CliChatBackgroundServiceConfigs {
     #[default(300)]
     #[arg(long, default_value = "300")]
     pub channel_messages_recycle_interval_secs: u64,
+
+    /// Delivered messages are written to the database at this interval.
+    #[default(1000)]
+    #[arg(long, default_value = "1000")]
+    pub chat_messages_persist_interval_millis: u64,
+
+    /// Messages written in one transaction.
+    #[default(500)]
+    #[arg(long, default_value = "500")]
+    pub chat_messages_persist_batch_size: usize,
 }
 
 pub struct UserSessionsRecycleConfig;
@@ -380,11 +456,34 @@ impl ChannelMessagesRecycleConfig {
     }
 }
 
+pub struct ChatMessagesPersistConfig;
+
+impl ChatMessagesPersistConfig {
+    pub fn build(loop_interval_millis: u64) -> Arc<LoopBackgroundTaskConfig> {
+        LoopBackgroundTaskConfig {
+            loop_interval: Atomic::new(Duration::from_millis(
+                loop_interval_millis,
+            )),
+            manual_stop: true.into(),
+        }
+        .into()
+    }
+
+    #[inline]
+    pub fn buid_with_cfg(
+        cfg: &CliChatBackgroundServiceConfigs,
+    ) -> Arc<LoopBackgroundTaskConfig> {
+        Self::build(cfg.chat_messages_persist_interval_millis)
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct ChatBackgroundServiceConfigs {
     pub user_sessions_recycle: Arc<CommonRecycleBackgroundTaskConfig>,
     pub notify_messages_recyce: Arc<LoopBackgroundTaskConfig>,
     pub channel_messages_recyce: Arc<LoopBackgroundTaskConfig>,
+    pub chat_messages_persist: Arc<LoopBackgroundTaskConfig>,
+    pub chat_messages_persist_batch_size: usize,
 }
 
 impl ChatBackgroundServiceConfigs {
@@ -393,11 +492,15 @@ impl ChatBackgroundServiceConfigs {
         user_sessions_recycle: Arc<CommonRecycleBackgroundTaskConfig>,
         notify_messages_recyce: Arc<LoopBackgroundTaskConfig>,
         channel_messages_recyce: Arc<LoopBackgroundTaskConfig>,
+        chat_messages_persist: Arc<LoopBackgroundTaskConfig>,
+        chat_messages_persist_batch_size: usize,
     ) -> Self {
         Self {
             user_sessions_recycle,
             notify_messages_recyce,
             channel_messages_recyce,
+            chat_messages_persist,
+            chat_messages_persist_batch_size,
         }
     }
 
@@ -412,6 +515,11 @@ impl ChatBackgroundServiceConfigs {
             ),
             channel_messages_recyce:
                 ChannelMessagesRecycleConfig::buid_with_cfg(cfg),
+            chat_messages_persist: ChatMessagesPersistConfig::buid_with_cfg(
+                cfg,
+            ),
+            chat_messages_persist_batch_size: cfg
+                .chat_messages_persist_batch_size,
         }
     }
 }
@@ -439,5 +547,13 @@ impl ChatBackgroundService for ChatBackgroundServiceImpl {
             ),
             configs.channel_messages_recyce,
         );
+
+        self.tasks.chat_messages_persist.start(
+            self.chat_messages_persist_factory(
+                configs.chat_messages_persist.clone(),
+                configs.chat_messages_persist_batch_size,
+            ),
+            configs.chat_messages_persist,
+        );
     }
 }
@@ -6,46 +6,83 @@ use domain_chat::{ChannelType, Platform};
 use infra_packets::{Packet, PacketsQueue};
 use infra_services::{FromRpcClient, IntoService, RpcClient, ServiceSnapshot};
 use infra_users::CreateSessionDto;
-use pb_bancho_state::{BanchoPackets, RawUserQuery, UserQuery};
+use pb_bancho_state::{
+    BanchoPackets, RawUserQuery, UpdateOnlyFriendPmAllowedRequest, UserQuery,
+};
 use pb_base::ExecSuccess;
 use pb_chat::{
-    chat_rpc_client::ChatRpcClient, ChannelInfo, ChatMessageTarget,
-    GetPublicChannelsRequest, GetPublicChannelsResponse, JoinChannelRequest,
-    LeaveChannelRequest, LoadPublicChannelsRequest, LoginRequest,
-    LogoutRequest, SendMessageRequest, SendMessageResponse,
+    chat_rpc_client::ChatRpcClient, ArchiveChannelRequest, BlockUserRequest,
+    BlockedUsers, ChannelInfo, ChannelQuery, ChatFilterRules, ChatMessage,
+    ChatMessageTarget, CreateChannelRequest, CreateGroupChannelRequest,
+    CreatePrivateChannelRequest, DeleteChannelRequest,
+    GetChatFilterRulesRequest, GetMessageHistoryRequest,
+    GetPublicChannelsRequest, GetPublicChannelsResponse,
+    InviteChannelUserRequest, JoinChannelRequest, JoinSpectatorChannelRequest,
+    KickChannelUserRequest, LeaveChannelRequest, LeaveSpectatorChannelRequest,
+    LoadPublicChannelsRequest, LoginRequest, LogoutRequest,
+    MarkMessagesReadRequest, MessageHistory, ModerateMessageRequest,
+    ModerateMessageResponse, MuteChannelUserRequest, PrivateConversation,
+    RawChannelQuery, RemoveChannelUserRequest, SendMessageRequest,
+    SendMessageResponse, TransferChannelOwnershipRequest, UnblockUserRequest,
+    UpdateChannelRequest,
 };
+use peace_db::peace::entity::users;
 use peace_message_queue::ReceivedMessages;
-use peace_repositories::users::DynUsersRepository;
+use peace_repositories::{
+    chat::{
+        ChannelWithMembers, CreateGroupChannel, CreatePublicChannel,
+        DynChatRepository, UpdateChannel,
+    },
+    users::DynUsersRepository,
+};
 use peace_snapshot::{
     CreateSnapshot, CreateSnapshotError, LoadSnapshotFrom, SaveSnapshotTo,
     SnapshotConfig, SnapshotExpired, SnapshotTime, SnapshotType,
 };
 use std::{
     borrow::Cow,
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     path::Path,
-    sync::Arc,
+    sync::{Arc, Weak},
 };
 use tokio::sync::RwLock;
 use tonic::{transport::Channel as RpcChannel, IntoRequest};
 use tools::atomic::{AtomicValue, U32};
 
+/// The target of a message which passed the send checks.
+enum MessageRecipient {
+    Channel(Arc<Channel>),
+    Online(Arc<ChatSession>),
+    Offline(users::Model),
+}
+
 #[derive(Clone)]
 pub struct ChatServiceImpl {
     pub user_sessions: Arc<UserSessions>,
     pub notify_queue: Arc<BanchoMessageQueue>,
     pub channels: Arc<Channels>,
     pub users_repository: DynUsersRepository,
+    pub chat_repository: DynChatRepository,
+    pub message_writer: Arc<ChatMessageWriter>,
+    pub filters: Arc<ChatFilters>,
 }
 
 impl ChatServiceImpl {
     #[inline]
-    pub fn new(users_repository: DynUsersRepository) -> Self {
+    pub fn new(
+        users_repository: DynUsersRepository,
+        chat_repository: DynChatRepository,
+        filters: ChatFilters,
+    ) -> Self {
         Self {
             user_sessions: UserSessions::default().into(),
             notify_queue: Arc::new(BanchoMessageQueue::default()),
             channels: Channels::default().into(),
             users_repository,
+            message_writer: ChatMessageWriter::new(chat_repository.clone())
+                .into(),
+            chat_repository,
+            filters: filters.into(),
         }
     }
 
@@ -53,6 +90,8 @@ impl ChatServiceImpl {
     pub async fn from_snapshot(
         snapshot: ChatServiceSnapshot,
         users_repository: DynUsersRepository,
+        chat_repository: DynChatRepository,
+        filters: ChatFilters,
     ) -> Self {
         let mut session_indexes =
             SessionIndexes::with_capacity(snapshot.user_sessions.len());
@@ -81,8 +120,13 @@ impl ChatServiceImpl {
                 name: ch.name.into(),
                 channel_type: ch.channel_type,
                 description: ch.description.into(),
+                icon: ch.icon.into(),
+                auto_join: ch.auto_join.into(),
+                privileges: ch.privileges.into(),
+                owner_id: ch.owner_id.into(),
                 users,
                 user_count,
+                muted_users: ch.muted_users.into(),
                 min_msg_index: ch.min_msg_index.into(),
                 message_queue: Arc::new(ch.message_queue.into()),
                 created_at: ch.created_at,
@@ -116,7 +160,16 @@ impl ChatServiceImpl {
         let user_sessions =
             Arc::new(UserSessions::from_indexes(session_indexes));
 
-        Self { user_sessions, notify_queue, channels, users_repository }
+        Self {
+            user_sessions,
+            notify_queue,
+            channels,
+            users_repository,
+            message_writer: ChatMessageWriter::new(chat_repository.clone())
+                .into(),
+            chat_repository,
+            filters: filters.into(),
+        }
     }
 
     #[inline]
@@ -133,22 +186,27 @@ impl ChatServiceImpl {
         privileges: i32,
         platforms: Platform,
     ) -> Result<Arc<ChatSession>, ChatError> {
+        let public_channels = self
+            .channels
+            .read()
+            .await
+            .public_channels
+            .values()
+            .filter(|channel| channel.is_joinable(privileges))
+            .cloned()
+            .collect::<Vec<_>>();
+
         let bancho_chat_ext = if platforms.contains(Platform::Bancho) {
             // prepare bancho packets
             let mut channel_packets = VecDeque::new();
 
-            for channel in self.channels.read().await.public_channels.values() {
+            for channel in public_channels.iter() {
                 channel_packets.push_back(
-                    server::ChannelInfo::pack(
-                        channel.name.load().as_ref().into(),
-                        channel
-                            .description
-                            .load()
-                            .as_deref()
-                            .map(|s| s.into())
-                            .unwrap_or_default(),
-                        channel.user_count.val() as i16,
-                    )
+                    if channel.auto_join.val() {
+                        channel.auto_join_packets()
+                    } else {
+                        channel.info_packets()
+                    }
                     .into(),
                 );
             }
@@ -172,9 +230,383 @@ impl ChatServiceImpl {
 
         let session = self.user_sessions.create(session.into()).await;
 
+        for channel in public_channels.iter().filter(|ch| ch.auto_join.val()) {
+            Channel::join(&session, channel).await;
+            channel.updated_at.set(Utc::now().into());
+        }
+
         Ok(session)
     }
 
+    /// Checks the operator may `handle` the target user in the channel, the
+    /// operator needs a higher privilege level than the target.
+    pub async fn moderate_channel_user(
+        &self,
+        handle: ChannelHandle,
+        channel_query: Option<RawChannelQuery>,
+        operator: Option<RawUserQuery>,
+        target: Option<RawUserQuery>,
+    ) -> Result<(Arc<Channel>, Arc<ChatSession>), ChatError> {
+        let channel_query = channel_query
+            .ok_or(ChatError::InvalidArgument)?
+            .into_channel_query()?;
+
+        let operator =
+            operator.ok_or(ChatError::InvalidArgument)?.into_user_query()?;
+
+        let target =
+            target.ok_or(ChatError::InvalidArgument)?.into_user_query()?;
+
+        let channel = self
+            .channels
+            .get_channel(&channel_query)
+            .await
+            .ok_or(ChatError::ChannelNotExists)?;
+
+        let operator = self.get_session(&operator, None).await?;
+        let target = self.get_session(&target, None).await?;
+
+        let operator_level = operator.privileges.val();
+
+        if !channel.is_permitted(handle, operator_level)
+            || operator_level <= target.privileges.val()
+        {
+            return Err(ChatError::PermissionDenied);
+        }
+
+        Ok((channel, target))
+    }
+
+    /// Gets the channel, the `#spectator` and `#multiplayer` aliases of
+    /// bancho clients resolve to the channels the user has joined.
+    pub async fn resolve_channel(
+        &self,
+        session: &ChatSession,
+        query: &ChannelQuery,
+    ) -> Option<Arc<Channel>> {
+        match query {
+            ChannelQuery::ChannelName(name)
+                if name == SPECTATOR_CHANNEL_ALIAS =>
+            {
+                Self::spectator_channel_of(session).await
+            },
+            ChannelQuery::ChannelName(name)
+                if name == MULTIPLAYER_CHANNEL_ALIAS =>
+            {
+                session
+                    .extends
+                    .joined_channels_of_type(ChannelType::Multiplayer)
+                    .await
+                    .into_iter()
+                    .next()
+            },
+            ChannelQuery::ChannelId(channel_id) => {
+                self.get_or_load_channel(*channel_id).await.ok()
+            },
+            query => self.channels.get_channel(query).await,
+        }
+    }
+
+    /// Gets the channel, private and group channels not in memory yet are
+    /// loaded from the database with their members.
+    pub async fn get_or_load_channel(
+        &self,
+        channel_id: u64,
+    ) -> Result<Arc<Channel>, ChatError> {
+        if let Some(channel) = self
+            .channels
+            .get_channel(&ChannelQuery::ChannelId(channel_id))
+            .await
+        {
+            return Ok(channel);
+        }
+
+        let ChannelWithMembers { channel: model, member_ids } = self
+            .chat_repository
+            .get_channel_with_members(channel_id as i64)
+            .await?
+            .ok_or(ChatError::ChannelNotExists)?;
+
+        let channel_type = channel_type(model.channel_type);
+        if !matches!(channel_type, ChannelType::Private | ChannelType::Group) {
+            return Err(ChatError::ChannelNotExists);
+        }
+
+        let channel = self
+            .channels
+            .get_or_create_channel(channel_id, || {
+                let channel = Channel::new(
+                    channel_id,
+                    model
+                        .name
+                        .unwrap_or_else(|| format!("#private_{channel_id}")),
+                    channel_type,
+                    model.description,
+                    None,
+                );
+                channel.icon.set(model.icon.map(Arc::new));
+                channel.owner_id.set(model.owner_id.map(Arc::new));
+                channel
+            })
+            .await;
+
+        for user_id in member_ids {
+            match self.user_sessions.get(&UserQuery::UserId(user_id)).await {
+                Some(session) => Channel::join(&session, &channel).await,
+                None => channel.add_member(user_id).await,
+            }
+        }
+
+        Ok(channel)
+    }
+
+    /// Gets the private or group channel, if the operator owns it.
+    pub async fn owned_channel(
+        &self,
+        channel_id: u64,
+        operator: Option<RawUserQuery>,
+    ) -> Result<(Arc<Channel>, users::Model), ChatError> {
+        let operator = self
+            .get_user(
+                &operator
+                    .ok_or(ChatError::InvalidArgument)?
+                    .into_user_query()?,
+            )
+            .await?;
+
+        let channel = self.get_or_load_channel(channel_id).await?;
+
+        if channel.owner_id.load().as_deref() != Some(&operator.id) {
+            return Err(ChatError::PermissionDenied);
+        }
+
+        Ok((channel, operator))
+    }
+
+    /// The spectator channel of the host the user is spectating, or the
+    /// user's own one if the user is not spectating anyone.
+    pub async fn spectator_channel_of(
+        session: &ChatSession,
+    ) -> Option<Arc<Channel>> {
+        let own_channel_id = spectator_channel_id(session.user_id);
+
+        let mut channels = session
+            .extends
+            .joined_channels_of_type(ChannelType::Spectaor)
+            .await;
+        channels.sort_by_key(|channel| channel.id == own_channel_id);
+
+        channels.into_iter().next()
+    }
+
+    /// Removes the user from the channel, spectator and multiplayer channels
+    /// left deserted are destroyed.
+    pub async fn leave_channel_inner(
+        &self,
+        session: &Arc<ChatSession>,
+        channel: &Arc<Channel>,
+    ) {
+        const LOG_TARGET: &str = "chat::channel::destroy";
+
+        // members of private and group channels leave the tab only
+        if channel.has_persistent_members() {
+            Channel::part(session, channel).await;
+            return;
+        }
+
+        // remove user from channel
+        Channel::remove(session, channel).await;
+
+        // update channel
+        channel.updated_at.set(Utc::now().into());
+
+        if !channel.is_dynamic() {
+            return;
+        }
+
+        let deserted = match channel.channel_type {
+            // spectating ends with the host, or with the last spectator
+            ChannelType::Spectaor => {
+                channel.id == spectator_channel_id(session.user_id)
+                    || channel.users.read().await.keys().all(|user_id| {
+                        channel.id == spectator_channel_id(*user_id)
+                    })
+            },
+            _ => channel.user_count.val() == 0,
+        };
+
+        if deserted {
+            Channel::remove_all(channel).await;
+            self.channels
+                .remove_channel(&ChannelQuery::ChannelId(channel.id))
+                .await;
+
+            info!(
+                target: LOG_TARGET,
+                "Channel {}({}) destroyed",
+                channel.name.load(),
+                channel.id
+            );
+        }
+    }
+
+    /// Closes the channel tab of the user's bancho client, with a notice of
+    /// the reason.
+    pub async fn reject_channel(
+        session: &ChatSession,
+        channel_name: &str,
+        reason: &ChatError,
+    ) {
+        if let Some(bancho_ext) = session.extends.bancho_ext.load().as_ref() {
+            let mut packets = server::ChannelKick::pack(channel_name.into());
+            packets.extend(server::Notification::pack(
+                format!("{channel_name}: {reason}").into(),
+            ));
+
+            bancho_ext.packets_queue.push_packet(packets.into()).await;
+        }
+    }
+
+    /// Users accepting messages from friends only accept the other users'
+    /// private messages if they befriended them.
+    pub async fn accepts_dm_from(
+        &self,
+        target: &ChatSession,
+        sender: &ChatSession,
+    ) -> Result<bool, ChatError> {
+        if !target.extends.only_friend_pm_allowed.val()
+            || target.user_id == sender.user_id
+        {
+            return Ok(true);
+        }
+
+        Ok(self
+            .users_repository
+            .is_friend(target.user_id, sender.user_id)
+            .await?)
+    }
+
+    /// Tells the sender's bancho client the target blocks its messages.
+    pub async fn reject_dm(sender: &ChatSession, target: &ChatSession) {
+        if let Some(bancho_ext) = sender.extends.bancho_ext.load().as_ref() {
+            bancho_ext
+                .packets_queue
+                .push_packet(
+                    server::UserDmBlocked::pack(
+                        target.username.load().as_str().into(),
+                    )
+                    .into(),
+                )
+                .await;
+        }
+    }
+
+    pub async fn get_user(
+        &self,
+        query: &UserQuery,
+    ) -> Result<users::Model, ChatError> {
+        Ok(match query {
+            UserQuery::SessionId(_) => return Err(ChatError::InvalidArgument),
+            UserQuery::UserId(user_id) => {
+                self.users_repository.get_user_by_id(*user_id).await
+            },
+            UserQuery::Username(username) => {
+                self.users_repository
+                    .get_user_by_username(username.as_str())
+                    .await
+            },
+            UserQuery::UsernameUnicode(username_unicode) => {
+                self.users_repository
+                    .get_user_by_username_unicode(username_unicode.as_str())
+                    .await
+            },
+        }?)
+    }
+
+    /// Gets the user and the target of a request, which must differ.
+    pub async fn get_user_pair(
+        &self,
+        user_query: Option<RawUserQuery>,
+        target: Option<RawUserQuery>,
+    ) -> Result<(users::Model, users::Model), ChatError> {
+        let user = self
+            .get_user(
+                &user_query
+                    .ok_or(ChatError::InvalidArgument)?
+                    .into_user_query()?,
+            )
+            .await?;
+
+        let target = self
+            .get_user(
+                &target.ok_or(ChatError::InvalidArgument)?.into_user_query()?,
+            )
+            .await?;
+
+        if user.id == target.id {
+            return Err(ChatError::InvalidArgument);
+        }
+
+        Ok((user, target))
+    }
+
+    /// Pushes the private messages sent while the user was offline to the
+    /// bancho client, then marks the delivered ones as read. Messages
+    /// received in the meantime stay unread for the next delivery.
+    pub async fn deliver_unread_messages(
+        &self,
+        session: &ChatSession,
+    ) -> Result<usize, ChatError> {
+        let Some(bancho_ext) = session.extends.bancho_ext.load_full() else {
+            return Ok(0);
+        };
+
+        // messages still queued are written first
+        self.message_writer.flush(FLUSH_BATCH_SIZE).await?;
+
+        let messages =
+            self.chat_repository.get_unread_messages(session.user_id).await?;
+
+        if messages.is_empty() {
+            return Ok(0);
+        }
+
+        let username = session.username.load();
+
+        for message in messages.iter() {
+            let content = if message.is_action {
+                Cow::Owned(format!(
+                    "{ACTION_PREFIX}{}\x01",
+                    message.content_string
+                ))
+            } else {
+                Cow::Borrowed(message.content_string.as_str())
+            };
+
+            bancho_ext
+                .packets_queue
+                .push_packet(
+                    server::SendMessage::pack(
+                        message.sender_name.as_str().into(),
+                        content,
+                        username.as_str().into(),
+                        message.sender_id,
+                    )
+                    .into(),
+                )
+                .await;
+        }
+
+        self.chat_repository
+            .mark_messages_read_by_ids(
+                session.user_id,
+                messages.iter().map(|message| message.id).collect(),
+            )
+            .await?;
+
+        Ok(messages.len())
+    }
+
     pub async fn get_session(
         &self,
         query: &UserQuery,
@@ -187,32 +619,18 @@ impl ChatServiceImpl {
             },
             None => {
                 if let Some(platforms) = create_if_not_exists {
-                    let user = match query {
-                        UserQuery::SessionId(_) => {
-                            return Err(ChatError::InvalidArgument)
-                        },
-                        UserQuery::UserId(user_id) => {
-                            self.users_repository.get_user_by_id(*user_id).await
-                        },
-                        UserQuery::Username(username) => {
-                            self.users_repository
-                                .get_user_by_username(username.as_str())
-                                .await
-                        },
-                        UserQuery::UsernameUnicode(username_unicode) => {
-                            self.users_repository
-                                .get_user_by_username_unicode(
-                                    username_unicode.as_str(),
-                                )
-                                .await
-                        },
-                    }?;
+                    let user = self.get_user(query).await?;
+
+                    let privileges = self
+                        .users_repository
+                        .get_user_privilege_level(user.id)
+                        .await?;
 
                     self.login_inner(
                         user.id,
                         user.name,
                         user.name_unicode,
-                        1, // todo
+                        privileges,
                         platforms,
                     )
                     .await
@@ -230,6 +648,8 @@ impl ChatServiceSnapshotLoader {
     pub async fn load(
         cfg: &CliChatServiceSnapshotConfigs,
         users_repository: DynUsersRepository,
+        chat_repository: DynChatRepository,
+        filters: ChatFilters,
     ) -> ChatServiceImpl {
         if cfg.should_load_snapshot() {
             let snapshot_path = Path::new(cfg.snapshot_path());
@@ -250,6 +670,8 @@ impl ChatServiceSnapshotLoader {
                             return ChatServiceImpl::from_snapshot(
                                 snapshot,
                                 users_repository,
+                                chat_repository,
+                                filters,
                             )
                             .await;
                         }
@@ -268,7 +690,7 @@ impl ChatServiceSnapshotLoader {
             }
         }
 
-        ChatServiceImpl::new(users_repository)
+        ChatServiceImpl::new(users_repository, chat_repository, filters)
     }
 }
 
@@ -319,6 +741,13 @@ impl ChannelStore for ChatServiceImpl {
     }
 }
 
+impl ChatMessagesStore for ChatServiceImpl {
+    #[inline]
+    fn message_writer(&self) -> &Arc<ChatMessageWriter> {
+        &self.message_writer
+    }
+}
+
 #[async_trait]
 impl ServiceSnapshot for ChatServiceImpl {
     async fn save_service_snapshot(
@@ -354,6 +783,7 @@ impl ChatService for ChatServiceImpl {
             username_unicode,
             privileges,
             platforms,
+            only_friend_pm_allowed,
         } = request;
 
         let platforms = Platform::from(platforms);
@@ -368,6 +798,21 @@ impl ChatService for ChatServiceImpl {
             )
             .await?;
 
+        session.extends.only_friend_pm_allowed.set(only_friend_pm_allowed);
+
+        match self.users_repository.get_blocked_user_ids(user_id).await {
+            Ok(blocked_users) => session
+                .extends
+                .blocked_users
+                .set(HashSet::from_iter(blocked_users).into()),
+            Err(err) => warn!(
+                target: LOG_TARGET,
+                "Failed to load blocked users of user {}({}), err: {err}",
+                session.username.load(),
+                session.user_id,
+            ),
+        }
+
         info!(
             target: LOG_TARGET,
             "User {}({}) logged in",
@@ -375,6 +820,22 @@ impl ChatService for ChatServiceImpl {
             session.user_id,
         );
 
+        match self.deliver_unread_messages(&session).await {
+            Ok(0) => {},
+            Ok(count) => info!(
+                target: LOG_TARGET,
+                "Delivered {count} unread messages to user {}({})",
+                session.username.load(),
+                session.user_id,
+            ),
+            Err(err) => warn!(
+                target: LOG_TARGET,
+                "Failed to deliver unread messages to user {}({}), err: {err}",
+                session.username.load(),
+                session.user_id,
+            ),
+        }
+
         Ok(ExecSuccess::default())
     }
 
@@ -416,16 +877,18 @@ impl ChatService for ChatServiceImpl {
         let platforms = curr_platforms.and(remove_platforms.not());
 
         if platforms.is_none() {
-            // leave all channels
-            for channel in session.extends.joined_channels.read().await.values()
-            {
-                if let Some(channel) = channel.ptr.load().upgrade() {
-                    // remove user from channel
-                    Channel::remove(&session, &channel).await;
+            // leave all channels, removing takes the lock of joined channels
+            let channels = session
+                .extends
+                .joined_channels
+                .read()
+                .await
+                .values()
+                .filter_map(|channel| channel.ptr.load().upgrade())
+                .collect::<Vec<_>>();
 
-                    // update channel
-                    channel.updated_at.set(Utc::now().into());
-                }
+            for channel in channels {
+                self.leave_channel_inner(&session, &channel).await;
             }
 
             // delete user session
@@ -454,6 +917,102 @@ impl ChatService for ChatServiceImpl {
         Ok(ExecSuccess::default())
     }
 
+    async fn update_only_friend_pm_allowed(
+        &self,
+        request: UpdateOnlyFriendPmAllowedRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        let UpdateOnlyFriendPmAllowedRequest {
+            user_query,
+            only_friend_pm_allowed,
+        } = request;
+
+        let user_query =
+            user_query.ok_or(ChatError::InvalidArgument)?.into_user_query()?;
+
+        let session = self.get_session(&user_query, None).await?;
+
+        session.extends.only_friend_pm_allowed.set(only_friend_pm_allowed);
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn block_user(
+        &self,
+        request: BlockUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        const LOG_TARGET: &str = "chat::block_user";
+
+        let BlockUserRequest { user_query, target } = request;
+
+        let (user, target) = self.get_user_pair(user_query, target).await?;
+
+        self.users_repository.block_user(user.id, target.id).await?;
+
+        if let Some(session) =
+            self.user_sessions.get(&UserQuery::UserId(user.id)).await
+        {
+            session.extends.set_blocked(target.id, true);
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "User {}({}) blocked user {}({})",
+            user.name,
+            user.id,
+            target.name,
+            target.id
+        );
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn unblock_user(
+        &self,
+        request: UnblockUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        const LOG_TARGET: &str = "chat::unblock_user";
+
+        let UnblockUserRequest { user_query, target } = request;
+
+        let (user, target) = self.get_user_pair(user_query, target).await?;
+
+        self.users_repository.unblock_user(user.id, target.id).await?;
+
+        if let Some(session) =
+            self.user_sessions.get(&UserQuery::UserId(user.id)).await
+        {
+            session.extends.set_blocked(target.id, false);
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "User {}({}) unblocked user {}({})",
+            user.name,
+            user.id,
+            target.name,
+            target.id
+        );
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn get_blocked_users(
+        &self,
+        query: UserQuery,
+    ) -> Result<BlockedUsers, ChatError> {
+        let user_ids = match self.user_sessions.get(&query).await {
+            Some(session) => {
+                session.extends.blocked_users.load().iter().copied().collect()
+            },
+            None => {
+                let user = self.get_user(&query).await?;
+                self.users_repository.get_blocked_user_ids(user.id).await?
+            },
+        };
+
+        Ok(BlockedUsers { user_ids })
+    }
+
     async fn send_message(
         &self,
         request: SendMessageRequest,
@@ -469,150 +1028,835 @@ impl ChatService for ChatServiceImpl {
             target.ok_or(ChatError::InvalidArgument)?.into_message_target()?;
 
         let sender =
-            self.get_session(&sender_query, Some(Platform::all())).await?;
+            self.get_session(&sender_query, Some(Platform::all_bits())).await?;
 
-        match target {
+        // authorize the target before the filters, a message that can not be
+        // sent must not count towards the repeated message detection
+        let recipient = match target {
             ChatMessageTarget::Channel(channel_query) => {
                 // get channel
                 let channel =
-                    match self.channels.get_channel(&channel_query).await {
+                    match self.resolve_channel(&sender, &channel_query).await {
                         Some(channel) => channel,
                         None => {
-                            todo!("channel not exists")
+                            let err = ChatError::ChannelNotExists;
+                            if let ChannelQuery::ChannelName(name) =
+                                &channel_query
+                            {
+                                Self::reject_channel(&sender, name, &err).await;
+                            }
+
+                            return Err(err);
                         },
                     };
 
-                let message_packet = server::SendMessage::pack(
-                    sender.username.load().as_ref().into(),
-                    Cow::Borrowed(message.as_ref()),
-                    channel.name.load().as_ref().into(),
-                    sender.user_id,
-                )
-                .into();
+                if !channel.is_member(sender.user_id).await {
+                    return Err(ChatError::NotInChannel);
+                }
 
-                // push msg into channel packets queue
-                channel.message_queue.write().await.push_message_excludes(
-                    Packet::Ptr(message_packet),
-                    [sender.user_id],
-                    None,
-                );
+                if !channel.is_permitted(
+                    ChannelHandle::SendMessage,
+                    sender.privileges.val(),
+                ) {
+                    return Err(ChatError::PermissionDenied);
+                }
 
-                info!(
-                    target: LOG_TARGET,
-                    "{}({}) @ {}({}): {}",
-                    sender.username.load(),
-                    sender.user_id,
-                    channel.name.load(),
-                    channel.id,
-                    message
-                );
+                if channel.is_muted(sender.user_id).await {
+                    return Err(ChatError::Muted);
+                }
+
+                MessageRecipient::Channel(channel)
             },
             ChatMessageTarget::User(target_query) => {
                 // get target user session
                 match self.get_session(&target_query, None).await.ok() {
                     Some(target_user) => {
-                        // push msg packet if target user's bancho packets queue is exists
-                        if let Some(bancho_ext) =
-                            target_user.extends.bancho_ext.load().as_ref()
+                        // Messages to a user who blocked the sender are
+                        // dropped silently.
+                        if target_user.extends.is_blocking(sender.user_id) {
+                            return Ok(SendMessageResponse::default());
+                        }
+
+                        if !self.accepts_dm_from(&target_user, &sender).await? {
+                            Self::reject_dm(&sender, &target_user).await;
+
+                            return Err(ChatError::DmBlocked);
+                        }
+
+                        MessageRecipient::Online(target_user)
+                    },
+                    None => {
+                        let target_user = self.get_user(&target_query).await?;
+
+                        if self
+                            .users_repository
+                            .is_blocked(target_user.id, sender.user_id)
+                            .await?
                         {
-                            bancho_ext
-                                .packets_queue
-                                .push_packet(
-                                    server::SendMessage::pack(
-                                        sender.username.load().as_ref().into(),
-                                        Cow::Borrowed(message.as_ref()),
-                                        target_user
-                                            .username
-                                            .load()
-                                            .as_ref()
-                                            .into(),
-                                        sender.user_id,
-                                    )
-                                    .into(),
-                                )
-                                .await;
+                            return Ok(SendMessageResponse::default());
                         }
 
-                        info!(
-                            target: LOG_TARGET,
-                            "{}({}) @ {}({}): {}",
-                            sender.username.load(),
+                        MessageRecipient::Offline(target_user)
+                    },
+                }
+            },
+        };
+
+        let is_action = message.starts_with(ACTION_PREFIX);
+        let message = match self.filters.filter(&message, Some(&sender)).await {
+            Ok(message) => message,
+            Err(err) => {
+                // tell the bancho client why nothing was sent
+                if let Some(bancho_ext) =
+                    sender.extends.bancho_ext.load().as_ref()
+                {
+                    bancho_ext
+                        .packets_queue
+                        .push_packet(
+                            server::Notification::pack(err.to_string().into())
+                                .into(),
+                        )
+                        .await;
+                }
+
+                return Err(err);
+            },
+        };
+        let content =
+            if is_action { action_content(&message) } else { message.as_str() };
+
+        let message_id = match recipient {
+            MessageRecipient::Channel(channel) => {
+                let message_packet = server::SendMessage::pack(
+                    sender.username.load().as_ref().into(),
+                    Cow::Borrowed(message.as_ref()),
+                    channel.bancho_name().into(),
+                    sender.user_id,
+                )
+                .into();
+
+                // hide the message from the members blocking the sender
+                let validator =
+                    channel.blocking_validator(sender.user_id).await;
+
+                // push msg into channel packets queue
+                channel.message_queue.write().await.push_message_excludes(
+                    Packet::Ptr(message_packet),
+                    [sender.user_id],
+                    validator,
+                );
+
+                info!(
+                    target: LOG_TARGET,
+                    "{}({}) @ {}({}): {}",
+                    sender.username.load(),
+                    sender.user_id,
+                    channel.name.load(),
+                    channel.id,
+                    message
+                );
+
+                self.message_writer
+                    .push(
+                        sender.user_id,
+                        channel_row(&channel),
+                        content.to_owned(),
+                        is_action,
+                        None,
+                    )
+                    .await?
+            },
+            MessageRecipient::Online(target_user) => {
+                // push msg packet if target user's bancho packets queue is exists
+                if let Some(bancho_ext) =
+                    target_user.extends.bancho_ext.load().as_ref()
+                {
+                    bancho_ext
+                        .packets_queue
+                        .push_packet(
+                            server::SendMessage::pack(
+                                sender.username.load().as_ref().into(),
+                                Cow::Borrowed(message.as_ref()),
+                                target_user.username.load().as_ref().into(),
+                                sender.user_id,
+                            )
+                            .into(),
+                        )
+                        .await;
+                }
+
+                info!(
+                    target: LOG_TARGET,
+                    "{}({}) @ {}({}): {}",
+                    sender.username.load(),
+                    sender.user_id,
+                    target_user.username.load(),
+                    target_user.user_id,
+                    message
+                );
+
+                self.message_writer
+                    .push(
+                        sender.user_id,
+                        private_channel_row(
                             sender.user_id,
-                            target_user.username.load(),
                             target_user.user_id,
-                            message
-                        );
-                    },
-                    None => {
-                        todo!("offline msg handle")
+                        ),
+                        content.to_owned(),
+                        is_action,
+                        None,
+                    )
+                    .await?
+            },
+            MessageRecipient::Offline(target_user) => {
+                info!(
+                    target: LOG_TARGET,
+                    "{}({}) @ {}({}) (offline): {}",
+                    sender.username.load(),
+                    sender.user_id,
+                    target_user.name,
+                    target_user.id,
+                    message
+                );
+
+                // delivered on the target's next login
+                self.message_writer
+                    .push(
+                        sender.user_id,
+                        private_channel_row(sender.user_id, target_user.id),
+                        content.to_owned(),
+                        is_action,
+                        Some(target_user.id),
+                    )
+                    .await?
+            },
+        };
+
+        Ok(SendMessageResponse { message_id: message_id as u64 })
+    }
+
+    async fn get_message_history(
+        &self,
+        request: GetMessageHistoryRequest,
+    ) -> Result<MessageHistory, ChatError> {
+        let GetMessageHistoryRequest {
+            channel_query,
+            conversation,
+            before_message_id,
+            limit,
+        } = request;
+
+        let channel_id = match (channel_query, conversation) {
+            (Some(channel_query), None) => {
+                match channel_query.into_channel_query()? {
+                    ChannelQuery::ChannelId(channel_id) => channel_id as i64,
+                    channel_query => {
+                        self.channels
+                            .get_channel(&channel_query)
+                            .await
+                            .ok_or(ChatError::ChannelNotExists)?
+                            .id as i64
                     },
-                };
+                }
             },
-        }
+            (None, Some(PrivateConversation { user_id, target_user_id })) => {
+                private_channel_id(user_id, target_user_id)
+            },
+            _ => return Err(ChatError::InvalidArgument),
+        };
+
+        let limit = match limit {
+            0 => DEFAULT_HISTORY_LIMIT,
+            limit => limit.min(MAX_HISTORY_LIMIT),
+        };
+
+        let messages = self
+            .chat_repository
+            .get_channel_messages(
+                channel_id,
+                before_message_id.map(|id| id as i64),
+                limit as u64,
+            )
+            .await?
+            .into_iter()
+            .map(|msg| ChatMessage {
+                id: msg.id as u64,
+                sender_id: msg.sender_id,
+                channel_id: msg.channel_id as u64,
+                timestamp: msg.timestamp.to_rfc3339(),
+                content: msg.content_string,
+                content_html: msg.content_html,
+                is_action: msg.is_action,
+            })
+            .collect();
+
+        Ok(MessageHistory { messages })
+    }
+
+    async fn mark_messages_read(
+        &self,
+        request: MarkMessagesReadRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        let MarkMessagesReadRequest { user_id, sender } = request;
+
+        let sender_id = match sender {
+            Some(sender) => {
+                Some(self.get_user(&sender.into_user_query()?).await?.id)
+            },
+            None => None,
+        };
+
+        self.chat_repository.mark_messages_read(user_id, sender_id).await?;
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn moderate_message(
+        &self,
+        request: ModerateMessageRequest,
+    ) -> Result<ModerateMessageResponse, ChatError> {
+        let ModerateMessageRequest { message, .. } = request;
 
-        Ok(SendMessageResponse::default())
+        Ok(ModerateMessageResponse {
+            message: self.filters.filter(&message, None).await?,
+        })
+    }
+
+    async fn get_chat_filter_rules(
+        &self,
+        _request: GetChatFilterRulesRequest,
+    ) -> Result<ChatFilterRules, ChatError> {
+        Ok(self.filters.get().await)
+    }
+
+    async fn update_chat_filter_rules(
+        &self,
+        request: ChatFilterRules,
+    ) -> Result<ChatFilterRules, ChatError> {
+        const LOG_TARGET: &str = "chat::update_chat_filter_rules";
+
+        info!(
+            target: LOG_TARGET,
+            "Chat filter rules updated: {} word filters, {} allowed link hosts",
+            request.word_filters.len(),
+            request.allowed_link_hosts.len()
+        );
+
+        Ok(self.filters.replace(request).await)
     }
 
     async fn join_channel(
         &self,
         request: JoinChannelRequest,
     ) -> Result<ExecSuccess, ChatError> {
-        let JoinChannelRequest { channel_query, user_query } = request;
+        let JoinChannelRequest { channel_query, user_query } = request;
+
+        let user_query =
+            user_query.ok_or(ChatError::InvalidArgument)?.into_user_query()?;
+
+        let channel_query = channel_query
+            .ok_or(ChatError::InvalidArgument)?
+            .into_channel_query()?;
+
+        let session =
+            self.get_session(&user_query, Some(Platform::all_bits())).await?;
+
+        let channel = match self.resolve_channel(&session, &channel_query).await
+        {
+            Some(channel) => channel,
+            None => {
+                let err = ChatError::ChannelNotExists;
+                if let ChannelQuery::ChannelName(name) = &channel_query {
+                    Self::reject_channel(&session, name, &err).await;
+                }
+
+                return Err(err);
+            },
+        };
+
+        if !channel.is_joinable(session.privileges.val())
+            && !channel.is_member(session.user_id).await
+        {
+            let err = ChatError::PermissionDenied;
+            Self::reject_channel(&session, &channel.bancho_name(), &err).await;
+
+            return Err(err);
+        }
+
+        // add user into channel
+        Channel::join(&session, &channel).await;
+
+        // update channel
+        channel.updated_at.set(Utc::now().into());
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn leave_channel(
+        &self,
+        request: LeaveChannelRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        let LeaveChannelRequest { channel_query, user_query } = request;
+
+        let user_query =
+            user_query.ok_or(ChatError::InvalidArgument)?.into_user_query()?;
+
+        let channel_query = channel_query
+            .ok_or(ChatError::InvalidArgument)?
+            .into_channel_query()?;
+
+        let session =
+            self.get_session(&user_query, Some(Platform::all_bits())).await?;
+
+        // the tab of bancho clients is already closed
+        let channel = self
+            .resolve_channel(&session, &channel_query)
+            .await
+            .ok_or(ChatError::ChannelNotExists)?;
+
+        self.leave_channel_inner(&session, &channel).await;
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn kick_channel_user(
+        &self,
+        request: KickChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::kick_user";
+
+        let KickChannelUserRequest { channel_query, operator, target } =
+            request;
+
+        let (channel, target) = self
+            .moderate_channel_user(
+                ChannelHandle::KickUser,
+                channel_query,
+                operator,
+                target,
+            )
+            .await?;
+
+        if !channel.is_member(target.user_id).await {
+            return Err(ChatError::NotInChannel);
+        }
+
+        Channel::remove(&target, &channel).await;
+
+        channel.updated_at.set(Utc::now().into());
+
+        info!(
+            target: LOG_TARGET,
+            "User {}({}) kicked from channel: {}({})",
+            target.username.load(),
+            target.user_id,
+            channel.name.load(),
+            channel.id
+        );
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn mute_channel_user(
+        &self,
+        request: MuteChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::mute_user";
+
+        let MuteChannelUserRequest {
+            channel_query,
+            operator,
+            target,
+            duration_secs,
+        } = request;
+
+        let (channel, target) = self
+            .moderate_channel_user(
+                ChannelHandle::MuteUser,
+                channel_query,
+                operator,
+                target,
+            )
+            .await?;
+
+        let until = (duration_secs > 0).then(|| {
+            chrono::Duration::from_std(std::time::Duration::from_secs(
+                duration_secs,
+            ))
+            .ok()
+            .and_then(|duration| Utc::now().checked_add_signed(duration))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+        });
+
+        channel.mute(target.user_id, until).await;
+
+        info!(
+            target: LOG_TARGET,
+            "User {}({}) muted in channel: {}({}) until {:?}",
+            target.username.load(),
+            target.user_id,
+            channel.name.load(),
+            channel.id,
+            until
+        );
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn create_private_channel(
+        &self,
+        request: CreatePrivateChannelRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::create_private_channel";
+
+        let CreatePrivateChannelRequest { owner, target } = request;
+
+        let owner = self
+            .get_user(
+                &owner.ok_or(ChatError::InvalidArgument)?.into_user_query()?,
+            )
+            .await?;
+
+        let target = self
+            .get_user(
+                &target.ok_or(ChatError::InvalidArgument)?.into_user_query()?,
+            )
+            .await?;
+
+        if owner.id == target.id {
+            return Err(ChatError::InvalidArgument);
+        }
+
+        // refused without telling the owner about the block
+        if self.users_repository.is_blocked(target.id, owner.id).await? {
+            return Err(ChatError::PermissionDenied);
+        }
+
+        let channel_id = private_channel_id(owner.id, target.id);
+
+        self.chat_repository
+            .create_private_channel(channel_id, owner.id, target.id)
+            .await?;
+
+        let channel = self.get_or_load_channel(channel_id as u64).await?;
+
+        info!(
+            target: LOG_TARGET,
+            "Private channel {}({}) created by user {}({})",
+            channel.name.load(),
+            channel.id,
+            owner.name,
+            owner.id
+        );
+
+        Ok(channel.to_channel_info())
+    }
+
+    async fn create_group_channel(
+        &self,
+        request: CreateGroupChannelRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::create_group_channel";
+
+        let CreateGroupChannelRequest { owner, description, member_ids } =
+            request;
+
+        let owner = self
+            .get_user(
+                &owner.ok_or(ChatError::InvalidArgument)?.into_user_query()?,
+            )
+            .await?;
+
+        // members blocking the owner are left out silently
+        let mut members = Vec::with_capacity(member_ids.len());
+        for member_id in member_ids {
+            if !self.users_repository.is_blocked(member_id, owner.id).await? {
+                members.push(member_id);
+            }
+        }
+
+        let model = self
+            .chat_repository
+            .create_group_channel(CreateGroupChannel {
+                owner_id: owner.id,
+                description,
+                member_ids: members,
+            })
+            .await?;
+
+        let channel = self.get_or_load_channel(model.id as u64).await?;
+
+        info!(
+            target: LOG_TARGET,
+            "Group channel {}({}) created by user {}({})",
+            channel.name.load(),
+            channel.id,
+            owner.name,
+            owner.id
+        );
+
+        Ok(channel.to_channel_info())
+    }
+
+    async fn invite_channel_user(
+        &self,
+        request: InviteChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::invite_user";
+
+        let InviteChannelUserRequest { channel_id, operator, target } = request;
+
+        let (channel, operator) =
+            self.owned_channel(channel_id, operator).await?;
+
+        // private channels are between their two users only
+        if channel.channel_type != ChannelType::Group {
+            return Err(ChatError::InvalidArgument);
+        }
+
+        let target = self
+            .get_user(
+                &target.ok_or(ChatError::InvalidArgument)?.into_user_query()?,
+            )
+            .await?;
+
+        // Invites to a user who blocked the operator are dropped silently,
+        // like private messages.
+        if self.users_repository.is_blocked(target.id, operator.id).await? {
+            return Ok(ExecSuccess::default());
+        }
+
+        self.chat_repository
+            .add_channel_user(channel.id as i64, target.id)
+            .await?;
+
+        match self.user_sessions.get(&UserQuery::UserId(target.id)).await {
+            Some(session) => Channel::join(&session, &channel).await,
+            None => channel.add_member(target.id).await,
+        }
+
+        channel.updated_at.set(Utc::now().into());
+
+        info!(
+            target: LOG_TARGET,
+            "User {}({}) invited into channel: {}({})",
+            target.name,
+            target.id,
+            channel.name.load(),
+            channel.id
+        );
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn remove_channel_user(
+        &self,
+        request: RemoveChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::remove_user";
+
+        let RemoveChannelUserRequest { channel_id, operator, target } = request;
+
+        let operator = self
+            .get_user(
+                &operator
+                    .ok_or(ChatError::InvalidArgument)?
+                    .into_user_query()?,
+            )
+            .await?;
+
+        let target = self
+            .get_user(
+                &target.ok_or(ChatError::InvalidArgument)?.into_user_query()?,
+            )
+            .await?;
+
+        let channel = self.get_or_load_channel(channel_id).await?;
+
+        if channel.channel_type != ChannelType::Group {
+            return Err(ChatError::InvalidArgument);
+        }
+
+        let owner_id = channel.owner_id.load().as_deref().copied();
+
+        // members may leave by themselves, the owner transfers the ownership
+        // or archives the channel instead
+        if owner_id == Some(target.id) {
+            return Err(ChatError::InvalidArgument);
+        }
+
+        if owner_id != Some(operator.id) && operator.id != target.id {
+            return Err(ChatError::PermissionDenied);
+        }
+
+        if !self
+            .chat_repository
+            .remove_channel_user(channel.id as i64, target.id)
+            .await?
+        {
+            return Err(ChatError::NotInChannel);
+        }
+
+        if let Some(session) =
+            self.user_sessions.get(&UserQuery::UserId(target.id)).await
+        {
+            Channel::remove(&session, &channel).await;
+        }
+        channel.remove_member(target.id).await;
+
+        channel.updated_at.set(Utc::now().into());
+
+        info!(
+            target: LOG_TARGET,
+            "User {}({}) removed from channel: {}({}) by user {}({})",
+            target.name,
+            target.id,
+            channel.name.load(),
+            channel.id,
+            operator.name,
+            operator.id
+        );
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn transfer_channel_ownership(
+        &self,
+        request: TransferChannelOwnershipRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::transfer_ownership";
+
+        let TransferChannelOwnershipRequest { channel_id, operator, target } =
+            request;
+
+        let (channel, operator) =
+            self.owned_channel(channel_id, operator).await?;
+
+        let target = self
+            .get_user(
+                &target.ok_or(ChatError::InvalidArgument)?.into_user_query()?,
+            )
+            .await?;
+
+        if !channel.is_member(target.id).await {
+            return Err(ChatError::NotInChannel);
+        }
+
+        self.chat_repository
+            .set_channel_owner(channel.id as i64, target.id)
+            .await?;
+
+        channel.owner_id.set(Some(target.id.into()));
+        channel.updated_at.set(Utc::now().into());
+
+        info!(
+            target: LOG_TARGET,
+            "Channel {}({}) transferred from user {}({}) to user {}({})",
+            channel.name.load(),
+            channel.id,
+            operator.name,
+            operator.id,
+            target.name,
+            target.id
+        );
+
+        Ok(channel.to_channel_info())
+    }
+
+    async fn archive_channel(
+        &self,
+        request: ArchiveChannelRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::archive_channel";
+
+        let ArchiveChannelRequest { channel_id, operator } = request;
+
+        let (channel, operator) =
+            self.owned_channel(channel_id, operator).await?;
+
+        if !self.chat_repository.archive_channel(channel.id as i64).await? {
+            return Err(ChatError::ChannelNotExists);
+        }
+
+        self.channels
+            .remove_channel(&ChannelQuery::ChannelId(channel.id))
+            .await;
+
+        Channel::remove_all(&channel).await;
+
+        info!(
+            target: LOG_TARGET,
+            "Channel {}({}) archived by user {}({})",
+            channel.name.load(),
+            channel.id,
+            operator.name,
+            operator.id
+        );
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn join_spectator_channel(
+        &self,
+        request: JoinSpectatorChannelRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        let JoinSpectatorChannelRequest { host_id, user_query } = request;
 
         let user_query =
             user_query.ok_or(ChatError::InvalidArgument)?.into_user_query()?;
 
-        let channel_query = channel_query
-            .ok_or(ChatError::InvalidArgument)?
-            .into_channel_query()?;
-
         let session =
-            self.get_session(&user_query, Some(Platform::all())).await?;
+            self.get_session(&user_query, Some(Platform::all_bits())).await?;
 
-        let channel = match self.channels.get_channel(&channel_query).await {
-            Some(channel) => channel,
-            None => {
-                todo!("channel not exists")
-            },
-        };
+        let host = self.get_session(&UserQuery::UserId(host_id), None).await?;
 
-        // add user into channel
-        Channel::join(&session, &channel).await;
+        let channel_id = spectator_channel_id(host_id);
 
-        // update channel
-        channel.updated_at.set(Utc::now().into());
+        // users spectate one host at a time
+        if let Some(spectating) = Self::spectator_channel_of(&session).await {
+            if spectating.id != channel_id
+                && spectating.id != spectator_channel_id(session.user_id)
+            {
+                self.leave_channel_inner(&session, &spectating).await;
+            }
+        }
+
+        let channel = self
+            .channels
+            .get_or_create_channel(channel_id, || {
+                Channel::new(
+                    channel_id,
+                    format!("#spec_{host_id}"),
+                    ChannelType::Spectaor,
+                    Some(format!("Spectating {}", host.username.load())),
+                    None,
+                )
+            })
+            .await;
+
+        Channel::join(&host, &channel).await;
+        Channel::join(&session, &channel).await;
 
         Ok(ExecSuccess::default())
     }
 
-    async fn leave_channel(
+    async fn leave_spectator_channel(
         &self,
-        request: LeaveChannelRequest,
+        request: LeaveSpectatorChannelRequest,
     ) -> Result<ExecSuccess, ChatError> {
-        let LeaveChannelRequest { channel_query, user_query } = request;
+        let LeaveSpectatorChannelRequest { user_query } = request;
 
         let user_query =
             user_query.ok_or(ChatError::InvalidArgument)?.into_user_query()?;
 
-        let channel_query = channel_query
-            .ok_or(ChatError::InvalidArgument)?
-            .into_channel_query()?;
-
         let session =
-            self.get_session(&user_query, Some(Platform::all())).await?;
-
-        let channel = match self.channels.get_channel(&channel_query).await {
-            Some(channel) => channel,
-            None => {
-                todo!("channel not exists")
-            },
-        };
-
-        // remove user from channel
-        Channel::remove(&session, &channel).await;
+            self.get_session(&user_query, Some(Platform::all_bits())).await?;
 
-        // update channel
-        channel.updated_at.set(Utc::now().into());
+        if let Some(channel) = Self::spectator_channel_of(&session).await {
+            if channel.id != spectator_channel_id(session.user_id) {
+                self.leave_channel_inner(&session, &channel).await;
+            }
+        }
 
         Ok(ExecSuccess::default())
     }
@@ -625,7 +1869,7 @@ impl ChatService for ChatServiceImpl {
 
         let bancho_ext = match session.extends.bancho_ext.load_full() {
             Some(bancho_ext) => bancho_ext,
-            None => todo!("invalid call"),
+            None => return Err(ChatError::NotBanchoSession),
         };
 
         let mut data = Vec::new();
@@ -713,12 +1957,19 @@ impl ChatService for ChatServiceImpl {
             }
         }
 
-        // TODO: real accessable check
+        // channels the user could join or is already in
         let accessable_channels = {
+            let privileges = session.privileges.val();
+            let joined_channels = session.extends.joined_channels.read().await;
+
             self.channels
                 .read()
                 .await
                 .values()
+                .filter(|ch| {
+                    ch.is_joinable(privileges)
+                        || joined_channels.contains_key(&ch.id)
+                })
                 .cloned()
                 .collect::<Vec<Arc<Channel>>>()
         };
@@ -741,7 +1992,7 @@ impl ChatService for ChatServiceImpl {
 
                 // update receive
                 receive_channel_updates
-                    .insert(ch.id, ch.updated_at.load().as_ref().clone());
+                    .insert(ch.id, *ch.updated_at.load().as_ref());
             }
         };
 
@@ -754,36 +2005,114 @@ impl ChatService for ChatServiceImpl {
     async fn load_public_channels(&self) -> Result<ExecSuccess, ChatError> {
         const LOG_TARGET: &str = "chat::channel::initialize_public_channels";
 
-        // todo: load public channels from database
-        let public_channels = vec![
-            Channel::new(
-                0,
-                "#osu".to_string(),
-                ChannelType::Public,
-                Some("default channel".to_string()),
-                None,
-            ),
-            Channel::new(
-                1,
-                "#peace".to_string(),
-                ChannelType::Public,
-                Some("peace channel".to_string()),
-                None,
-            ),
-        ];
+        let public_channels =
+            self.chat_repository.get_public_channels().await?;
+
+        let mut channel_privileges = HashMap::<u64, ChannelPrivileges>::new();
+        for privilege in self.chat_repository.get_channel_privileges().await? {
+            channel_privileges
+                .entry(privilege.channel_id as u64)
+                .or_default()
+                .set(
+                    channel_handle(privilege.handle),
+                    privilege.required_level,
+                );
+        }
 
-        let () = {
+        let removed_channels = {
             let mut indexes = self.channels.write().await;
-            for channel in public_channels {
-                self.channels.create_channel_inner(
+
+            for model in public_channels.iter() {
+                let Some(name) = model.name.clone() else {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Public channel {} has no name, skipped.", model.id
+                    );
+                    continue;
+                };
+
+                let exists = self.channels.get_channel_inner(
+                    &indexes,
+                    &ChannelQuery::ChannelId(model.id as u64),
+                );
+
+                match exists {
+                    Some(channel) => {
+                        if channel.name.load().as_str() != name {
+                            indexes.rename_channel(&channel, name);
+                        }
+                        channel.update_info(
+                            model.description.clone(),
+                            model.icon.clone(),
+                            model.auto_join,
+                        );
+                        channel.privileges.set(
+                            channel_privileges
+                                .get(&channel.id)
+                                .copied()
+                                .unwrap_or_default()
+                                .into(),
+                        );
+                    },
+                    None => {
+                        let channel = Channel::new(
+                            model.id as u64,
+                            name,
+                            ChannelType::Public,
+                            None,
+                            None,
+                        );
+                        channel.update_info(
+                            model.description.clone(),
+                            model.icon.clone(),
+                            model.auto_join,
+                        );
+                        channel.privileges.set(
+                            channel_privileges
+                                .get(&channel.id)
+                                .copied()
+                                .unwrap_or_default()
+                                .into(),
+                        );
+
+                        self.channels.create_channel_inner(
+                            &mut indexes,
+                            channel.into(),
+                            false,
+                        );
+                    },
+                }
+            }
+
+            let removed_channels = indexes
+                .public_channels
+                .values()
+                .filter(|ch| {
+                    !public_channels.iter().any(|m| m.id as u64 == ch.id)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+
+            for channel in removed_channels.iter() {
+                self.channels.remove_channel_inner(
                     &mut indexes,
-                    channel.into(),
-                    false,
+                    &channel.id,
+                    &channel.name.load(),
                 );
             }
+
+            removed_channels
         };
 
-        info!(target: LOG_TARGET, "Public channels successfully initialized.",);
+        for channel in removed_channels {
+            Channel::remove_all(&channel).await;
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Public channels successfully initialized ({} channels).",
+            self.channels.channel_count()
+        );
 
         Ok(ExecSuccess::default())
     }
@@ -791,33 +2120,215 @@ impl ChatService for ChatServiceImpl {
     async fn get_public_channels(
         &self,
     ) -> Result<GetPublicChannelsResponse, ChatError> {
-        fn to_channel_info(ch: &Arc<Channel>) -> ChannelInfo {
-            ChannelInfo {
-                id: ch.id,
-                name: ch.name.to_string(),
-                channel_type: ch.channel_type as i32,
-                description: ch
-                    .description
-                    .load()
-                    .as_deref()
-                    .map(|s| s.to_string()),
-                online_users: ch.user_count.val(),
-                users: None,
-            }
-        }
-
         let channel_indexes = self.channels.read().await;
 
         let res = GetPublicChannelsResponse {
             channels: channel_indexes
                 .public_channels
                 .values()
-                .map(to_channel_info)
+                .map(|ch| ch.to_channel_info())
                 .collect(),
         };
 
         Ok(res)
     }
+
+    async fn create_channel(
+        &self,
+        request: CreateChannelRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::create_channel";
+
+        let CreateChannelRequest {
+            name,
+            description,
+            icon,
+            auto_join,
+            creator_id,
+        } = request;
+
+        if !Channel::is_valid_name(&name) {
+            return Err(ChatError::InvalidChannelName);
+        }
+
+        if self
+            .channels
+            .is_channel_exists(&ChannelQuery::ChannelName(name.clone()))
+            .await
+        {
+            return Err(ChatError::ChannelExists);
+        }
+
+        let model = self
+            .chat_repository
+            .create_public_channel(CreatePublicChannel {
+                name: name.clone(),
+                description,
+                icon,
+                auto_join,
+                creator_id,
+            })
+            .await?;
+
+        let channel = Channel::new(
+            model.id as u64,
+            name,
+            ChannelType::Public,
+            None,
+            None,
+        );
+        channel.update_info(model.description, model.icon, model.auto_join);
+
+        let channel = self.channels.create_channel(channel, false).await;
+
+        info!(
+            target: LOG_TARGET,
+            "Channel {}({}) created",
+            channel.name.load(),
+            channel.id
+        );
+
+        Ok(channel.to_channel_info())
+    }
+
+    async fn update_channel(
+        &self,
+        request: UpdateChannelRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::update_channel";
+
+        let UpdateChannelRequest {
+            channel_query,
+            name,
+            description,
+            icon,
+            auto_join,
+        } = request;
+
+        let channel_query = channel_query
+            .ok_or(ChatError::InvalidArgument)?
+            .into_channel_query()?;
+
+        let channel = self
+            .channels
+            .get_channel(&channel_query)
+            .await
+            .filter(|ch| ch.channel_type == ChannelType::Public)
+            .ok_or(ChatError::ChannelNotExists)?;
+
+        // keep the name if it is not changed
+        let name = name.filter(|name| name != channel.name.load().as_str());
+
+        if let Some(name) = name.as_ref() {
+            if !Channel::is_valid_name(name) {
+                return Err(ChatError::InvalidChannelName);
+            }
+
+            if self
+                .channels
+                .is_channel_exists(&ChannelQuery::ChannelName(name.clone()))
+                .await
+            {
+                return Err(ChatError::ChannelExists);
+            }
+        }
+
+        // empty strings clear the field
+        fn clearable(val: Option<String>) -> Option<Option<String>> {
+            val.map(|s| (!s.is_empty()).then_some(s))
+        }
+
+        let model = self
+            .chat_repository
+            .update_channel(
+                channel.id as i64,
+                UpdateChannel {
+                    name: name.clone(),
+                    description: clearable(description),
+                    icon: clearable(icon),
+                    auto_join,
+                },
+            )
+            .await?
+            .ok_or(ChatError::ChannelNotExists)?;
+
+        if let Some(name) = name {
+            let members = channel
+                .users
+                .read()
+                .await
+                .values()
+                .filter_map(|session| session.as_ref().and_then(Weak::upgrade))
+                .filter_map(|session| session.extends.bancho_ext.load_full())
+                .collect::<Vec<_>>();
+
+            // bancho clients only know channels by name
+            let kick_packets = channel.kick_packets();
+
+            self.channels.write().await.rename_channel(&channel, name);
+
+            let join_packets = channel.join_packets();
+
+            for bancho_ext in members {
+                bancho_ext
+                    .packets_queue
+                    .push_packet(kick_packets.clone().into())
+                    .await;
+                bancho_ext
+                    .packets_queue
+                    .push_packet(join_packets.clone().into())
+                    .await;
+            }
+        }
+
+        channel.update_info(model.description, model.icon, model.auto_join);
+
+        info!(
+            target: LOG_TARGET,
+            "Channel {}({}) updated",
+            channel.name.load(),
+            channel.id
+        );
+
+        Ok(channel.to_channel_info())
+    }
+
+    async fn delete_channel(
+        &self,
+        request: DeleteChannelRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        const LOG_TARGET: &str = "chat::channel::delete_channel";
+
+        let DeleteChannelRequest { channel_query } = request;
+
+        let channel_query = channel_query
+            .ok_or(ChatError::InvalidArgument)?
+            .into_channel_query()?;
+
+        let channel = self
+            .channels
+            .get_channel(&channel_query)
+            .await
+            .filter(|ch| ch.channel_type == ChannelType::Public)
+            .ok_or(ChatError::ChannelNotExists)?;
+
+        self.chat_repository.delete_channel(channel.id as i64).await?;
+
+        self.channels
+            .remove_channel(&ChannelQuery::ChannelId(channel.id))
+            .await;
+
+        Channel::remove_all(&channel).await;
+
+        info!(
+            target: LOG_TARGET,
+            "Channel {}({}) deleted",
+            channel.name.load(),
+            channel.id
+        );
+
+        Ok(ExecSuccess::default())
+    }
 }
 
 #[derive(Clone)]
@@ -854,6 +2365,8 @@ impl NotifyMessagesQueue for ChatServiceRemote {}
 
 impl ChannelStore for ChatServiceRemote {}
 
+impl ChatMessagesStore for ChatServiceRemote {}
+
 #[async_trait]
 impl CreateSnapshot<ChatServiceSnapshot> for ChatServiceRemote {
     async fn create_snapshot(&self) -> ChatServiceSnapshot {
@@ -895,6 +2408,42 @@ impl ChatService for ChatServiceRemote {
         Ok(self.client().logout(req).await?.into_inner())
     }
 
+    async fn update_only_friend_pm_allowed(
+        &self,
+        request: UpdateOnlyFriendPmAllowedRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self
+            .client()
+            .update_only_friend_pm_allowed(request)
+            .await?
+            .into_inner())
+    }
+
+    async fn block_user(
+        &self,
+        request: BlockUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().block_user(request).await?.into_inner())
+    }
+
+    async fn unblock_user(
+        &self,
+        request: UnblockUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().unblock_user(request).await?.into_inner())
+    }
+
+    async fn get_blocked_users(
+        &self,
+        query: UserQuery,
+    ) -> Result<BlockedUsers, ChatError> {
+        Ok(self
+            .client()
+            .get_blocked_users(Into::<RawUserQuery>::into(query))
+            .await?
+            .into_inner())
+    }
+
     async fn send_message(
         &self,
         request: SendMessageRequest,
@@ -906,6 +2455,49 @@ impl ChatService for ChatServiceRemote {
             .into_inner())
     }
 
+    async fn get_message_history(
+        &self,
+        request: GetMessageHistoryRequest,
+    ) -> Result<MessageHistory, ChatError> {
+        Ok(self
+            .client()
+            .get_message_history(request.into_request())
+            .await?
+            .into_inner())
+    }
+
+    async fn mark_messages_read(
+        &self,
+        request: MarkMessagesReadRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().mark_messages_read(request).await?.into_inner())
+    }
+
+    async fn moderate_message(
+        &self,
+        request: ModerateMessageRequest,
+    ) -> Result<ModerateMessageResponse, ChatError> {
+        Ok(self
+            .client()
+            .moderate_message(request.into_request())
+            .await?
+            .into_inner())
+    }
+
+    async fn get_chat_filter_rules(
+        &self,
+        request: GetChatFilterRulesRequest,
+    ) -> Result<ChatFilterRules, ChatError> {
+        Ok(self.client().get_chat_filter_rules(request).await?.into_inner())
+    }
+
+    async fn update_chat_filter_rules(
+        &self,
+        request: ChatFilterRules,
+    ) -> Result<ChatFilterRules, ChatError> {
+        Ok(self.client().update_chat_filter_rules(request).await?.into_inner())
+    }
+
     async fn join_channel(
         &self,
         request: JoinChannelRequest,
@@ -928,6 +2520,80 @@ impl ChatService for ChatServiceRemote {
             .into_inner())
     }
 
+    async fn kick_channel_user(
+        &self,
+        request: KickChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().kick_channel_user(request).await?.into_inner())
+    }
+
+    async fn mute_channel_user(
+        &self,
+        request: MuteChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().mute_channel_user(request).await?.into_inner())
+    }
+
+    async fn create_private_channel(
+        &self,
+        request: CreatePrivateChannelRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        Ok(self.client().create_private_channel(request).await?.into_inner())
+    }
+
+    async fn create_group_channel(
+        &self,
+        request: CreateGroupChannelRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        Ok(self.client().create_group_channel(request).await?.into_inner())
+    }
+
+    async fn invite_channel_user(
+        &self,
+        request: InviteChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().invite_channel_user(request).await?.into_inner())
+    }
+
+    async fn remove_channel_user(
+        &self,
+        request: RemoveChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().remove_channel_user(request).await?.into_inner())
+    }
+
+    async fn transfer_channel_ownership(
+        &self,
+        request: TransferChannelOwnershipRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        Ok(self
+            .client()
+            .transfer_channel_ownership(request)
+            .await?
+            .into_inner())
+    }
+
+    async fn archive_channel(
+        &self,
+        request: ArchiveChannelRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().archive_channel(request).await?.into_inner())
+    }
+
+    async fn join_spectator_channel(
+        &self,
+        request: JoinSpectatorChannelRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().join_spectator_channel(request).await?.into_inner())
+    }
+
+    async fn leave_spectator_channel(
+        &self,
+        request: LeaveSpectatorChannelRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().leave_spectator_channel(request).await?.into_inner())
+    }
+
     async fn dequeue_chat_packets(
         &self,
         query: UserQuery,
@@ -956,4 +2622,25 @@ impl ChatService for ChatServiceRemote {
             .await?
             .into_inner())
     }
+
+    async fn create_channel(
+        &self,
+        request: CreateChannelRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        Ok(self.client().create_channel(request).await?.into_inner())
+    }
+
+    async fn update_channel(
+        &self,
+        request: UpdateChannelRequest,
+    ) -> Result<ChannelInfo, ChatError> {
+        Ok(self.client().update_channel(request).await?.into_inner())
+    }
+
+    async fn delete_channel(
+        &self,
+        request: DeleteChannelRequest,
+    ) -> Result<ExecSuccess, ChatError> {
+        Ok(self.client().delete_channel(request).await?.into_inner())
+    }
 }
@@ -2,7 +2,9 @@ use crate::*;
 use domain_chat::Platform;
 use infra_packets::Packet;
 use infra_services::ServiceSnapshot;
-use pb_bancho_state::{BanchoPackets, UserQuery};
+use pb_bancho_state::{
+    BanchoPackets, UpdateOnlyFriendPmAllowedRequest, UserQuery,
+};
 use pb_base::ExecSuccess;
 use pb_chat::*;
 use peace_message_queue::{MessageData, MessageQueue};
@@ -37,11 +39,18 @@ pub trait NotifyMessagesQueue {
     }
 }
 
+pub trait ChatMessagesStore {
+    fn message_writer(&self) -> &Arc<ChatMessageWriter> {
+        unimplemented!()
+    }
+}
+
 #[async_trait]
 pub trait ChatService:
     UserSessionsStore
     + NotifyMessagesQueue
     + ChannelStore
+    + ChatMessagesStore
     + CreateSnapshot<ChatServiceSnapshot>
     + SaveSnapshotTo<ChatServiceSnapshot>
     + ServiceSnapshot
@@ -57,11 +66,59 @@ pub trait ChatService:
         remove_platforms: Platform,
     ) -> Result<ExecSuccess, ChatError>;
 
+    async fn update_only_friend_pm_allowed(
+        &self,
+        request: UpdateOnlyFriendPmAllowedRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn block_user(
+        &self,
+        request: BlockUserRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn unblock_user(
+        &self,
+        request: UnblockUserRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn get_blocked_users(
+        &self,
+        query: UserQuery,
+    ) -> Result<BlockedUsers, ChatError>;
+
     async fn send_message(
         &self,
         request: SendMessageRequest,
     ) -> Result<SendMessageResponse, ChatError>;
 
+    /// Pages through the stored messages of a channel or a private
+    /// conversation, the latest first.
+    async fn get_message_history(
+        &self,
+        request: GetMessageHistoryRequest,
+    ) -> Result<MessageHistory, ChatError>;
+
+    async fn mark_messages_read(
+        &self,
+        request: MarkMessagesReadRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn moderate_message(
+        &self,
+        request: ModerateMessageRequest,
+    ) -> Result<ModerateMessageResponse, ChatError>;
+
+    async fn get_chat_filter_rules(
+        &self,
+        request: GetChatFilterRulesRequest,
+    ) -> Result<ChatFilterRules, ChatError>;
+
+    /// Replaces the rules of the filter chain, returns the previous ones.
+    async fn update_chat_filter_rules(
+        &self,
+        request: ChatFilterRules,
+    ) -> Result<ChatFilterRules, ChatError>;
+
     async fn join_channel(
         &self,
         request: JoinChannelRequest,
@@ -72,6 +129,56 @@ pub trait ChatService:
         request: LeaveChannelRequest,
     ) -> Result<ExecSuccess, ChatError>;
 
+    async fn kick_channel_user(
+        &self,
+        request: KickChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn mute_channel_user(
+        &self,
+        request: MuteChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn create_private_channel(
+        &self,
+        request: CreatePrivateChannelRequest,
+    ) -> Result<ChannelInfo, ChatError>;
+
+    async fn create_group_channel(
+        &self,
+        request: CreateGroupChannelRequest,
+    ) -> Result<ChannelInfo, ChatError>;
+
+    async fn invite_channel_user(
+        &self,
+        request: InviteChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn remove_channel_user(
+        &self,
+        request: RemoveChannelUserRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn transfer_channel_ownership(
+        &self,
+        request: TransferChannelOwnershipRequest,
+    ) -> Result<ChannelInfo, ChatError>;
+
+    async fn archive_channel(
+        &self,
+        request: ArchiveChannelRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn join_spectator_channel(
+        &self,
+        request: JoinSpectatorChannelRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
+    async fn leave_spectator_channel(
+        &self,
+        request: LeaveSpectatorChannelRequest,
+    ) -> Result<ExecSuccess, ChatError>;
+
     async fn dequeue_chat_packets(
         &self,
         query: UserQuery,
@@ -82,6 +189,21 @@ pub trait ChatService:
     async fn get_public_channels(
         &self,
     ) -> Result<GetPublicChannelsResponse, ChatError>;
+
+    async fn create_channel(
+        &self,
+        request: CreateChannelRequest,
+    ) -> Result<ChannelInfo, ChatError>;
+
+    async fn update_channel(
+        &self,
+        request: UpdateChannelRequest,
+    ) -> Result<ChannelInfo, ChatError>;
+
+    async fn delete_channel(
+        &self,
+        request: DeleteChannelRequest,
+    ) -> Result<ExecSuccess, ChatError>;
 }
 
 #[async_trait]
@@ -0,0 +1,880 @@
+mod chat_service {
+    use crate::{
+        private_channel_row, Channel, ChannelHandle, ChannelPrivileges,
+        ChatError, ChatFilters, ChatMessageWriter, ChatService,
+        ChatServiceImpl, ChatSession, MESSAGE_ID_BLOCK_SIZE,
+    };
+    use async_trait::async_trait;
+    use bancho_packets::{PacketId, PacketReader};
+    use chrono::Utc;
+    use domain_chat::{ChannelType, Platform};
+    use domain_users::{CreateUser, Email, UsernameSafe};
+    use pb_bancho_state::UserQuery;
+    use pb_chat::{
+        ChannelQuery, ChatMessageTarget, JoinChannelRequest,
+        LeaveChannelRequest, SendMessageRequest,
+    };
+    use peace_db::{
+        peace::entity::{channels, chat_messages, users},
+        DbErr, InsertResult,
+    };
+    use peace_repositories::{
+        chat::{
+            ChannelPrivilege, ChannelWithMembers, ChatRepository,
+            CreateChatChannel, CreateChatMessage, CreateGroupChannel,
+            CreatePublicChannel, UnreadChatMessage, UpdateChannel,
+        },
+        users::UsersRepository,
+        GetUserError,
+    };
+    use std::sync::{
+        atomic::{AtomicI64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+    use tools::atomic::AtomicValue;
+
+    const USER_ID: i32 = 1000;
+
+    /// The paths under test never reach the database, saved messages are
+    /// recorded as `(channel ids, message ids)` unless the save is set to
+    /// fail, and the ids of messages marked as read are recorded.
+    #[derive(Default)]
+    struct OfflineRepository {
+        last_message_id: AtomicI64,
+        failing_saves: AtomicUsize,
+        saved_messages: Mutex<Vec<(Vec<i64>, Vec<i64>)>>,
+        unread_messages: Vec<UnreadChatMessage>,
+        read_message_ids: Mutex<Vec<i64>>,
+    }
+
+    fn offline() -> DbErr {
+        DbErr::Custom("offline".to_owned())
+    }
+
+    #[async_trait]
+    impl UsersRepository for OfflineRepository {
+        async fn get_user(
+            &self,
+            _user_id: Option<i32>,
+            _username: Option<&str>,
+            _username_unicode: Option<&str>,
+        ) -> Result<users::Model, GetUserError> {
+            Err(GetUserError::UserNotExists)
+        }
+
+        async fn get_user_by_id(
+            &self,
+            _user_id: i32,
+        ) -> Result<users::Model, GetUserError> {
+            Err(GetUserError::UserNotExists)
+        }
+
+        async fn get_user_by_username(
+            &self,
+            _username: &str,
+        ) -> Result<users::Model, GetUserError> {
+            Err(GetUserError::UserNotExists)
+        }
+
+        async fn get_user_by_username_unicode(
+            &self,
+            _username_unicode: &str,
+        ) -> Result<users::Model, GetUserError> {
+            Err(GetUserError::UserNotExists)
+        }
+
+        async fn get_user_by_email(
+            &self,
+            _email: &Email,
+        ) -> Result<users::Model, GetUserError> {
+            Err(GetUserError::UserNotExists)
+        }
+
+        async fn create_user(
+            &self,
+            _creat_user: CreateUser,
+        ) -> Result<InsertResult<users::ActiveModel>, DbErr> {
+            Err(offline())
+        }
+
+        async fn change_user_password(
+            &self,
+            _user_id: Option<i32>,
+            _username: Option<UsernameSafe>,
+            _username_unicode: Option<UsernameSafe>,
+            _password: String,
+        ) -> Result<InsertResult<users::ActiveModel>, DbErr> {
+            Err(offline())
+        }
+
+        async fn get_user_ids_after(
+            &self,
+            _after_user_id: i32,
+            _limit: u64,
+        ) -> Result<Vec<i32>, DbErr> {
+            Err(offline())
+        }
+
+        async fn get_user_privilege_level(
+            &self,
+            _user_id: i32,
+        ) -> Result<i32, DbErr> {
+            Err(offline())
+        }
+
+        async fn get_user_privilege_name(
+            &self,
+            _user_id: i32,
+        ) -> Result<Option<String>, DbErr> {
+            Err(offline())
+        }
+
+        async fn is_friend(
+            &self,
+            _user_id: i32,
+            _friend_id: i32,
+        ) -> Result<bool, DbErr> {
+            Ok(false)
+        }
+
+        async fn block_user(
+            &self,
+            _user_id: i32,
+            _block_id: i32,
+        ) -> Result<bool, DbErr> {
+            Err(offline())
+        }
+
+        async fn unblock_user(
+            &self,
+            _user_id: i32,
+            _block_id: i32,
+        ) -> Result<bool, DbErr> {
+            Err(offline())
+        }
+
+        async fn is_blocked(
+            &self,
+            _user_id: i32,
+            _block_id: i32,
+        ) -> Result<bool, DbErr> {
+            Ok(false)
+        }
+
+        async fn get_blocked_user_ids(
+            &self,
+            _user_id: i32,
+        ) -> Result<Vec<i32>, DbErr> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[async_trait]
+    impl ChatRepository for OfflineRepository {
+        async fn get_public_channels(
+            &self,
+        ) -> Result<Vec<channels::Model>, DbErr> {
+            Err(offline())
+        }
+
+        async fn get_channel_privileges(
+            &self,
+        ) -> Result<Vec<ChannelPrivilege>, DbErr> {
+            Err(offline())
+        }
+
+        async fn create_public_channel(
+            &self,
+            _channel: CreatePublicChannel,
+        ) -> Result<channels::Model, DbErr> {
+            Err(offline())
+        }
+
+        async fn update_channel(
+            &self,
+            _channel_id: i64,
+            _update: UpdateChannel,
+        ) -> Result<Option<channels::Model>, DbErr> {
+            Err(offline())
+        }
+
+        async fn delete_channel(
+            &self,
+            _channel_id: i64,
+        ) -> Result<bool, DbErr> {
+            Err(offline())
+        }
+
+        async fn create_private_channel(
+            &self,
+            _channel_id: i64,
+            _owner_id: i32,
+            _target_user_id: i32,
+        ) -> Result<channels::Model, DbErr> {
+            Err(offline())
+        }
+
+        async fn create_group_channel(
+            &self,
+            _channel: CreateGroupChannel,
+        ) -> Result<channels::Model, DbErr> {
+            Err(offline())
+        }
+
+        async fn get_channel_with_members(
+            &self,
+            _channel_id: i64,
+        ) -> Result<Option<ChannelWithMembers>, DbErr> {
+            Ok(None)
+        }
+
+        async fn add_channel_user(
+            &self,
+            _channel_id: i64,
+            _user_id: i32,
+        ) -> Result<bool, DbErr> {
+            Err(offline())
+        }
+
+        async fn remove_channel_user(
+            &self,
+            _channel_id: i64,
+            _user_id: i32,
+        ) -> Result<bool, DbErr> {
+            Err(offline())
+        }
+
+        async fn set_channel_owner(
+            &self,
+            _channel_id: i64,
+            _owner_id: i32,
+        ) -> Result<(), DbErr> {
+            Err(offline())
+        }
+
+        async fn archive_channel(
+            &self,
+            _channel_id: i64,
+        ) -> Result<bool, DbErr> {
+            Err(offline())
+        }
+
+        async fn reserve_message_ids(
+            &self,
+            count: u64,
+        ) -> Result<Vec<i64>, DbErr> {
+            let last =
+                self.last_message_id.fetch_add(count as i64, Ordering::SeqCst);
+            Ok((last + 1..=last + count as i64).collect())
+        }
+
+        async fn save_messages(
+            &self,
+            channels: Vec<CreateChatChannel>,
+            messages: Vec<CreateChatMessage>,
+        ) -> Result<(), DbErr> {
+            if self
+                .failing_saves
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                    n.checked_sub(1)
+                })
+                .is_ok()
+            {
+                return Err(offline());
+            }
+
+            self.saved_messages.lock().unwrap().push((
+                channels.iter().map(|ch| ch.id).collect(),
+                messages.iter().map(|msg| msg.id).collect(),
+            ));
+
+            Ok(())
+        }
+
+        async fn get_unread_messages(
+            &self,
+            _user_id: i32,
+        ) -> Result<Vec<UnreadChatMessage>, DbErr> {
+            Ok(self.unread_messages.clone())
+        }
+
+        async fn mark_messages_read(
+            &self,
+            _user_id: i32,
+            _sender_id: Option<i32>,
+        ) -> Result<u64, DbErr> {
+            Ok(0)
+        }
+
+        async fn mark_messages_read_by_ids(
+            &self,
+            _user_id: i32,
+            message_ids: Vec<i64>,
+        ) -> Result<u64, DbErr> {
+            let count = message_ids.len() as u64;
+            self.read_message_ids.lock().unwrap().extend(message_ids);
+            Ok(count)
+        }
+
+        async fn get_channel_messages(
+            &self,
+            _channel_id: i64,
+            _before_message_id: Option<i64>,
+            _limit: u64,
+        ) -> Result<Vec<chat_messages::Model>, DbErr> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn chat_service() -> ChatServiceImpl {
+        ChatServiceImpl::new(
+            Arc::new(OfflineRepository::default()),
+            Arc::new(OfflineRepository::default()),
+            ChatFilters::default(),
+        )
+    }
+
+    async fn login(
+        service: &ChatServiceImpl,
+        platforms: Platform,
+    ) -> Arc<ChatSession> {
+        service
+            .login_inner(USER_ID, "test".to_owned(), None, 1, platforms)
+            .await
+            .unwrap()
+    }
+
+    /// Ids of the packets queued for the bancho client of the session.
+    async fn queued_packet_ids(session: &ChatSession) -> Vec<PacketId> {
+        let packets = session
+            .extends
+            .bancho_ext
+            .load()
+            .as_ref()
+            .unwrap()
+            .packets_queue
+            .dequeue_all_packets(None)
+            .await;
+
+        PacketReader::new(&packets).map(|packet| packet.id).collect()
+    }
+
+    fn user_query() -> Option<pb_bancho_state::RawUserQuery> {
+        Some(UserQuery::UserId(USER_ID).into())
+    }
+
+    fn channel_name(name: &str) -> Option<pb_chat::RawChannelQuery> {
+        Some(ChannelQuery::ChannelName(name.to_owned()).into())
+    }
+
+    #[tokio::test]
+    async fn send_message_to_unknown_channel() {
+        let service = chat_service();
+        let session = login(&service, Platform::Bancho).await;
+        queued_packet_ids(&session).await;
+
+        let res = service
+            .send_message(SendMessageRequest {
+                sender: user_query(),
+                message: "hello".to_owned(),
+                target: Some(
+                    ChatMessageTarget::Channel(ChannelQuery::ChannelName(
+                        "#unknown".to_owned(),
+                    ))
+                    .into(),
+                ),
+            })
+            .await;
+
+        assert!(matches!(res, Err(ChatError::ChannelNotExists)));
+        assert_eq!(
+            queued_packet_ids(&session).await,
+            [PacketId::BANCHO_CHANNEL_KICK, PacketId::BANCHO_NOTIFICATION]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_message_to_spectator_without_spectating() {
+        let service = chat_service();
+        login(&service, Platform::Bancho).await;
+
+        let res = service
+            .send_message(SendMessageRequest {
+                sender: user_query(),
+                message: "hello".to_owned(),
+                target: Some(
+                    ChatMessageTarget::Channel(ChannelQuery::ChannelName(
+                        "#spectator".to_owned(),
+                    ))
+                    .into(),
+                ),
+            })
+            .await;
+
+        assert!(matches!(res, Err(ChatError::ChannelNotExists)));
+    }
+
+    #[tokio::test]
+    async fn rejected_message_is_not_counted_as_repeat() {
+        let service = ChatServiceImpl::new(
+            Arc::new(OfflineRepository::default()),
+            Arc::new(OfflineRepository::default()),
+            ChatFilters::new(pb_chat::ChatFilterRules {
+                max_repeated_messages: 1,
+                repeat_window_secs: 60,
+                ..Default::default()
+            }),
+        );
+        let session = login(&service, Platform::Bancho).await;
+
+        for _ in 0..2 {
+            let res = service
+                .send_message(SendMessageRequest {
+                    sender: user_query(),
+                    message: "hello".to_owned(),
+                    target: Some(
+                        ChatMessageTarget::Channel(ChannelQuery::ChannelName(
+                            "#unknown".to_owned(),
+                        ))
+                        .into(),
+                    ),
+                })
+                .await;
+
+            assert!(matches!(res, Err(ChatError::ChannelNotExists)));
+        }
+
+        assert_eq!(session.extends.recent_message.lock().await.count, 0);
+    }
+
+    #[tokio::test]
+    async fn join_unknown_channel() {
+        let service = chat_service();
+        let session = login(&service, Platform::Bancho).await;
+        queued_packet_ids(&session).await;
+
+        let res = service
+            .join_channel(JoinChannelRequest {
+                channel_query: channel_name("#unknown"),
+                user_query: user_query(),
+            })
+            .await;
+
+        assert!(matches!(res, Err(ChatError::ChannelNotExists)));
+        assert_eq!(
+            queued_packet_ids(&session).await,
+            [PacketId::BANCHO_CHANNEL_KICK, PacketId::BANCHO_NOTIFICATION]
+        );
+        assert_eq!(session.extends.channel_count.val(), 0);
+    }
+
+    #[tokio::test]
+    async fn join_channel_without_permission() {
+        let service = chat_service();
+
+        let channel = Channel::new(
+            0,
+            "#staff".to_owned(),
+            ChannelType::Public,
+            None,
+            None,
+        );
+        let mut privileges = ChannelPrivileges::default();
+        privileges.set(ChannelHandle::Join, 100);
+        channel.privileges.set(privileges.into());
+        service.channels.create_channel(channel, false).await;
+
+        let session = login(&service, Platform::Bancho).await;
+        queued_packet_ids(&session).await;
+
+        let res = service
+            .join_channel(JoinChannelRequest {
+                channel_query: channel_name("#staff"),
+                user_query: user_query(),
+            })
+            .await;
+
+        assert!(matches!(res, Err(ChatError::PermissionDenied)));
+        assert_eq!(
+            queued_packet_ids(&session).await,
+            [PacketId::BANCHO_CHANNEL_KICK, PacketId::BANCHO_NOTIFICATION]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_message_to_user_only_accepting_friends() {
+        let service = chat_service();
+        let sender = login(&service, Platform::Bancho).await;
+        queued_packet_ids(&sender).await;
+
+        let target = service
+            .login_inner(
+                USER_ID + 1,
+                "friends_only".to_owned(),
+                None,
+                1,
+                Platform::Bancho,
+            )
+            .await
+            .unwrap();
+        target.extends.only_friend_pm_allowed.set(true);
+
+        let res = service
+            .send_message(SendMessageRequest {
+                sender: user_query(),
+                message: "hello".to_owned(),
+                target: Some(
+                    ChatMessageTarget::User(UserQuery::UserId(USER_ID + 1))
+                        .into(),
+                ),
+            })
+            .await;
+
+        assert!(matches!(res, Err(ChatError::DmBlocked)));
+        assert_eq!(
+            queued_packet_ids(&sender).await,
+            [PacketId::BANCHO_USER_DM_BLOCKED]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_message_to_user_blocking_sender() {
+        let service = chat_service();
+        let sender = login(&service, Platform::Bancho).await;
+        queued_packet_ids(&sender).await;
+
+        let target = service
+            .login_inner(
+                USER_ID + 1,
+                "blocker".to_owned(),
+                None,
+                1,
+                Platform::Bancho,
+            )
+            .await
+            .unwrap();
+        target.extends.set_blocked(USER_ID, true);
+        queued_packet_ids(&target).await;
+
+        let res = service
+            .send_message(SendMessageRequest {
+                sender: user_query(),
+                message: "hello".to_owned(),
+                target: Some(
+                    ChatMessageTarget::User(UserQuery::UserId(USER_ID + 1))
+                        .into(),
+                ),
+            })
+            .await;
+
+        // the sender is not told about the block
+        assert_eq!(res.unwrap().message_id, 0);
+        assert!(queued_packet_ids(&sender).await.is_empty());
+        assert!(queued_packet_ids(&target).await.is_empty());
+        assert!(service
+            .message_writer
+            .pending
+            .lock()
+            .await
+            .messages
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn leave_and_join_group_channel_again() {
+        let service = chat_service();
+
+        let channel = Channel::new(
+            1 << 59,
+            "#group_1".to_owned(),
+            ChannelType::Group,
+            None,
+            Some(vec![USER_ID]),
+        );
+        service.channels.create_channel(channel, false).await;
+
+        let session = login(&service, Platform::Bancho).await;
+
+        let join = || {
+            service.join_channel(JoinChannelRequest {
+                channel_query: channel_name("#group_1"),
+                user_query: user_query(),
+            })
+        };
+
+        join().await.unwrap();
+        assert_eq!(session.extends.channel_count.val(), 1);
+
+        service
+            .leave_channel(LeaveChannelRequest {
+                channel_query: channel_name("#group_1"),
+                user_query: user_query(),
+            })
+            .await
+            .unwrap();
+
+        // the user stays a member of the group channel
+        assert_eq!(session.extends.channel_count.val(), 0);
+        join().await.unwrap();
+        assert_eq!(session.extends.channel_count.val(), 1);
+    }
+
+    #[tokio::test]
+    async fn leave_unknown_channel() {
+        let service = chat_service();
+        login(&service, Platform::Bancho).await;
+
+        let res = service
+            .leave_channel(LeaveChannelRequest {
+                channel_query: channel_name("#unknown"),
+                user_query: user_query(),
+            })
+            .await;
+
+        assert!(matches!(res, Err(ChatError::ChannelNotExists)));
+    }
+
+    #[tokio::test]
+    async fn message_ids_are_reserved_in_blocks() {
+        // rows written by another writer of the table
+        let repository = Arc::new(OfflineRepository {
+            last_message_id: AtomicI64::new(41),
+            ..Default::default()
+        });
+        let writer = ChatMessageWriter::new(repository.clone());
+        let channel = private_channel_row(USER_ID, 1001);
+
+        let mut ids = Vec::new();
+        for _ in 0..MESSAGE_ID_BLOCK_SIZE + 1 {
+            ids.push(
+                writer
+                    .push(
+                        USER_ID,
+                        channel.clone(),
+                        "hi".to_owned(),
+                        false,
+                        None,
+                    )
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        assert_eq!(ids.first(), Some(&42));
+        assert_eq!(ids.last(), Some(&(42 + MESSAGE_ID_BLOCK_SIZE as i64)));
+        assert_eq!(
+            repository.last_message_id.load(Ordering::SeqCst),
+            41 + 2 * MESSAGE_ID_BLOCK_SIZE as i64
+        );
+    }
+
+    #[tokio::test]
+    async fn flush_puts_failed_batch_back() {
+        let repository = Arc::new(OfflineRepository {
+            failing_saves: AtomicUsize::new(1),
+            ..Default::default()
+        });
+        let writer = ChatMessageWriter::new(repository.clone());
+
+        let first = private_channel_row(USER_ID, 1001);
+        let second = private_channel_row(USER_ID, 1002);
+        let (first_id, second_id) = (first.id, second.id);
+
+        for channel in [first.clone(), first, second] {
+            writer
+                .push(USER_ID, channel, "hi".to_owned(), false, None)
+                .await
+                .unwrap();
+        }
+
+        assert!(writer.flush(2).await.is_err());
+        assert!(repository.saved_messages.lock().unwrap().is_empty());
+
+        // The second channel's row stays queued with its message
+        assert_eq!(writer.flush(2).await.unwrap(), 3);
+        assert_eq!(
+            *repository.saved_messages.lock().unwrap(),
+            vec![(vec![first_id], vec![1, 2]), (vec![second_id], vec![3])]
+        );
+    }
+
+    #[tokio::test]
+    async fn deliver_unread_messages_marks_delivered_ones_read() {
+        let unread_message = |id| UnreadChatMessage {
+            id,
+            sender_id: 1001,
+            sender_name: "sender".to_owned(),
+            timestamp: Utc::now().into(),
+            content_string: "hi".to_owned(),
+            is_action: false,
+        };
+        let repository = Arc::new(OfflineRepository {
+            unread_messages: vec![unread_message(7), unread_message(9)],
+            ..Default::default()
+        });
+        let service = ChatServiceImpl::new(
+            Arc::new(OfflineRepository::default()),
+            repository.clone(),
+            ChatFilters::default(),
+        );
+        let session = login(&service, Platform::Bancho).await;
+        queued_packet_ids(&session).await;
+        repository.read_message_ids.lock().unwrap().clear();
+
+        assert_eq!(service.deliver_unread_messages(&session).await.unwrap(), 2);
+        assert_eq!(*repository.read_message_ids.lock().unwrap(), vec![7, 9]);
+        assert_eq!(
+            queued_packet_ids(&session).await,
+            vec![PacketId::BANCHO_SEND_MESSAGE, PacketId::BANCHO_SEND_MESSAGE]
+        );
+    }
+
+    #[tokio::test]
+    async fn dequeue_packets_without_bancho() {
+        let service = chat_service();
+        login(&service, Platform::Lazer).await;
+
+        let res =
+            service.dequeue_chat_packets(UserQuery::UserId(USER_ID)).await;
+
+        assert!(matches!(res, Err(ChatError::NotBanchoSession)));
+    }
+
+    #[test]
+    fn errors_through_tonic_status() {
+        for err in [
+            ChatError::ChannelNotExists,
+            ChatError::PermissionDenied,
+            ChatError::NotBanchoSession,
+        ] {
+            let expected = err.to_string();
+            let status = tonic::Status::from(err);
+
+            assert_eq!(ChatError::from(status).to_string(), expected);
+        }
+    }
+}
+
+mod chat_filters {
+    use crate::{
+        action_content, filter_words, is_allowed_host, link_hosts,
+        rewrite_links, ChatError, ChatFilters, ChatSession,
+    };
+    use pb_chat::{ChatFilterRules, WordFilter};
+
+    #[test]
+    fn replace_and_block_words() {
+        let filters = [
+            WordFilter {
+                word: "heck".to_owned(),
+                replacement: Some("****".to_owned()),
+            },
+            WordFilter { word: "spam".to_owned(), replacement: None },
+        ];
+
+        assert_eq!(
+            filter_words(&filters, "What the HECK, heck!".to_owned()).unwrap(),
+            "What the ****, ****!"
+        );
+        assert_eq!(
+            filter_words(&filters, "héllo heck".to_owned()).unwrap(),
+            "héllo ****"
+        );
+        assert!(matches!(
+            filter_words(&filters, "buy Spam now".to_owned()),
+            Err(ChatError::MessageRejected(_))
+        ));
+    }
+
+    #[test]
+    fn rewrite_markdown_and_www_links() {
+        assert_eq!(
+            rewrite_links("see [the wiki](https://osu.ppy.sh/wiki) now"),
+            "see [https://osu.ppy.sh/wiki the wiki] now"
+        );
+        assert_eq!(
+            rewrite_links("[www.example.com my site]"),
+            "[https://www.example.com my site]"
+        );
+        assert_eq!(
+            rewrite_links("[https://osu.ppy.sh osu!] [not a link] [x](y)"),
+            "[https://osu.ppy.sh osu!] [not a link] [x](y)"
+        );
+        assert_eq!(rewrite_links("[[[ ]"), "[[[ ]");
+    }
+
+    #[test]
+    fn allow_listed_link_hosts() {
+        let allowed = vec!["ppy.sh".to_owned()];
+        let hosts = link_hosts(
+            "[https://osu.ppy.sh/b/1 map] http://evil.com:80/x notppy.sh",
+        )
+        .collect::<Vec<_>>();
+
+        assert_eq!(hosts, ["osu.ppy.sh", "evil.com"]);
+        assert!(is_allowed_host(&allowed, "osu.ppy.sh"));
+        assert!(is_allowed_host(&allowed, "PPY.SH"));
+        assert!(!is_allowed_host(&allowed, "evil.com"));
+        assert!(!is_allowed_host(&allowed, "notppy.sh"));
+    }
+
+    #[tokio::test]
+    async fn filter_chain() {
+        let filters = ChatFilters::new(ChatFilterRules {
+            link_allow_list: true,
+            allowed_link_hosts: vec!["ppy.sh".to_owned()],
+            max_message_length: 16,
+            max_repeated_messages: 2,
+            repeat_window_secs: 60,
+            rewrite_links: true,
+            ..Default::default()
+        });
+        let sender = ChatSession::default();
+
+        assert!(matches!(
+            filters.filter("a message longer than 16", None).await,
+            Err(ChatError::MessageRejected(_))
+        ));
+        assert!(matches!(
+            filters.filter("[www.evil.com x]", None).await,
+            Err(ChatError::MessageRejected(_))
+        ));
+
+        for _ in 0..2 {
+            assert_eq!(
+                filters.filter(" hi ", Some(&sender)).await.unwrap(),
+                "hi"
+            );
+        }
+        assert!(matches!(
+            filters.filter("hi", Some(&sender)).await,
+            Err(ChatError::MessageRejected(_))
+        ));
+        assert!(filters.filter("hello", Some(&sender)).await.is_ok());
+
+        // rules are replaced at runtime
+        filters.replace(ChatFilterRules::default()).await;
+        assert!(filters.filter("[www.evil.com x]", None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn action_message_through_filter_chain() {
+        let filters = ChatFilters::new(ChatFilterRules {
+            max_message_length: 32,
+            rewrite_links: true,
+            ..Default::default()
+        });
+
+        let message = filters
+            .filter("\x01ACTION waves\x07 hello \x01", None)
+            .await
+            .unwrap();
+
+        assert_eq!(message, "\x01ACTION waves hello\x01");
+        assert_eq!(action_content(&message), "waves hello");
+        assert!(matches!(
+            filters.filter("\x01ACTION  \x01", None).await,
+            Err(ChatError::MessageRejected(_))
+        ));
+    }
+}
@@ -163,6 +163,13 @@ impl EventsServiceImpl {
     }
 }
 
+impl Default for EventsServiceImpl {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl EventsService for EventsServiceImpl {
     async fn create_subscription(
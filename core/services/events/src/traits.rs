@@ -15,11 +15,13 @@ pub trait EventsService {
         buffer_client: usize,
     ) -> Result<SubscriptionWithOutputStream<Event>, EventsError>;
 
+    #[allow(clippy::ptr_arg)]
     async fn remove_subscription(
         &self,
         subscriber_key: &String,
     ) -> Result<Option<Arc<Subscription<Event>>>, EventsError>;
 
+    #[allow(clippy::ptr_arg)]
     async fn publish(
         &self,
         subscriber_key: &String,
@@ -34,5 +34,11 @@ use super::routes::{bancho, debug};
 pub struct BanchoEndpointsDocs;
 
 #[derive(OpenApi)]
-#[openapi(paths(debug::test, debug::get_all_sessions,))]
+#[openapi(paths(
+    debug::test,
+    debug::get_all_sessions,
+    debug::get_error_reports,
+    debug::get_menu_content,
+    debug::update_menu_content
+))]
 pub struct BanchoDebugEndpointsDocs;
@@ -2,13 +2,14 @@ use super::{parser, BanchoHttpError};
 use axum::{
     async_trait,
     body::Bytes,
-    extract::{FromRequest, FromRequestParts},
+    extract::{FromRequest, FromRequestParts, Multipart},
     headers::HeaderName,
     http::{request::Parts, Request},
 };
 use derive_deref::Deref;
 use hyper::header::USER_AGENT;
 use pb_bancho::LoginRequest;
+use serde::de::DeserializeOwned;
 
 pub static OSU_USER_AGENT: HeaderName = HeaderName::from_static("osu!");
 pub static OSU_VERSION: HeaderName = HeaderName::from_static("osu-version");
@@ -137,3 +138,87 @@ where
         ))
     }
 }
+
+/// Deserializes the text fields of a `multipart/form-data` body (sent by the
+/// osu! client to some `/web` endpoints), file fields are skipped.
+#[derive(Debug, Deref)]
+pub struct MultipartForm<T>(pub T);
+
+#[async_trait]
+impl<S, B, T> FromRequest<S, B> for MultipartForm<T>
+where
+    Multipart: FromRequest<S, B>,
+    T: DeserializeOwned,
+    B: Send + 'static,
+    S: Send + Sync,
+{
+    type Rejection = BanchoHttpError;
+
+    async fn from_request(
+        req: Request<B>,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let mut multipart = Multipart::from_request(req, state)
+            .await
+            .map_err(|_| BanchoHttpError::ParseRequestError)?;
+
+        let mut fields = Vec::new();
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(|_| BanchoHttpError::ParseRequestError)?
+        {
+            if field.file_name().is_some() {
+                continue;
+            }
+
+            let Some(name) = field.name().map(|name| name.to_owned()) else {
+                continue;
+            };
+
+            fields.push((
+                name,
+                field
+                    .text()
+                    .await
+                    .map_err(|_| BanchoHttpError::ParseRequestError)?,
+            ));
+        }
+
+        // Re-encode the fields so that values are parsed like query strings
+        serde_urlencoded::to_string(&fields)
+            .ok()
+            .and_then(|encoded| serde_urlencoded::from_str(&encoded).ok())
+            .map(Self)
+            .ok_or(BanchoHttpError::ParseRequestError)
+    }
+}
+
+/// Deserializes a JSON body, the osu! client does not always send a
+/// `Content-Type` header, so unlike `axum::Json` it is not checked.
+#[derive(Debug, Deref)]
+pub struct RawJson<T>(pub T);
+
+#[async_trait]
+impl<S, B, T> FromRequest<S, B> for RawJson<T>
+where
+    Bytes: FromRequest<S, B>,
+    T: DeserializeOwned,
+    B: Send + 'static,
+    S: Send + Sync,
+{
+    type Rejection = BanchoHttpError;
+
+    async fn from_request(
+        req: Request<B>,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|_| BanchoHttpError::ParseRequestError)?;
+
+        serde_json::from_slice(&body)
+            .map(Self)
+            .map_err(|_| BanchoHttpError::ParseRequestError)
+    }
+}
@@ -1,6 +1,7 @@
 pub mod docs;
 pub mod error;
 pub mod extractors;
+pub mod params;
 pub mod parser;
 pub mod routes;
 pub mod services;
@@ -0,0 +1,443 @@
+use domain_bancho::{Mods, OsuDirectRankStatus};
+use pb_bancho::{
+    ClientRegisterRequest, DifficultyRatingRequest,
+    GetErrorReportGroupsRequest, OsuAddFavouriteRequest,
+    OsuCheckUpdatesRequest, OsuCommentRequest, OsuErrorRequest,
+    OsuGetBeatmapInfoRequest, OsuGetFavouritesRequest, OsuMarkAsReadRequest,
+    OsuRateRequest, OsuSearchRequest, OsuSearchSetRequest,
+};
+
+#[inline]
+fn all_modes() -> i32 {
+    -1
+}
+
+#[inline]
+fn all_rank_statuses() -> i32 {
+    OsuDirectRankStatus::All.val()
+}
+
+/// Empty or malformed values are treated as missing.
+#[inline]
+fn parse_optional<T: std::str::FromStr>(s: Option<String>) -> Option<T> {
+    s.and_then(|s| s.trim().parse().ok())
+}
+
+/// The client sends `True` / `False`.
+#[inline]
+fn parse_client_bool(s: Option<String>) -> bool {
+    s.map(|s| s.eq_ignore_ascii_case("true") || s == "1").unwrap_or_default()
+}
+
+/// Query of `/web/osu-search.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuSearchParams {
+    #[serde(rename = "u")]
+    pub username: String,
+    #[serde(rename = "h")]
+    pub password: String,
+    /// Searches beatmapsets of any status if missing, like the client's
+    /// "All" filter.
+    #[serde(rename = "r", default = "all_rank_statuses")]
+    pub ranked_status: i32,
+    #[serde(rename = "q", default)]
+    pub query: String,
+    #[serde(rename = "m", default = "all_modes")]
+    pub mode: i32,
+    #[serde(rename = "p", default)]
+    pub page: i32,
+}
+
+impl From<OsuSearchParams> for OsuSearchRequest {
+    fn from(params: OsuSearchParams) -> Self {
+        let OsuSearchParams {
+            username,
+            password,
+            ranked_status,
+            query,
+            mode,
+            page,
+        } = params;
+
+        Self { username, password, ranked_status, query, mode, page }
+    }
+}
+
+/// Query of `/web/osu-search-set.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuSearchSetParams {
+    #[serde(rename = "u")]
+    pub username: String,
+    #[serde(rename = "h")]
+    pub password: String,
+    #[serde(rename = "s")]
+    pub beatmapset_id: Option<i32>,
+    #[serde(rename = "b")]
+    pub beatmap_id: Option<i32>,
+    #[serde(rename = "c")]
+    pub beatmap_md5: Option<String>,
+}
+
+impl From<OsuSearchSetParams> for OsuSearchSetRequest {
+    fn from(params: OsuSearchSetParams) -> Self {
+        let OsuSearchSetParams {
+            username,
+            password,
+            beatmapset_id,
+            beatmap_id,
+            beatmap_md5,
+        } = params;
+
+        Self { username, password, beatmapset_id, beatmap_id, beatmap_md5 }
+    }
+}
+
+/// Query of `/web/osu-getbeatmapinfo.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuGetBeatmapInfoParams {
+    #[serde(rename = "u")]
+    pub username: String,
+    #[serde(rename = "h")]
+    pub password: String,
+}
+
+/// JSON body of `/web/osu-getbeatmapinfo.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuGetBeatmapInfoForm {
+    #[serde(rename = "Filenames", default)]
+    pub filenames: Vec<String>,
+    #[serde(rename = "Ids", default)]
+    pub beatmap_ids: Vec<i32>,
+}
+
+impl OsuGetBeatmapInfoParams {
+    #[inline]
+    pub fn into_request(
+        self,
+        form: OsuGetBeatmapInfoForm,
+    ) -> OsuGetBeatmapInfoRequest {
+        let Self { username, password } = self;
+        let OsuGetBeatmapInfoForm { filenames, beatmap_ids } = form;
+
+        OsuGetBeatmapInfoRequest { username, password, filenames, beatmap_ids }
+    }
+}
+
+/// Query of `/web/osu-getfavourites.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuGetFavouritesParams {
+    #[serde(rename = "u")]
+    pub username: String,
+    #[serde(rename = "h")]
+    pub password: String,
+}
+
+impl From<OsuGetFavouritesParams> for OsuGetFavouritesRequest {
+    fn from(params: OsuGetFavouritesParams) -> Self {
+        let OsuGetFavouritesParams { username, password } = params;
+
+        Self { username, password }
+    }
+}
+
+/// Query of `/web/osu-addfavourite.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuAddFavouriteParams {
+    #[serde(rename = "u")]
+    pub username: String,
+    #[serde(rename = "h")]
+    pub password: String,
+    #[serde(rename = "a")]
+    pub beatmapset_id: i32,
+}
+
+impl From<OsuAddFavouriteParams> for OsuAddFavouriteRequest {
+    fn from(params: OsuAddFavouriteParams) -> Self {
+        let OsuAddFavouriteParams { username, password, beatmapset_id } =
+            params;
+
+        Self { username, password, beatmapset_id }
+    }
+}
+
+/// Query of `/web/osu-rate.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuRateParams {
+    #[serde(rename = "u")]
+    pub username: String,
+    #[serde(rename = "p")]
+    pub password: String,
+    #[serde(rename = "c")]
+    pub beatmap_md5: String,
+    #[serde(rename = "v")]
+    pub vote: Option<i32>,
+}
+
+impl From<OsuRateParams> for OsuRateRequest {
+    fn from(params: OsuRateParams) -> Self {
+        let OsuRateParams { username, password, beatmap_md5, vote } = params;
+
+        Self { username, password, beatmap_md5, vote }
+    }
+}
+
+/// Query of `/web/osu-markasread.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuMarkAsReadParams {
+    #[serde(rename = "u")]
+    pub username: String,
+    #[serde(rename = "h")]
+    pub password: String,
+    pub channel: String,
+}
+
+impl From<OsuMarkAsReadParams> for OsuMarkAsReadRequest {
+    fn from(params: OsuMarkAsReadParams) -> Self {
+        let OsuMarkAsReadParams { username, password, channel } = params;
+
+        Self { username, password, channel }
+    }
+}
+
+/// Form of `/web/osu-comment.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuCommentParams {
+    #[serde(rename = "u")]
+    pub username: String,
+    #[serde(rename = "p")]
+    pub password: String,
+    #[serde(rename = "b", default)]
+    pub beatmap_id: i32,
+    #[serde(rename = "s", default)]
+    pub beatmapset_id: i32,
+    #[serde(rename = "r", default)]
+    pub score_id: i64,
+    #[serde(rename = "m", default)]
+    pub mode: i32,
+    #[serde(rename = "a")]
+    pub action: String,
+    pub target: Option<String>,
+    #[serde(rename = "f")]
+    pub colour: Option<String>,
+    #[serde(rename = "starttime")]
+    pub start_time: Option<i32>,
+    pub comment: Option<String>,
+}
+
+impl From<OsuCommentParams> for OsuCommentRequest {
+    fn from(params: OsuCommentParams) -> Self {
+        let OsuCommentParams {
+            username,
+            password,
+            beatmap_id,
+            beatmapset_id,
+            score_id,
+            mode,
+            action,
+            target,
+            colour,
+            start_time,
+            comment,
+        } = params;
+
+        Self {
+            username,
+            password,
+            beatmap_id,
+            beatmapset_id,
+            score_id,
+            mode,
+            action,
+            target,
+            colour,
+            start_time,
+            comment,
+        }
+    }
+}
+
+/// Form of `/users`
+#[derive(Debug, Deserialize)]
+pub struct ClientRegisterParams {
+    #[serde(rename = "user[username]")]
+    pub username: String,
+    #[serde(rename = "user[user_email]")]
+    pub email: String,
+    #[serde(rename = "user[password]")]
+    pub password: String,
+    #[serde(default)]
+    pub check: i32,
+}
+
+impl From<ClientRegisterParams> for ClientRegisterRequest {
+    fn from(params: ClientRegisterParams) -> Self {
+        let ClientRegisterParams { username, email, password, check } = params;
+
+        Self { username, email, password, check: check == 1 }
+    }
+}
+
+/// Form of `/web/osu-error.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuErrorParams {
+    #[serde(rename = "u")]
+    pub username: Option<String>,
+    #[serde(rename = "h")]
+    pub password: Option<String>,
+    #[serde(rename = "version", default)]
+    pub client_version: String,
+    #[serde(rename = "exehash")]
+    pub exe_hash: Option<String>,
+    #[serde(rename = "osumode")]
+    pub osu_mode: Option<String>,
+    #[serde(rename = "gamemode")]
+    pub game_mode: Option<String>,
+    #[serde(rename = "gametime")]
+    pub game_time: Option<String>,
+    #[serde(rename = "audiotime")]
+    pub audio_time: Option<String>,
+    pub culture: Option<String>,
+    pub beatmap_id: Option<String>,
+    #[serde(rename = "beatmap_checksum")]
+    pub beatmap_md5: Option<String>,
+    #[serde(default)]
+    pub exception: String,
+    #[serde(default)]
+    pub stacktrace: String,
+    pub feedback: Option<String>,
+    pub config: Option<String>,
+    pub soft: Option<String>,
+    pub beatmap_count: Option<String>,
+    pub compatibility: Option<String>,
+    pub ram: Option<String>,
+}
+
+impl From<OsuErrorParams> for OsuErrorRequest {
+    fn from(params: OsuErrorParams) -> Self {
+        let OsuErrorParams {
+            username,
+            password,
+            client_version,
+            exe_hash,
+            osu_mode,
+            game_mode,
+            game_time,
+            audio_time,
+            culture,
+            beatmap_id,
+            beatmap_md5,
+            exception,
+            stacktrace,
+            feedback,
+            config,
+            soft,
+            beatmap_count,
+            compatibility,
+            ram,
+        } = params;
+
+        Self {
+            username,
+            password,
+            client_version,
+            exe_hash,
+            osu_mode,
+            game_mode,
+            game_time: parse_optional(game_time),
+            audio_time: parse_optional(audio_time),
+            culture,
+            beatmap_id: parse_optional(beatmap_id),
+            beatmap_md5: beatmap_md5.filter(|md5| !md5.is_empty()),
+            exception,
+            stacktrace,
+            feedback: feedback.filter(|feedback| !feedback.is_empty()),
+            config,
+            soft: parse_client_bool(soft),
+            beatmap_count: parse_optional(beatmap_count),
+            compatibility: parse_client_bool(compatibility),
+            ram: parse_optional(ram),
+        }
+    }
+}
+
+/// Query of `/web/check-updates.php`
+#[derive(Debug, Deserialize)]
+pub struct OsuCheckUpdatesParams {
+    pub action: Option<String>,
+    pub stream: Option<String>,
+}
+
+impl From<OsuCheckUpdatesParams> for OsuCheckUpdatesRequest {
+    fn from(params: OsuCheckUpdatesParams) -> Self {
+        let OsuCheckUpdatesParams { action, stream } = params;
+
+        Self { action, stream }
+    }
+}
+
+/// JSON body of `/difficulty-rating`, sent by osu!lazer (osu-web)
+#[derive(Debug, Deserialize)]
+pub struct DifficultyRatingParams {
+    pub beatmap_id: i32,
+    pub ruleset_id: Option<i32>,
+    #[serde(default)]
+    pub mods: DifficultyRatingMods,
+}
+
+/// osu!lazer sends a list of mods, legacy bits are accepted as well.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum DifficultyRatingMods {
+    Bits(u32),
+    Acronyms(Vec<DifficultyRatingMod>),
+}
+
+impl Default for DifficultyRatingMods {
+    fn default() -> Self {
+        Self::Bits(0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DifficultyRatingMod {
+    pub acronym: String,
+}
+
+impl From<DifficultyRatingMods> for Mods {
+    fn from(mods: DifficultyRatingMods) -> Self {
+        match mods {
+            DifficultyRatingMods::Bits(bits) => Mods::from(bits),
+            // Mods without a legacy equivalent don't change the difficulty
+            DifficultyRatingMods::Acronyms(mods) => mods
+                .iter()
+                .filter_map(|m| Mods::from_acronym(&m.acronym))
+                .fold(Mods::NoMod, |acc, m| acc | m),
+        }
+    }
+}
+
+impl From<DifficultyRatingParams> for DifficultyRatingRequest {
+    fn from(params: DifficultyRatingParams) -> Self {
+        let DifficultyRatingParams { beatmap_id, ruleset_id, mods } = params;
+
+        Self { beatmap_id, ruleset_id, mods: Mods::from(mods).bits() }
+    }
+}
+
+/// Query of the `/error_reports` debug endpoint
+#[derive(Debug, Deserialize)]
+pub struct ErrorReportGroupsParams {
+    pub client_version: Option<String>,
+    #[serde(default)]
+    pub offset: u64,
+    #[serde(default)]
+    pub limit: u64,
+}
+
+impl From<ErrorReportGroupsParams> for GetErrorReportGroupsRequest {
+    fn from(params: ErrorReportGroupsParams) -> Self {
+        let ErrorReportGroupsParams { client_version, offset, limit } = params;
+
+        Self { client_version, offset, limit }
+    }
+}
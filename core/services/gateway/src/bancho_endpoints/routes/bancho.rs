@@ -1,8 +1,23 @@
 use crate::bancho_endpoints::{
-    extractors::{BanchoClientVersion, BanchoRequestBody, OsuTokenHeader},
+    extractors::{
+        BanchoClientVersion, BanchoRequestBody, MultipartForm, OsuTokenHeader,
+        RawJson,
+    },
+    params::{
+        ClientRegisterParams, DifficultyRatingParams, OsuAddFavouriteParams,
+        OsuCheckUpdatesParams, OsuCommentParams, OsuErrorParams,
+        OsuGetBeatmapInfoForm, OsuGetBeatmapInfoParams, OsuGetFavouritesParams,
+        OsuMarkAsReadParams, OsuRateParams, OsuSearchParams,
+        OsuSearchSetParams,
+    },
     BanchoHttpError, DynBanchoRoutingService,
 };
-use axum::{extract::Path, response::Response, routing::*, Extension, Router};
+use axum::{
+    extract::{Path, Query},
+    response::Response,
+    routing::*,
+    Extension, Router,
+};
 use peace_api::extractors::*;
 
 pub struct BanchoRouter;
@@ -18,11 +33,11 @@ impl BanchoRouter {
             .route("/d/:beatmapset_id", get(download_beatmapset))
             .route("/users", post(client_register))
             .route("/p/doyoureallywanttoaskpeppy", get(ask_peppy))
-            .route("/difficulty-rating", get(difficulty_rating))
+            .route("/difficulty-rating", post(difficulty_rating))
             .route("/web/osu-error.php", post(osu_error))
             .route("/web/osu-screenshot.php", post(osu_screenshot))
             .route("/web/osu-getfriends.php", get(osu_getfriends))
-            .route("/web/osu-getbeatmapinfo.php", get(osu_getbeatmapinfo))
+            .route("/web/osu-getbeatmapinfo.php", post(osu_getbeatmapinfo))
             .route("/web/osu-getfavourites.php", get(osu_getfavourites))
             .route("/web/osu-addfavourite.php", get(osu_addfavourite))
             .route("/web/lastfm.php", get(lastfm))
@@ -39,7 +54,7 @@ impl BanchoRouter {
             .route("/web/osu-markasread.php", get(osu_markasread))
             .route("/web/osu-getseasonal.php", get(osu_getseasonal))
             .route("/web/bancho_connect.php", get(bancho_connect))
-            .route("/web/check-updates", get(check_updates))
+            .route("/web/check-updates.php", get(check_updates))
             .route("/web/maps/:beatmap_file_name", get(update_beatmap))
             .layer(Extension(bancho_routing_service))
     }
@@ -121,8 +136,10 @@ pub async fn download_beatmapset(
 )]
 pub async fn client_register(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    ClientIp(ip): ClientIp,
+    MultipartForm(params): MultipartForm<ClientRegisterParams>,
 ) -> Response {
-    routing_service.client_register().await
+    routing_service.client_register(ip, params).await
 }
 
 /// Bancho ask_peppy
@@ -142,17 +159,20 @@ pub async fn ask_peppy(
 
 /// Bancho difficulty_rating
 #[utoipa::path(
-    get,
+    post,
     path = "/difficulty-rating",
     tag = "bancho",
     responses(
-        (status = 200, description = "Bancho difficulty_rating"),
+        (status = 200, description = "Star rating of the beatmap", body = f64),
+        (status = 400, description = "Invalid ruleset or conversion"),
+        (status = 404, description = "Beatmap or beatmap file not found"),
     )
 )]
 pub async fn difficulty_rating(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    RawJson(params): RawJson<DifficultyRatingParams>,
 ) -> Response {
-    routing_service.difficulty_rating().await
+    routing_service.difficulty_rating(params).await
 }
 
 /// Bancho osu_error
@@ -166,8 +186,9 @@ pub async fn difficulty_rating(
 )]
 pub async fn osu_error(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    MultipartForm(params): MultipartForm<OsuErrorParams>,
 ) -> Response {
-    routing_service.osu_error().await
+    routing_service.osu_error(params).await
 }
 
 /// Bancho osu_screenshot
@@ -202,7 +223,7 @@ pub async fn osu_getfriends(
 
 /// Bancho osu_getbeatmapinfo
 #[utoipa::path(
-    get,
+    post,
     path = "/web/osu-getbeatmapinfo.php",
     tag = "bancho",
     responses(
@@ -211,8 +232,10 @@ pub async fn osu_getfriends(
 )]
 pub async fn osu_getbeatmapinfo(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    Query(params): Query<OsuGetBeatmapInfoParams>,
+    RawJson(form): RawJson<OsuGetBeatmapInfoForm>,
 ) -> Response {
-    routing_service.osu_getbeatmapinfo().await
+    routing_service.osu_getbeatmapinfo(params, form).await
 }
 
 /// Bancho osu_getfavourites
@@ -226,8 +249,9 @@ pub async fn osu_getbeatmapinfo(
 )]
 pub async fn osu_getfavourites(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    Query(params): Query<OsuGetFavouritesParams>,
 ) -> Response {
-    routing_service.osu_getfavourites().await
+    routing_service.osu_getfavourites(params).await
 }
 
 /// Bancho osu_addfavourite
@@ -241,8 +265,9 @@ pub async fn osu_getfavourites(
 )]
 pub async fn osu_addfavourite(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    Query(params): Query<OsuAddFavouriteParams>,
 ) -> Response {
-    routing_service.osu_addfavourite().await
+    routing_service.osu_addfavourite(params).await
 }
 
 /// Bancho lastfm
@@ -271,8 +296,9 @@ pub async fn lastfm(
 )]
 pub async fn osu_search(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    Query(params): Query<OsuSearchParams>,
 ) -> Response {
-    routing_service.osu_search().await
+    routing_service.osu_search(params).await
 }
 
 /// Bancho osu_search_set
@@ -286,8 +312,9 @@ pub async fn osu_search(
 )]
 pub async fn osu_search_set(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    Query(params): Query<OsuSearchSetParams>,
 ) -> Response {
-    routing_service.osu_search_set().await
+    routing_service.osu_search_set(params).await
 }
 
 /// Bancho osu_submit_modular_selector
@@ -331,8 +358,9 @@ pub async fn osu_getreplay(
 )]
 pub async fn osu_rate(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    Query(params): Query<OsuRateParams>,
 ) -> Response {
-    routing_service.osu_rate().await
+    routing_service.osu_rate(params).await
 }
 
 /// Bancho osu_osz2_getscores
@@ -361,8 +389,9 @@ pub async fn osu_osz2_getscores(
 )]
 pub async fn osu_comment(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    MultipartForm(params): MultipartForm<OsuCommentParams>,
 ) -> Response {
-    routing_service.osu_comment().await
+    routing_service.osu_comment(params).await
 }
 
 /// Bancho osu_markasread
@@ -376,8 +405,9 @@ pub async fn osu_comment(
 )]
 pub async fn osu_markasread(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    Query(params): Query<OsuMarkAsReadParams>,
 ) -> Response {
-    routing_service.osu_markasread().await
+    routing_service.osu_markasread(params).await
 }
 
 /// Bancho osu_getseasonal
@@ -421,8 +451,9 @@ pub async fn bancho_connect(
 )]
 pub async fn check_updates(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    Query(params): Query<OsuCheckUpdatesParams>,
 ) -> Response {
-    routing_service.check_updates().await
+    routing_service.check_updates(params).await
 }
 
 /// Bancho update_beatmap
@@ -432,10 +463,12 @@ pub async fn check_updates(
     tag = "bancho",
     responses(
         (status = 200, description = "Bancho update_beatmap"),
+        (status = 404, description = "Beatmap or beatmap file not found"),
     )
 )]
 pub async fn update_beatmap(
     Extension(routing_service): Extension<DynBanchoRoutingService>,
+    Path(beatmap_file_name): Path<String>,
 ) -> Response {
-    routing_service.update_beatmap().await
+    routing_service.update_beatmap(beatmap_file_name).await
 }
@@ -1,9 +1,14 @@
+use crate::bancho_endpoints::params::ErrorReportGroupsParams;
 use axum::{
+    extract::Query,
+    http::StatusCode,
     response::{IntoResponse, Response},
     routing::*,
-    Extension, Router,
+    Extension, Json, Router,
 };
+use core_bancho::DynBanchoService;
 use core_bancho_state::DynBanchoStateService;
+use pb_bancho::{GetMenuContentRequest, MenuContent};
 use pb_bancho_state::UserData;
 use serde_json::{Map, Value};
 
@@ -11,11 +16,18 @@ pub struct BanchoDebugRouter;
 
 impl BanchoDebugRouter {
     pub fn new_router<T: Clone + Sync + Send + 'static>(
+        bancho_service: DynBanchoService,
         bancho_state_service: DynBanchoStateService,
     ) -> Router<T> {
         Router::new()
             .route("/test", get(test))
             .route("/get_all_sessions", get(get_all_sessions))
+            .route("/error_reports", get(get_error_reports))
+            .route(
+                "/menu_content",
+                get(get_menu_content).post(update_menu_content),
+            )
+            .layer(Extension(bancho_service))
             .layer(Extension(bancho_state_service))
     }
 }
@@ -82,3 +94,74 @@ pub async fn get_all_sessions(
                 .into_response()
         })
 }
+
+/// get client error reports grouped by exception signature and client version
+#[utoipa::path(
+    get,
+    path = "/error_reports",
+    tag = "bancho_debug",
+    params(
+        ("client_version" = Option<String>, Query, description = "only show reports of this client version"),
+        ("offset" = Option<u64>, Query, description = "offset of the groups"),
+        ("limit" = Option<u64>, Query, description = "max amount of groups"),
+    ),
+    responses(
+        (status = 200, description = "error report groups"),
+    )
+)]
+pub async fn get_error_reports(
+    Extension(bancho_service): Extension<DynBanchoService>,
+    Query(params): Query<ErrorReportGroupsParams>,
+) -> Response {
+    bancho_service
+        .get_error_report_groups(params.into())
+        .await
+        .map(|res| serde_json::to_string_pretty(&res).unwrap().into_response())
+        .unwrap_or_else(|err| {
+            (axum::http::StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+                .into_response()
+        })
+}
+
+/// get the seasonal backgrounds and the main menu icon
+#[utoipa::path(
+    get,
+    path = "/menu_content",
+    tag = "bancho_debug",
+    responses(
+        (status = 200, description = "current menu content"),
+    )
+)]
+pub async fn get_menu_content(
+    Extension(bancho_service): Extension<DynBanchoService>,
+) -> Response {
+    bancho_service
+        .get_menu_content(GetMenuContentRequest {})
+        .await
+        .map(|res| serde_json::to_string_pretty(&res).unwrap().into_response())
+        .unwrap_or_else(|err| {
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+        })
+}
+
+/// replace the seasonal backgrounds and the main menu icon without restarting
+#[utoipa::path(
+    post,
+    path = "/menu_content",
+    tag = "bancho_debug",
+    responses(
+        (status = 200, description = "previous menu content"),
+    )
+)]
+pub async fn update_menu_content(
+    Extension(bancho_service): Extension<DynBanchoService>,
+    Json(content): Json<MenuContent>,
+) -> Response {
+    bancho_service
+        .update_menu_content(content)
+        .await
+        .map(|res| serde_json::to_string_pretty(&res).unwrap().into_response())
+        .unwrap_or_else(|err| {
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+        })
+}
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use axum::response::{IntoResponse, Response};
 use bancho_packets::PacketBuilder;
 use bancho_packets::PacketReader;
-use core_bancho::DynBanchoService;
+use core_bancho::{BanchoServiceError, DynBanchoService};
 use core_bancho_state::{BanchoStateError, DynBanchoStateService};
 use core_chat::{ChatError, DynChatService};
 use domain_bancho::BanchoClientToken;
@@ -182,4 +182,117 @@ impl BanchoHandlerService for BanchoHandlerServiceImpl {
 
         Ok(is_valid)
     }
+
+    #[inline]
+    async fn client_register(
+        &self,
+        client_ip: IpAddr,
+        request: ClientRegisterRequest,
+    ) -> Result<ClientRegisterResponse, BanchoServiceError> {
+        self.bancho_service.client_register(client_ip, request).await
+    }
+
+    #[inline]
+    async fn osu_search(
+        &self,
+        request: OsuSearchRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_search(request).await
+    }
+
+    #[inline]
+    async fn osu_search_set(
+        &self,
+        request: OsuSearchSetRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_search_set(request).await
+    }
+
+    #[inline]
+    async fn osu_getbeatmapinfo(
+        &self,
+        request: OsuGetBeatmapInfoRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_getbeatmapinfo(request).await
+    }
+
+    #[inline]
+    async fn osu_getfavourites(
+        &self,
+        request: OsuGetFavouritesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_getfavourites(request).await
+    }
+
+    #[inline]
+    async fn osu_addfavourite(
+        &self,
+        request: OsuAddFavouriteRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_addfavourite(request).await
+    }
+
+    #[inline]
+    async fn osu_rate(
+        &self,
+        request: OsuRateRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_rate(request).await
+    }
+
+    #[inline]
+    async fn osu_comment(
+        &self,
+        request: OsuCommentRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_comment(request).await
+    }
+
+    #[inline]
+    async fn osu_mark_as_read(
+        &self,
+        request: OsuMarkAsReadRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_mark_as_read(request).await
+    }
+
+    #[inline]
+    async fn osu_error(
+        &self,
+        request: OsuErrorRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_error(request).await
+    }
+
+    #[inline]
+    async fn osu_getseasonal(
+        &self,
+        request: OsuGetSeasonalRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_get_seasonal(request).await
+    }
+
+    #[inline]
+    async fn update_beatmap(
+        &self,
+        request: OsuGetBeatmapFileRequest,
+    ) -> Result<BeatmapFile, BanchoServiceError> {
+        self.bancho_service.osu_get_beatmap_file(request).await
+    }
+
+    #[inline]
+    async fn check_updates(
+        &self,
+        request: OsuCheckUpdatesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError> {
+        self.bancho_service.osu_check_updates(request).await
+    }
+
+    #[inline]
+    async fn difficulty_rating(
+        &self,
+        request: DifficultyRatingRequest,
+    ) -> Result<DifficultyRating, BanchoServiceError> {
+        self.bancho_service.get_difficulty_rating(request).await
+    }
 }
@@ -3,10 +3,28 @@ use super::traits::{
 };
 use crate::bancho_endpoints::{
     extractors::{BanchoClientVersion, OsuTokenHeader},
+    params::{
+        ClientRegisterParams, DifficultyRatingParams, OsuAddFavouriteParams,
+        OsuCheckUpdatesParams, OsuCommentParams, OsuErrorParams,
+        OsuGetBeatmapInfoForm, OsuGetBeatmapInfoParams, OsuGetFavouritesParams,
+        OsuMarkAsReadParams, OsuRateParams, OsuSearchParams,
+        OsuSearchSetParams,
+    },
     BanchoHttpError,
 };
 use async_trait::async_trait;
-use axum::response::{IntoResponse, Response};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use core_bancho::BanchoServiceError;
+use pb_bancho::{
+    BeatmapFile, ClientRegisterResponse, DifficultyRating, HttpResponse,
+    OsuGetBeatmapFileRequest, OsuGetSeasonalRequest,
+};
+use peace_repositories::AddFavouriteBeatmapError;
+use serde_json::json;
 use std::{net::IpAddr, sync::Arc};
 
 pub struct BanchoRoutingServiceImpl {
@@ -56,19 +74,98 @@ impl BanchoRoutingService for BanchoRoutingServiceImpl {
         unimplemented!()
     }
 
-    async fn client_register(&self) -> Response {
-        unimplemented!()
+    async fn client_register(
+        &self,
+        ip: IpAddr,
+        params: ClientRegisterParams,
+    ) -> Response {
+        // The client renders `form_error.user.{field}` under each field
+        let form_error = |username: Vec<String>,
+                          user_email: Vec<String>,
+                          password: Vec<String>| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "form_error": {
+                        "user": {
+                            "username": username,
+                            "user_email": user_email,
+                            "password": password,
+                        }
+                    }
+                })),
+            )
+                .into_response()
+        };
+
+        match self
+            .bancho_handler_service
+            .client_register(ip, params.into())
+            .await
+        {
+            Ok(ClientRegisterResponse {
+                username_errors,
+                email_errors,
+                password_errors,
+            }) => {
+                if username_errors.is_empty()
+                    && email_errors.is_empty()
+                    && password_errors.is_empty()
+                {
+                    "ok".into_response()
+                } else {
+                    form_error(username_errors, email_errors, password_errors)
+                }
+            },
+            Err(
+                err @ (BanchoServiceError::RegistrationRateLimited
+                | BanchoServiceError::RegistrationNotAllowed),
+            ) => form_error(vec![err.to_string()], vec![], vec![]),
+            Err(err) => {
+                warn!("[client_register] Failed to register: {err}");
+                form_error(
+                    vec!["registration failed, please try again later".into()],
+                    vec![],
+                    vec![],
+                )
+            },
+        }
     }
 
     async fn ask_peppy(&self) -> Response {
         unimplemented!()
     }
 
-    async fn difficulty_rating(&self) -> Response {
-        unimplemented!()
+    async fn difficulty_rating(
+        &self,
+        params: DifficultyRatingParams,
+    ) -> Response {
+        match self.bancho_handler_service.difficulty_rating(params.into()).await
+        {
+            Ok(DifficultyRating { star_rating, .. }) => {
+                Json(star_rating).into_response()
+            },
+            Err(
+                BanchoServiceError::GetBeatmapError(_)
+                | BanchoServiceError::BeatmapFileNotExists,
+            ) => StatusCode::NOT_FOUND.into_response(),
+            Err(BanchoServiceError::InvalidRuleset(_)) => {
+                StatusCode::BAD_REQUEST.into_response()
+            },
+            Err(err) => {
+                warn!("[difficulty_rating] Failed to calculate: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            },
+        }
     }
 
-    async fn osu_error(&self) -> Response {
+    async fn osu_error(&self, params: OsuErrorParams) -> Response {
+        if let Err(err) =
+            self.bancho_handler_service.osu_error(params.into()).await
+        {
+            warn!("[osu_error] Failed to save error report: {err}");
+        }
+
         "ok".into_response()
     }
 
@@ -80,28 +177,90 @@ impl BanchoRoutingService for BanchoRoutingServiceImpl {
         "".into_response()
     }
 
-    async fn osu_getbeatmapinfo(&self) -> Response {
-        unimplemented!()
+    async fn osu_getbeatmapinfo(
+        &self,
+        params: OsuGetBeatmapInfoParams,
+        form: OsuGetBeatmapInfoForm,
+    ) -> Response {
+        match self
+            .bancho_handler_service
+            .osu_getbeatmapinfo(params.into_request(form))
+            .await
+        {
+            Ok(HttpResponse { body }) => body.into_response(),
+            Err(err) => {
+                warn!("[osu_getbeatmapinfo] Failed to get beatmap info: {err}");
+                "".into_response()
+            },
+        }
     }
 
-    async fn osu_getfavourites(&self) -> Response {
-        unimplemented!()
+    async fn osu_getfavourites(
+        &self,
+        params: OsuGetFavouritesParams,
+    ) -> Response {
+        match self.bancho_handler_service.osu_getfavourites(params.into()).await
+        {
+            Ok(HttpResponse { body }) => body.into_response(),
+            Err(err) => {
+                warn!("[osu_getfavourites] Failed to get favourites: {err}");
+                "".into_response()
+            },
+        }
     }
 
-    async fn osu_addfavourite(&self) -> Response {
-        unimplemented!()
+    async fn osu_addfavourite(
+        &self,
+        params: OsuAddFavouriteParams,
+    ) -> Response {
+        // The client shows the response body to the user
+        match self.bancho_handler_service.osu_addfavourite(params.into()).await
+        {
+            Ok(HttpResponse { body }) => body.into_response(),
+            Err(
+                BanchoServiceError::UserNotExists(_)
+                | BanchoServiceError::PasswordError(_),
+            ) => "Authentication failed.".into_response(),
+            Err(BanchoServiceError::AddFavouriteBeatmapError(
+                AddFavouriteBeatmapError::LimitExceeded(limit),
+            )) => format!(
+                "You have reached the maximum of {limit} favourite beatmaps."
+            )
+            .into_response(),
+            Err(err) => {
+                warn!("[osu_addfavourite] Failed to add favourite: {err}");
+                "Failed to add favourite.".into_response()
+            },
+        }
     }
 
     async fn lastfm(&self) -> Response {
         "ok".into_response()
     }
 
-    async fn osu_search(&self) -> Response {
-        unimplemented!()
+    async fn osu_search(&self, params: OsuSearchParams) -> Response {
+        match self.bancho_handler_service.osu_search(params.into()).await {
+            Ok(HttpResponse { body }) => body.into_response(),
+            // osu!direct shows the second line when the first one is `-1`
+            Err(
+                BanchoServiceError::UserNotExists(_)
+                | BanchoServiceError::PasswordError(_),
+            ) => "-1\nAuthentication failed.".into_response(),
+            Err(err) => {
+                warn!("[osu_search] Failed to search beatmaps: {err}");
+                "-1\nFailed to search beatmaps.".into_response()
+            },
+        }
     }
 
-    async fn osu_search_set(&self) -> Response {
-        unimplemented!()
+    async fn osu_search_set(&self, params: OsuSearchSetParams) -> Response {
+        match self.bancho_handler_service.osu_search_set(params.into()).await {
+            Ok(HttpResponse { body }) => body.into_response(),
+            Err(err) => {
+                warn!("[osu_search_set] Failed to get beatmapset: {err}");
+                "".into_response()
+            },
+        }
     }
 
     async fn osu_submit_modular_selector(&self) -> Response {
@@ -112,35 +271,94 @@ impl BanchoRoutingService for BanchoRoutingServiceImpl {
         unimplemented!()
     }
 
-    async fn osu_rate(&self) -> Response {
-        unimplemented!()
+    async fn osu_rate(&self, params: OsuRateParams) -> Response {
+        match self.bancho_handler_service.osu_rate(params.into()).await {
+            Ok(HttpResponse { body }) => body.into_response(),
+            Err(
+                BanchoServiceError::UserNotExists(_)
+                | BanchoServiceError::PasswordError(_),
+            ) => "auth fail".into_response(),
+            Err(err) => {
+                warn!("[osu_rate] Failed to rate beatmap: {err}");
+                "".into_response()
+            },
+        }
     }
 
     async fn osu_osz2_getscores(&self) -> Response {
         unimplemented!()
     }
 
-    async fn osu_comment(&self) -> Response {
-        unimplemented!()
+    async fn osu_comment(&self, params: OsuCommentParams) -> Response {
+        match self.bancho_handler_service.osu_comment(params.into()).await {
+            Ok(HttpResponse { body }) => body.into_response(),
+            Err(err) => {
+                warn!("[osu_comment] Failed to handle comment: {err}");
+                "".into_response()
+            },
+        }
     }
 
-    async fn osu_markasread(&self) -> Response {
-        "ok".into_response()
+    async fn osu_markasread(&self, params: OsuMarkAsReadParams) -> Response {
+        match self.bancho_handler_service.osu_mark_as_read(params.into()).await
+        {
+            Ok(HttpResponse { body }) => body.into_response(),
+            Err(err) => {
+                warn!(
+                    "[osu_markasread] Failed to mark messages as read: {err}"
+                );
+                "".into_response()
+            },
+        }
     }
 
     async fn osu_getseasonal(&self) -> Response {
-        "ok".into_response()
+        match self
+            .bancho_handler_service
+            .osu_getseasonal(OsuGetSeasonalRequest {})
+            .await
+        {
+            Ok(HttpResponse { body }) => body.into_response(),
+            Err(err) => {
+                warn!(
+                    "[osu_getseasonal] Failed to get seasonal backgrounds: {err}"
+                );
+                "[]".into_response()
+            },
+        }
     }
 
     async fn bancho_connect(&self) -> Response {
         "ok".into_response()
     }
 
-    async fn check_updates(&self) -> Response {
-        "ok".into_response()
+    async fn check_updates(&self, params: OsuCheckUpdatesParams) -> Response {
+        match self.bancho_handler_service.check_updates(params.into()).await {
+            Ok(HttpResponse { body }) => body.into_response(),
+            Err(err) => {
+                warn!("[check_updates] Failed to get update manifest: {err}");
+                "[]".into_response()
+            },
+        }
     }
 
-    async fn update_beatmap(&self) -> Response {
-        "ok".into_response()
+    async fn update_beatmap(&self, beatmap_file_name: String) -> Response {
+        match self
+            .bancho_handler_service
+            .update_beatmap(OsuGetBeatmapFileRequest {
+                file_name: beatmap_file_name,
+            })
+            .await
+        {
+            Ok(BeatmapFile { content }) => content.into_response(),
+            Err(
+                BanchoServiceError::GetBeatmapError(_)
+                | BanchoServiceError::BeatmapFileNotExists,
+            ) => StatusCode::NOT_FOUND.into_response(),
+            Err(err) => {
+                warn!("[update_beatmap] Failed to get beatmap file: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            },
+        }
     }
 }
@@ -1,13 +1,28 @@
 use crate::bancho_endpoints::{
     extractors::{BanchoClientVersion, OsuTokenHeader},
+    params::{
+        ClientRegisterParams, DifficultyRatingParams, OsuAddFavouriteParams,
+        OsuCheckUpdatesParams, OsuCommentParams, OsuErrorParams,
+        OsuGetBeatmapInfoForm, OsuGetBeatmapInfoParams, OsuGetFavouritesParams,
+        OsuMarkAsReadParams, OsuRateParams, OsuSearchParams,
+        OsuSearchSetParams,
+    },
     *,
 };
 use async_trait::async_trait;
 use axum::response::Response;
+use core_bancho::BanchoServiceError;
 use core_bancho_state::BanchoStateError;
 use core_chat::ChatError;
 use domain_bancho::BanchoClientToken;
-use pb_bancho::LoginSuccess;
+use pb_bancho::{
+    BeatmapFile, ClientRegisterRequest, ClientRegisterResponse,
+    DifficultyRating, DifficultyRatingRequest, HttpResponse, LoginSuccess,
+    OsuAddFavouriteRequest, OsuCheckUpdatesRequest, OsuCommentRequest,
+    OsuErrorRequest, OsuGetBeatmapFileRequest, OsuGetBeatmapInfoRequest,
+    OsuGetFavouritesRequest, OsuGetSeasonalRequest, OsuMarkAsReadRequest,
+    OsuRateRequest, OsuSearchRequest, OsuSearchSetRequest,
+};
 use pb_bancho_state::UserQuery;
 use std::{net::IpAddr, sync::Arc};
 
@@ -35,16 +50,23 @@ pub trait BanchoRoutingService {
     async fn download_beatmapset(&self, beatmapset_id: i32) -> Response;
 
     /// post `/users`
-    async fn client_register(&self) -> Response;
+    async fn client_register(
+        &self,
+        ip: IpAddr,
+        params: ClientRegisterParams,
+    ) -> Response;
 
     /// get `/p/doyoureallywanttoaskpeppy`
     async fn ask_peppy(&self) -> Response;
 
-    /// get `/difficulty-rating`
-    async fn difficulty_rating(&self) -> Response;
+    /// post `/difficulty-rating`
+    async fn difficulty_rating(
+        &self,
+        params: DifficultyRatingParams,
+    ) -> Response;
 
     /// post `/web/osu-error.php`
-    async fn osu_error(&self) -> Response;
+    async fn osu_error(&self, params: OsuErrorParams) -> Response;
 
     /// post `/web/osu-screenshot.php`
     async fn osu_screenshot(&self) -> Response;
@@ -52,23 +74,31 @@ pub trait BanchoRoutingService {
     /// get `/web/osu-getfriends.php`
     async fn osu_getfriends(&self) -> Response;
 
-    /// get `/web/osu-getbeatmapinfo.php`
-    async fn osu_getbeatmapinfo(&self) -> Response;
+    /// post `/web/osu-getbeatmapinfo.php`
+    async fn osu_getbeatmapinfo(
+        &self,
+        params: OsuGetBeatmapInfoParams,
+        form: OsuGetBeatmapInfoForm,
+    ) -> Response;
 
     /// get `/web/osu-getfavourites.php`
-    async fn osu_getfavourites(&self) -> Response;
+    async fn osu_getfavourites(
+        &self,
+        params: OsuGetFavouritesParams,
+    ) -> Response;
 
     /// get `/web/osu-addfavourite.php`
-    async fn osu_addfavourite(&self) -> Response;
+    async fn osu_addfavourite(&self, params: OsuAddFavouriteParams)
+        -> Response;
 
     /// get `/web/osu-lastfm.php`
     async fn lastfm(&self) -> Response;
 
     /// get `/web/osu-search.php`
-    async fn osu_search(&self) -> Response;
+    async fn osu_search(&self, params: OsuSearchParams) -> Response;
 
     /// get `/web/osu-search-set.php`
-    async fn osu_search_set(&self) -> Response;
+    async fn osu_search_set(&self, params: OsuSearchSetParams) -> Response;
 
     /// post `/web/osu-submit-modular-selector.php`
     async fn osu_submit_modular_selector(&self) -> Response;
@@ -77,16 +107,16 @@ pub trait BanchoRoutingService {
     async fn osu_getreplay(&self) -> Response;
 
     /// get `/web/osu-rate.php`
-    async fn osu_rate(&self) -> Response;
+    async fn osu_rate(&self, params: OsuRateParams) -> Response;
 
     /// get `/web/osu-osz2-getscores.php`
     async fn osu_osz2_getscores(&self) -> Response;
 
     /// post `/web/osu-comment.php`
-    async fn osu_comment(&self) -> Response;
+    async fn osu_comment(&self, params: OsuCommentParams) -> Response;
 
     /// get `/web/osu-markasread.php`
-    async fn osu_markasread(&self) -> Response;
+    async fn osu_markasread(&self, params: OsuMarkAsReadParams) -> Response;
 
     /// get `/web/osu-getseasonal.php`
     async fn osu_getseasonal(&self) -> Response;
@@ -95,10 +125,10 @@ pub trait BanchoRoutingService {
     async fn bancho_connect(&self) -> Response;
 
     /// get `/web/check-updates.php`
-    async fn check_updates(&self) -> Response;
+    async fn check_updates(&self, params: OsuCheckUpdatesParams) -> Response;
 
     /// get `/web/maps/{beatmap_file_name}`
-    async fn update_beatmap(&self) -> Response;
+    async fn update_beatmap(&self, beatmap_file_name: String) -> Response;
 }
 
 #[async_trait]
@@ -143,4 +173,75 @@ pub trait BanchoHandlerService {
         &self,
         token: BanchoClientToken,
     ) -> Result<bool, BanchoStateError>;
+
+    async fn client_register(
+        &self,
+        client_ip: IpAddr,
+        request: ClientRegisterRequest,
+    ) -> Result<ClientRegisterResponse, BanchoServiceError>;
+
+    async fn osu_search(
+        &self,
+        request: OsuSearchRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn osu_search_set(
+        &self,
+        request: OsuSearchSetRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn osu_getbeatmapinfo(
+        &self,
+        request: OsuGetBeatmapInfoRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn osu_getfavourites(
+        &self,
+        request: OsuGetFavouritesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn osu_addfavourite(
+        &self,
+        request: OsuAddFavouriteRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn osu_rate(
+        &self,
+        request: OsuRateRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn osu_comment(
+        &self,
+        request: OsuCommentRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn osu_mark_as_read(
+        &self,
+        request: OsuMarkAsReadRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn osu_error(
+        &self,
+        request: OsuErrorRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn osu_getseasonal(
+        &self,
+        request: OsuGetSeasonalRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn update_beatmap(
+        &self,
+        request: OsuGetBeatmapFileRequest,
+    ) -> Result<BeatmapFile, BanchoServiceError>;
+
+    async fn check_updates(
+        &self,
+        request: OsuCheckUpdatesRequest,
+    ) -> Result<HttpResponse, BanchoServiceError>;
+
+    async fn difficulty_rating(
+        &self,
+        request: DifficultyRatingRequest,
+    ) -> Result<DifficultyRating, BanchoServiceError>;
 }
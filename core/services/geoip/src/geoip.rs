@@ -30,9 +30,9 @@ impl GeoipServiceBuilder {
             .ok()
             .map(|svc| svc.into_service());
 
-        if service.is_some() {
+        if let Some(service) = service {
             info!("Geoip service init successful, type: \"Local\"");
-            return service.unwrap();
+            return service;
         }
 
         if let Some(cfg) = cfg {
@@ -161,7 +161,7 @@ impl LookupIpAddress for GeoipServiceImpl {
             .subdivisions
             .as_ref()
             .filter(|regions| !regions.is_empty())
-            .and_then(|regions| regions.get(0))
+            .and_then(|regions| regions.first())
             .map(|r| Region {
                 geoname_id: r.geoname_id.unwrap_or_default(),
                 code: r.iso_code.map(|s| s.to_string()).unwrap_or_default(),
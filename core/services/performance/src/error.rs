@@ -0,0 +1,69 @@
+use peace_db::DbErr;
+use peace_performance::{CalculateError, ParseError};
+use peace_repositories::GetBeatmapError;
+use peace_rpc_error::{RpcError, TonicError};
+use tokio::task::JoinError;
+use tonic::Status;
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize, RpcError)]
+pub enum PerformanceError {
+    #[error("invalid game mode: {0}")]
+    InvalidMode(i32),
+    #[error("score not exists")]
+    ScoreNotExists,
+    #[error("beatmap not exists")]
+    BeatmapNotExists,
+    #[error("beatmap file not exists")]
+    BeatmapFileNotExists,
+    #[error("beatmap file err: {0}")]
+    BeatmapFileError(String),
+    #[error("calculate err: {0}")]
+    CalculateError(String),
+    #[error("recalculation is already running")]
+    RecalculationRunning,
+    #[error("recalculation checkpoint err: {0}")]
+    CheckpointError(String),
+    #[error("database err: {0}")]
+    DbErr(String),
+    #[error("TonicError: {0}")]
+    TonicError(String),
+}
+
+impl TonicError for PerformanceError {
+    fn tonic_error(s: Status) -> Self {
+        Self::TonicError(s.message().to_owned())
+    }
+}
+
+impl From<DbErr> for PerformanceError {
+    fn from(err: DbErr) -> Self {
+        Self::DbErr(err.to_string())
+    }
+}
+
+impl From<GetBeatmapError> for PerformanceError {
+    fn from(err: GetBeatmapError) -> Self {
+        match err {
+            GetBeatmapError::BeatmapNotExists => Self::BeatmapNotExists,
+            GetBeatmapError::DbErr(err) => Self::DbErr(err),
+        }
+    }
+}
+
+impl From<ParseError> for PerformanceError {
+    fn from(err: ParseError) -> Self {
+        Self::CalculateError(err.to_string())
+    }
+}
+
+impl From<CalculateError> for PerformanceError {
+    fn from(err: CalculateError) -> Self {
+        Self::CalculateError(err.to_string())
+    }
+}
+
+impl From<JoinError> for PerformanceError {
+    fn from(err: JoinError) -> Self {
+        Self::CalculateError(err.to_string())
+    }
+}
@@ -0,0 +1,31 @@
+#[macro_use]
+extern crate peace_logs;
+
+#[macro_use]
+extern crate serde;
+
+#[cfg(test)]
+mod tests;
+
+pub mod error;
+pub mod performance;
+pub mod recalculation;
+pub mod stats;
+pub mod traits;
+
+pub use performance::*;
+pub use recalculation::*;
+pub use traits::*;
+
+pub mod rpc_config {
+    use clap_serde_derive::ClapSerde;
+    use pb_performance as performance;
+    use peace_cfg::macro_define_rpc_client_config;
+
+    macro_define_rpc_client_config!(
+        service_name: performance,
+        config_name: PerformanceRpcConfig,
+        default_uri: "http://127.0.0.1:5016"
+    );
+}
+pub use rpc_config::*;
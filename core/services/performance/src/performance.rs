@@ -0,0 +1,547 @@
+use super::{
+    error::PerformanceError, stats, CalculateScorePp, DynPerformanceService,
+    PerformanceService, RecalculatePerformances, RecalculationCheckpoint,
+    RecalculationConfig, UpdateUserPerformance,
+};
+use async_trait::async_trait;
+use domain_bancho::{GameMode as BanchoGameMode, Mods};
+use infra_beatmaps::BeatmapFileStorage;
+use infra_services::{FromRpcClient, IntoService, RpcClient};
+use pb_base::ExecSuccess;
+use pb_performance::{
+    performance_rpc_client::PerformanceRpcClient, CalculateScorePpRequest,
+    RecalculateUserRequest, ScorePp, StartRecalculationRequest,
+    StopRecalculationRequest, SubmittedScorePerformance, UserPerformance,
+};
+use peace_db::{
+    peace::entity::sea_orm_active_enums::PpVersion, prelude::Decimal,
+};
+use peace_performance::{Beatmap, GameMode, ScoreState};
+use peace_repositories::{
+    beatmaps::DynBeatmapsRepository,
+    leaderboards::DynLeaderboardsRepository,
+    scores::{DynScoresRepository, ScoreRow},
+    user_stats::{DynUserStatsRepository, UserModeStats},
+    users::DynUsersRepository,
+};
+use std::sync::Arc;
+use tonic::transport::Channel;
+use tools::async_collections::{
+    BackgroundTaskManager, OnceBackgroundTaskConfig,
+};
+
+/// The ruleset and the mods implied by the score table of `mode`, relax and
+/// autopilot are calculated through their mod bits.
+#[inline]
+pub fn ruleset_of(mode: BanchoGameMode) -> (GameMode, Mods) {
+    match mode {
+        BanchoGameMode::Standard | BanchoGameMode::StandardScoreV2 => {
+            (GameMode::Osu, Mods::NoMod)
+        },
+        BanchoGameMode::StandardRelax => (GameMode::Osu, Mods::Relax),
+        BanchoGameMode::StandardAutopilot => (GameMode::Osu, Mods::AutoPilot),
+        BanchoGameMode::Taiko => (GameMode::Taiko, Mods::NoMod),
+        BanchoGameMode::TaikoRelax => (GameMode::Taiko, Mods::Relax),
+        BanchoGameMode::Fruits => (GameMode::Catch, Mods::NoMod),
+        BanchoGameMode::FruitsRelax => (GameMode::Catch, Mods::Relax),
+        BanchoGameMode::Mania => (GameMode::Mania, Mods::NoMod),
+    }
+}
+
+#[inline]
+pub fn score_state(score: &ScoreRow) -> ScoreState {
+    let count = |n: i32| n.max(0) as usize;
+
+    ScoreState {
+        max_combo: count(score.combo),
+        ngeki: count(score.geki),
+        n300: count(score.n300),
+        nkatu: count(score.katu),
+        n100: count(score.n100),
+        n50: count(score.n50),
+        nmiss: count(score.miss),
+    }
+}
+
+/// Saturates the `bigint` sums into the `integer` columns of the stats.
+#[inline]
+fn saturating_i32(value: Option<i64>) -> i32 {
+    value.unwrap_or_default().clamp(0, i32::MAX as i64) as i32
+}
+
+#[inline]
+fn decimal_to_f64(value: Decimal) -> f64 {
+    f64::try_from(value).unwrap_or_default()
+}
+
+#[derive(Clone, Default)]
+pub struct Tasks {
+    pub recalculation: BackgroundTaskManager,
+}
+
+#[derive(Clone)]
+pub struct PerformanceServiceImpl {
+    pub scores_repository: DynScoresRepository,
+    pub beatmaps_repository: DynBeatmapsRepository,
+    pub users_repository: DynUsersRepository,
+    pub user_stats_repository: DynUserStatsRepository,
+    pub leaderboards_repository: DynLeaderboardsRepository,
+    pub beatmap_file_storage: BeatmapFileStorage,
+    pub recalculation_config: Arc<RecalculationConfig>,
+    pub tasks: Tasks,
+}
+
+impl PerformanceServiceImpl {
+    #[inline]
+    pub fn new(
+        scores_repository: DynScoresRepository,
+        beatmaps_repository: DynBeatmapsRepository,
+        users_repository: DynUsersRepository,
+        user_stats_repository: DynUserStatsRepository,
+        leaderboards_repository: DynLeaderboardsRepository,
+        beatmap_file_storage: BeatmapFileStorage,
+        recalculation_config: RecalculationConfig,
+    ) -> Self {
+        Self {
+            scores_repository,
+            beatmaps_repository,
+            users_repository,
+            user_stats_repository,
+            leaderboards_repository,
+            beatmap_file_storage,
+            recalculation_config: Arc::new(recalculation_config),
+            tasks: Tasks::default(),
+        }
+    }
+}
+
+impl PerformanceService for PerformanceServiceImpl {}
+
+impl IntoService<DynPerformanceService> for PerformanceServiceImpl {
+    #[inline]
+    fn into_service(self) -> DynPerformanceService {
+        Arc::new(self) as DynPerformanceService
+    }
+}
+
+#[async_trait]
+impl CalculateScorePp for PerformanceServiceImpl {
+    async fn calculate_score_pp(
+        &self,
+        mode: BanchoGameMode,
+        score_id: i64,
+    ) -> Result<ScorePp, PerformanceError> {
+        let score = self
+            .scores_repository
+            .get_score(mode, score_id)
+            .await?
+            .ok_or(PerformanceError::ScoreNotExists)?;
+
+        let beatmap = self
+            .beatmaps_repository
+            .get_beatmap(None, None, Some(&score.map_md5))
+            .await?;
+
+        let content = self
+            .beatmap_file_storage
+            .get(beatmap.bid, &beatmap.md5)
+            .await
+            .map_err(|err| PerformanceError::BeatmapFileError(err.to_string()))?
+            .ok_or(PerformanceError::BeatmapFileNotExists)?
+            .content;
+
+        let (ruleset, mode_mods) = ruleset_of(mode);
+        let mods = (Mods::from(score.mods as u32) | mode_mods).bits();
+        let state = score_state(&score);
+
+        // Large beatmaps can take a while, keep them off the async workers
+        let (difficulty, performance) =
+            tokio::task::spawn_blocking(move || {
+                let map = Beatmap::parse(&content)?;
+
+                Ok::<_, PerformanceError>(
+                    peace_performance::calculate_performance(
+                        &map,
+                        Some(ruleset),
+                        mods,
+                        &state,
+                    )?,
+                )
+            })
+            .await??;
+
+        let pp = performance.pp();
+        let raw_pp = serde_json::to_value(&performance).ok();
+
+        self.scores_repository
+            .save_score_pp(mode, score_id, PpVersion::V1, pp, raw_pp.clone())
+            .await?;
+
+        self.leaderboards_repository
+            .update_user_leaderboards(
+                mode,
+                score.user_id,
+                Some(vec![score.map_md5]),
+            )
+            .await?;
+
+        debug!(
+            "Calculated pp of score <{score_id}> ({mode:?}): {pp:.2}, stars: \
+             {:.2}",
+            difficulty.stars()
+        );
+
+        Ok(ScorePp {
+            score_id,
+            pp,
+            stars: difficulty.stars(),
+            raw_pp: raw_pp.map(|raw| raw.to_string()).unwrap_or_default(),
+        })
+    }
+}
+
+#[async_trait]
+impl UpdateUserPerformance for PerformanceServiceImpl {
+    async fn update_user_performance(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+    ) -> Result<UserPerformance, PerformanceError> {
+        let repo = &self.user_stats_repository;
+
+        let totals = repo.get_score_totals(mode, user_id).await?;
+        let (ranked_scores, ranked_score) =
+            repo.get_ranked_score(mode, user_id).await?;
+
+        // Score v2 is ranked by score, it has no pp
+        let best_scores = if mode == BanchoGameMode::StandardScoreV2 {
+            Vec::new()
+        } else {
+            repo.get_ranked_scores_pp(
+                mode,
+                user_id,
+                PpVersion::V1,
+                stats::WEIGHTED_SCORES_LIMIT,
+            )
+            .await?
+        };
+
+        let (pps, accuracies): (Vec<f64>, Vec<f64>) = best_scores
+            .into_iter()
+            .map(|score| {
+                (decimal_to_f64(score.pp), decimal_to_f64(score.accuracy))
+            })
+            .unzip();
+
+        let weighted_pp = stats::weighted_pp(&pps);
+        let bonus_pp =
+            if pps.is_empty() { 0.0 } else { stats::bonus_pp(ranked_scores) };
+        let pp = weighted_pp + bonus_pp;
+        let accuracy = stats::weighted_accuracy(&accuracies);
+
+        let user_stats = UserModeStats {
+            total_score: totals.total_score.unwrap_or_default(),
+            ranked_score,
+            playcount: saturating_i32(Some(totals.playcount)),
+            total_hits: saturating_i32(totals.total_hits),
+            accuracy,
+            max_combo: totals.max_combo.unwrap_or_default(),
+            total_seconds_played: saturating_i32(totals.total_seconds_played),
+            count300: saturating_i32(totals.count300),
+            count100: saturating_i32(totals.count100),
+            count50: saturating_i32(totals.count50),
+            count_miss: saturating_i32(totals.count_miss),
+            count_failed: saturating_i32(totals.count_failed),
+        };
+
+        repo.save_user_pp(
+            mode,
+            user_id,
+            PpVersion::V1,
+            pp,
+            Some(serde_json::json!({
+                "weighted_pp": weighted_pp,
+                "bonus_pp": bonus_pp,
+                "ranked_scores": ranked_scores,
+            })),
+        )
+        .await?;
+
+        let performance = UserPerformance {
+            user_id,
+            mode: mode.val() as i32,
+            pp,
+            bonus_pp,
+            accuracy,
+            max_combo: user_stats.max_combo,
+            ranked_score: user_stats.ranked_score,
+            total_score: user_stats.total_score,
+            playcount: user_stats.playcount,
+        };
+
+        repo.save_user_stats(mode, user_id, user_stats).await?;
+
+        Ok(performance)
+    }
+
+    async fn process_submitted_score(
+        &self,
+        mode: BanchoGameMode,
+        score_id: i64,
+    ) -> Result<SubmittedScorePerformance, PerformanceError> {
+        let ScoreRow { user_id, map_md5, .. } = self
+            .scores_repository
+            .get_score(mode, score_id)
+            .await?
+            .ok_or(PerformanceError::ScoreNotExists)?;
+
+        // The score rankings are kept right even if the pp fails
+        self.leaderboards_repository
+            .update_user_leaderboards(mode, user_id, Some(vec![map_md5]))
+            .await?;
+
+        let score = self.calculate_score_pp(mode, score_id).await?;
+        let user = self.update_user_performance(mode, user_id).await?;
+
+        Ok(SubmittedScorePerformance { score: Some(score), user: Some(user) })
+    }
+
+    async fn recalculate_user(
+        &self,
+        user_id: i32,
+        mode: Option<BanchoGameMode>,
+        recalculate_scores: bool,
+        rebuild_leaderboards: bool,
+    ) -> Result<Vec<UserPerformance>, PerformanceError> {
+        let modes = match mode {
+            Some(mode) => vec![mode],
+            None => stats::ALL_MODES.to_vec(),
+        };
+
+        let mut performances = Vec::with_capacity(modes.len());
+
+        for mode in modes {
+            // Score v2 scores are in the table of osu!standard
+            if recalculate_scores && mode != BanchoGameMode::StandardScoreV2 {
+                let score_ids = self
+                    .scores_repository
+                    .get_user_score_ids(mode, user_id)
+                    .await?;
+
+                for score_id in score_ids {
+                    if let Err(err) =
+                        self.calculate_score_pp(mode, score_id).await
+                    {
+                        warn!(
+                            "Failed to calculate pp of score <{score_id}> \
+                             ({mode:?}): {err}"
+                        );
+                    }
+                }
+            }
+
+            if rebuild_leaderboards {
+                self.leaderboards_repository
+                    .update_user_leaderboards(mode, user_id, None)
+                    .await?;
+            }
+
+            performances
+                .push(self.update_user_performance(mode, user_id).await?);
+        }
+
+        Ok(performances)
+    }
+}
+
+#[async_trait]
+impl RecalculatePerformances for PerformanceServiceImpl {
+    async fn start_recalculation(
+        &self,
+        mode: Option<BanchoGameMode>,
+        recalculate_scores: bool,
+        rebuild_leaderboards: bool,
+        resume: bool,
+    ) -> Result<ExecSuccess, PerformanceError> {
+        let manager = &self.tasks.recalculation;
+
+        if manager.task().load().as_ref().is_some_and(|task| task.is_started())
+        {
+            return Err(PerformanceError::RecalculationRunning);
+        }
+
+        let checkpoint_path = &self.recalculation_config.checkpoint_path;
+        let saved = if resume {
+            RecalculationCheckpoint::load(checkpoint_path).await.map_err(
+                |err| PerformanceError::CheckpointError(err.to_string()),
+            )?
+        } else {
+            None
+        };
+
+        let checkpoint = saved.unwrap_or_else(|| {
+            RecalculationCheckpoint::new(
+                mode.map(|mode| vec![mode])
+                    .unwrap_or_else(|| stats::ALL_MODES.to_vec()),
+                recalculate_scores,
+                rebuild_leaderboards,
+            )
+        });
+
+        // Clears the finished task, the manager only starts one task
+        manager.stop().ok();
+        manager.start(
+            self.recalculation_factory(checkpoint),
+            Arc::new(OnceBackgroundTaskConfig),
+        );
+
+        Ok(ExecSuccess::default())
+    }
+
+    async fn stop_recalculation(
+        &self,
+    ) -> Result<ExecSuccess, PerformanceError> {
+        self.tasks.recalculation.stop().map_err(|err| {
+            PerformanceError::CheckpointError(err.to_string())
+        })?;
+
+        Ok(ExecSuccess::default())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PerformanceServiceRemote(PerformanceRpcClient<Channel>);
+
+impl RpcClient for PerformanceServiceRemote {
+    type Client = PerformanceRpcClient<Channel>;
+
+    #[inline]
+    fn client(&self) -> Self::Client {
+        self.0.clone()
+    }
+}
+
+impl FromRpcClient for PerformanceServiceRemote {
+    #[inline]
+    fn from_client(client: Self::Client) -> Self {
+        Self(client)
+    }
+}
+
+impl PerformanceService for PerformanceServiceRemote {}
+
+impl IntoService<DynPerformanceService> for PerformanceServiceRemote {
+    #[inline]
+    fn into_service(self) -> DynPerformanceService {
+        Arc::new(self) as DynPerformanceService
+    }
+}
+
+#[async_trait]
+impl CalculateScorePp for PerformanceServiceRemote {
+    #[inline]
+    async fn calculate_score_pp(
+        &self,
+        mode: BanchoGameMode,
+        score_id: i64,
+    ) -> Result<ScorePp, PerformanceError> {
+        Ok(self
+            .client()
+            .calculate_score_pp(CalculateScorePpRequest {
+                score_id,
+                mode: mode.val() as i32,
+            })
+            .await?
+            .into_inner())
+    }
+}
+
+#[async_trait]
+impl UpdateUserPerformance for PerformanceServiceRemote {
+    /// Not exposed by the RPC, the remote service updates the users itself
+    /// when processing scores or recalculating.
+    #[inline]
+    async fn update_user_performance(
+        &self,
+        mode: BanchoGameMode,
+        user_id: i32,
+    ) -> Result<UserPerformance, PerformanceError> {
+        self.recalculate_user(user_id, Some(mode), false, false)
+            .await?
+            .pop()
+            .ok_or(PerformanceError::TonicError(
+                "empty recalculation response".to_owned(),
+            ))
+    }
+
+    #[inline]
+    async fn process_submitted_score(
+        &self,
+        mode: BanchoGameMode,
+        score_id: i64,
+    ) -> Result<SubmittedScorePerformance, PerformanceError> {
+        Ok(self
+            .client()
+            .process_submitted_score(CalculateScorePpRequest {
+                score_id,
+                mode: mode.val() as i32,
+            })
+            .await?
+            .into_inner())
+    }
+
+    #[inline]
+    async fn recalculate_user(
+        &self,
+        user_id: i32,
+        mode: Option<BanchoGameMode>,
+        recalculate_scores: bool,
+        rebuild_leaderboards: bool,
+    ) -> Result<Vec<UserPerformance>, PerformanceError> {
+        Ok(self
+            .client()
+            .recalculate_user(RecalculateUserRequest {
+                user_id,
+                mode: mode.map(|mode| mode.val() as i32),
+                recalculate_scores,
+                rebuild_leaderboards,
+            })
+            .await?
+            .into_inner()
+            .performances)
+    }
+}
+
+#[async_trait]
+impl RecalculatePerformances for PerformanceServiceRemote {
+    #[inline]
+    async fn start_recalculation(
+        &self,
+        mode: Option<BanchoGameMode>,
+        recalculate_scores: bool,
+        rebuild_leaderboards: bool,
+        resume: bool,
+    ) -> Result<ExecSuccess, PerformanceError> {
+        Ok(self
+            .client()
+            .start_recalculation(StartRecalculationRequest {
+                mode: mode.map(|mode| mode.val() as i32),
+                recalculate_scores,
+                resume,
+                rebuild_leaderboards,
+            })
+            .await?
+            .into_inner())
+    }
+
+    #[inline]
+    async fn stop_recalculation(
+        &self,
+    ) -> Result<ExecSuccess, PerformanceError> {
+        Ok(self
+            .client()
+            .stop_recalculation(StopRecalculationRequest {})
+            .await?
+            .into_inner())
+    }
+}
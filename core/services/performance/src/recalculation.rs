@@ -0,0 +1,220 @@
+use crate::{PerformanceServiceImpl, UpdateUserPerformance};
+use clap::Parser;
+use clap_serde_derive::ClapSerde;
+use domain_bancho::GameMode;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tools::async_collections::{BackgroundTaskFactory, SignalHandle};
+
+const LOG_TARGET: &str = "performance::background_tasks::recalculation";
+
+#[derive(Debug, Clone, Parser, ClapSerde, Serialize, Deserialize)]
+pub struct CliPerformanceRecalculationConfigs {
+    /// Users recalculated in one batch of a full recalculation.
+    #[default(50)]
+    #[arg(long, default_value = "50")]
+    pub recalculation_batch_size: u64,
+
+    /// Pause between two batches, keeps the database responsive.
+    #[default(1000)]
+    #[arg(long, default_value = "1000")]
+    pub recalculation_batch_interval_millis: u64,
+
+    /// The progress is saved to this file after each batch, so a stopped
+    /// recalculation can be resumed.
+    #[default("./.data/performance_recalculation.json".to_owned())]
+    #[arg(long, default_value = "./.data/performance_recalculation.json")]
+    pub recalculation_checkpoint_path: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecalculationConfig {
+    pub batch_size: u64,
+    pub batch_interval: Duration,
+    pub checkpoint_path: PathBuf,
+}
+
+impl Default for RecalculationConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::with_cfg(&CliPerformanceRecalculationConfigs::default())
+    }
+}
+
+impl RecalculationConfig {
+    #[inline]
+    pub fn with_cfg(cfg: &CliPerformanceRecalculationConfigs) -> Self {
+        Self {
+            batch_size: cfg.recalculation_batch_size.max(1),
+            batch_interval: Duration::from_millis(
+                cfg.recalculation_batch_interval_millis,
+            ),
+            checkpoint_path: cfg.recalculation_checkpoint_path.as_str().into(),
+        }
+    }
+}
+
+/// Progress of a full recalculation, users are walked in ascending id order.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecalculationCheckpoint {
+    pub modes: Vec<GameMode>,
+    pub recalculate_scores: bool,
+    /// Missing in the checkpoints of older versions.
+    #[serde(default)]
+    pub rebuild_leaderboards: bool,
+    /// Every user up to this id is done.
+    pub last_user_id: i32,
+}
+
+impl RecalculationCheckpoint {
+    #[inline]
+    pub fn new(
+        modes: Vec<GameMode>,
+        recalculate_scores: bool,
+        rebuild_leaderboards: bool,
+    ) -> Self {
+        Self {
+            modes,
+            recalculate_scores,
+            rebuild_leaderboards,
+            last_user_id: 0,
+        }
+    }
+
+    /// `Ok(None)` if there is no checkpoint.
+    pub async fn load(path: &Path) -> io::Result<Option<Self>> {
+        match tokio::fs::read(path).await {
+            Ok(content) => Ok(Some(serde_json::from_slice(&content)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub async fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        tokio::fs::write(path, serde_json::to_vec(self)?).await
+    }
+
+    pub async fn remove(path: &Path) -> io::Result<()> {
+        match tokio::fs::remove_file(path).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl PerformanceServiceImpl {
+    pub fn recalculation_factory(
+        &self,
+        checkpoint: RecalculationCheckpoint,
+    ) -> BackgroundTaskFactory {
+        let service = self.to_owned();
+
+        BackgroundTaskFactory::new(Arc::new(move |stop: SignalHandle| {
+            let service = service.to_owned();
+            let checkpoint = checkpoint.to_owned();
+
+            info!(
+                target: LOG_TARGET,
+                "Service started! (modes={:?}, recalculate_scores={}, \
+                 rebuild_leaderboards={}, after_user_id={})",
+                checkpoint.modes,
+                checkpoint.recalculate_scores,
+                checkpoint.rebuild_leaderboards,
+                checkpoint.last_user_id
+            );
+
+            Box::pin(async move {
+                tokio::select!(
+                    _ = service.recalculate_all(checkpoint) => {},
+                    _ = stop.wait_signal() => {
+                        warn!(target: LOG_TARGET, "Service stopped!");
+                    }
+                );
+            })
+        }))
+    }
+
+    async fn recalculate_all(&self, mut checkpoint: RecalculationCheckpoint) {
+        let cfg = &self.recalculation_config;
+        let start = Instant::now();
+        let mut recalculated = 0usize;
+
+        loop {
+            let user_ids = match self
+                .users_repository
+                .get_user_ids_after(checkpoint.last_user_id, cfg.batch_size)
+                .await
+            {
+                Ok(user_ids) => user_ids,
+                Err(err) => {
+                    error!(
+                        target: LOG_TARGET,
+                        "Failed to get users after <{}>, the recalculation \
+                         can be resumed: {err}",
+                        checkpoint.last_user_id
+                    );
+                    return;
+                },
+            };
+
+            let Some(last_user_id) = user_ids.last().copied() else {
+                break;
+            };
+
+            for user_id in user_ids {
+                for mode in checkpoint.modes.iter().copied() {
+                    if let Err(err) = self
+                        .recalculate_user(
+                            user_id,
+                            Some(mode),
+                            checkpoint.recalculate_scores,
+                            checkpoint.rebuild_leaderboards,
+                        )
+                        .await
+                    {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Failed to recalculate user <{user_id}> \
+                             ({mode:?}): {err}"
+                        );
+                    }
+                }
+
+                recalculated += 1;
+            }
+
+            checkpoint.last_user_id = last_user_id;
+            if let Err(err) = checkpoint.save(&cfg.checkpoint_path).await {
+                warn!(target: LOG_TARGET, "Failed to save checkpoint: {err}");
+            }
+
+            debug!(
+                target: LOG_TARGET,
+                "Recalculated up to user <{last_user_id}> \
+                 ({recalculated} users)"
+            );
+
+            tokio::time::sleep(cfg.batch_interval).await;
+        }
+
+        if let Err(err) =
+            RecalculationCheckpoint::remove(&cfg.checkpoint_path).await
+        {
+            warn!(target: LOG_TARGET, "Failed to remove checkpoint: {err}");
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Done in: {:?} ({recalculated} users)",
+            start.elapsed()
+        );
+    }
+}
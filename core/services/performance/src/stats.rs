@@ -0,0 +1,51 @@
+use domain_bancho::GameMode;
+
+/// Each best score is worth `0.95` times the pp of the previous one.
+pub const PP_WEIGHT: f64 = 0.95;
+
+/// Only the top scores are weighted, the ones below are worth next to
+/// nothing.
+pub const WEIGHTED_SCORES_LIMIT: u64 = 100;
+
+pub const ALL_MODES: [GameMode; 9] = [
+    GameMode::Standard,
+    GameMode::Taiko,
+    GameMode::Fruits,
+    GameMode::Mania,
+    GameMode::StandardRelax,
+    GameMode::TaikoRelax,
+    GameMode::FruitsRelax,
+    GameMode::StandardAutopilot,
+    GameMode::StandardScoreV2,
+];
+
+/// `pps` must be sorted from the highest pp.
+#[inline]
+pub fn weighted_pp(pps: &[f64]) -> f64 {
+    pps.iter()
+        .zip(std::iter::successors(Some(1.0), |weight| {
+            Some(weight * PP_WEIGHT)
+        }))
+        .map(|(pp, weight)| pp * weight)
+        .sum()
+}
+
+/// Rewards the count of ranked best scores, up to `416.67` pp.
+#[inline]
+pub fn bonus_pp(ranked_scores: i64) -> f64 {
+    416.6667 * (1.0 - 0.9994_f64.powf(ranked_scores as f64))
+}
+
+/// Accuracy weighted like the pp, `accuracies` must be in the order of
+/// their scores' pp.
+#[inline]
+pub fn weighted_accuracy(accuracies: &[f64]) -> f64 {
+    if accuracies.is_empty() {
+        return 0.0;
+    }
+
+    let total_weight =
+        (1.0 - PP_WEIGHT.powi(accuracies.len() as i32)) / (1.0 - PP_WEIGHT);
+
+    weighted_pp(accuracies) / total_weight
+}
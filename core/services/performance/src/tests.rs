@@ -0,0 +1,129 @@
+mod performance {
+    use crate::ruleset_of;
+    use domain_bancho::{GameMode as BanchoGameMode, Mods};
+    use peace_performance::GameMode;
+
+    #[test]
+    fn test_ruleset_of_score_tables() {
+        for (mode, expected) in [
+            (BanchoGameMode::Standard, (GameMode::Osu, Mods::NoMod)),
+            (BanchoGameMode::StandardScoreV2, (GameMode::Osu, Mods::NoMod)),
+            (BanchoGameMode::StandardRelax, (GameMode::Osu, Mods::Relax)),
+            (
+                BanchoGameMode::StandardAutopilot,
+                (GameMode::Osu, Mods::AutoPilot),
+            ),
+            (BanchoGameMode::Taiko, (GameMode::Taiko, Mods::NoMod)),
+            (BanchoGameMode::TaikoRelax, (GameMode::Taiko, Mods::Relax)),
+            (BanchoGameMode::Fruits, (GameMode::Catch, Mods::NoMod)),
+            (BanchoGameMode::FruitsRelax, (GameMode::Catch, Mods::Relax)),
+            (BanchoGameMode::Mania, (GameMode::Mania, Mods::NoMod)),
+        ] {
+            assert_eq!(ruleset_of(mode), expected, "{mode:?}");
+        }
+    }
+}
+
+mod stats {
+    use crate::stats::{bonus_pp, weighted_accuracy, weighted_pp, PP_WEIGHT};
+
+    fn assert_approx(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_weighted_pp() {
+        assert_eq!(weighted_pp(&[]), 0.0);
+        assert_eq!(weighted_pp(&[727.0]), 727.0);
+        assert_approx(
+            weighted_pp(&[300.0, 200.0, 100.0]),
+            300.0 + 190.0 + 90.25,
+        );
+
+        // the n-th best score is worth 0.95^n of its pp
+        let pps = vec![100.0; 100];
+        assert_approx(
+            weighted_pp(&pps),
+            100.0 * (1.0 - PP_WEIGHT.powi(100)) / (1.0 - PP_WEIGHT),
+        );
+    }
+
+    #[test]
+    fn test_bonus_pp() {
+        assert_eq!(bonus_pp(0), 0.0);
+        assert_approx(bonus_pp(1), 416.6667 * 0.0006);
+        assert_approx(bonus_pp(1000), 416.6667 * (1.0 - 0.9994_f64.powi(1000)));
+        assert!(bonus_pp(100_000) <= 416.6667);
+    }
+
+    #[test]
+    fn test_weighted_accuracy() {
+        assert_eq!(weighted_accuracy(&[]), 0.0);
+        assert_approx(weighted_accuracy(&[98.5]), 98.5);
+        assert_approx(weighted_accuracy(&[97.0; 50]), 97.0);
+        assert_approx(
+            weighted_accuracy(&[100.0, 90.0]),
+            (100.0 + 90.0 * 0.95) / 1.95,
+        );
+    }
+}
+
+mod recalculation {
+    use crate::RecalculationCheckpoint;
+    use domain_bancho::GameMode;
+    use std::path::PathBuf;
+
+    fn checkpoint_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("peace-recalculation-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_round_trip() {
+        let path = checkpoint_path("round_trip.json");
+
+        let mut checkpoint = RecalculationCheckpoint::new(
+            vec![GameMode::Standard, GameMode::TaikoRelax],
+            true,
+            false,
+        );
+        checkpoint.last_user_id = 1000;
+        checkpoint.save(&path).await.unwrap();
+
+        assert_eq!(
+            RecalculationCheckpoint::load(&path).await.unwrap(),
+            Some(checkpoint)
+        );
+
+        RecalculationCheckpoint::remove(&path).await.unwrap();
+        assert_eq!(RecalculationCheckpoint::load(&path).await.unwrap(), None);
+
+        // removing a missing checkpoint is fine
+        RecalculationCheckpoint::remove(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_load_checkpoint_of_older_versions() {
+        let path = checkpoint_path("older_version.json");
+        tokio::fs::create_dir_all(path.parent().unwrap()).await.unwrap();
+        tokio::fs::write(
+            &path,
+            r#"{"modes":[],"recalculate_scores":true,"last_user_id":42}"#,
+        )
+        .await
+        .unwrap();
+
+        let checkpoint =
+            RecalculationCheckpoint::load(&path).await.unwrap().unwrap();
+
+        assert_eq!(checkpoint.last_user_id, 42);
+        assert!(checkpoint.recalculate_scores);
+        assert!(!checkpoint.rebuild_leaderboards);
+
+        RecalculationCheckpoint::remove(&path).await.unwrap();
+    }
+}
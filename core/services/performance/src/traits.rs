@@ -0,0 +1,76 @@
+use super::error::PerformanceError;
+use async_trait::async_trait;
+use domain_bancho::GameMode;
+use pb_base::ExecSuccess;
+use pb_performance::{ScorePp, SubmittedScorePerformance, UserPerformance};
+use std::sync::Arc;
+
+pub type DynPerformanceService = Arc<dyn PerformanceService + Send + Sync>;
+
+pub trait PerformanceService:
+    CalculateScorePp + UpdateUserPerformance + RecalculatePerformances
+{
+}
+
+#[async_trait]
+pub trait CalculateScorePp {
+    /// Calculates the pp of the score in the table of `mode` and saves it,
+    /// then updates the pp leaderboards of its beatmap.
+    async fn calculate_score_pp(
+        &self,
+        mode: GameMode,
+        score_id: i64,
+    ) -> Result<ScorePp, PerformanceError>;
+}
+
+#[async_trait]
+pub trait UpdateUserPerformance {
+    /// Aggregates the pp and stats of the user in `mode` from their scores
+    /// and saves them.
+    async fn update_user_performance(
+        &self,
+        mode: GameMode,
+        user_id: i32,
+    ) -> Result<UserPerformance, PerformanceError>;
+
+    /// Updates the leaderboards of the beatmap of a newly submitted score and
+    /// calculates its pp, then updates the pp and stats of its user in the
+    /// same game mode.
+    async fn process_submitted_score(
+        &self,
+        mode: GameMode,
+        score_id: i64,
+    ) -> Result<SubmittedScorePerformance, PerformanceError>;
+
+    /// Updates the user in every game mode, or only in `mode`.
+    ///
+    /// With `recalculate_scores`, the pp of each of the user's scores is
+    /// calculated again first. With `rebuild_leaderboards`, the user's
+    /// leaderboard entries of every beatmap are rebuilt.
+    async fn recalculate_user(
+        &self,
+        user_id: i32,
+        mode: Option<GameMode>,
+        recalculate_scores: bool,
+        rebuild_leaderboards: bool,
+    ) -> Result<Vec<UserPerformance>, PerformanceError>;
+}
+
+#[async_trait]
+pub trait RecalculatePerformances {
+    /// Starts recalculating every user in the background, batch by batch.
+    ///
+    /// With `resume`, continues from the checkpoint of a stopped
+    /// recalculation with its original options.
+    async fn start_recalculation(
+        &self,
+        mode: Option<GameMode>,
+        recalculate_scores: bool,
+        rebuild_leaderboards: bool,
+        resume: bool,
+    ) -> Result<ExecSuccess, PerformanceError>;
+
+    /// The checkpoint is kept, the recalculation can be resumed later.
+    async fn stop_recalculation(&self)
+        -> Result<ExecSuccess, PerformanceError>;
+}
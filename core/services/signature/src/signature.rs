@@ -40,9 +40,9 @@ impl SignatureServiceBuilder {
         .ok()
         .map(|signer| I::from(signer).into_service());
 
-        if service.is_some() {
+        if let Some(service) = service {
             info!("Signature service init successful, type: \"Local\"");
-            return service.unwrap();
+            return service;
         }
 
         if let Some(cfg) = signature_rpc_config {
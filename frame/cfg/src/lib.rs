@@ -139,10 +139,10 @@ where
 /// * `$file_type` - The file type to use for serialization.
 /// * `$cfg` - The configuration to serialize.
 /// * `$(($typ: ident, $serde: ident, $fn: ident)),*` - A list of tuples
-///   representing each possible
-/// file format to use for serialization. Each tuple contains an identifier for
-/// the file format, an identifier for the corresponding serde library, and an
-/// identifier for the serde function to use for serialization.
+///   representing each possible file format to use for serialization. Each
+///   tuple contains an identifier for the file format, an identifier for the
+///   corresponding serde library, and an identifier for the serde function to
+///   use for serialization.
 macro_rules! cfg_to_string {
     ($file_type: expr, $cfg: expr, $(($typ: ident, $serde: ident, $fn: ident)),*) => {
         match $file_type {
@@ -1,3 +1,5 @@
+#![allow(clippy::result_large_err)]
+
 #[macro_use]
 extern crate peace_logs;
 
@@ -36,7 +38,7 @@ pub trait RpcApplication: Clone + Send + Sync + 'static {
     /// In order to implement reflection, the descriptor needs to be returned in
     /// this method.
     #[cfg(feature = "reflection")]
-    fn service_descriptors(&self) -> Option<&[DescriptorBuf]>;
+    fn service_descriptors(&self) -> Option<&[DescriptorBuf<'_>]>;
 
     fn default_listen_addr(&self) -> Option<SocketAddr> {
         None
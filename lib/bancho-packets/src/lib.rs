@@ -1059,7 +1059,7 @@ impl BanchoPacketLength for &str {
     #[inline]
     fn packet_len(&self) -> usize {
         if !self.is_empty() {
-            self.as_bytes().len() + 6
+            self.len() + 6
         } else {
             1
         }
@@ -1070,7 +1070,7 @@ impl BanchoPacketLength for String {
     #[inline]
     fn packet_len(&self) -> usize {
         if !self.is_empty() {
-            self.as_bytes().len() + 6
+            self.len() + 6
         } else {
             1
         }
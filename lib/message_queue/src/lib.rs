@@ -10,8 +10,10 @@ use std::{
 };
 use tokio::sync::RwLock;
 
+/// Checks the message is valid for the reader, or for any reader if `None`.
+/// Messages invalid for any reader are removed from the queue.
 pub type MessageValidator<T, K> =
-    Arc<dyn Fn(&Message<T, K>) -> bool + Sync + Send + 'static>;
+    Arc<dyn Fn(&Message<T, K>, Option<&K>) -> bool + Sync + Send + 'static>;
 
 pub trait MessageId: Clone + Eq + Ord {
     fn generate() -> Self;
@@ -55,7 +57,16 @@ impl<T, K> Message<T, K> {
     #[inline]
     pub fn is_valid(&self) -> bool {
         if let Some(validator) = &self.validator {
-            validator(self)
+            validator(self, None)
+        } else {
+            true
+        }
+    }
+
+    #[inline]
+    pub fn is_valid_for(&self, reader: &K) -> bool {
+        if let Some(validator) = &self.validator {
+            validator(self, Some(reader))
         } else {
             true
         }
@@ -350,7 +361,7 @@ where
         let mut received_msg_count = 0;
 
         for (msg_id, msg) in self.messages.range(from_msg_id..) {
-            if !msg.is_valid() {
+            if !msg.is_valid_for(reader) {
                 continue;
             }
 
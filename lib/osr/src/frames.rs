@@ -0,0 +1,150 @@
+use crate::ReplayError;
+use bitmask_enum::bitmask;
+use lzma_rs::compress::{Options, UnpackedSize};
+use std::{fmt, io::Cursor, str::FromStr};
+
+/// Time delta of the frame holding the RNG seed, the last frame of replays
+/// since `20130319`.
+pub const RNG_SEED_FRAME_TIME: i32 = -12345;
+
+/// Pressed buttons of a frame, pressing `K1` or `K2` also sets `M1` or `M2`.
+#[rustfmt::skip]
+#[bitmask(u32)]
+pub enum Keys {
+    M1      = 1 << 0,
+    M2      = 1 << 1,
+    K1      = 1 << 2,
+    K2      = 1 << 3,
+    Smoke   = 1 << 4,
+}
+
+/// `w|x|y|z`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayFrame {
+    /// Milliseconds since the previous frame.
+    pub time_delta: i32,
+    /// Cursor position in osu! pixels, in osu!mania `x` holds the pressed
+    /// columns bits instead.
+    pub x: f32,
+    pub y: f32,
+    /// Always empty in osu!mania.
+    pub keys: Keys,
+}
+
+impl ReplayFrame {
+    /// Pressed columns bits of an osu!mania frame.
+    #[inline]
+    pub fn mania_columns(&self) -> u32 {
+        self.x as u32
+    }
+}
+
+impl fmt::Display for ReplayFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}|{}",
+            self.time_delta,
+            self.x,
+            self.y,
+            self.keys.bits()
+        )
+    }
+}
+
+/// The LZMA compressed frames of a replay, also uploaded as it is by the
+/// client on score submission.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReplayData {
+    pub frames: Vec<ReplayFrame>,
+    /// Seed of the RNG used by the play, missing in old replays.
+    pub rng_seed: Option<i32>,
+}
+
+impl ReplayData {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty() && self.rng_seed.is_none()
+    }
+
+    /// Empty data is allowed, the game writes it for replays without frames.
+    pub fn decompress(compressed: &[u8]) -> Result<Self, ReplayError> {
+        if compressed.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut decompressed = Vec::new();
+        lzma_rs::lzma_decompress(
+            &mut Cursor::new(compressed),
+            &mut decompressed,
+        )?;
+
+        String::from_utf8_lossy(&decompressed).parse()
+    }
+
+    /// The encoder only writes literals, the output is larger than the
+    /// game's but can be read by any LZMA decoder.
+    pub fn compress(&self) -> Result<Vec<u8>, ReplayError> {
+        let raw = self.to_string();
+        let options = Options {
+            unpacked_size: UnpackedSize::WriteToHeader(Some(raw.len() as u64)),
+        };
+
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress_with_options(
+            &mut raw.as_bytes(),
+            &mut compressed,
+            &options,
+        )?;
+
+        Ok(compressed)
+    }
+}
+
+impl FromStr for ReplayData {
+    type Err = ReplayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut data = Self::default();
+
+        for frame in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let invalid = || ReplayError::InvalidFrame(frame.to_owned());
+
+            let mut split = frame.split('|');
+            let mut next = || split.next().ok_or_else(invalid);
+
+            let time_delta = next()?.parse().map_err(|_| invalid())?;
+            let x = next()?.parse().map_err(|_| invalid())?;
+            let y = next()?.parse().map_err(|_| invalid())?;
+            let z = next()?;
+
+            if time_delta == RNG_SEED_FRAME_TIME {
+                data.rng_seed = Some(z.parse().map_err(|_| invalid())?);
+                continue;
+            }
+
+            data.frames.push(ReplayFrame {
+                time_delta,
+                x,
+                y,
+                keys: Keys::from(z.parse::<u32>().map_err(|_| invalid())?),
+            });
+        }
+
+        Ok(data)
+    }
+}
+
+impl fmt::Display for ReplayData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for frame in &self.frames {
+            write!(f, "{frame},")?;
+        }
+
+        if let Some(seed) = self.rng_seed {
+            write!(f, "{RNG_SEED_FRAME_TIME}|0|0|{seed},")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//! osu! replay (`.osr`) file parsing & writing.
+//!
+//! ```ignore
+//! let mut replay = Replay::parse(&bytes)?;
+//! replay.player_name = "Peace".to_owned();
+//! std::fs::write("replay.osr", replay.write()?)?;
+//! ```
+//!
+//! Strings are encoded as in bancho packets, with an uleb128 length.
+
+pub mod frames;
+pub mod life_bar;
+
+#[cfg(test)]
+mod tests;
+
+pub use frames::{Keys, ReplayData, ReplayFrame};
+pub use life_bar::LifeBarPoint;
+
+use bancho_packets::{BanchoPacketRead, BanchoPacketWrite, PayloadReader};
+
+/// Mods bit of Target Practice, which appends the accuracy to the file.
+pub const TARGET_PRACTICE: u32 = 1 << 23;
+
+/// Replays older than this version store the online score id as an `i32`.
+pub const LONG_SCORE_ID_VERSION: i32 = 20140721;
+
+const TICKS_PER_SECOND: i64 = 10_000_000;
+/// Windows ticks of `1970-01-01T00:00:00Z`.
+const UNIX_EPOCH_TICKS: i64 = 621_355_968_000_000_000;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ReplayError {
+    #[error("missing or invalid field: {0}")]
+    InvalidField(&'static str),
+    #[error("invalid replay frame: {0}")]
+    InvalidFrame(String),
+    #[error("failed to decompress replay data: {0}")]
+    Decompress(#[from] lzma_rs::error::Error),
+    #[error("failed to compress replay data: {0}")]
+    Compress(#[from] std::io::Error),
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Replay {
+    /// `0` osu!, `1` osu!taiko, `2` osu!catch, `3` osu!mania.
+    pub mode: u8,
+    /// Version of the game which created the replay, e.g. `20230326`.
+    pub version: i32,
+    pub beatmap_md5: String,
+    pub player_name: String,
+    pub replay_md5: String,
+    pub n300: u16,
+    pub n100: u16,
+    pub n50: u16,
+    pub ngeki: u16,
+    pub nkatu: u16,
+    pub nmiss: u16,
+    pub score: i32,
+    pub max_combo: u16,
+    pub perfect: bool,
+    pub mods: u32,
+    pub life_bar: Vec<LifeBarPoint>,
+    /// Windows ticks (100ns since `0001-01-01`) of when the play was set.
+    pub timestamp: i64,
+    pub data: ReplayData,
+    /// `0` if the score was not submitted.
+    pub online_score_id: i64,
+    /// Total accuracy of all hits, only written with Target Practice.
+    pub target_practice_accuracy: Option<f64>,
+}
+
+impl Replay {
+    pub fn parse(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = PayloadReader::new(bytes);
+
+        let mode = read(&mut reader, "mode")?;
+        let version = read(&mut reader, "version")?;
+        let beatmap_md5 = read_string(&mut reader, "beatmap_md5")?;
+        let player_name = read_string(&mut reader, "player_name")?;
+        let replay_md5 = read_string(&mut reader, "replay_md5")?;
+        let n300 = read(&mut reader, "n300")?;
+        let n100 = read(&mut reader, "n100")?;
+        let n50 = read(&mut reader, "n50")?;
+        let ngeki = read(&mut reader, "ngeki")?;
+        let nkatu = read(&mut reader, "nkatu")?;
+        let nmiss = read(&mut reader, "nmiss")?;
+        let score = read(&mut reader, "score")?;
+        let max_combo = read(&mut reader, "max_combo")?;
+        let perfect = read::<u8>(&mut reader, "perfect")? != 0;
+        let mods = read(&mut reader, "mods")?;
+        let life_bar = read_string(&mut reader, "life_bar")?;
+        let timestamp = read(&mut reader, "timestamp")?;
+
+        let data_len = read::<i32>(&mut reader, "data_length")?;
+        let data_end = usize::try_from(data_len)
+            .ok()
+            .and_then(|len| reader.index().checked_add(len))
+            .filter(|end| *end <= bytes.len())
+            .ok_or(ReplayError::InvalidField("data_length"))?;
+        let data = ReplayData::decompress(&bytes[reader.index()..data_end])?;
+
+        // The reader can't skip the compressed data, continue after it
+        let mut reader = PayloadReader::new(&bytes[data_end..]);
+
+        let online_score_id = if version >= LONG_SCORE_ID_VERSION {
+            read(&mut reader, "online_score_id")?
+        } else {
+            read::<i32>(&mut reader, "online_score_id")? as i64
+        };
+
+        let target_practice_accuracy = if mods & TARGET_PRACTICE != 0 {
+            Some(f64::from_bits(read(&mut reader, "target_practice_accuracy")?))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            mode,
+            version,
+            beatmap_md5,
+            player_name,
+            replay_md5,
+            n300,
+            n100,
+            n50,
+            ngeki,
+            nkatu,
+            nmiss,
+            score,
+            max_combo,
+            perfect,
+            mods,
+            life_bar: LifeBarPoint::parse_all(&life_bar),
+            timestamp,
+            data,
+            online_score_id,
+            target_practice_accuracy,
+        })
+    }
+
+    pub fn write(&self) -> Result<Vec<u8>, ReplayError> {
+        let data = if self.data.is_empty() {
+            Vec::new()
+        } else {
+            self.data.compress()?
+        };
+
+        let mut buf = Vec::with_capacity(data.len() + 256);
+
+        self.mode.write_into_buf(&mut buf);
+        self.version.write_into_buf(&mut buf);
+        self.beatmap_md5.as_str().write_into_buf(&mut buf);
+        self.player_name.as_str().write_into_buf(&mut buf);
+        self.replay_md5.as_str().write_into_buf(&mut buf);
+        self.n300.write_into_buf(&mut buf);
+        self.n100.write_into_buf(&mut buf);
+        self.n50.write_into_buf(&mut buf);
+        self.ngeki.write_into_buf(&mut buf);
+        self.nkatu.write_into_buf(&mut buf);
+        self.nmiss.write_into_buf(&mut buf);
+        self.score.write_into_buf(&mut buf);
+        self.max_combo.write_into_buf(&mut buf);
+        (self.perfect as u8).write_into_buf(&mut buf);
+        self.mods.write_into_buf(&mut buf);
+        LifeBarPoint::write_all(&self.life_bar).write_into_buf(&mut buf);
+        self.timestamp.write_into_buf(&mut buf);
+        (data.len() as i32).write_into_buf(&mut buf);
+        data.write_into_buf(&mut buf);
+
+        if self.version >= LONG_SCORE_ID_VERSION {
+            self.online_score_id.write_into_buf(&mut buf);
+        } else {
+            (self.online_score_id as i32).write_into_buf(&mut buf);
+        }
+
+        if self.mods & TARGET_PRACTICE != 0 {
+            self.target_practice_accuracy
+                .unwrap_or_default()
+                .write_into_buf(&mut buf);
+        }
+
+        Ok(buf)
+    }
+
+    /// Seconds since the unix epoch of when the play was set.
+    #[inline]
+    pub fn unix_timestamp(&self) -> i64 {
+        (self.timestamp - UNIX_EPOCH_TICKS) / TICKS_PER_SECOND
+    }
+
+    #[inline]
+    pub fn set_unix_timestamp(&mut self, secs: i64) {
+        self.timestamp = secs * TICKS_PER_SECOND + UNIX_EPOCH_TICKS;
+    }
+}
+
+#[inline]
+fn read<T: BanchoPacketRead<T>>(
+    reader: &mut PayloadReader,
+    field: &'static str,
+) -> Result<T, ReplayError> {
+    reader.read().ok_or(ReplayError::InvalidField(field))
+}
+
+/// Empty strings are a single `0x00` byte, rejected by the packet reader.
+#[inline]
+fn read_string(
+    reader: &mut PayloadReader,
+    field: &'static str,
+) -> Result<String, ReplayError> {
+    if reader.payload().get(reader.index()) == Some(&0) {
+        read::<u8>(reader, field)?;
+        return Ok(String::new());
+    }
+
+    read(reader, field)
+}
@@ -0,0 +1,35 @@
+use std::fmt;
+
+/// `u|v`, the life bar graph shown on the results screen.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LifeBarPoint {
+    /// Milliseconds since the start of the song.
+    pub time: i32,
+    /// From `0` (empty) to `1` (full).
+    pub life: f32,
+}
+
+impl LifeBarPoint {
+    #[inline]
+    pub fn parse(point: &str) -> Option<Self> {
+        let (time, life) = point.trim().split_once('|')?;
+
+        Some(Self { time: time.parse().ok()?, life: life.parse().ok()? })
+    }
+
+    /// The graph is only drawn by the game, malformed points are ignored.
+    #[inline]
+    pub fn parse_all(life_bar: &str) -> Vec<Self> {
+        life_bar.split(',').filter_map(Self::parse).collect()
+    }
+
+    pub fn write_all(points: &[Self]) -> String {
+        points.iter().map(|point| format!("{point},")).collect()
+    }
+}
+
+impl fmt::Display for LifeBarPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}|{}", self.time, self.life)
+    }
+}
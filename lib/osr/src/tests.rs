@@ -0,0 +1,137 @@
+mod parsing {
+    use crate::{Keys, Replay, ReplayData, ReplayError};
+
+    const V20230326_OSU: &[u8] = include_bytes!("../samples/v20230326_osu.osr");
+
+    #[test]
+    fn test_header() {
+        let replay = Replay::parse(V20230326_OSU).unwrap();
+
+        assert_eq!(replay.mode, 0);
+        assert_eq!(replay.version, 20230326);
+        assert_eq!(replay.beatmap_md5, "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(replay.player_name, "Peace");
+        assert_eq!(
+            (replay.n300, replay.n100, replay.n50, replay.nmiss),
+            (412, 23, 1, 2)
+        );
+        assert_eq!((replay.ngeki, replay.nkatu), (87, 12));
+        assert_eq!(replay.score, 7654321);
+        assert_eq!(replay.max_combo, 689);
+        assert!(!replay.perfect);
+        assert_eq!(replay.mods, 8 | 64);
+        assert_eq!(replay.unix_timestamp(), 1680105600);
+        assert_eq!(replay.online_score_id, 4489123456);
+        assert_eq!(replay.target_practice_accuracy, None);
+
+        assert_eq!(replay.life_bar.len(), 4);
+        assert_eq!(replay.life_bar[1].time, 1500);
+        assert_eq!(replay.life_bar[1].life, 0.98);
+    }
+
+    #[test]
+    fn test_frames() {
+        let replay = Replay::parse(V20230326_OSU).unwrap();
+        let frames = &replay.data.frames;
+
+        assert_eq!(frames.len(), 209);
+        assert_eq!(replay.data.rng_seed, Some(1337420));
+
+        assert_eq!(frames[1].time_delta, -1);
+        assert_eq!((frames[2].x, frames[2].y), (252.5, 190.25));
+        assert_eq!(frames[3].keys, Keys::M1 | Keys::K1);
+        assert!(frames[6].keys.contains(Keys::K2));
+        assert_eq!(frames[8].keys, Keys::Smoke);
+    }
+
+    #[test]
+    fn test_mania_frames() {
+        let data = "0|0|0|0,20|5|0|0,".parse::<ReplayData>().unwrap();
+
+        assert_eq!(data.rng_seed, None);
+        assert_eq!(data.frames[1].mania_columns(), 0b101);
+        assert!(data.frames[1].keys.is_none());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            Replay::parse(&V20230326_OSU[..60]),
+            Err(ReplayError::InvalidField(_))
+        ));
+        assert!(matches!(
+            "16|256|192|0,17|256".parse::<ReplayData>(),
+            Err(ReplayError::InvalidFrame(frame)) if frame == "17|256"
+        ));
+        assert!(matches!(
+            ReplayData::decompress(b"not lzma"),
+            Err(ReplayError::Decompress(_))
+        ));
+    }
+}
+
+mod round_trip {
+    use crate::{
+        Keys, LifeBarPoint, Replay, ReplayData, ReplayFrame, TARGET_PRACTICE,
+    };
+
+    const V20230326_OSU: &[u8] = include_bytes!("../samples/v20230326_osu.osr");
+
+    #[test]
+    fn test_round_trip_sample() {
+        let parsed = Replay::parse(V20230326_OSU).unwrap();
+        let written = parsed.write().unwrap();
+
+        assert_eq!(Replay::parse(&written).unwrap(), parsed);
+    }
+
+    #[test]
+    fn test_frames_text() {
+        let raw = "0|256|-500|0,-1|256|-500|0,16|252.5|190.25|5,\
+                   17|281.3333|180|10,-12345|0|0|1337420,";
+        let data = raw.parse::<ReplayData>().unwrap();
+
+        assert_eq!(data.to_string(), raw);
+        assert_eq!(
+            ReplayData::decompress(&data.compress().unwrap()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_round_trip_old_version_and_target_practice() {
+        let mut replay = Replay {
+            mode: 2,
+            version: 20131216,
+            player_name: "Peace".to_owned(),
+            mods: TARGET_PRACTICE,
+            life_bar: vec![LifeBarPoint { time: 0, life: 1.0 }],
+            data: ReplayData {
+                frames: vec![ReplayFrame {
+                    time_delta: 16,
+                    x: 100.0,
+                    y: 0.0,
+                    keys: Keys::M2 | Keys::K2,
+                }],
+                rng_seed: None,
+            },
+            online_score_id: 123456,
+            target_practice_accuracy: Some(0.9876),
+            ..Default::default()
+        };
+        replay.set_unix_timestamp(1680105600);
+
+        let parsed = Replay::parse(&replay.write().unwrap()).unwrap();
+
+        assert_eq!(parsed, replay);
+        assert_eq!(parsed.unix_timestamp(), 1680105600);
+    }
+
+    #[test]
+    fn test_empty_data() {
+        let replay = Replay { version: 20230326, ..Default::default() };
+        let written = replay.write().unwrap();
+
+        assert_eq!(Replay::parse(&written).unwrap(), replay);
+    }
+}
@@ -0,0 +1,345 @@
+use crate::Section;
+use std::fmt;
+
+/// Type bits of a hit object.
+pub const CIRCLE: u8 = 1 << 0;
+pub const SLIDER: u8 = 1 << 1;
+pub const NEW_COMBO: u8 = 1 << 2;
+pub const SPINNER: u8 = 1 << 3;
+pub const COMBO_SKIP_SHIFT: u8 = 4;
+pub const COMBO_SKIP_MASK: u8 = 0b111 << COMBO_SKIP_SHIFT;
+pub const HOLD: u8 = 1 << 7;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Position {
+    #[inline]
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CurveType {
+    #[default]
+    Bezier,
+    Catmull,
+    Linear,
+    PerfectCurve,
+}
+
+impl CurveType {
+    #[inline]
+    pub fn from_letter(letter: &str) -> Option<Self> {
+        match letter {
+            "B" => Some(Self::Bezier),
+            "C" => Some(Self::Catmull),
+            "L" => Some(Self::Linear),
+            "P" => Some(Self::PerfectCurve),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn letter(&self) -> char {
+        match self {
+            Self::Bezier => 'B',
+            Self::Catmull => 'C',
+            Self::Linear => 'L',
+            Self::PerfectCurve => 'P',
+        }
+    }
+}
+
+/// `curveType|curvePoints,slides,length,edgeSounds,edgeSets`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Slider {
+    pub curve_type: CurveType,
+    /// Absolute positions, without the head of the slider.
+    pub curve_points: Vec<Position>,
+    /// Repeats + 1.
+    pub slides: i32,
+    /// Length in osu! pixels, missing in some old beatmaps.
+    pub length: Option<f64>,
+    /// Hitsound of the head, every repeat and the tail.
+    pub edge_sounds: Option<Vec<u8>>,
+    /// `(normalSet, additionSet)` of the head, every repeat and the tail.
+    pub edge_sets: Option<Vec<(u8, u8)>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HitObjectKind {
+    Circle,
+    Slider(Slider),
+    Spinner {
+        end_time: f64,
+    },
+    /// osu!mania hold note.
+    Hold {
+        end_time: f64,
+    },
+}
+
+/// `x,y,time,type,hitSound,objectParams,hitSample`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitObject {
+    pub x: f64,
+    pub y: f64,
+    pub time: f64,
+    pub new_combo: bool,
+    /// Combo colours skipped by a new combo.
+    pub combo_skip: u8,
+    pub hit_sound: u8,
+    pub kind: HitObjectKind,
+    /// `normalSet:additionSet:index:volume:filename`, kept as it is since
+    /// old beatmaps omit any of the trailing fields.
+    pub hit_sample: Option<String>,
+}
+
+impl HitObject {
+    #[inline]
+    pub fn is_circle(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Circle)
+    }
+
+    #[inline]
+    pub fn is_slider(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Slider(_))
+    }
+
+    #[inline]
+    pub fn is_spinner(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Spinner { .. })
+    }
+
+    #[inline]
+    pub fn is_hold(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Hold { .. })
+    }
+
+    /// Sliders need the timing points to know their end time, so this is
+    /// the start time for them.
+    #[inline]
+    pub fn end_time(&self) -> f64 {
+        match self.kind {
+            HitObjectKind::Spinner { end_time }
+            | HitObjectKind::Hold { end_time } => end_time,
+            _ => self.time,
+        }
+    }
+
+    #[inline]
+    pub fn type_bits(&self) -> u8 {
+        let kind = match self.kind {
+            HitObjectKind::Circle => CIRCLE,
+            HitObjectKind::Slider(_) => SLIDER,
+            HitObjectKind::Spinner { .. } => SPINNER,
+            HitObjectKind::Hold { .. } => HOLD,
+        };
+
+        let new_combo = if self.new_combo { NEW_COMBO } else { 0 };
+
+        kind | new_combo
+            | (self.combo_skip << COMBO_SKIP_SHIFT) & COMBO_SKIP_MASK
+    }
+
+    /// Malformed hit objects are `None`, the game skips them as well.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut split = line.split(',').map(str::trim);
+
+        let x = split.next()?.parse().ok()?;
+        let y = split.next()?.parse().ok()?;
+        let time = split.next()?.parse().ok()?;
+        let type_bits = split.next()?.parse::<u8>().ok()?;
+        let hit_sound = split.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+
+        let (kind, hit_sample) = if type_bits & CIRCLE != 0 {
+            (HitObjectKind::Circle, split.next())
+        } else if type_bits & SLIDER != 0 {
+            let slider = parse_slider(&mut split)?;
+
+            (HitObjectKind::Slider(slider), split.next())
+        } else if type_bits & SPINNER != 0 {
+            let end_time =
+                split.next().and_then(|s| s.parse().ok()).unwrap_or(time);
+
+            (HitObjectKind::Spinner { end_time }, split.next())
+        } else if type_bits & HOLD != 0 {
+            // The hit sample follows the end time: `endTime:hitSample`
+            let field = split.next().unwrap_or_default();
+            let (end_time, hit_sample) = match field.split_once(':') {
+                Some((end_time, hit_sample)) => (end_time, Some(hit_sample)),
+                None => (field, None),
+            };
+
+            (
+                HitObjectKind::Hold {
+                    end_time: end_time.parse().unwrap_or(time),
+                },
+                hit_sample,
+            )
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            x,
+            y,
+            time,
+            new_combo: type_bits & NEW_COMBO != 0,
+            combo_skip: (type_bits & COMBO_SKIP_MASK) >> COMBO_SKIP_SHIFT,
+            hit_sound,
+            kind,
+            hit_sample: hit_sample.map(str::to_owned),
+        })
+    }
+}
+
+fn parse_slider<'a>(
+    split: &mut impl Iterator<Item = &'a str>,
+) -> Option<Slider> {
+    let mut points = split.next()?.split('|').peekable();
+
+    // Very old sliders may start with a point instead of the curve type
+    let curve_type = points.next_if(|s| CurveType::from_letter(s).is_some());
+    let curve_type =
+        curve_type.and_then(CurveType::from_letter).unwrap_or_default();
+
+    let curve_points = points
+        .filter_map(|point| {
+            let (x, y) = point.split_once(':')?;
+
+            Some(Position::new(x.parse().ok()?, y.parse().ok()?))
+        })
+        .collect();
+
+    let slides = split.next()?.parse().ok()?;
+    let length = split.next().and_then(|s| s.parse().ok());
+
+    let edge_sounds = split
+        .next()
+        .map(|s| s.split('|').filter_map(|s| s.parse().ok()).collect());
+
+    let edge_sets = split.next().map(|s| {
+        s.split('|')
+            .filter_map(|set| {
+                let (normal, addition) = set.split_once(':')?;
+
+                Some((normal.parse().ok()?, addition.parse().ok()?))
+            })
+            .collect()
+    });
+
+    Some(Slider {
+        curve_type,
+        curve_points,
+        slides,
+        length,
+        edge_sounds,
+        edge_sets,
+    })
+}
+
+impl fmt::Display for HitObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{},{},{},{},{}",
+            self.x,
+            self.y,
+            self.time,
+            self.type_bits(),
+            self.hit_sound
+        )?;
+
+        match &self.kind {
+            HitObjectKind::Circle => {},
+            HitObjectKind::Slider(slider) => write!(f, ",{slider}")?,
+            HitObjectKind::Spinner { end_time } => write!(f, ",{end_time}")?,
+            HitObjectKind::Hold { end_time } => {
+                write!(f, ",{end_time}")?;
+
+                // Joined with the end time instead of a comma
+                if let Some(hit_sample) = &self.hit_sample {
+                    write!(f, ":{hit_sample}")?;
+                }
+
+                return Ok(());
+            },
+        }
+
+        if let Some(hit_sample) = &self.hit_sample {
+            write!(f, ",{hit_sample}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Slider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.curve_type.letter())?;
+
+        for point in &self.curve_points {
+            write!(f, "|{}:{}", point.x, point.y)?;
+        }
+
+        write!(f, ",{}", self.slides)?;
+
+        let Some(length) = self.length else {
+            return Ok(());
+        };
+        write!(f, ",{length}")?;
+
+        let Some(edge_sounds) = &self.edge_sounds else {
+            return Ok(());
+        };
+        f.write_str(",")?;
+        for (i, sound) in edge_sounds.iter().enumerate() {
+            if i > 0 {
+                f.write_str("|")?;
+            }
+            write!(f, "{sound}")?;
+        }
+
+        let Some(edge_sets) = &self.edge_sets else {
+            return Ok(());
+        };
+        f.write_str(",")?;
+        for (i, (normal, addition)) in edge_sets.iter().enumerate() {
+            if i > 0 {
+                f.write_str("|")?;
+            }
+            write!(f, "{normal}:{addition}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `[HitObjects]`
+impl Section for Vec<HitObject> {
+    #[inline]
+    fn parse_line(&mut self, line: &str) {
+        if let Some(hit_object) = HitObject::parse(line) {
+            self.push(hit_object);
+        }
+    }
+
+    fn write(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        for hit_object in self {
+            writeln!(f, "{hit_object}")?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        <[HitObject]>::is_empty(self)
+    }
+}
@@ -0,0 +1,169 @@
+use crate::GameMode;
+use std::fmt;
+
+/// A value of a `Key: Value` line.
+pub trait Value: Sized {
+    fn parse_value(s: &str) -> Option<Self>;
+
+    fn write_value(&self, f: &mut impl fmt::Write) -> fmt::Result;
+}
+
+impl Value for String {
+    #[inline]
+    fn parse_value(s: &str) -> Option<Self> {
+        Some(s.to_owned())
+    }
+
+    #[inline]
+    fn write_value(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(self)
+    }
+}
+
+impl Value for i32 {
+    #[inline]
+    fn parse_value(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+
+    #[inline]
+    fn write_value(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl Value for f64 {
+    #[inline]
+    fn parse_value(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+
+    #[inline]
+    fn write_value(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// Booleans are written as `0` or `1`.
+impl Value for bool {
+    #[inline]
+    fn parse_value(s: &str) -> Option<Self> {
+        match s {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn write_value(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(if *self { "1" } else { "0" })
+    }
+}
+
+impl Value for GameMode {
+    #[inline]
+    fn parse_value(s: &str) -> Option<Self> {
+        s.parse().ok().and_then(Self::from_id)
+    }
+
+    #[inline]
+    fn write_value(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "{}", *self as i32)
+    }
+}
+
+/// Comma separated lists, e.g. the editor bookmarks.
+impl Value for Vec<i32> {
+    #[inline]
+    fn parse_value(s: &str) -> Option<Self> {
+        s.split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.trim().parse().ok())
+            .collect()
+    }
+
+    fn write_value(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        for (i, value) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            write!(f, "{value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Declares a section made of `Key: Value` lines.
+///
+/// Every known key is an optional field, so a parsed section is written back
+/// with exactly the keys it was read with. Unknown keys are kept in `extra`.
+macro_rules! key_value_section {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident, separator = $separator:literal {
+            $(
+                $(#[$field_meta:meta])*
+                $key:literal => $field:ident: $ty:ty,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, PartialEq)]
+        pub struct $name {
+            $(
+                $(#[$field_meta])*
+                pub $field: Option<$ty>,
+            )*
+            /// Keys unknown to this parser, written back after the known ones.
+            pub extra: Vec<(String, String)>,
+        }
+
+        impl $crate::Section for $name {
+            fn parse_line(&mut self, line: &str) {
+                let Some((key, value)) = line.split_once(':') else {
+                    return;
+                };
+                let (key, value) = (key.trim(), value.trim());
+
+                match key {
+                    $(
+                        $key => {
+                            if let Some(value) =
+                                <$ty as $crate::key_value::Value>::parse_value(
+                                    value,
+                                )
+                            {
+                                self.$field = Some(value);
+                            }
+                        },
+                    )*
+                    _ => self.extra.push((key.to_owned(), value.to_owned())),
+                }
+            }
+
+            fn write(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+                $(
+                    if let Some(value) = &self.$field {
+                        f.write_str(concat!($key, $separator))?;
+                        $crate::key_value::Value::write_value(value, f)?;
+                        f.write_char('\n')?;
+                    }
+                )*
+
+                for (key, value) in &self.extra {
+                    writeln!(f, "{key}{}{value}", $separator)?;
+                }
+
+                Ok(())
+            }
+
+            fn is_empty(&self) -> bool {
+                $(self.$field.is_none() &&)* self.extra.is_empty()
+            }
+        }
+    };
+}
+
+pub(crate) use key_value_section;
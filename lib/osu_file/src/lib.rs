@@ -0,0 +1,243 @@
+//! osu! beatmap (`.osu`) file parsing & writing, file format v3 - v14.
+//!
+//! ```ignore
+//! let mut file = OsuFile::parse(&bytes)?;
+//! file.metadata.version = Some("Insane".to_owned());
+//! std::fs::write("beatmap.osu", file.to_string())?;
+//! ```
+//!
+//! Parsed values are kept as they are written in the file, e.g. the timing
+//! of beatmaps older than v5 is not adjusted.
+
+pub mod hit_objects;
+pub mod key_value;
+pub mod sections;
+pub mod timing_points;
+
+#[cfg(test)]
+mod tests;
+
+pub use hit_objects::{CurveType, HitObject, HitObjectKind, Position, Slider};
+pub use sections::{Colours, Difficulty, Editor, Events, General, Metadata};
+pub use timing_points::TimingPoint;
+
+use std::{fmt, str::FromStr};
+
+/// File format version written by the current osu! stable client.
+pub const LATEST_FORMAT_VERSION: i32 = 14;
+
+const FORMAT_VERSION_PREFIX: &str = "osu file format v";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameMode {
+    #[default]
+    Osu = 0,
+    Taiko = 1,
+    Catch = 2,
+    Mania = 3,
+}
+
+impl GameMode {
+    #[inline]
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::Osu),
+            1 => Some(Self::Taiko),
+            2 => Some(Self::Catch),
+            3 => Some(Self::Mania),
+            _ => None,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ParseError {
+    #[error("missing file format header")]
+    MissingFormatVersion,
+    #[error("invalid game mode: {0}")]
+    InvalidGameMode(String),
+}
+
+/// A section of the file, parsed line by line.
+pub trait Section {
+    /// Lines that can't be parsed are ignored, as the game does.
+    fn parse_line(&mut self, line: &str);
+
+    fn write(&self, f: &mut impl fmt::Write) -> fmt::Result;
+
+    /// Empty sections are not written.
+    fn is_empty(&self) -> bool;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OsuFile {
+    pub version: i32,
+    pub general: General,
+    pub editor: Editor,
+    pub metadata: Metadata,
+    pub difficulty: Difficulty,
+    pub events: Events,
+    pub timing_points: Vec<TimingPoint>,
+    pub colours: Colours,
+    pub hit_objects: Vec<HitObject>,
+}
+
+impl Default for OsuFile {
+    fn default() -> Self {
+        Self {
+            version: LATEST_FORMAT_VERSION,
+            general: General::default(),
+            editor: Editor::default(),
+            metadata: Metadata::default(),
+            difficulty: Difficulty::default(),
+            events: Events::default(),
+            timing_points: Vec::new(),
+            colours: Colours::default(),
+            hit_objects: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SectionKind {
+    General,
+    Editor,
+    Metadata,
+    Difficulty,
+    Events,
+    TimingPoints,
+    Colours,
+    HitObjects,
+    Unknown,
+}
+
+impl SectionKind {
+    #[inline]
+    fn from_header(header: &str) -> Self {
+        match header {
+            "General" => Self::General,
+            "Editor" => Self::Editor,
+            "Metadata" => Self::Metadata,
+            "Difficulty" => Self::Difficulty,
+            "Events" => Self::Events,
+            "TimingPoints" => Self::TimingPoints,
+            "Colours" => Self::Colours,
+            "HitObjects" => Self::HitObjects,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl OsuFile {
+    /// Files are not always valid UTF-8, invalid characters are replaced.
+    #[inline]
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        String::from_utf8_lossy(bytes).parse()
+    }
+
+    /// Game mode of the beatmap, osu!standard if it is not set.
+    #[inline]
+    pub fn mode(&self) -> GameMode {
+        self.general.mode.unwrap_or_default()
+    }
+}
+
+impl FromStr for OsuFile {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+
+        let version = lines
+            .by_ref()
+            .map(|line| line.trim().trim_start_matches('\u{feff}'))
+            .find(|line| !line.is_empty())
+            .and_then(|line| line.strip_prefix(FORMAT_VERSION_PREFIX))
+            .and_then(|version| version.trim().parse().ok())
+            .ok_or(ParseError::MissingFormatVersion)?;
+
+        let mut file = Self { version, ..Default::default() };
+        let mut section = SectionKind::Unknown;
+
+        for raw_line in lines {
+            let line = raw_line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                section = SectionKind::from_header(&line[1..line.len() - 1]);
+                continue;
+            }
+
+            // Storyboard commands are nested by their indentation and the
+            // layers are separated by comments, so events are kept as is
+            if section == SectionKind::Events {
+                file.events.parse_line(raw_line.trim_end());
+                continue;
+            }
+
+            if line.starts_with("//") {
+                continue;
+            }
+
+            if section == SectionKind::General {
+                if let Some(("Mode", value)) = line
+                    .split_once(':')
+                    .map(|(key, value)| (key.trim(), value.trim()))
+                {
+                    if value.parse().ok().and_then(GameMode::from_id).is_none()
+                    {
+                        return Err(ParseError::InvalidGameMode(
+                            value.to_owned(),
+                        ));
+                    }
+                }
+            }
+
+            match section {
+                SectionKind::General => file.general.parse_line(line),
+                SectionKind::Editor => file.editor.parse_line(line),
+                SectionKind::Metadata => file.metadata.parse_line(line),
+                SectionKind::Difficulty => file.difficulty.parse_line(line),
+                SectionKind::TimingPoints => {
+                    file.timing_points.parse_line(line)
+                },
+                SectionKind::Colours => file.colours.parse_line(line),
+                SectionKind::HitObjects => file.hit_objects.parse_line(line),
+                SectionKind::Events | SectionKind::Unknown => {},
+            }
+        }
+
+        Ok(file)
+    }
+}
+
+impl fmt::Display for OsuFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{FORMAT_VERSION_PREFIX}{}", self.version)?;
+
+        write_section(f, "General", &self.general)?;
+        write_section(f, "Editor", &self.editor)?;
+        write_section(f, "Metadata", &self.metadata)?;
+        write_section(f, "Difficulty", &self.difficulty)?;
+        write_section(f, "Events", &self.events)?;
+        write_section(f, "TimingPoints", &self.timing_points)?;
+        write_section(f, "Colours", &self.colours)?;
+        write_section(f, "HitObjects", &self.hit_objects)
+    }
+}
+
+fn write_section(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    section: &impl Section,
+) -> fmt::Result {
+    if section.is_empty() {
+        return Ok(());
+    }
+
+    write!(f, "\n[{name}]\n")?;
+    section.write(f)
+}
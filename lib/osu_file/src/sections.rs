@@ -0,0 +1,128 @@
+use crate::{key_value::key_value_section, GameMode, Section};
+use std::fmt;
+
+key_value_section! {
+    /// `[General]`
+    pub struct General, separator = ": " {
+        "AudioFilename" => audio_filename: String,
+        "AudioLeadIn" => audio_lead_in: i32,
+        /// Deprecated.
+        "AudioHash" => audio_hash: String,
+        "PreviewTime" => preview_time: i32,
+        "Countdown" => countdown: i32,
+        /// `Normal`, `Soft` or `Drum`.
+        "SampleSet" => sample_set: String,
+        "StackLeniency" => stack_leniency: f64,
+        "Mode" => mode: GameMode,
+        "LetterboxInBreaks" => letterbox_in_breaks: bool,
+        "StoryFireInFront" => story_fire_in_front: bool,
+        "UseSkinSprites" => use_skin_sprites: bool,
+        "AlwaysShowPlayfield" => always_show_playfield: bool,
+        "OverlayPosition" => overlay_position: String,
+        "SkinPreference" => skin_preference: String,
+        "EpilepsyWarning" => epilepsy_warning: bool,
+        "CountdownOffset" => countdown_offset: i32,
+        "SpecialStyle" => special_style: bool,
+        "WidescreenStoryboard" => widescreen_storyboard: bool,
+        "SamplesMatchPlaybackRate" => samples_match_playback_rate: bool,
+    }
+}
+
+key_value_section! {
+    /// `[Editor]`
+    pub struct Editor, separator = ": " {
+        "Bookmarks" => bookmarks: Vec<i32>,
+        "DistanceSpacing" => distance_spacing: f64,
+        "BeatDivisor" => beat_divisor: i32,
+        "GridSize" => grid_size: i32,
+        "TimelineZoom" => timeline_zoom: f64,
+    }
+}
+
+key_value_section! {
+    /// `[Metadata]`
+    pub struct Metadata, separator = ":" {
+        "Title" => title: String,
+        "TitleUnicode" => title_unicode: String,
+        "Artist" => artist: String,
+        "ArtistUnicode" => artist_unicode: String,
+        "Creator" => creator: String,
+        /// Difficulty name.
+        "Version" => version: String,
+        "Source" => source: String,
+        /// Space separated.
+        "Tags" => tags: String,
+        "BeatmapID" => beatmap_id: i32,
+        "BeatmapSetID" => beatmapset_id: i32,
+    }
+}
+
+key_value_section! {
+    /// `[Difficulty]`
+    pub struct Difficulty, separator = ":" {
+        "HPDrainRate" => hp_drain_rate: f64,
+        "CircleSize" => circle_size: f64,
+        "OverallDifficulty" => overall_difficulty: f64,
+        /// Missing in old beatmaps, where it is the same as the overall
+        /// difficulty.
+        "ApproachRate" => approach_rate: f64,
+        "SliderMultiplier" => slider_multiplier: f64,
+        "SliderTickRate" => slider_tick_rate: f64,
+    }
+}
+
+/// `[Events]`, backgrounds, breaks and storyboard commands.
+///
+/// The lines are kept as they are, including the comments separating the
+/// storyboard layers.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Events {
+    pub lines: Vec<String>,
+}
+
+impl Section for Events {
+    #[inline]
+    fn parse_line(&mut self, line: &str) {
+        self.lines.push(line.to_owned());
+    }
+
+    fn write(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{line}")?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+/// `[Colours]`, e.g. `Combo1 : 255,128,0`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Colours {
+    pub colours: Vec<(String, String)>,
+}
+
+impl Section for Colours {
+    fn parse_line(&mut self, line: &str) {
+        if let Some((key, value)) = line.split_once(':') {
+            self.colours.push((key.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+
+    fn write(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        for (key, value) in &self.colours {
+            writeln!(f, "{key} : {value}")?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.colours.is_empty()
+    }
+}
@@ -0,0 +1,149 @@
+mod round_trip {
+    use crate::OsuFile;
+
+    const V14_OSU: &str = include_str!("../samples/v14_osu.osu");
+    const V14_TAIKO: &str = include_str!("../samples/v14_taiko.osu");
+    const V14_MANIA: &str = include_str!("../samples/v14_mania.osu");
+    const V3_OSU: &str = include_str!("../samples/v3_osu.osu");
+
+    fn assert_round_trip(sample: &str) {
+        let parsed = sample.parse::<OsuFile>().unwrap();
+        let written = parsed.to_string();
+        let reparsed = written.parse::<OsuFile>().unwrap();
+
+        assert_eq!(parsed, reparsed);
+        assert_eq!(written, reparsed.to_string());
+    }
+
+    #[test]
+    fn test_round_trip_samples() {
+        for sample in [V14_OSU, V14_TAIKO, V14_MANIA, V3_OSU] {
+            assert_round_trip(sample);
+        }
+    }
+
+    #[test]
+    fn test_write_is_identical_to_latest_format() {
+        for sample in [V14_OSU, V14_TAIKO, V14_MANIA] {
+            assert_eq!(sample.parse::<OsuFile>().unwrap().to_string(), sample);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_crlf_and_bom() {
+        let sample = format!("\u{feff}{}", V14_OSU.replace('\n', "\r\n"));
+
+        assert_eq!(
+            sample.parse::<OsuFile>().unwrap(),
+            V14_OSU.parse::<OsuFile>().unwrap()
+        );
+    }
+}
+
+mod parsing {
+    use crate::{
+        hit_objects::HitObjectKind, CurveType, GameMode, OsuFile, ParseError,
+    };
+
+    const V14_OSU: &str = include_str!("../samples/v14_osu.osu");
+    const V14_MANIA: &str = include_str!("../samples/v14_mania.osu");
+    const V3_OSU: &str = include_str!("../samples/v3_osu.osu");
+
+    #[test]
+    fn test_sections() {
+        let file = V14_OSU.parse::<OsuFile>().unwrap();
+
+        assert_eq!(file.version, 14);
+        assert_eq!(file.mode(), GameMode::Osu);
+        assert_eq!(file.general.stack_leniency, Some(0.7));
+        assert_eq!(file.editor.bookmarks, Some(vec![1000, 41000, 82000]));
+        assert_eq!(file.metadata.version.as_deref(), Some("Insane"));
+        assert_eq!(file.metadata.source.as_deref(), Some(""));
+        assert_eq!(file.metadata.beatmap_id, Some(1001));
+        assert_eq!(file.difficulty.approach_rate, Some(9.0));
+        assert_eq!(file.events.lines.len(), 10);
+        assert_eq!(file.events.lines[6], " F,0,1000,2000,0,1");
+        assert_eq!(file.colours.colours[0].1, "255,128,64");
+
+        assert_eq!(file.timing_points.len(), 4);
+        assert!(file.timing_points[0].is_uninherited());
+        assert!(file.timing_points[1].kiai());
+        assert_eq!(file.timing_points[1].slider_velocity(), 1.0);
+        assert!((file.timing_points[2].slider_velocity() - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hit_objects() {
+        let file = V14_OSU.parse::<OsuFile>().unwrap();
+        let objects = &file.hit_objects;
+
+        assert_eq!(objects.len(), 8);
+        assert!(objects[0].is_circle() && objects[0].new_combo);
+
+        let HitObjectKind::Slider(slider) = &objects[2].kind else {
+            panic!("expected a slider");
+        };
+        assert_eq!(slider.curve_type, CurveType::Bezier);
+        assert_eq!(slider.curve_points.len(), 4);
+        assert_eq!(slider.length, Some(180.0));
+        assert_eq!(slider.edge_sounds, Some(vec![2, 0]));
+        assert_eq!(slider.edge_sets, Some(vec![(0, 0), (1, 2)]));
+
+        assert_eq!(objects[3].combo_skip, 2);
+        assert_eq!(objects[3].type_bits(), 38);
+        assert_eq!(objects[4].hit_sample, None);
+
+        assert!(objects[6].is_spinner());
+        assert_eq!(objects[6].end_time(), 7000.0);
+        assert_eq!(objects[7].x, 312.5);
+        assert_eq!(
+            objects[7].hit_sample.as_deref(),
+            Some("0:0:0:0:sample.wav")
+        );
+    }
+
+    #[test]
+    fn test_hold_notes() {
+        let file = V14_MANIA.parse::<OsuFile>().unwrap();
+
+        assert_eq!(file.mode(), GameMode::Mania);
+        assert!(file.hit_objects[3].is_hold());
+        assert_eq!(file.hit_objects[3].end_time(), 1500.0);
+        assert_eq!(
+            file.hit_objects[3].hit_sample.as_deref(),
+            Some("1:2:0:80:hold.wav")
+        );
+    }
+
+    #[test]
+    fn test_old_format() {
+        let file = V3_OSU.parse::<OsuFile>().unwrap();
+
+        assert_eq!(file.version, 3);
+        assert_eq!(file.difficulty.approach_rate, None);
+        assert_eq!(file.general.extra[0].0, "EditorBookmarks");
+
+        assert_eq!(file.timing_points[0].meter, None);
+        assert_eq!(file.timing_points[1].sample_set, Some(1));
+        assert_eq!(file.timing_points[1].to_string(), "5000,500,4,1");
+
+        assert_eq!(file.hit_objects.len(), 5);
+        assert_eq!(file.hit_objects[0].hit_sample, None);
+        assert_eq!(
+            file.hit_objects[2].to_string(),
+            "320,64,2000,2,0,B|380:64|440:128,2,140"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            "[General]\nMode: 0".parse::<OsuFile>(),
+            Err(ParseError::MissingFormatVersion)
+        ));
+        assert!(matches!(
+            "osu file format v14\n[General]\nMode: 7".parse::<OsuFile>(),
+            Err(ParseError::InvalidGameMode(_))
+        ));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::Section;
+use std::fmt;
+
+/// Effect bits of a timing point.
+pub const KIAI: i32 = 1 << 0;
+pub const OMIT_FIRST_BAR_LINE: i32 = 1 << 3;
+
+/// `time,beatLength,meter,sampleSet,sampleIndex,volume,uninherited,effects`
+///
+/// Old beatmaps stop after any of the columns, the missing ones are `None`
+/// and are not written back.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TimingPoint {
+    pub time: f64,
+    /// Milliseconds per beat if uninherited, otherwise the negative inverse
+    /// slider velocity percentage (`-50` is 2x).
+    pub beat_len: f64,
+    pub meter: Option<i32>,
+    pub sample_set: Option<i32>,
+    pub sample_index: Option<i32>,
+    pub volume: Option<i32>,
+    pub uninherited: Option<bool>,
+    pub effects: Option<i32>,
+}
+
+impl TimingPoint {
+    /// Red lines set the bpm, old beatmaps without the column mark
+    /// inherited ones with a negative beat length.
+    #[inline]
+    pub fn is_uninherited(&self) -> bool {
+        self.uninherited.unwrap_or(!self.beat_len.is_sign_negative())
+    }
+
+    /// Slider velocity multiplier of inherited timing points, `1` for
+    /// uninherited ones.
+    #[inline]
+    pub fn slider_velocity(&self) -> f64 {
+        if self.beat_len < 0.0 {
+            (100.0 / -self.beat_len).clamp(0.1, 10.0)
+        } else {
+            1.0
+        }
+    }
+
+    #[inline]
+    pub fn kiai(&self) -> bool {
+        self.effects.unwrap_or_default() & KIAI != 0
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut split = line.split(',').map(str::trim);
+
+        let time = split.next()?.parse().ok()?;
+        let beat_len = split.next()?.parse().ok()?;
+
+        let mut next_i32 = || split.next().and_then(|s| s.parse().ok());
+        let meter = next_i32();
+        let sample_set = next_i32();
+        let sample_index = next_i32();
+        let volume = next_i32();
+        let uninherited = next_i32().map(|value| value != 0);
+        let effects = next_i32();
+
+        Some(Self {
+            time,
+            beat_len,
+            meter,
+            sample_set,
+            sample_index,
+            volume,
+            uninherited,
+            effects,
+        })
+    }
+}
+
+impl fmt::Display for TimingPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.time, self.beat_len)?;
+
+        let columns = [
+            self.meter,
+            self.sample_set,
+            self.sample_index,
+            self.volume,
+            self.uninherited.map(i32::from),
+            self.effects,
+        ];
+
+        for value in columns.into_iter().map_while(|value| value) {
+            write!(f, ",{value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `[TimingPoints]`
+impl Section for Vec<TimingPoint> {
+    #[inline]
+    fn parse_line(&mut self, line: &str) {
+        if let Some(timing_point) = TimingPoint::parse(line) {
+            self.push(timing_point);
+        }
+    }
+
+    fn write(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        for timing_point in self {
+            writeln!(f, "{timing_point}")?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        <[TimingPoint]>::is_empty(self)
+    }
+}
@@ -0,0 +1,331 @@
+use crate::{math::Pos2, mods::ModsExt};
+use peace_osu_file::{CurveType, OsuFile};
+use std::str::FromStr;
+
+pub use peace_osu_file::GameMode;
+
+/// Timing of beatmaps older than v5 is 24ms late.
+const LEGACY_FORMAT_OFFSET: f64 = 24.0;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ParseError {
+    #[error(transparent)]
+    OsuFile(#[from] peace_osu_file::ParseError),
+    #[error("beatmap has no hit objects")]
+    NoHitObjects,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingPoint {
+    pub time: f64,
+    pub beat_len: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyPoint {
+    pub time: f64,
+    pub slider_velocity: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathType {
+    Bezier,
+    Catmull,
+    Linear,
+    PerfectCurve,
+}
+
+/// Slider control point, relative to the slider position. Points with a
+/// `kind` start a new segment of the path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathControlPoint {
+    pub pos: Pos2,
+    pub kind: Option<PathType>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HitObjectKind {
+    Circle,
+    Slider {
+        repeats: usize,
+        pixel_len: f64,
+        control_points: Vec<PathControlPoint>,
+        /// Hitsound of the head, every repeat and the tail.
+        edge_sounds: Vec<u8>,
+    },
+    Spinner {
+        end_time: f64,
+    },
+    Hold {
+        end_time: f64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitObject {
+    pub pos: Pos2,
+    pub start_time: f64,
+    pub kind: HitObjectKind,
+    pub sound: u8,
+}
+
+impl HitObject {
+    #[inline]
+    pub fn is_circle(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Circle)
+    }
+
+    #[inline]
+    pub fn is_slider(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Slider { .. })
+    }
+
+    #[inline]
+    pub fn is_spinner(&self) -> bool {
+        matches!(self.kind, HitObjectKind::Spinner { .. })
+    }
+}
+
+/// The parts of a `.osu` file needed to calculate difficulty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Beatmap {
+    pub version: u8,
+    pub mode: GameMode,
+    pub stack_leniency: f64,
+    pub hp: f64,
+    pub cs: f64,
+    pub od: f64,
+    pub ar: f64,
+    pub slider_multiplier: f64,
+    pub slider_tick_rate: f64,
+    /// Uninherited (red) timing points.
+    pub timing_points: Vec<TimingPoint>,
+    /// Slider velocity of every timing point, 1 for uninherited ones.
+    pub difficulty_points: Vec<DifficultyPoint>,
+    pub hit_objects: Vec<HitObject>,
+}
+
+impl Default for Beatmap {
+    fn default() -> Self {
+        Self {
+            version: 14,
+            mode: GameMode::Osu,
+            stack_leniency: 0.7,
+            hp: 5.0,
+            cs: 5.0,
+            od: 5.0,
+            ar: 5.0,
+            slider_multiplier: 1.4,
+            slider_tick_rate: 1.0,
+            timing_points: Vec::new(),
+            difficulty_points: Vec::new(),
+            hit_objects: Vec::new(),
+        }
+    }
+}
+
+/// Difficulty settings after applying HR or EZ, not affected by the clock
+/// rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeatmapDifficulty {
+    pub hp: f64,
+    pub cs: f64,
+    pub od: f64,
+    pub ar: f64,
+}
+
+impl Beatmap {
+    #[inline]
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        String::from_utf8_lossy(bytes).parse()
+    }
+
+    pub fn difficulty(&self, mods: u32) -> BeatmapDifficulty {
+        let multiplier = mods.difficulty_multiplier();
+        // CS is changed less by HR
+        let cs_multiplier = if mods.hr() { 1.3 } else { multiplier };
+
+        BeatmapDifficulty {
+            hp: (self.hp * multiplier).min(10.0),
+            cs: (self.cs * cs_multiplier).min(10.0),
+            od: (self.od * multiplier).min(10.0),
+            ar: (self.ar * multiplier).min(10.0),
+        }
+    }
+
+    /// Beat length of the timing point active at `time`, the first one if
+    /// `time` is before all of them.
+    pub fn beat_len_at(&self, time: f64) -> f64 {
+        let idx = self.timing_points.partition_point(|p| p.time <= time);
+
+        idx.checked_sub(1)
+            .or_else(|| (!self.timing_points.is_empty()).then_some(0))
+            .map(|idx| self.timing_points[idx].beat_len)
+            .unwrap_or(1000.0)
+    }
+
+    pub fn slider_velocity_at(&self, time: f64) -> f64 {
+        let idx = self.difficulty_points.partition_point(|p| p.time <= time);
+
+        idx.checked_sub(1)
+            .map(|idx| self.difficulty_points[idx].slider_velocity)
+            .unwrap_or(1.0)
+    }
+}
+
+impl FromStr for Beatmap {
+    type Err = ParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_osu_file(&s.parse()?)
+    }
+}
+
+impl Beatmap {
+    pub fn from_osu_file(file: &OsuFile) -> Result<Self, ParseError> {
+        let version = file.version.clamp(0, u8::MAX as i32) as u8;
+        let offset = if version < 5 { LEGACY_FORMAT_OFFSET } else { 0.0 };
+
+        let defaults = Self::default();
+        let difficulty = &file.difficulty;
+        let od = difficulty.overall_difficulty.unwrap_or(defaults.od);
+
+        let mut map = Self {
+            version,
+            mode: file.mode(),
+            stack_leniency: file
+                .general
+                .stack_leniency
+                .unwrap_or(defaults.stack_leniency),
+            hp: difficulty.hp_drain_rate.unwrap_or(defaults.hp),
+            cs: difficulty.circle_size.unwrap_or(defaults.cs),
+            od,
+            // Old beatmaps have no approach rate, it was the same as the OD
+            ar: difficulty.approach_rate.unwrap_or(od),
+            slider_multiplier: difficulty
+                .slider_multiplier
+                .unwrap_or(defaults.slider_multiplier),
+            slider_tick_rate: difficulty
+                .slider_tick_rate
+                .unwrap_or(defaults.slider_tick_rate),
+            ..defaults
+        };
+
+        for point in &file.timing_points {
+            let time = point.time + offset;
+
+            if point.is_uninherited() {
+                map.timing_points
+                    .push(TimingPoint { time, beat_len: point.beat_len });
+            }
+
+            map.difficulty_points.push(DifficultyPoint {
+                time,
+                slider_velocity: point.slider_velocity(),
+            });
+        }
+
+        map.hit_objects = file
+            .hit_objects
+            .iter()
+            .map(|h| convert_hit_object(h, offset, version))
+            .collect();
+
+        if map.hit_objects.is_empty() {
+            return Err(ParseError::NoHitObjects);
+        }
+
+        // Objects can only be out of order in manually edited files
+        map.hit_objects.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+        map.timing_points.sort_by(|a, b| a.time.total_cmp(&b.time));
+        map.difficulty_points.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Ok(map)
+    }
+}
+
+fn convert_hit_object(
+    h: &peace_osu_file::HitObject,
+    offset: f64,
+    version: u8,
+) -> HitObject {
+    use peace_osu_file::HitObjectKind as Kind;
+
+    let pos = Pos2::new(h.x, h.y);
+    let start_time = h.time + offset;
+
+    let kind = match &h.kind {
+        Kind::Circle => HitObjectKind::Circle,
+        Kind::Slider(slider) => HitObjectKind::Slider {
+            repeats: (slider.slides.max(1) - 1) as usize,
+            pixel_len: slider.length.unwrap_or(0.0).max(0.0),
+            control_points: convert_control_points(slider, pos, version),
+            edge_sounds: slider.edge_sounds.clone().unwrap_or_default(),
+        },
+        Kind::Spinner { end_time } => HitObjectKind::Spinner {
+            end_time: (end_time + offset).max(start_time),
+        },
+        Kind::Hold { end_time } => HitObjectKind::Hold {
+            end_time: (end_time + offset).max(start_time),
+        },
+    };
+
+    HitObject { pos, start_time, kind, sound: h.hit_sound }
+}
+
+fn convert_control_points(
+    slider: &peace_osu_file::Slider,
+    start_pos: Pos2,
+    version: u8,
+) -> Vec<PathControlPoint> {
+    let mut path_type = match slider.curve_type {
+        CurveType::Linear => PathType::Linear,
+        CurveType::PerfectCurve => PathType::PerfectCurve,
+        CurveType::Catmull => PathType::Catmull,
+        CurveType::Bezier => PathType::Bezier,
+    };
+
+    let mut points =
+        vec![PathControlPoint { pos: Pos2::default(), kind: None }];
+    points.extend(slider.curve_points.iter().map(|point| PathControlPoint {
+        pos: Pos2::new(point.x, point.y) - start_pos,
+        kind: None,
+    }));
+
+    if path_type == PathType::PerfectCurve {
+        if points.len() != 3 {
+            path_type = PathType::Bezier;
+        } else if is_linear(&points) {
+            path_type = PathType::Linear;
+        }
+    }
+
+    points[0].kind = Some(path_type);
+
+    // Two sequential points at the same position start a new segment of the
+    // same type, legacy catmull sliders have a single segment
+    let mut i = 1;
+    while i < points.len() {
+        let is_duplicate = points[i].pos == points[i - 1].pos;
+        let is_last = i == points.len() - 1;
+        let split_catmull =
+            path_type != PathType::Catmull || i <= 1 || version >= 128;
+
+        if is_duplicate && !is_last && split_catmull {
+            points[i - 1].kind = Some(path_type);
+            points.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    points
+}
+
+#[inline]
+fn is_linear(points: &[PathControlPoint]) -> bool {
+    let (a, b, c) = (points[0].pos, points[1].pos, points[2].pos);
+
+    ((b.y - a.y) * (c.x - a.x) - (b.x - a.x) * (c.y - a.y)).abs() < 1e-3
+}
@@ -0,0 +1,72 @@
+/// osu!stable's xorshift random number generator, needed to reproduce the
+/// random offsets of juice streams and bananas.
+#[derive(Debug, Clone)]
+pub(crate) struct LegacyRandom {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+    bit_buffer: u32,
+    bit_idx: u32,
+}
+
+impl LegacyRandom {
+    const INT_TO_REAL: f64 = 1.0 / (i32::MAX as f64 + 1.0);
+    const INT_MASK: u32 = 0x7FFF_FFFF;
+    const Y: u32 = 842_502_087;
+    const Z: u32 = 3_579_807_591;
+    const W: u32 = 273_326_509;
+
+    #[inline]
+    pub fn new(seed: i32) -> Self {
+        Self {
+            x: seed as u32,
+            y: Self::Y,
+            z: Self::Z,
+            w: Self::W,
+            bit_buffer: 0,
+            bit_idx: 32,
+        }
+    }
+
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+
+        self.w
+    }
+
+    #[inline]
+    pub fn next(&mut self) -> i32 {
+        (Self::INT_MASK & self.next_u32()) as i32
+    }
+
+    #[inline]
+    pub fn next_double(&mut self) -> f64 {
+        Self::INT_TO_REAL * self.next() as f64
+    }
+
+    #[inline]
+    pub fn next_range(&mut self, lower: f64, upper: f64) -> i32 {
+        (lower + self.next_double() * (upper - lower)) as i32
+    }
+
+    #[inline]
+    pub fn next_bool(&mut self) -> bool {
+        if self.bit_idx == 32 {
+            self.bit_buffer = self.next_u32();
+            self.bit_idx = 1;
+
+            return self.bit_buffer & 1 == 1;
+        }
+
+        self.bit_idx += 1;
+        self.bit_buffer >>= 1;
+
+        self.bit_buffer & 1 == 1
+    }
+}
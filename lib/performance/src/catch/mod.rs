@@ -0,0 +1,488 @@
+//! osu!catch difficulty and performance, the same as
+//! osu!lazer in late 2022.
+
+mod legacy_random;
+mod movement;
+mod pp;
+
+pub use self::pp::{performance, CatchPerformanceAttributes};
+
+use self::{legacy_random::LegacyRandom, movement::Movement};
+use crate::{
+    beatmap::{Beatmap, HitObject, HitObjectKind},
+    curve::SliderPath,
+    math::difficulty_range,
+    mods::ModsExt,
+    slider::{
+        SliderEvent, SliderEventKind, SliderEvents, BASE_SCORING_DISTANCE,
+    },
+};
+
+const STAR_SCALING_FACTOR: f64 = 0.153;
+const PLAYFIELD_WIDTH: f64 = 512.0;
+const RNG_SEED: i32 = 1337;
+
+const CATCHER_BASE_SIZE: f64 = 106.75;
+/// Part of the catcher that can catch fruits.
+const ALLOWED_CATCH_RANGE: f64 = 0.8;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CatchDifficultyAttributes {
+    /// Approach rate, adjusted by the clock rate.
+    pub ar: f64,
+    pub n_fruits: usize,
+    pub n_droplets: usize,
+    pub n_tiny_droplets: usize,
+    pub max_combo: usize,
+    pub stars: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NestedKind {
+    Fruit,
+    Droplet,
+    TinyDroplet,
+}
+
+#[derive(Debug, Clone)]
+struct NestedObject {
+    start_time: f64,
+    x: f64,
+    x_offset: f64,
+    kind: NestedKind,
+}
+
+#[derive(Debug, Clone)]
+enum CatchObjectKind {
+    Fruit,
+    JuiceStream {
+        /// x of the last control point, relative to the stream.
+        last_control_point_x: f64,
+        nested: Vec<NestedObject>,
+    },
+    BananaShower {
+        n_bananas: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct CatchObject {
+    start_time: f64,
+    x: f64,
+    x_offset: f64,
+    kind: CatchObjectKind,
+}
+
+/// Fruits and droplets, the objects that need to be caught.
+#[derive(Debug, Clone)]
+pub(crate) struct PalpableObject {
+    pub start_time: f64,
+    pub effective_x: f64,
+    pub hyper_dash: bool,
+    pub distance_to_hyper_dash: f64,
+}
+
+pub fn calculate(map: &Beatmap, mods: u32) -> CatchDifficultyAttributes {
+    let difficulty = map.difficulty(mods);
+    let clock_rate = mods.clock_rate();
+
+    let preempt =
+        difficulty_range(difficulty.ar, 1800.0, 1200.0, 450.0) / clock_rate;
+
+    let mut attrs = CatchDifficultyAttributes {
+        ar: if preempt > 1200.0 {
+            -(preempt - 1800.0) / 120.0
+        } else {
+            -(preempt - 1200.0) / 150.0 + 5.0
+        },
+        ..Default::default()
+    };
+
+    let mut objects: Vec<CatchObject> =
+        map.hit_objects.iter().map(|h| convert_object(map, h)).collect();
+
+    apply_position_offsets(&mut objects, mods.hr());
+
+    let mut palpable = Vec::new();
+
+    for obj in objects.iter() {
+        match &obj.kind {
+            CatchObjectKind::Fruit => {
+                attrs.n_fruits += 1;
+                palpable.push(PalpableObject::new(
+                    obj.start_time,
+                    obj.x,
+                    obj.x_offset,
+                ));
+            },
+            CatchObjectKind::JuiceStream { nested, .. } => {
+                for nested in nested.iter() {
+                    match nested.kind {
+                        NestedKind::Fruit => attrs.n_fruits += 1,
+                        NestedKind::Droplet => attrs.n_droplets += 1,
+                        NestedKind::TinyDroplet => {
+                            attrs.n_tiny_droplets += 1;
+                            continue;
+                        },
+                    }
+
+                    palpable.push(PalpableObject::new(
+                        nested.start_time,
+                        nested.x,
+                        nested.x_offset,
+                    ));
+                }
+            },
+            CatchObjectKind::BananaShower { .. } => {},
+        }
+    }
+
+    palpable.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    attrs.max_combo = palpable.len();
+
+    let catcher_width = calculate_catch_width(difficulty.cs);
+    initialise_hyper_dash(&mut palpable, catcher_width / 2.0);
+
+    // Reduced further for circle sizes above 5.5 to simulate imperfect
+    // gameplay
+    let half_catcher_width =
+        catcher_width * 0.5 * (1.0 - (difficulty.cs - 5.5).max(0.0) * 0.0625);
+
+    let mut movement = Movement::new(half_catcher_width, clock_rate);
+
+    for (i, pair) in palpable.windows(2).enumerate() {
+        movement.process(&pair[1], &pair[0], i == 0);
+    }
+
+    attrs.stars = movement.difficulty_value().sqrt() * STAR_SCALING_FACTOR;
+
+    attrs
+}
+
+impl PalpableObject {
+    #[inline]
+    fn new(start_time: f64, x: f64, x_offset: f64) -> Self {
+        Self {
+            start_time,
+            effective_x: (x + x_offset).clamp(0.0, PLAYFIELD_WIDTH),
+            hyper_dash: false,
+            distance_to_hyper_dash: 0.0,
+        }
+    }
+}
+
+#[inline]
+fn calculate_catch_width(cs: f64) -> f64 {
+    let scale = 1.0 - 0.7 * (cs - 5.0) / 5.0;
+
+    CATCHER_BASE_SIZE * scale.abs() * ALLOWED_CATCH_RANGE
+}
+
+fn convert_object(map: &Beatmap, h: &HitObject) -> CatchObject {
+    let kind = match &h.kind {
+        HitObjectKind::Circle | HitObjectKind::Hold { .. } => {
+            CatchObjectKind::Fruit
+        },
+        HitObjectKind::Spinner { end_time } => {
+            let duration = end_time - h.start_time;
+            let mut spacing = duration;
+
+            while spacing > 100.0 {
+                spacing /= 2.0;
+            }
+
+            let n_bananas = if spacing <= 0.0 {
+                0
+            } else {
+                (duration / spacing).floor() as usize + 1
+            };
+
+            CatchObjectKind::BananaShower { n_bananas }
+        },
+        HitObjectKind::Slider {
+            repeats, pixel_len, control_points, ..
+        } => {
+            let path = SliderPath::new(
+                control_points,
+                (*pixel_len > 0.0).then_some(*pixel_len),
+            );
+
+            let scoring_distance = BASE_SCORING_DISTANCE
+                * map.slider_multiplier
+                * map.slider_velocity_at(h.start_time);
+            let velocity = scoring_distance / map.beat_len_at(h.start_time);
+
+            let span_count = repeats + 1;
+            let distance = path.distance();
+
+            let events = SliderEvents {
+                start_time: h.start_time,
+                span_duration: distance / velocity,
+                velocity,
+                tick_distance: scoring_distance / map.slider_tick_rate,
+                total_distance: distance,
+                span_count,
+            }
+            .generate();
+
+            CatchObjectKind::JuiceStream {
+                last_control_point_x: control_points
+                    .last()
+                    .map_or(0.0, |point| point.pos.x),
+                nested: juice_stream_nested(&path, h.pos.x, &events),
+            }
+        },
+    };
+
+    CatchObject { start_time: h.start_time, x: h.pos.x, x_offset: 0.0, kind }
+}
+
+/// Fruits and droplets of a juice stream, with tiny droplets between all of
+/// them.
+fn juice_stream_nested(
+    path: &SliderPath,
+    x: f64,
+    events: &[SliderEvent],
+) -> Vec<NestedObject> {
+    let x_at = |progress: f64| {
+        (x + path.position_at(progress).x).clamp(0.0, PLAYFIELD_WIDTH)
+    };
+
+    let mut nested = Vec::new();
+    let mut last_event: Option<&SliderEvent> = None;
+
+    for e in events {
+        if let Some(last) = last_event {
+            // Truncated to match osu!stable
+            let since_last_tick = e.time.trunc() - last.time.trunc();
+
+            if since_last_tick > 80.0 {
+                let mut time_between_tiny = since_last_tick;
+
+                while time_between_tiny > 100.0 {
+                    time_between_tiny /= 2.0;
+                }
+
+                let mut t = time_between_tiny;
+
+                while t < since_last_tick {
+                    let progress = last.path_progress
+                        + (t / since_last_tick)
+                            * (e.path_progress - last.path_progress);
+
+                    nested.push(NestedObject {
+                        start_time: t + last.time,
+                        x: x_at(progress),
+                        x_offset: 0.0,
+                        kind: NestedKind::TinyDroplet,
+                    });
+
+                    t += time_between_tiny;
+                }
+            }
+        }
+
+        // The legacy last tick is used for the tiny droplets, which makes
+        // the last ones increasingly mistimed
+        last_event = Some(e);
+
+        let kind = match e.kind {
+            SliderEventKind::Tick => NestedKind::Droplet,
+            SliderEventKind::Head
+            | SliderEventKind::Repeat
+            | SliderEventKind::Tail => NestedKind::Fruit,
+            SliderEventKind::LegacyLastTick => continue,
+        };
+
+        nested.push(NestedObject {
+            start_time: e.time,
+            x: x_at(e.path_progress),
+            x_offset: 0.0,
+            kind,
+        });
+    }
+
+    nested.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+    nested
+}
+
+fn apply_position_offsets(objects: &mut [CatchObject], hard_rock: bool) {
+    let mut rng = LegacyRandom::new(RNG_SEED);
+    let mut last_position: Option<f64> = None;
+    let mut last_start_time = 0.0;
+
+    for obj in objects.iter_mut() {
+        obj.x_offset = 0.0;
+
+        match &mut obj.kind {
+            CatchObjectKind::Fruit => {
+                if hard_rock {
+                    apply_hard_rock_offset(
+                        obj,
+                        &mut last_position,
+                        &mut last_start_time,
+                        &mut rng,
+                    );
+                }
+            },
+            CatchObjectKind::BananaShower { n_bananas } => {
+                // x offset, type, rotation and colour of every banana
+                for _ in 0..*n_bananas {
+                    rng.next_double();
+                    rng.next();
+                    rng.next();
+                    rng.next();
+                }
+            },
+            CatchObjectKind::JuiceStream { last_control_point_x, nested } => {
+                // osu!stable used the last control point instead of the end
+                // of the path, and the start instead of the end time
+                last_position = Some(obj.x + *last_control_point_x);
+                last_start_time = obj.start_time;
+
+                for nested in nested.iter_mut() {
+                    nested.x_offset = 0.0;
+
+                    match nested.kind {
+                        NestedKind::TinyDroplet => {
+                            nested.x_offset = (rng.next_range(-20.0, 20.0)
+                                as f64)
+                                .clamp(-nested.x, PLAYFIELD_WIDTH - nested.x);
+                        },
+                        // Droplet rotation
+                        NestedKind::Droplet => {
+                            rng.next();
+                        },
+                        NestedKind::Fruit => {},
+                    }
+                }
+            },
+        }
+    }
+}
+
+fn apply_hard_rock_offset(
+    obj: &mut CatchObject,
+    last_position: &mut Option<f64>,
+    last_start_time: &mut f64,
+    rng: &mut LegacyRandom,
+) {
+    let mut offset_position = obj.x;
+    let start_time = obj.start_time;
+
+    let Some(last_pos) = *last_position else {
+        *last_position = Some(offset_position);
+        *last_start_time = start_time;
+
+        return;
+    };
+
+    let position_diff = offset_position - last_pos;
+
+    // osu!stable calculated the time deltas as ints
+    let time_diff = (start_time - *last_start_time) as i32;
+
+    if time_diff > 1000 {
+        *last_position = Some(offset_position);
+        *last_start_time = start_time;
+
+        return;
+    }
+
+    if position_diff == 0.0 {
+        apply_random_offset(&mut offset_position, time_diff as f64 / 4.0, rng);
+        obj.x_offset = offset_position - obj.x;
+
+        return;
+    }
+
+    if position_diff.abs() < (time_diff / 3) as f64 {
+        apply_offset(&mut offset_position, position_diff);
+    }
+
+    obj.x_offset = offset_position - obj.x;
+
+    *last_position = Some(offset_position);
+    *last_start_time = start_time;
+}
+
+fn apply_random_offset(
+    position: &mut f64,
+    max_offset: f64,
+    rng: &mut LegacyRandom,
+) {
+    let right = rng.next_bool();
+    let rand = (rng.next_range(0.0, max_offset.max(0.0)) as f64).min(20.0);
+
+    if right {
+        // Clamp to the right bound
+        if *position + rand <= PLAYFIELD_WIDTH {
+            *position += rand;
+        } else {
+            *position -= rand;
+        }
+    } else if *position - rand >= 0.0 {
+        // Clamp to the left bound
+        *position -= rand;
+    } else {
+        *position += rand;
+    }
+}
+
+fn apply_offset(position: &mut f64, amount: f64) {
+    if amount > 0.0 {
+        // Clamp to the right bound
+        if *position + amount < PLAYFIELD_WIDTH {
+            *position += amount;
+        }
+    } else if *position + amount > 0.0 {
+        // Clamp to the left bound
+        *position += amount;
+    }
+}
+
+/// osu!stable used the full catcher width, excluding the margins.
+fn initialise_hyper_dash(
+    objects: &mut [PalpableObject],
+    half_catcher_width: f64,
+) {
+    let half_catcher_width = half_catcher_width / ALLOWED_CATCH_RANGE;
+
+    let mut last_direction = 0;
+    let mut last_excess = half_catcher_width;
+
+    for i in 0..objects.len().saturating_sub(1) {
+        let next_x = objects[i + 1].effective_x;
+        let next_start_time = objects[i + 1].start_time;
+        let current = &mut objects[i];
+
+        current.hyper_dash = false;
+        current.distance_to_hyper_dash = 0.0;
+
+        let this_direction = if next_x > current.effective_x { 1 } else { -1 };
+
+        // Truncated like osu!stable, with a quarter of a frame of grace time
+        let time_to_next = next_start_time.trunc()
+            - current.start_time.trunc()
+            - 1000.0 / 60.0 / 4.0;
+        let distance_to_next = (next_x - current.effective_x).abs()
+            - if last_direction == this_direction {
+                last_excess
+            } else {
+                half_catcher_width
+            };
+
+        let distance_to_hyper = time_to_next - distance_to_next;
+
+        if distance_to_hyper < 0.0 {
+            current.hyper_dash = true;
+            last_excess = half_catcher_width;
+        } else {
+            current.distance_to_hyper_dash = distance_to_hyper;
+            last_excess = distance_to_hyper.clamp(0.0, half_catcher_width);
+        }
+
+        last_direction = this_direction;
+    }
+}
@@ -0,0 +1,153 @@
+use super::PalpableObject;
+use crate::strain::{difficulty_value, strain_decay, StrainPeaks};
+
+const ABSOLUTE_PLAYER_POSITIONING_ERROR: f64 = 16.0;
+const NORMALIZED_HITOBJECT_RADIUS: f64 = 41.0;
+const DIRECTION_CHANGE_BONUS: f64 = 21.0;
+
+const SKILL_MULTIPLIER: f64 = 900.0;
+const STRAIN_DECAY_BASE: f64 = 0.2;
+const DECAY_WEIGHT: f64 = 0.94;
+const SECTION_LENGTH: f64 = 750.0;
+
+pub(crate) struct Movement {
+    half_catcher_width: f64,
+    /// The clock rate changes the speed of the catcher as well.
+    catcher_speed_multiplier: f64,
+    clock_rate: f64,
+    last_player_position: Option<f64>,
+    last_distance_moved: f64,
+    last_strain_time: f64,
+    current_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl Movement {
+    #[inline]
+    pub fn new(half_catcher_width: f64, clock_rate: f64) -> Self {
+        Self {
+            half_catcher_width,
+            catcher_speed_multiplier: clock_rate,
+            clock_rate,
+            last_player_position: None,
+            last_distance_moved: 0.0,
+            last_strain_time: 0.0,
+            current_strain: 0.0,
+            peaks: StrainPeaks::new(SECTION_LENGTH),
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        current: &PalpableObject,
+        last: &PalpableObject,
+        is_first: bool,
+    ) {
+        let start_time = current.start_time / self.clock_rate;
+        let prev_start_time = last.start_time / self.clock_rate;
+        let delta_time = start_time - prev_start_time;
+        let current_strain = self.current_strain;
+
+        self.peaks.start_sections_until(start_time, is_first, |time| {
+            current_strain
+                * strain_decay(time - prev_start_time, STRAIN_DECAY_BASE)
+        });
+
+        self.current_strain *= strain_decay(delta_time, STRAIN_DECAY_BASE);
+        self.current_strain +=
+            self.strain_value_of(current, last, delta_time) * SKILL_MULTIPLIER;
+
+        self.peaks.add(self.current_strain);
+    }
+
+    fn strain_value_of(
+        &mut self,
+        current: &PalpableObject,
+        last: &PalpableObject,
+        delta_time: f64,
+    ) -> f64 {
+        // Positions are scaled to assume the same circle size for all maps
+        let scaling_factor =
+            NORMALIZED_HITOBJECT_RADIUS / self.half_catcher_width;
+        let normalized_position = current.effective_x * scaling_factor;
+        let last_normalized_position = last.effective_x * scaling_factor;
+
+        // Hard capped at the equivalent of 375 BPM streaming speed
+        let strain_time = delta_time.max(40.0);
+
+        let last_player_position =
+            *self.last_player_position.get_or_insert(last_normalized_position);
+
+        let mut player_position = last_player_position.clamp(
+            normalized_position
+                - (NORMALIZED_HITOBJECT_RADIUS
+                    - ABSOLUTE_PLAYER_POSITIONING_ERROR),
+            normalized_position
+                + (NORMALIZED_HITOBJECT_RADIUS
+                    - ABSOLUTE_PLAYER_POSITIONING_ERROR),
+        );
+
+        let distance_moved = player_position - last_player_position;
+
+        let weighted_strain_time =
+            strain_time + 13.0 + (3.0 / self.catcher_speed_multiplier);
+
+        let mut distance_addition = distance_moved.abs().powf(1.3) / 510.0;
+        let sqrt_strain = weighted_strain_time.sqrt();
+
+        let mut edge_dash_bonus = 0.0;
+
+        if distance_moved.abs() > 0.1 {
+            // Direction change bonus
+            if self.last_distance_moved.abs() > 0.1
+                && distance_moved.signum() != self.last_distance_moved.signum()
+            {
+                let bonus_factor = distance_moved.abs().min(50.0) / 50.0;
+                let anti_flow_factor =
+                    (self.last_distance_moved.abs().min(70.0) / 70.0).max(0.38);
+
+                distance_addition += DIRECTION_CHANGE_BONUS
+                    / (self.last_strain_time + 16.0).sqrt()
+                    * bonus_factor
+                    * anti_flow_factor
+                    * (1.0 - (weighted_strain_time / 1000.0).powi(3)).max(0.0);
+            }
+
+            // Base bonus for every movement, giving some weight to streams
+            distance_addition += 12.5
+                * distance_moved.abs().min(NORMALIZED_HITOBJECT_RADIUS * 2.0)
+                / (NORMALIZED_HITOBJECT_RADIUS * 6.0)
+                / sqrt_strain;
+        }
+
+        // Bonus for edge dashes
+        if last.distance_to_hyper_dash <= 20.0 {
+            if last.hyper_dash {
+                // The player is in the correct position after a hyper dash
+                player_position = normalized_position;
+            } else {
+                edge_dash_bonus += 5.7;
+            }
+
+            // Edge dashes are easier at lower ms values
+            distance_addition *= 1.0
+                + edge_dash_bonus
+                    * ((20.0 - last.distance_to_hyper_dash) / 20.0)
+                    * ((strain_time * self.catcher_speed_multiplier)
+                        .min(265.0)
+                        / 265.0)
+                        .powf(1.5);
+        }
+
+        self.last_player_position = Some(player_position);
+        self.last_distance_moved = distance_moved;
+        self.last_strain_time = strain_time;
+
+        distance_addition / weighted_strain_time
+    }
+
+    #[inline]
+    pub fn difficulty_value(self) -> f64 {
+        difficulty_value(self.peaks.into_peaks(), DECAY_WEIGHT)
+    }
+}
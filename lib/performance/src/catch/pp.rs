@@ -0,0 +1,75 @@
+use super::CatchDifficultyAttributes;
+use crate::{mods::ModsExt, ScoreState};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CatchPerformanceAttributes {
+    pub pp: f64,
+}
+
+/// Relax only frees the player from the dashes, which is already part of
+/// the difficulty, so it is calculated as any other score.
+pub fn performance(
+    attrs: &CatchDifficultyAttributes,
+    mods: u32,
+    state: &ScoreState,
+) -> CatchPerformanceAttributes {
+    // Tiny droplets don't give any combo
+    let combo_hits = (state.n300 + state.n100 + state.nmiss) as f64;
+
+    if combo_hits == 0.0 {
+        return CatchPerformanceAttributes::default();
+    }
+
+    let mut pp =
+        (5.0 * (attrs.stars / 0.0049).max(1.0) - 4.0).powi(2) / 100_000.0;
+
+    let len_bonus = 0.95
+        + 0.3 * (combo_hits / 2500.0).min(1.0)
+        + if combo_hits > 2500.0 {
+            (combo_hits / 2500.0).log10() * 0.475
+        } else {
+            0.0
+        };
+    pp *= len_bonus;
+
+    pp *= 0.97_f64.powi(state.nmiss as i32);
+
+    if attrs.max_combo > 0 {
+        pp *= ((state.max_combo as f64).powf(0.8)
+            / (attrs.max_combo as f64).powf(0.8))
+        .min(1.0);
+    }
+
+    let ar = attrs.ar;
+    let mut ar_factor = 1.0;
+    if ar > 9.0 {
+        ar_factor += 0.1 * (ar - 9.0);
+    }
+    if ar > 10.0 {
+        ar_factor += 0.1 * (ar - 10.0);
+    } else if ar < 8.0 {
+        ar_factor += 0.025 * (8.0 - ar);
+    }
+    pp *= ar_factor;
+
+    if mods.hd() {
+        pp *= if ar <= 10.0 {
+            1.05 + 0.075 * (10.0 - ar)
+        } else {
+            1.01 + 0.04 * (11.0 - ar.min(11.0))
+        };
+    }
+
+    if mods.fl() {
+        pp *= 1.35 * len_bonus;
+    }
+
+    pp *= state.accuracy_catch().powf(5.5);
+
+    if mods.nf() {
+        pp *= 0.9;
+    }
+
+    CatchPerformanceAttributes { pp }
+}
@@ -0,0 +1,401 @@
+//! Slider paths, approximated the same way as osu!lazer.
+
+use crate::{
+    beatmap::{PathControlPoint, PathType},
+    math::Pos2,
+};
+
+const BEZIER_TOLERANCE: f64 = 0.25;
+const CATMULL_DETAIL: usize = 50;
+const CIRCULAR_ARC_TOLERANCE: f64 = 0.1;
+
+#[derive(Debug, Clone, Default)]
+pub struct SliderPath {
+    points: Vec<Pos2>,
+    cumulative_lengths: Vec<f64>,
+}
+
+impl SliderPath {
+    /// Without an `expected_len` the path keeps its calculated length.
+    pub fn new(
+        control_points: &[PathControlPoint],
+        expected_len: Option<f64>,
+    ) -> Self {
+        let mut path = Self {
+            points: calculate_path(control_points),
+            ..Default::default()
+        };
+        path.calculate_length(control_points, expected_len);
+
+        path
+    }
+
+    #[inline]
+    pub fn distance(&self) -> f64 {
+        self.cumulative_lengths.last().copied().unwrap_or(0.0)
+    }
+
+    /// Position at `progress` (0-1) of the path, relative to its start.
+    pub fn position_at(&self, progress: f64) -> Pos2 {
+        let d = progress.clamp(0.0, 1.0) * self.distance();
+        let i = self.cumulative_lengths.partition_point(|&len| len < d);
+
+        self.interpolate_vertices(i, d)
+    }
+
+    fn interpolate_vertices(&self, i: usize, d: f64) -> Pos2 {
+        if self.points.is_empty() {
+            return Pos2::default();
+        }
+
+        if i == 0 {
+            return self.points[0];
+        } else if i >= self.points.len() {
+            return self.points[self.points.len() - 1];
+        }
+
+        let p0 = self.points[i - 1];
+        let p1 = self.points[i];
+        let d0 = self.cumulative_lengths[i - 1];
+        let d1 = self.cumulative_lengths[i];
+
+        if (d0 - d1).abs() < f64::EPSILON {
+            return p0;
+        }
+
+        p0 + (p1 - p0) * ((d - d0) / (d1 - d0))
+    }
+
+    /// Trims or extends the path to the expected length.
+    fn calculate_length(
+        &mut self,
+        control_points: &[PathControlPoint],
+        expected_len: Option<f64>,
+    ) {
+        let mut calculated_len = 0.0;
+        self.cumulative_lengths.clear();
+        self.cumulative_lengths.push(0.0);
+
+        for pair in self.points.windows(2) {
+            calculated_len += pair[0].distance(pair[1]);
+            self.cumulative_lengths.push(calculated_len);
+        }
+
+        let Some(expected_len) = expected_len else {
+            return;
+        };
+
+        if calculated_len == expected_len {
+            return;
+        }
+
+        // osu!stable does not extend sliders whose last two control points
+        // are equal
+        if let [.., second_last, last] = control_points {
+            if second_last.pos == last.pos && expected_len > calculated_len {
+                return;
+            }
+        }
+
+        // The last length is always incorrect
+        self.cumulative_lengths.pop();
+        let mut path_end_idx = self.points.len().saturating_sub(1);
+
+        if calculated_len > expected_len {
+            while self
+                .cumulative_lengths
+                .last()
+                .is_some_and(|&len| len >= expected_len)
+            {
+                self.cumulative_lengths.pop();
+                self.points.truncate(path_end_idx);
+                path_end_idx = path_end_idx.saturating_sub(1);
+            }
+        }
+
+        if path_end_idx == 0 {
+            self.cumulative_lengths.push(0.0);
+            return;
+        }
+
+        let prev = self.points[path_end_idx - 1];
+        let dir = (self.points[path_end_idx] - prev).normalize();
+        let last_len = self.cumulative_lengths.last().copied().unwrap_or(0.0);
+
+        self.points[path_end_idx] = prev + dir * (expected_len - last_len);
+        self.cumulative_lengths.push(expected_len);
+    }
+}
+
+fn calculate_path(control_points: &[PathControlPoint]) -> Vec<Pos2> {
+    let mut path = Vec::new();
+    let mut start = 0;
+
+    for i in 0..control_points.len() {
+        let is_last = i == control_points.len() - 1;
+
+        if control_points[i].kind.is_none() && !is_last {
+            continue;
+        }
+
+        // A segment is the points between two typed points, including both
+        let end = i + 1;
+        if end - start > 1 || is_last {
+            let kind = control_points[start].kind.unwrap_or(PathType::Linear);
+            let vertices: Vec<Pos2> = control_points[start..end]
+                .iter()
+                .map(|point| point.pos)
+                .collect();
+
+            for vertex in approximate_segment(kind, &vertices) {
+                // Segments share their first point with the previous one
+                if path.last() != Some(&vertex) {
+                    path.push(vertex);
+                }
+            }
+        }
+
+        start = i;
+    }
+
+    path
+}
+
+fn approximate_segment(kind: PathType, vertices: &[Pos2]) -> Vec<Pos2> {
+    match kind {
+        PathType::Linear => vertices.to_vec(),
+        PathType::PerfectCurve => {
+            if vertices.len() == 3 {
+                if let Some(arc) = approximate_circular_arc(vertices) {
+                    return arc;
+                }
+            }
+
+            approximate_bezier(vertices)
+        },
+        PathType::Catmull => approximate_catmull(vertices),
+        PathType::Bezier => approximate_bezier(vertices),
+    }
+}
+
+fn approximate_bezier(control_points: &[Pos2]) -> Vec<Pos2> {
+    let count = control_points.len();
+    let mut output = Vec::new();
+
+    if count == 0 {
+        return output;
+    }
+
+    let mut subdivision_buffer1 = vec![Pos2::default(); count];
+    let mut subdivision_buffer2 = vec![Pos2::default(); count * 2 - 1];
+    let mut to_flatten = vec![control_points.to_vec()];
+    let mut free_buffers: Vec<Vec<Pos2>> = Vec::new();
+    let mut left_child = subdivision_buffer2.clone();
+
+    while let Some(parent) = to_flatten.pop() {
+        if bezier_is_flat_enough(&parent) {
+            bezier_approximate(
+                &parent,
+                &mut output,
+                &mut subdivision_buffer1,
+                &mut subdivision_buffer2,
+            );
+            free_buffers.push(parent);
+            continue;
+        }
+
+        let mut right_child =
+            free_buffers.pop().unwrap_or_else(|| vec![Pos2::default(); count]);
+        bezier_subdivide(
+            &parent,
+            &mut left_child,
+            &mut right_child,
+            &mut subdivision_buffer1,
+        );
+
+        // The parent buffer is reused for the left child
+        let mut parent = parent;
+        parent.copy_from_slice(&left_child[..count]);
+
+        to_flatten.push(right_child);
+        to_flatten.push(parent);
+    }
+
+    output.push(control_points[count - 1]);
+
+    output
+}
+
+#[inline]
+fn bezier_is_flat_enough(control_points: &[Pos2]) -> bool {
+    let tolerance = BEZIER_TOLERANCE * BEZIER_TOLERANCE * 4.0;
+
+    control_points
+        .windows(3)
+        .all(|w| (w[0] - w[1] * 2.0 + w[2]).length_squared() <= tolerance)
+}
+
+fn bezier_subdivide(
+    control_points: &[Pos2],
+    left: &mut [Pos2],
+    right: &mut [Pos2],
+    midpoints: &mut [Pos2],
+) {
+    let count = control_points.len();
+    midpoints[..count].copy_from_slice(control_points);
+
+    for i in 0..count {
+        left[i] = midpoints[0];
+        right[count - i - 1] = midpoints[count - i - 1];
+
+        for j in 0..count - i - 1 {
+            midpoints[j] = (midpoints[j] + midpoints[j + 1]) / 2.0;
+        }
+    }
+}
+
+fn bezier_approximate(
+    control_points: &[Pos2],
+    output: &mut Vec<Pos2>,
+    subdivision_buffer1: &mut [Pos2],
+    subdivision_buffer2: &mut [Pos2],
+) {
+    let count = control_points.len();
+    let mut right = vec![Pos2::default(); count];
+
+    bezier_subdivide(
+        control_points,
+        subdivision_buffer2,
+        &mut right,
+        subdivision_buffer1,
+    );
+
+    subdivision_buffer2[count..count * 2 - 1].copy_from_slice(&right[1..count]);
+
+    output.push(control_points[0]);
+
+    for i in 1..count - 1 {
+        let index = 2 * i;
+        let p = (subdivision_buffer2[index - 1]
+            + subdivision_buffer2[index] * 2.0
+            + subdivision_buffer2[index + 1])
+            * 0.25;
+        output.push(p);
+    }
+}
+
+fn approximate_catmull(control_points: &[Pos2]) -> Vec<Pos2> {
+    let count = control_points.len();
+    let mut output = Vec::with_capacity((count - 1) * CATMULL_DETAIL * 2);
+
+    for i in 0..count - 1 {
+        let v1 = if i > 0 { control_points[i - 1] } else { control_points[i] };
+        let v2 = control_points[i];
+        let v3 =
+            if i < count - 1 { control_points[i + 1] } else { v2 * 2.0 - v1 };
+        let v4 =
+            if i < count - 2 { control_points[i + 2] } else { v3 * 2.0 - v2 };
+
+        for c in 0..CATMULL_DETAIL {
+            output.push(catmull_find_point(
+                v1,
+                v2,
+                v3,
+                v4,
+                c as f64 / CATMULL_DETAIL as f64,
+            ));
+            output.push(catmull_find_point(
+                v1,
+                v2,
+                v3,
+                v4,
+                (c + 1) as f64 / CATMULL_DETAIL as f64,
+            ));
+        }
+    }
+
+    output
+}
+
+#[inline]
+fn catmull_find_point(v1: Pos2, v2: Pos2, v3: Pos2, v4: Pos2, t: f64) -> Pos2 {
+    let t2 = t * t;
+    let t3 = t * t2;
+
+    let component = |v1: f64, v2: f64, v3: f64, v4: f64| {
+        0.5 * (2.0 * v2
+            + (-v1 + v3) * t
+            + (2.0 * v1 - 5.0 * v2 + 4.0 * v3 - v4) * t2
+            + (-v1 + 3.0 * v2 - 3.0 * v3 + v4) * t3)
+    };
+
+    Pos2::new(
+        component(v1.x, v2.x, v3.x, v4.x),
+        component(v1.y, v2.y, v3.y, v4.y),
+    )
+}
+
+/// `None` if the points are (almost) on a line.
+fn approximate_circular_arc(control_points: &[Pos2]) -> Option<Vec<Pos2>> {
+    let (a, b, c) = (control_points[0], control_points[1], control_points[2]);
+
+    let a_sq = (b - c).length_squared();
+    let b_sq = (a - c).length_squared();
+    let c_sq = (a - b).length_squared();
+
+    if a_sq.abs() < 1e-3 || b_sq.abs() < 1e-3 || c_sq.abs() < 1e-3 {
+        return None;
+    }
+
+    let s = a_sq * (b_sq + c_sq - a_sq);
+    let t = b_sq * (a_sq + c_sq - b_sq);
+    let u = c_sq * (a_sq + b_sq - c_sq);
+    let sum = s + t + u;
+
+    if sum.abs() < 1e-3 {
+        return None;
+    }
+
+    let centre = (a * s + b * t + c * u) / sum;
+    let d_a = a - centre;
+    let d_c = c - centre;
+
+    let radius = d_a.length();
+    let theta_start = d_a.y.atan2(d_a.x);
+    let mut theta_end = d_c.y.atan2(d_c.x);
+
+    while theta_end < theta_start {
+        theta_end += 2.0 * std::f64::consts::PI;
+    }
+
+    let mut dir = 1.0;
+    let mut theta_range = theta_end - theta_start;
+
+    // Decide the direction by checking on which side of AC the point B lies
+    let ortho_a_to_c = Pos2::new(c.y - a.y, -(c.x - a.x));
+    if ortho_a_to_c.dot(b - a) < 0.0 {
+        dir = -dir;
+        theta_range = 2.0 * std::f64::consts::PI - theta_range;
+    }
+
+    let amount_points = if 2.0 * radius <= CIRCULAR_ARC_TOLERANCE {
+        2
+    } else {
+        let points = (theta_range
+            / (2.0 * (1.0 - CIRCULAR_ARC_TOLERANCE / radius).acos()))
+        .ceil();
+
+        (points.max(2.0) as usize).min(1000)
+    };
+
+    Some(
+        (0..amount_points)
+            .map(|i| {
+                let fract = i as f64 / (amount_points - 1) as f64;
+                let theta = theta_start + dir * fract * theta_range;
+
+                centre + Pos2::new(theta.cos(), theta.sin()) * radius
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,256 @@
+//! Star rating and performance points calculation of osu! beatmaps for all
+//! four rulesets.
+//!
+//! ```ignore
+//! let map = Beatmap::parse(&bytes)?;
+//! let attrs = peace_performance::calculate(&map, None, mods)?;
+//! println!("{:.2}*", attrs.stars());
+//!
+//! let state = ScoreState { max_combo: 727, n300: 500, ..Default::default() };
+//! println!("{:.2}pp", attrs.performance(mods, &state).pp());
+//! ```
+
+pub mod beatmap;
+pub mod catch;
+pub mod curve;
+pub mod mania;
+pub mod math;
+pub mod mods;
+pub mod osu;
+pub mod taiko;
+
+#[cfg(test)]
+mod tests;
+
+mod slider;
+mod strain;
+
+pub use beatmap::{Beatmap, GameMode, ParseError};
+pub use catch::{CatchDifficultyAttributes, CatchPerformanceAttributes};
+pub use mania::{ManiaDifficultyAttributes, ManiaPerformanceAttributes};
+pub use osu::{OsuDifficultyAttributes, OsuPerformanceAttributes};
+pub use taiko::{TaikoDifficultyAttributes, TaikoPerformanceAttributes};
+
+#[derive(thiserror::Error, Debug)]
+pub enum CalculateError {
+    #[error("beatmaps of {from:?} can not be converted to {to:?}")]
+    UnsupportedConversion { from: GameMode, to: GameMode },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DifficultyAttributes {
+    Osu(OsuDifficultyAttributes),
+    Taiko(TaikoDifficultyAttributes),
+    Catch(CatchDifficultyAttributes),
+    Mania(ManiaDifficultyAttributes),
+}
+
+impl DifficultyAttributes {
+    #[inline]
+    pub fn mode(&self) -> GameMode {
+        match self {
+            Self::Osu(_) => GameMode::Osu,
+            Self::Taiko(_) => GameMode::Taiko,
+            Self::Catch(_) => GameMode::Catch,
+            Self::Mania(_) => GameMode::Mania,
+        }
+    }
+
+    #[inline]
+    pub fn stars(&self) -> f64 {
+        match self {
+            Self::Osu(attrs) => attrs.stars,
+            Self::Taiko(attrs) => attrs.stars,
+            Self::Catch(attrs) => attrs.stars,
+            Self::Mania(attrs) => attrs.stars,
+        }
+    }
+
+    #[inline]
+    pub fn max_combo(&self) -> usize {
+        match self {
+            Self::Osu(attrs) => attrs.max_combo,
+            Self::Taiko(attrs) => attrs.max_combo,
+            Self::Catch(attrs) => attrs.max_combo,
+            Self::Mania(attrs) => attrs.max_combo,
+        }
+    }
+
+    /// Performance points of a score set with the same `mods` as the
+    /// difficulty was calculated with.
+    pub fn performance(
+        &self,
+        mods: u32,
+        state: &ScoreState,
+    ) -> PerformanceAttributes {
+        match self {
+            Self::Osu(attrs) => {
+                PerformanceAttributes::Osu(osu::performance(attrs, mods, state))
+            },
+            Self::Taiko(attrs) => PerformanceAttributes::Taiko(
+                taiko::performance(attrs, mods, state),
+            ),
+            Self::Catch(attrs) => PerformanceAttributes::Catch(
+                catch::performance(attrs, mods, state),
+            ),
+            Self::Mania(attrs) => PerformanceAttributes::Mania(
+                mania::performance(attrs, mods, state),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "mode", rename_all = "lowercase")
+)]
+pub enum PerformanceAttributes {
+    Osu(OsuPerformanceAttributes),
+    Taiko(TaikoPerformanceAttributes),
+    Catch(CatchPerformanceAttributes),
+    Mania(ManiaPerformanceAttributes),
+}
+
+impl PerformanceAttributes {
+    #[inline]
+    pub fn pp(&self) -> f64 {
+        match self {
+            Self::Osu(attrs) => attrs.pp,
+            Self::Taiko(attrs) => attrs.pp,
+            Self::Catch(attrs) => attrs.pp,
+            Self::Mania(attrs) => attrs.pp,
+        }
+    }
+}
+
+/// Hit results of a score, named as in the score rows:
+///
+/// | ruleset | `ngeki` | `n300` | `nkatu` | `n100` | `n50` |
+/// | --- | --- | --- | --- | --- | --- |
+/// | osu!, osu!taiko | | great | | ok (good) | meh |
+/// | osu!catch | | fruits | tiny droplet misses | droplets | tiny droplets |
+/// | osu!mania | max (320) | 300 | 200 | 100 | 50 |
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreState {
+    pub max_combo: usize,
+    pub ngeki: usize,
+    pub n300: usize,
+    pub nkatu: usize,
+    pub n100: usize,
+    pub n50: usize,
+    pub nmiss: usize,
+}
+
+impl ScoreState {
+    #[inline]
+    pub fn total_hits_osu(&self) -> usize {
+        self.n300 + self.n100 + self.n50 + self.nmiss
+    }
+
+    #[inline]
+    pub fn total_hits_taiko(&self) -> usize {
+        self.n300 + self.n100 + self.nmiss
+    }
+
+    #[inline]
+    pub fn total_hits_catch(&self) -> usize {
+        self.n300 + self.n100 + self.n50 + self.nkatu + self.nmiss
+    }
+
+    #[inline]
+    pub fn total_hits_mania(&self) -> usize {
+        self.ngeki + self.n300 + self.nkatu + self.n100 + self.n50 + self.nmiss
+    }
+
+    #[inline]
+    pub fn accuracy_osu(&self) -> f64 {
+        ratio(
+            self.n300 * 6 + self.n100 * 2 + self.n50,
+            self.total_hits_osu() * 6,
+        )
+    }
+
+    #[inline]
+    pub fn accuracy_taiko(&self) -> f64 {
+        ratio(self.n300 * 2 + self.n100, self.total_hits_taiko() * 2)
+    }
+
+    #[inline]
+    pub fn accuracy_catch(&self) -> f64 {
+        ratio(self.n300 + self.n100 + self.n50, self.total_hits_catch())
+    }
+
+    /// Weighted as in osu!lazer, a max (320) is worth more than a 300.
+    #[inline]
+    pub fn accuracy_mania(&self) -> f64 {
+        ratio(
+            self.ngeki * 32
+                + self.n300 * 30
+                + self.nkatu * 20
+                + self.n100 * 10
+                + self.n50 * 5,
+            self.total_hits_mania() * 32,
+        )
+    }
+}
+
+#[inline]
+fn ratio(hits: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    hits as f64 / total as f64
+}
+
+/// Calculates the difficulty of `map` with the legacy `mods` bits.
+///
+/// `mode` defaults to the mode of the beatmap, osu!standard beatmaps can be
+/// converted to osu!taiko and osu!catch.
+pub fn calculate(
+    map: &Beatmap,
+    mode: Option<GameMode>,
+    mods: u32,
+) -> Result<DifficultyAttributes, CalculateError> {
+    let mode = mode.unwrap_or(map.mode);
+
+    if mode != map.mode
+        && (map.mode != GameMode::Osu || mode == GameMode::Mania)
+    {
+        return Err(CalculateError::UnsupportedConversion {
+            from: map.mode,
+            to: mode,
+        });
+    }
+
+    Ok(match mode {
+        GameMode::Osu => DifficultyAttributes::Osu(osu::calculate(map, mods)),
+        GameMode::Taiko => {
+            DifficultyAttributes::Taiko(taiko::calculate(map, mods))
+        },
+        GameMode::Catch => {
+            DifficultyAttributes::Catch(catch::calculate(map, mods))
+        },
+        GameMode::Mania => {
+            DifficultyAttributes::Mania(mania::calculate(map, mods))
+        },
+    })
+}
+
+/// Calculates the difficulty of `map` and the performance points of a score
+/// set on it, see [`calculate`].
+#[inline]
+pub fn calculate_performance(
+    map: &Beatmap,
+    mode: Option<GameMode>,
+    mods: u32,
+    state: &ScoreState,
+) -> Result<(DifficultyAttributes, PerformanceAttributes), CalculateError> {
+    let difficulty = calculate(map, mode, mods)?;
+    let performance = difficulty.performance(mods, state);
+
+    Ok((difficulty, performance))
+}
@@ -0,0 +1,75 @@
+//! osu!mania difficulty and performance, the same as
+//! osu!lazer in late 2022.
+
+mod pp;
+mod strain;
+
+pub use self::pp::{performance, ManiaPerformanceAttributes};
+
+use self::strain::Strain;
+use crate::{
+    beatmap::{Beatmap, HitObjectKind},
+    mods::ModsExt,
+};
+
+const STAR_SCALING_FACTOR: f64 = 0.018;
+const PLAYFIELD_WIDTH: f64 = 512.0;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ManiaDifficultyAttributes {
+    pub n_keys: usize,
+    pub max_combo: usize,
+    pub stars: f64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ManiaObject {
+    pub start_time: f64,
+    pub end_time: f64,
+    pub column: usize,
+}
+
+impl ManiaObject {
+    /// Holds give a combo for every 100ms.
+    #[inline]
+    fn max_combo(&self) -> usize {
+        1 + ((self.end_time - self.start_time) / 100.0) as usize
+    }
+}
+
+/// Beatmaps of other rulesets can't be converted to osu!mania yet.
+pub fn calculate(map: &Beatmap, mods: u32) -> ManiaDifficultyAttributes {
+    let clock_rate = mods.clock_rate();
+    let n_keys = (map.cs.round() as usize).max(1);
+    let column_width = PLAYFIELD_WIDTH / n_keys as f64;
+
+    let mut objects: Vec<ManiaObject> = map
+        .hit_objects
+        .iter()
+        .map(|h| ManiaObject {
+            start_time: h.start_time,
+            end_time: match h.kind {
+                HitObjectKind::Hold { end_time } => end_time,
+                _ => h.start_time,
+            },
+            column: ((h.pos.x / column_width).floor().max(0.0) as usize)
+                .min(n_keys - 1),
+        })
+        .collect();
+
+    let max_combo = objects.iter().map(ManiaObject::max_combo).sum();
+
+    objects.sort_by_key(|h| h.start_time.round() as i64);
+
+    let mut strain = Strain::new(n_keys);
+
+    for (i, pair) in objects.windows(2).enumerate() {
+        strain.process(&pair[1], &pair[0], clock_rate, i == 0);
+    }
+
+    ManiaDifficultyAttributes {
+        n_keys,
+        max_combo,
+        stars: strain.difficulty_value() * STAR_SCALING_FACTOR,
+    }
+}
@@ -0,0 +1,37 @@
+use super::ManiaDifficultyAttributes;
+use crate::{mods::ModsExt, ScoreState};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManiaPerformanceAttributes {
+    pub pp: f64,
+    pub difficulty: f64,
+}
+
+pub fn performance(
+    attrs: &ManiaDifficultyAttributes,
+    mods: u32,
+    state: &ScoreState,
+) -> ManiaPerformanceAttributes {
+    let total_hits = state.total_hits_mania() as f64;
+
+    if total_hits == 0.0 {
+        return ManiaPerformanceAttributes::default();
+    }
+
+    let mut multiplier = 8.0;
+
+    if mods.nf() {
+        multiplier *= 0.75;
+    }
+
+    if mods.ez() {
+        multiplier *= 0.5;
+    }
+
+    let difficulty = (attrs.stars - 0.15).max(0.05).powf(2.2)
+        * (5.0 * state.accuracy_mania() - 4.0).max(0.0)
+        * (1.0 + 0.1 * (total_hits / 1500.0).min(1.0));
+
+    ManiaPerformanceAttributes { pp: difficulty * multiplier, difficulty }
+}
@@ -0,0 +1,144 @@
+use super::ManiaObject;
+use crate::strain::{difficulty_value, StrainPeaks};
+
+const INDIVIDUAL_DECAY_BASE: f64 = 0.125;
+const OVERALL_DECAY_BASE: f64 = 0.3;
+const RELEASE_THRESHOLD: f64 = 24.0;
+const DECAY_WEIGHT: f64 = 0.9;
+
+pub(crate) struct Strain {
+    start_times: Vec<f64>,
+    end_times: Vec<f64>,
+    individual_strains: Vec<f64>,
+    individual_strain: f64,
+    overall_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl Strain {
+    #[inline]
+    pub fn new(n_keys: usize) -> Self {
+        Self {
+            start_times: vec![0.0; n_keys],
+            end_times: vec![0.0; n_keys],
+            individual_strains: vec![0.0; n_keys],
+            individual_strain: 0.0,
+            overall_strain: 1.0,
+            peaks: StrainPeaks::default(),
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        current: &ManiaObject,
+        last: &ManiaObject,
+        clock_rate: f64,
+        is_first: bool,
+    ) {
+        let start_time = current.start_time / clock_rate;
+        let prev_start_time = last.start_time / clock_rate;
+        let (individual_strain, overall_strain) =
+            (self.individual_strain, self.overall_strain);
+
+        self.peaks.start_sections_until(start_time, is_first, |offset| {
+            apply_decay(
+                individual_strain,
+                offset - prev_start_time,
+                INDIVIDUAL_DECAY_BASE,
+            ) + apply_decay(
+                overall_strain,
+                offset - prev_start_time,
+                OVERALL_DECAY_BASE,
+            )
+        });
+
+        let strain = self.strain_value_of(
+            current,
+            clock_rate,
+            start_time - prev_start_time,
+        );
+        self.peaks.add(strain);
+    }
+
+    /// Only the hardest object of every section counts.
+    fn strain_value_of(
+        &mut self,
+        current: &ManiaObject,
+        clock_rate: f64,
+        delta_time: f64,
+    ) -> f64 {
+        let start_time = current.start_time / clock_rate;
+        let end_time = current.end_time / clock_rate;
+        let column = current.column;
+
+        let mut is_overlapping = false;
+
+        // Lowest value we can assume with the current information
+        let mut closest_end_time = (end_time - start_time).abs();
+        // Factor to all additional strains in case something else is held
+        let mut hold_factor = 1.0;
+        // Addition to the current note in case it's a hold and has to be
+        // released awkwardly
+        let mut hold_addition = 0.0;
+
+        for &other_end_time in self.end_times.iter() {
+            // Overlapped by a previous note or end of a hold
+            is_overlapping |= definitely_bigger(other_end_time, start_time)
+                && definitely_bigger(end_time, other_end_time);
+
+            // Slight bonus to everything if something is held meanwhile
+            if definitely_bigger(other_end_time, end_time) {
+                hold_factor = 1.25;
+            }
+
+            closest_end_time =
+                closest_end_time.min((end_time - other_end_time).abs());
+        }
+
+        // Releasing multiple notes is as easy as releasing one, the hold
+        // addition is halved if the closest release is `RELEASE_THRESHOLD`
+        // away
+        if is_overlapping {
+            hold_addition = 1.0
+                / (1.0 + (0.5 * (RELEASE_THRESHOLD - closest_end_time)).exp());
+        }
+
+        self.individual_strains[column] = apply_decay(
+            self.individual_strains[column],
+            start_time - self.start_times[column],
+            INDIVIDUAL_DECAY_BASE,
+        );
+        self.individual_strains[column] += 2.0 * hold_factor;
+
+        // Chords take the hardest individual strain of their columns
+        self.individual_strain = if delta_time <= 1.0 {
+            self.individual_strain.max(self.individual_strains[column])
+        } else {
+            self.individual_strains[column]
+        };
+
+        self.overall_strain =
+            apply_decay(self.overall_strain, delta_time, OVERALL_DECAY_BASE);
+        self.overall_strain += (1.0 + hold_addition) * hold_factor;
+
+        self.start_times[column] = start_time;
+        self.end_times[column] = end_time;
+
+        self.individual_strain + self.overall_strain
+    }
+
+    #[inline]
+    pub fn difficulty_value(self) -> f64 {
+        difficulty_value(self.peaks.into_peaks(), DECAY_WEIGHT)
+    }
+}
+
+#[inline]
+fn definitely_bigger(value: f64, than: f64) -> bool {
+    value - 1.0 > than
+}
+
+#[inline]
+fn apply_decay(value: f64, delta_time: f64, decay_base: f64) -> f64 {
+    value * decay_base.powf(delta_time / 1000.0)
+}
@@ -0,0 +1,127 @@
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
+
+/// Playfield position, osu!pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Pos2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Pos2 {
+    #[inline]
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    #[inline]
+    pub fn length_squared(self) -> f64 {
+        self.x * self.x + self.y * self.y
+    }
+
+    #[inline]
+    pub fn length(self) -> f64 {
+        self.length_squared().sqrt()
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    #[inline]
+    pub fn distance(self, other: Self) -> f64 {
+        (self - other).length()
+    }
+
+    #[inline]
+    pub fn normalize(self) -> Self {
+        let len = self.length();
+
+        if len == 0.0 {
+            self
+        } else {
+            self / len
+        }
+    }
+}
+
+impl Add for Pos2 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Pos2 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl Sub for Pos2 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<f64> for Pos2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: f64) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Div<f64> for Pos2 {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: f64) -> Self {
+        Self::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+/// Maps a difficulty value (0-10) to a range, `mid` at 5.
+#[inline]
+pub fn difficulty_range(difficulty: f64, min: f64, mid: f64, max: f64) -> f64 {
+    if difficulty > 5.0 {
+        mid + (max - mid) * (difficulty - 5.0) / 5.0
+    } else if difficulty < 5.0 {
+        mid - (mid - min) * (5.0 - difficulty) / 5.0
+    } else {
+        mid
+    }
+}
+
+#[inline]
+pub fn lerp(start: f64, end: f64, amount: f64) -> f64 {
+    start + (end - start) * amount
+}
+
+/// Values must be sorted in descending order.
+#[inline]
+pub fn weighted_sum<I>(sorted_values: I, decay_weight: f64) -> f64
+where
+    I: IntoIterator<Item = f64>,
+{
+    let mut weight = 1.0;
+
+    sorted_values.into_iter().fold(0.0, |sum, value| {
+        let sum = sum + value * weight;
+        weight *= decay_weight;
+        sum
+    })
+}
+
+#[inline]
+pub fn sort_desc(values: &mut [f64]) {
+    values.sort_by(|a, b| b.total_cmp(a));
+}
@@ -0,0 +1,105 @@
+//! The calculators take the legacy mod bits of scores, only the bits they
+//! read are named here.
+
+pub(crate) const NO_FAIL: u32 = 1 << 0;
+pub(crate) const EASY: u32 = 1 << 1;
+pub(crate) const TOUCH_DEVICE: u32 = 1 << 2;
+pub(crate) const HIDDEN: u32 = 1 << 3;
+pub(crate) const HARD_ROCK: u32 = 1 << 4;
+pub(crate) const DOUBLE_TIME: u32 = 1 << 6;
+pub(crate) const RELAX: u32 = 1 << 7;
+pub(crate) const HALF_TIME: u32 = 1 << 8;
+pub(crate) const NIGHTCORE: u32 = 1 << 9;
+pub(crate) const FLASHLIGHT: u32 = 1 << 10;
+pub(crate) const SPUN_OUT: u32 = 1 << 12;
+pub(crate) const AUTOPILOT: u32 = 1 << 13;
+
+pub trait ModsExt: Copy {
+    fn has(self, mods: u32) -> bool;
+
+    #[inline]
+    fn nf(self) -> bool {
+        self.has(NO_FAIL)
+    }
+
+    #[inline]
+    fn ez(self) -> bool {
+        self.has(EASY)
+    }
+
+    #[inline]
+    fn td(self) -> bool {
+        self.has(TOUCH_DEVICE)
+    }
+
+    #[inline]
+    fn hd(self) -> bool {
+        self.has(HIDDEN)
+    }
+
+    #[inline]
+    fn hr(self) -> bool {
+        self.has(HARD_ROCK)
+    }
+
+    /// Nightcore implies double time.
+    #[inline]
+    fn dt(self) -> bool {
+        self.has(DOUBLE_TIME | NIGHTCORE)
+    }
+
+    #[inline]
+    fn rx(self) -> bool {
+        self.has(RELAX)
+    }
+
+    #[inline]
+    fn ht(self) -> bool {
+        self.has(HALF_TIME)
+    }
+
+    #[inline]
+    fn fl(self) -> bool {
+        self.has(FLASHLIGHT)
+    }
+
+    #[inline]
+    fn so(self) -> bool {
+        self.has(SPUN_OUT)
+    }
+
+    #[inline]
+    fn ap(self) -> bool {
+        self.has(AUTOPILOT)
+    }
+
+    #[inline]
+    fn clock_rate(self) -> f64 {
+        if self.dt() {
+            1.5
+        } else if self.ht() {
+            0.75
+        } else {
+            1.0
+        }
+    }
+
+    /// Multiplier of CS, AR, OD and HP.
+    #[inline]
+    fn difficulty_multiplier(self) -> f64 {
+        if self.hr() {
+            1.4
+        } else if self.ez() {
+            0.5
+        } else {
+            1.0
+        }
+    }
+}
+
+impl ModsExt for u32 {
+    #[inline]
+    fn has(self, mods: u32) -> bool {
+        self & mods != 0
+    }
+}
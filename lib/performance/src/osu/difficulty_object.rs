@@ -0,0 +1,184 @@
+use super::object::{
+    OsuObject, OsuPlayfield, ASSUMED_SLIDER_RADIUS, NORMALISED_RADIUS,
+};
+use crate::math::Pos2;
+
+const MIN_DELTA_TIME: f64 = 25.0;
+const MAXIMUM_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 2.4;
+
+#[derive(Debug, Clone)]
+pub(crate) struct OsuDifficultyObject<'a> {
+    pub idx: usize,
+    pub base: &'a OsuObject,
+    pub start_time: f64,
+    pub delta_time: f64,
+    /// Delta time capped to prevent simultaneous objects from breaking the
+    /// calculation.
+    pub strain_time: f64,
+    /// Distance from the lazy end of the previous object, normalised to a
+    /// radius of 50.
+    pub lazy_jump_dist: f64,
+    pub min_jump_dist: f64,
+    pub min_jump_time: f64,
+    pub travel_dist: f64,
+    pub travel_time: f64,
+    /// Angle between the previous two movements.
+    pub angle: Option<f64>,
+}
+
+impl<'a> OsuDifficultyObject<'a> {
+    pub fn new(
+        base: &'a OsuObject,
+        last: &'a OsuObject,
+        last_last: Option<&'a OsuObject>,
+        playfield: &OsuPlayfield,
+        clock_rate: f64,
+        idx: usize,
+    ) -> Self {
+        let delta_time = (base.start_time - last.start_time) / clock_rate;
+        let strain_time = delta_time.max(MIN_DELTA_TIME);
+
+        let mut this = Self {
+            idx,
+            base,
+            start_time: base.start_time / clock_rate,
+            delta_time,
+            strain_time,
+            lazy_jump_dist: 0.0,
+            min_jump_dist: 0.0,
+            min_jump_time: 0.0,
+            travel_dist: 0.0,
+            travel_time: 0.0,
+            angle: None,
+        };
+
+        this.set_distances(last, last_last, playfield, clock_rate);
+
+        this
+    }
+
+    fn set_distances(
+        &mut self,
+        last: &OsuObject,
+        last_last: Option<&OsuObject>,
+        playfield: &OsuPlayfield,
+        clock_rate: f64,
+    ) {
+        if let Some(slider) = self.base.slider() {
+            // Bonus for repeat sliders until a better per nested object
+            // strain system can be achieved
+            self.travel_dist = slider.lazy_travel_dist
+                * (1.0 + slider.repeats as f64 / 2.5).powf(1.0 / 2.5);
+            self.travel_time =
+                (slider.lazy_travel_time / clock_rate).max(MIN_DELTA_TIME);
+        }
+
+        // Neither angle nor distance matter when spinners are involved
+        if self.base.is_spinner() || last.is_spinner() {
+            return;
+        }
+
+        // Distances are scaled to assume the same circle size for all maps
+        let mut scaling_factor = NORMALISED_RADIUS / playfield.radius;
+
+        if playfield.radius < 30.0 {
+            let small_circle_bonus = (30.0 - playfield.radius).min(5.0) / 50.0;
+            scaling_factor *= 1.0 + small_circle_bonus;
+        }
+
+        let last_cursor_pos = end_cursor_pos(last);
+
+        self.lazy_jump_dist = (self.base.stacked_pos() * scaling_factor
+            - last_cursor_pos * scaling_factor)
+            .length();
+        self.min_jump_time = self.strain_time;
+        self.min_jump_dist = self.lazy_jump_dist;
+
+        if let Some(last_slider) = last.slider() {
+            let last_travel_time =
+                (last_slider.lazy_travel_time / clock_rate).max(MIN_DELTA_TIME);
+            self.min_jump_time =
+                (self.strain_time - last_travel_time).max(MIN_DELTA_TIME);
+
+            // The player either follows the slider to its tail or leaves
+            // the follow circle as soon as possible, whichever is shorter
+            let tail_jump_dist =
+                (last.stacked_end_pos() - self.base.stacked_pos()).length()
+                    * scaling_factor;
+
+            self.min_jump_dist = (self.lazy_jump_dist
+                - (MAXIMUM_SLIDER_RADIUS - ASSUMED_SLIDER_RADIUS))
+                .min(tail_jump_dist - MAXIMUM_SLIDER_RADIUS)
+                .max(0.0);
+        }
+
+        if let Some(last_last) = last_last.filter(|h| !h.is_spinner()) {
+            let last_last_cursor_pos = end_cursor_pos(last_last);
+
+            let v1 = last_last_cursor_pos - last.stacked_pos();
+            let v2 = self.base.stacked_pos() - last_cursor_pos;
+
+            let dot = v1.dot(v2);
+            let det = v1.x * v2.y - v1.y * v2.x;
+
+            self.angle = Some(det.atan2(dot).abs());
+        }
+    }
+
+    /// Opacity of this object at `time`.
+    pub fn opacity_at(
+        &self,
+        time: f64,
+        hidden: bool,
+        playfield: &OsuPlayfield,
+    ) -> f64 {
+        if time > self.base.start_time {
+            // Invisible once its start time passed
+            return 0.0;
+        }
+
+        let fade_in_start_time = self.base.start_time - playfield.time_preempt;
+        let fade_in_duration = playfield.time_fade_in;
+
+        let fade_in =
+            ((time - fade_in_start_time) / fade_in_duration).clamp(0.0, 1.0);
+
+        if !hidden {
+            return fade_in;
+        }
+
+        let fade_out_start_time = self.base.start_time - playfield.time_preempt
+            + playfield.time_fade_in;
+        let fade_out_duration =
+            playfield.time_preempt * super::HIDDEN_FADE_OUT_DURATION_MULTIPLIER;
+
+        fade_in.min(
+            1.0 - ((time - fade_out_start_time) / fade_out_duration)
+                .clamp(0.0, 1.0),
+        )
+    }
+
+    /// The object `back` objects before this one.
+    #[inline]
+    pub fn previous<'o>(
+        &self,
+        back: usize,
+        objects: &'o [OsuDifficultyObject<'a>],
+    ) -> Option<&'o OsuDifficultyObject<'a>> {
+        self.idx.checked_sub(back + 1).and_then(|idx| objects.get(idx))
+    }
+
+    #[inline]
+    pub fn next<'o>(
+        &self,
+        forward: usize,
+        objects: &'o [OsuDifficultyObject<'a>],
+    ) -> Option<&'o OsuDifficultyObject<'a>> {
+        objects.get(self.idx + forward + 1)
+    }
+}
+
+#[inline]
+fn end_cursor_pos(obj: &OsuObject) -> Pos2 {
+    obj.slider().map_or_else(|| obj.stacked_pos(), |slider| slider.lazy_end_pos)
+}
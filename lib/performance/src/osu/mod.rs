@@ -0,0 +1,175 @@
+//! osu!standard difficulty and performance, the same as
+//! osu!lazer in late 2022.
+
+mod difficulty_object;
+mod object;
+mod pp;
+mod skills;
+
+pub use self::pp::{performance, OsuPerformanceAttributes};
+
+use self::{
+    difficulty_object::OsuDifficultyObject,
+    object::{convert_objects, OsuObjectKind, OsuPlayfield},
+    skills::{Aim, Flashlight, Speed},
+};
+use crate::{beatmap::Beatmap, math::difficulty_range, mods::ModsExt};
+
+const DIFFICULTY_MULTIPLIER: f64 = 0.0675;
+const PERFORMANCE_BASE_MULTIPLIER: f64 = 1.14;
+const OBJECT_RADIUS: f64 = 64.0;
+const PREEMPT_MIN: f64 = 450.0;
+const HIDDEN_FADE_IN_DURATION_MULTIPLIER: f64 = 0.4;
+pub(crate) const HIDDEN_FADE_OUT_DURATION_MULTIPLIER: f64 = 0.3;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OsuDifficultyAttributes {
+    pub aim: f64,
+    pub speed: f64,
+    pub flashlight: f64,
+    /// Ratio of the aim difficulty without and with sliders.
+    pub slider_factor: f64,
+    pub speed_note_count: f64,
+    /// Approach rate, adjusted by the clock rate.
+    pub ar: f64,
+    /// Overall difficulty, adjusted by the clock rate.
+    pub od: f64,
+    pub hp: f64,
+    pub n_circles: usize,
+    pub n_sliders: usize,
+    pub n_spinners: usize,
+    pub max_combo: usize,
+    pub stars: f64,
+}
+
+pub fn calculate(map: &Beatmap, mods: u32) -> OsuDifficultyAttributes {
+    let difficulty = map.difficulty(mods);
+    let clock_rate = mods.clock_rate();
+
+    let scale = (1.0 - 0.7 * (difficulty.cs - 5.0) / 5.0) / 2.0;
+    let time_preempt = difficulty_range(difficulty.ar, 1800.0, 1200.0, 450.0);
+    let time_fade_in = if mods.hd() {
+        time_preempt * HIDDEN_FADE_IN_DURATION_MULTIPLIER
+    } else {
+        400.0 * (time_preempt / PREEMPT_MIN).min(1.0)
+    };
+
+    let playfield = OsuPlayfield {
+        radius: OBJECT_RADIUS * scale,
+        scale,
+        time_preempt,
+        time_fade_in,
+    };
+
+    let great_window =
+        difficulty_range(difficulty.od, 80.0, 50.0, 20.0) / clock_rate;
+    let preempt = time_preempt / clock_rate;
+
+    let mut attrs = OsuDifficultyAttributes {
+        ar: if preempt > 1200.0 {
+            (1800.0 - preempt) / 120.0
+        } else {
+            (1200.0 - preempt) / 150.0 + 5.0
+        },
+        od: (80.0 - great_window) / 6.0,
+        hp: difficulty.hp,
+        slider_factor: 1.0,
+        ..Default::default()
+    };
+
+    let objects = convert_objects(map, &playfield, mods.hr());
+
+    for obj in objects.iter() {
+        match obj.kind {
+            OsuObjectKind::Circle => attrs.n_circles += 1,
+            OsuObjectKind::Slider(_) => attrs.n_sliders += 1,
+            OsuObjectKind::Spinner { .. } => attrs.n_spinners += 1,
+        }
+
+        attrs.max_combo += obj.max_combo();
+    }
+
+    let diff_objects: Vec<OsuDifficultyObject> = objects
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, obj)| {
+            let last_last = i.checked_sub(2).map(|idx| &objects[idx]);
+
+            OsuDifficultyObject::new(
+                obj,
+                &objects[i - 1],
+                last_last,
+                &playfield,
+                clock_rate,
+                i - 1,
+            )
+        })
+        .collect();
+
+    let mut aim = Aim::new(true);
+    let mut aim_no_sliders = Aim::new(false);
+    let mut speed = Speed::new(great_window);
+    let mut flashlight =
+        mods.fl().then(|| Flashlight::new(mods.hd(), playfield));
+
+    for current in diff_objects.iter() {
+        aim.process(current, &diff_objects);
+        aim_no_sliders.process(current, &diff_objects);
+        speed.process(current, &diff_objects);
+
+        if let Some(flashlight) = flashlight.as_mut() {
+            flashlight.process(current, &diff_objects);
+        }
+    }
+
+    let speed_note_count = speed.relevant_note_count();
+
+    let mut aim_rating = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let aim_rating_no_sliders =
+        aim_no_sliders.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let speed_rating = speed.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let mut flashlight_rating = flashlight.map_or(0.0, |flashlight| {
+        flashlight.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER
+    });
+
+    if aim_rating > 0.0 {
+        attrs.slider_factor = aim_rating_no_sliders / aim_rating;
+    }
+
+    if mods.td() {
+        aim_rating = aim_rating.powf(0.8);
+        flashlight_rating = flashlight_rating.powf(0.8);
+    }
+
+    let base_aim_performance = base_performance(aim_rating);
+    let base_speed_performance = base_performance(speed_rating);
+    let base_flashlight_performance =
+        if mods.fl() { flashlight_rating.powi(2) * 25.0 } else { 0.0 };
+
+    let base_performance = (base_aim_performance.powf(1.1)
+        + base_speed_performance.powf(1.1)
+        + base_flashlight_performance.powf(1.1))
+    .powf(1.0 / 1.1);
+
+    attrs.stars = if base_performance > 0.00001 {
+        PERFORMANCE_BASE_MULTIPLIER.cbrt()
+            * 0.027
+            * ((100_000.0 / 2.0_f64.powf(1.0 / 1.1) * base_performance).cbrt()
+                + 4.0)
+    } else {
+        0.0
+    };
+
+    attrs.aim = aim_rating;
+    attrs.speed = speed_rating;
+    attrs.flashlight = flashlight_rating;
+    attrs.speed_note_count = speed_note_count;
+
+    attrs
+}
+
+#[inline]
+fn base_performance(rating: f64) -> f64 {
+    (5.0 * (rating / DIFFICULTY_MULTIPLIER).max(1.0) - 4.0).powi(3) / 100_000.0
+}
@@ -0,0 +1,502 @@
+use crate::{
+    beatmap::{Beatmap, HitObject, HitObjectKind, PathControlPoint},
+    curve::SliderPath,
+    math::Pos2,
+    slider::{SliderEventKind, SliderEvents, BASE_SCORING_DISTANCE},
+};
+
+const STACK_DISTANCE: f64 = 3.0;
+const PLAYFIELD_HEIGHT: f64 = 384.0;
+
+pub(crate) const NORMALISED_RADIUS: f64 = 50.0;
+pub(crate) const ASSUMED_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 1.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NestedKind {
+    Head,
+    Tick,
+    Repeat,
+    Tail,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct NestedObject {
+    pub pos: Pos2,
+    pub start_time: f64,
+    pub kind: NestedKind,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct OsuSlider {
+    pub end_time: f64,
+    /// Position at the end of the last span.
+    pub end_pos: Pos2,
+    /// Position at the end of the path, regardless of the repeats.
+    pub path_end_pos: Pos2,
+    pub repeats: usize,
+    /// Sorted by time, the tail is at the legacy last tick.
+    pub nested: Vec<NestedObject>,
+    /// Where the cursor is at the end of the slider, assuming the player
+    /// follows it as lazily as possible.
+    pub lazy_end_pos: Pos2,
+    pub lazy_travel_dist: f64,
+    pub lazy_travel_time: f64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum OsuObjectKind {
+    Circle,
+    Slider(Box<OsuSlider>),
+    Spinner { end_time: f64 },
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct OsuObject {
+    pub pos: Pos2,
+    pub start_time: f64,
+    pub stack_height: i32,
+    pub stack_offset: Pos2,
+    pub kind: OsuObjectKind,
+}
+
+impl OsuObject {
+    #[inline]
+    pub fn stacked_pos(&self) -> Pos2 {
+        self.pos + self.stack_offset
+    }
+
+    #[inline]
+    pub fn end_pos(&self) -> Pos2 {
+        match &self.kind {
+            OsuObjectKind::Slider(slider) => slider.end_pos,
+            _ => self.pos,
+        }
+    }
+
+    #[inline]
+    pub fn stacked_end_pos(&self) -> Pos2 {
+        self.end_pos() + self.stack_offset
+    }
+
+    #[inline]
+    pub fn end_time(&self) -> f64 {
+        match &self.kind {
+            OsuObjectKind::Circle => self.start_time,
+            OsuObjectKind::Slider(slider) => slider.end_time,
+            OsuObjectKind::Spinner { end_time } => *end_time,
+        }
+    }
+
+    #[inline]
+    pub fn is_slider(&self) -> bool {
+        matches!(self.kind, OsuObjectKind::Slider(_))
+    }
+
+    #[inline]
+    pub fn is_spinner(&self) -> bool {
+        matches!(self.kind, OsuObjectKind::Spinner { .. })
+    }
+
+    #[inline]
+    pub fn slider(&self) -> Option<&OsuSlider> {
+        match &self.kind {
+            OsuObjectKind::Slider(slider) => Some(slider),
+            _ => None,
+        }
+    }
+
+    /// Combo of the object, every nested object of a slider counts.
+    #[inline]
+    pub fn max_combo(&self) -> usize {
+        match &self.kind {
+            OsuObjectKind::Slider(slider) => slider.nested.len(),
+            _ => 1,
+        }
+    }
+}
+
+/// Playfield settings shared by all objects.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OsuPlayfield {
+    pub radius: f64,
+    pub scale: f64,
+    pub time_preempt: f64,
+    pub time_fade_in: f64,
+}
+
+pub(crate) fn convert_objects(
+    map: &Beatmap,
+    playfield: &OsuPlayfield,
+    hard_rock: bool,
+) -> Vec<OsuObject> {
+    let mut objects: Vec<OsuObject> = map
+        .hit_objects
+        .iter()
+        .map(|h| convert_object(map, h, hard_rock))
+        .collect();
+
+    if map.version >= 6 {
+        apply_stacking(map, playfield, &mut objects);
+    } else {
+        apply_stacking_old(map, playfield, &mut objects);
+    }
+
+    for obj in objects.iter_mut() {
+        let offset = obj.stack_height as f64 * playfield.scale * -6.4;
+        obj.stack_offset = Pos2::new(offset, offset);
+
+        let stacked_pos = obj.stacked_pos();
+        if let OsuObjectKind::Slider(slider) = &mut obj.kind {
+            compute_lazy_travel(
+                slider,
+                stacked_pos,
+                obj.stack_offset,
+                playfield.radius,
+            );
+        }
+    }
+
+    objects
+}
+
+fn convert_object(map: &Beatmap, h: &HitObject, hard_rock: bool) -> OsuObject {
+    let mut pos = h.pos;
+    if hard_rock {
+        pos.y = PLAYFIELD_HEIGHT - pos.y;
+    }
+
+    let kind = match &h.kind {
+        HitObjectKind::Circle | HitObjectKind::Hold { .. } => {
+            OsuObjectKind::Circle
+        },
+        HitObjectKind::Spinner { end_time } => {
+            OsuObjectKind::Spinner { end_time: *end_time }
+        },
+        HitObjectKind::Slider {
+            repeats, pixel_len, control_points, ..
+        } => {
+            let control_points: Vec<PathControlPoint> = if hard_rock {
+                control_points
+                    .iter()
+                    .map(|point| PathControlPoint {
+                        pos: Pos2::new(point.pos.x, -point.pos.y),
+                        kind: point.kind,
+                    })
+                    .collect()
+            } else {
+                control_points.clone()
+            };
+
+            OsuObjectKind::Slider(Box::new(convert_slider(
+                map,
+                pos,
+                h.start_time,
+                *repeats,
+                *pixel_len,
+                &control_points,
+            )))
+        },
+    };
+
+    OsuObject {
+        pos,
+        start_time: h.start_time,
+        stack_height: 0,
+        stack_offset: Pos2::default(),
+        kind,
+    }
+}
+
+fn convert_slider(
+    map: &Beatmap,
+    pos: Pos2,
+    start_time: f64,
+    repeats: usize,
+    pixel_len: f64,
+    control_points: &[PathControlPoint],
+) -> OsuSlider {
+    let path =
+        SliderPath::new(control_points, (pixel_len > 0.0).then_some(pixel_len));
+
+    let slider_velocity = map.slider_velocity_at(start_time);
+    let beat_len = map.beat_len_at(start_time);
+
+    let scoring_distance =
+        BASE_SCORING_DISTANCE * map.slider_multiplier * slider_velocity;
+    let velocity = scoring_distance / beat_len;
+
+    // Old beatmaps did not speed up the ticks with the slider velocity
+    let tick_distance_multiplier =
+        if map.version < 8 { 1.0 / slider_velocity } else { 1.0 };
+    let tick_distance =
+        scoring_distance / map.slider_tick_rate * tick_distance_multiplier;
+
+    let span_count = repeats + 1;
+    let distance = path.distance();
+    let span_duration = distance / velocity;
+    let end_time = start_time + span_count as f64 * span_duration;
+
+    let end_pos = pos + path.position_at(progress_at(1.0, span_count));
+    let path_end_pos = pos + path.position_at(1.0);
+
+    let events = SliderEvents {
+        start_time,
+        span_duration,
+        velocity,
+        tick_distance,
+        total_distance: distance,
+        span_count,
+    };
+
+    let mut nested: Vec<NestedObject> = events
+        .generate()
+        .into_iter()
+        .filter_map(|e| {
+            let (kind, pos) = match e.kind {
+                SliderEventKind::Head => (NestedKind::Head, pos),
+                SliderEventKind::Tick => {
+                    (NestedKind::Tick, pos + path.position_at(e.path_progress))
+                },
+                SliderEventKind::Repeat => (
+                    NestedKind::Repeat,
+                    pos + path.position_at(e.path_progress),
+                ),
+                SliderEventKind::LegacyLastTick => (NestedKind::Tail, end_pos),
+                SliderEventKind::Tail => return None,
+            };
+
+            Some(NestedObject { pos, start_time: e.time, kind })
+        })
+        .collect();
+
+    nested.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+    // Temporary lazy end position until the cursor movement is known
+    let lazy_travel_time =
+        nested.last().map_or(0.0, |nested| nested.start_time - start_time);
+
+    let mut end_time_min = lazy_travel_time / span_duration;
+    if end_time_min % 2.0 >= 1.0 {
+        end_time_min = 1.0 - end_time_min % 1.0;
+    } else {
+        end_time_min %= 1.0;
+    }
+
+    OsuSlider {
+        end_time,
+        end_pos,
+        path_end_pos,
+        repeats,
+        nested,
+        lazy_end_pos: path.position_at(end_time_min),
+        lazy_travel_dist: 0.0,
+        lazy_travel_time,
+    }
+}
+
+/// Progress along the path at `progress` of the whole slider duration.
+#[inline]
+fn progress_at(progress: f64, span_count: usize) -> f64 {
+    let p = progress * span_count as f64 % 1.0;
+    let span = (progress * span_count as f64) as usize;
+
+    if span % 2 == 1 {
+        1.0 - p
+    } else {
+        p
+    }
+}
+
+/// `slider.lazy_end_pos` holds the temporary end position relative to the
+/// slider when this is called.
+fn compute_lazy_travel(
+    slider: &mut OsuSlider,
+    stacked_pos: Pos2,
+    stack_offset: Pos2,
+    radius: f64,
+) {
+    let mut lazy_end_pos = stacked_pos + slider.lazy_end_pos;
+    let mut cursor_pos = stacked_pos;
+    let scaling_factor = NORMALISED_RADIUS / radius;
+    let last_idx = slider.nested.len().saturating_sub(1);
+
+    for (i, nested) in slider.nested.iter().enumerate().skip(1) {
+        let mut movement = nested.pos + stack_offset - cursor_pos;
+        let mut movement_len = scaling_factor * movement.length();
+
+        // Amount of movement required so that the cursor position needs to
+        // be updated
+        let mut required_movement = ASSUMED_SLIDER_RADIUS;
+
+        if i == last_idx {
+            // The end of a slider has special aim rules due to the relaxed
+            // time constraint on position, the player takes the simpler
+            // movement of the lazy and the actual end
+            let lazy_movement = lazy_end_pos - cursor_pos;
+
+            if lazy_movement.length() < movement.length() {
+                movement = lazy_movement;
+            }
+
+            movement_len = scaling_factor * movement.length();
+        } else if nested.kind == NestedKind::Repeat {
+            // Tighter threshold to better assess repeat sliders
+            required_movement = NORMALISED_RADIUS;
+        }
+
+        if movement_len > required_movement {
+            cursor_pos +=
+                movement * ((movement_len - required_movement) / movement_len);
+            movement_len *= (movement_len - required_movement) / movement_len;
+            slider.lazy_travel_dist += movement_len;
+        }
+
+        if i == last_idx {
+            lazy_end_pos = cursor_pos;
+        }
+    }
+
+    slider.lazy_end_pos = lazy_end_pos;
+}
+
+fn apply_stacking(
+    map: &Beatmap,
+    playfield: &OsuPlayfield,
+    objects: &mut [OsuObject],
+) {
+    if objects.is_empty() {
+        return;
+    }
+
+    let stack_threshold = playfield.time_preempt * map.stack_leniency;
+    let start_idx = 0;
+    let extended_end_idx = objects.len() - 1;
+
+    // Reverse pass for the stack calculation
+    let mut extended_start_idx = start_idx;
+
+    for i in (start_idx + 1..=extended_end_idx).rev() {
+        let mut n = i;
+        let mut object_i_idx = i;
+
+        if objects[i].stack_height != 0 || objects[i].is_spinner() {
+            continue;
+        }
+
+        match objects[i].kind {
+            OsuObjectKind::Circle => {
+                while n > 0 {
+                    n -= 1;
+
+                    if objects[n].is_spinner() {
+                        continue;
+                    }
+
+                    let end_time = objects[n].end_time();
+                    if objects[object_i_idx].start_time - end_time
+                        > stack_threshold
+                    {
+                        break;
+                    }
+
+                    if n < extended_start_idx {
+                        objects[n].stack_height = 0;
+                        extended_start_idx = n;
+                    }
+
+                    // Stacks of circles ending at a slider are moved in the
+                    // other direction
+                    if objects[n].is_slider()
+                        && objects[n]
+                            .end_pos()
+                            .distance(objects[object_i_idx].pos)
+                            < STACK_DISTANCE
+                    {
+                        let offset = objects[object_i_idx].stack_height
+                            - objects[n].stack_height
+                            + 1;
+                        let end_pos_n = objects[n].end_pos();
+
+                        for object_j in objects[n + 1..=i].iter_mut() {
+                            if end_pos_n.distance(object_j.pos) < STACK_DISTANCE
+                            {
+                                object_j.stack_height -= offset;
+                            }
+                        }
+
+                        break;
+                    }
+
+                    if objects[n].pos.distance(objects[object_i_idx].pos)
+                        < STACK_DISTANCE
+                    {
+                        objects[n].stack_height =
+                            objects[object_i_idx].stack_height + 1;
+                        object_i_idx = n;
+                    }
+                }
+            },
+            OsuObjectKind::Slider(_) => {
+                while n > start_idx {
+                    n -= 1;
+
+                    if objects[n].is_spinner() {
+                        continue;
+                    }
+
+                    if objects[object_i_idx].start_time - objects[n].start_time
+                        > stack_threshold
+                    {
+                        break;
+                    }
+
+                    if objects[n].end_pos().distance(objects[object_i_idx].pos)
+                        < STACK_DISTANCE
+                    {
+                        objects[n].stack_height =
+                            objects[object_i_idx].stack_height + 1;
+                        object_i_idx = n;
+                    }
+                }
+            },
+            OsuObjectKind::Spinner { .. } => {},
+        }
+    }
+}
+
+/// Stacking of beatmaps older than v6.
+fn apply_stacking_old(
+    map: &Beatmap,
+    playfield: &OsuPlayfield,
+    objects: &mut [OsuObject],
+) {
+    let stack_threshold = playfield.time_preempt * map.stack_leniency;
+
+    for i in 0..objects.len() {
+        if objects[i].stack_height != 0 && !objects[i].is_slider() {
+            continue;
+        }
+
+        let mut start_time = objects[i].end_time();
+        let mut slider_stack = 0;
+
+        let pos = objects[i].pos;
+        let path_end_pos =
+            objects[i].slider().map_or(pos, |slider| slider.path_end_pos);
+
+        for j in i + 1..objects.len() {
+            if objects[j].start_time - stack_threshold > start_time {
+                break;
+            }
+
+            if objects[j].pos.distance(pos) < STACK_DISTANCE {
+                objects[i].stack_height += 1;
+                start_time = objects[j].start_time;
+            } else if objects[j].pos.distance(path_end_pos) < STACK_DISTANCE {
+                slider_stack += 1;
+                objects[j].stack_height -= slider_stack;
+                start_time = objects[j].start_time;
+            }
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use super::{
+    base_performance, OsuDifficultyAttributes, PERFORMANCE_BASE_MULTIPLIER,
+};
+use crate::{mods::ModsExt, ScoreState};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OsuPerformanceAttributes {
+    pub pp: f64,
+    pub aim: f64,
+    pub speed: f64,
+    pub accuracy: f64,
+    pub flashlight: f64,
+    /// Misses plus the combo breaks guessed from the max combo.
+    pub effective_miss_count: f64,
+}
+
+/// Relax scores have no speed and accuracy pp and count the 100s and 50s
+/// as partial misses, autopilot scores have no aim pp.
+pub fn performance(
+    attrs: &OsuDifficultyAttributes,
+    mods: u32,
+    state: &ScoreState,
+) -> OsuPerformanceAttributes {
+    let total_hits = state.total_hits_osu() as f64;
+
+    if total_hits == 0.0 {
+        return OsuPerformanceAttributes::default();
+    }
+
+    let acc = state.accuracy_osu();
+    let mut effective_miss_count = effective_miss_count(attrs, state);
+
+    let mut multiplier = PERFORMANCE_BASE_MULTIPLIER;
+
+    if mods.nf() {
+        multiplier *= (1.0 - 0.02 * effective_miss_count).max(0.9);
+    }
+
+    if mods.so() && attrs.n_spinners > 0 {
+        multiplier *= 1.0 - (attrs.n_spinners as f64 / total_hits).powf(0.85);
+    }
+
+    if mods.rx() {
+        let od = attrs.od;
+        let ok_multiplier = if od > 0.0 {
+            (1.0 - (od / 13.33).powf(1.8)).max(0.0)
+        } else {
+            1.0
+        };
+        let meh_multiplier =
+            if od > 0.0 { (1.0 - (od / 13.33).powi(5)).max(0.0) } else { 1.0 };
+
+        effective_miss_count = (effective_miss_count
+            + state.n100 as f64 * ok_multiplier
+            + state.n50 as f64 * meh_multiplier)
+            .min(total_hits);
+    }
+
+    let ctx =
+        Context { attrs, mods, state, total_hits, acc, effective_miss_count };
+
+    let aim = ctx.aim();
+    let speed = ctx.speed();
+    let accuracy = ctx.accuracy();
+    let flashlight = ctx.flashlight();
+
+    let pp = (aim.powf(1.1)
+        + speed.powf(1.1)
+        + accuracy.powf(1.1)
+        + flashlight.powf(1.1))
+    .powf(1.0 / 1.1)
+        * multiplier;
+
+    OsuPerformanceAttributes {
+        pp,
+        aim,
+        speed,
+        accuracy,
+        flashlight,
+        effective_miss_count,
+    }
+}
+
+/// Sliders can be dropped without a miss, so the combo breaks are guessed
+/// from the max combo.
+fn effective_miss_count(
+    attrs: &OsuDifficultyAttributes,
+    state: &ScoreState,
+) -> f64 {
+    let mut combo_based_miss_count = 0.0;
+
+    if attrs.n_sliders > 0 {
+        let full_combo_threshold =
+            attrs.max_combo as f64 - 0.1 * attrs.n_sliders as f64;
+
+        if (state.max_combo as f64) < full_combo_threshold {
+            combo_based_miss_count =
+                full_combo_threshold / (state.max_combo as f64).max(1.0);
+        }
+    }
+
+    combo_based_miss_count = combo_based_miss_count
+        .min((state.n100 + state.n50 + state.nmiss) as f64);
+
+    combo_based_miss_count.max(state.nmiss as f64)
+}
+
+struct Context<'a> {
+    attrs: &'a OsuDifficultyAttributes,
+    mods: u32,
+    state: &'a ScoreState,
+    total_hits: f64,
+    acc: f64,
+    effective_miss_count: f64,
+}
+
+impl Context<'_> {
+    fn aim(&self) -> f64 {
+        if self.mods.ap() {
+            return 0.0;
+        }
+
+        let attrs = self.attrs;
+        let mut aim = base_performance(attrs.aim);
+
+        let len_bonus = self.len_bonus();
+        aim *= len_bonus;
+
+        if self.effective_miss_count > 0.0 {
+            aim *= 0.97
+                * (1.0
+                    - (self.effective_miss_count / self.total_hits)
+                        .powf(0.775))
+                .powf(self.effective_miss_count);
+        }
+
+        aim *= self.combo_scaling_factor();
+
+        let ar_factor = if attrs.ar > 10.33 {
+            0.3 * (attrs.ar - 10.33)
+        } else if attrs.ar < 8.0 {
+            0.05 * (8.0 - attrs.ar)
+        } else {
+            0.0
+        };
+        aim *= 1.0 + ar_factor * len_bonus;
+
+        if self.mods.hd() {
+            aim *= 1.0 + 0.04 * (12.0 - attrs.ar);
+        }
+
+        if attrs.n_sliders > 0 {
+            let estimate_diff_sliders = attrs.n_sliders as f64 * 0.15;
+            let estimate_slider_ends_dropped =
+                ((self.state.n100 + self.state.n50 + self.state.nmiss)
+                    .min(attrs.max_combo.saturating_sub(self.state.max_combo))
+                    as f64)
+                    .clamp(0.0, estimate_diff_sliders);
+
+            let slider_nerf_factor = (1.0 - attrs.slider_factor)
+                * (1.0 - estimate_slider_ends_dropped / estimate_diff_sliders)
+                    .powi(3)
+                + attrs.slider_factor;
+
+            aim *= slider_nerf_factor;
+        }
+
+        aim * self.acc * (0.98 + attrs.od * attrs.od / 2500.0)
+    }
+
+    fn speed(&self) -> f64 {
+        if self.mods.rx() {
+            return 0.0;
+        }
+
+        let attrs = self.attrs;
+        let state = self.state;
+        let mut speed = base_performance(attrs.speed);
+
+        let len_bonus = self.len_bonus();
+        speed *= len_bonus;
+
+        if self.effective_miss_count > 0.0 {
+            speed *= 0.97
+                * (1.0
+                    - (self.effective_miss_count / self.total_hits)
+                        .powf(0.775))
+                .powf(self.effective_miss_count.powf(0.875));
+        }
+
+        speed *= self.combo_scaling_factor();
+
+        if attrs.ar > 10.33 {
+            speed *= 1.0 + 0.3 * (attrs.ar - 10.33) * len_bonus;
+        }
+
+        if self.mods.hd() {
+            speed *= 1.0 + 0.04 * (12.0 - attrs.ar);
+        }
+
+        // Only the hits of the notes relevant to the speed difficulty count
+        let relevant_total_diff = self.total_hits - attrs.speed_note_count;
+        let n300 = state.n300 as f64;
+        let n100 = state.n100 as f64;
+        let n50 = state.n50 as f64;
+
+        let relevant_n300 = (n300 - relevant_total_diff).max(0.0);
+        let relevant_n100 =
+            (n100 - (relevant_total_diff - n300).max(0.0)).max(0.0);
+        let relevant_n50 =
+            (n50 - (relevant_total_diff - n300 - n100).max(0.0)).max(0.0);
+
+        let relevant_acc = if attrs.speed_note_count > 0.0 {
+            (relevant_n300 * 6.0 + relevant_n100 * 2.0 + relevant_n50)
+                / (attrs.speed_note_count * 6.0)
+        } else {
+            0.0
+        };
+
+        speed *= (0.95 + attrs.od * attrs.od / 750.0)
+            * ((self.acc + relevant_acc) / 2.0)
+                .powf((14.5 - attrs.od.max(8.0)) / 2.0);
+
+        let n50_threshold = self.total_hits / 500.0;
+        if n50 >= n50_threshold {
+            speed *= 0.99_f64.powf(n50 - n50_threshold);
+        }
+
+        speed
+    }
+
+    fn accuracy(&self) -> f64 {
+        if self.mods.rx() {
+            return 0.0;
+        }
+
+        let attrs = self.attrs;
+        let state = self.state;
+
+        // Only circles are judged by their timing
+        let n_circles = attrs.n_circles as f64;
+        let better_acc = if n_circles > 0.0 {
+            (((state.n300 as f64 - (self.total_hits - n_circles)) * 6.0
+                + state.n100 as f64 * 2.0
+                + state.n50 as f64)
+                / (n_circles * 6.0))
+                .max(0.0)
+        } else {
+            0.0
+        };
+
+        let mut accuracy =
+            1.52163_f64.powf(attrs.od) * better_acc.powi(24) * 2.83;
+
+        accuracy *= (n_circles / 1000.0).powf(0.3).min(1.15);
+
+        if self.mods.hd() {
+            accuracy *= 1.08;
+        }
+
+        if self.mods.fl() {
+            accuracy *= 1.02;
+        }
+
+        accuracy
+    }
+
+    fn flashlight(&self) -> f64 {
+        if !self.mods.fl() {
+            return 0.0;
+        }
+
+        let attrs = self.attrs;
+        let mut flashlight = attrs.flashlight * attrs.flashlight * 25.0;
+
+        if self.effective_miss_count > 0.0 {
+            flashlight *= 0.97
+                * (1.0
+                    - (self.effective_miss_count / self.total_hits)
+                        .powf(0.775))
+                .powf(self.effective_miss_count.powf(0.875));
+        }
+
+        flashlight *= self.combo_scaling_factor();
+
+        flashlight *= 0.7
+            + 0.1 * (self.total_hits / 200.0).min(1.0)
+            + if self.total_hits > 200.0 {
+                0.2 * ((self.total_hits - 200.0) / 200.0).min(1.0)
+            } else {
+                0.0
+            };
+
+        flashlight
+            * (0.5 + self.acc / 2.0)
+            * (0.98 + attrs.od * attrs.od / 2500.0)
+    }
+
+    #[inline]
+    fn len_bonus(&self) -> f64 {
+        0.95 + 0.4 * (self.total_hits / 2000.0).min(1.0)
+            + if self.total_hits > 2000.0 {
+                (self.total_hits / 2000.0).log10() * 0.5
+            } else {
+                0.0
+            }
+    }
+
+    #[inline]
+    fn combo_scaling_factor(&self) -> f64 {
+        if self.attrs.max_combo == 0 {
+            return 1.0;
+        }
+
+        ((self.state.max_combo as f64).powf(0.8)
+            / (self.attrs.max_combo as f64).powf(0.8))
+        .min(1.0)
+    }
+}
@@ -0,0 +1,677 @@
+use super::{difficulty_object::OsuDifficultyObject, object::OsuPlayfield};
+use crate::{
+    math::{lerp, sort_desc, weighted_sum},
+    strain::{strain_decay, StrainPeaks},
+};
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_6, PI};
+
+const REDUCED_SECTION_COUNT: usize = 10;
+const REDUCED_STRAIN_BASELINE: f64 = 0.75;
+const DECAY_WEIGHT: f64 = 0.9;
+pub(crate) const DEFAULT_DIFFICULTY_MULTIPLIER: f64 = 1.06;
+
+/// The hardest sections are reduced to account for extreme difficulty
+/// spikes.
+fn osu_difficulty_value(
+    peaks: Vec<f64>,
+    reduced_section_count: usize,
+    difficulty_multiplier: f64,
+) -> f64 {
+    let mut strains: Vec<f64> =
+        peaks.into_iter().filter(|&p| p > 0.0).collect();
+    sort_desc(&mut strains);
+
+    for (i, strain) in
+        strains.iter_mut().take(reduced_section_count).enumerate()
+    {
+        let scale = lerp(
+            1.0,
+            10.0,
+            (i as f64 / reduced_section_count as f64).clamp(0.0, 1.0),
+        )
+        .log10();
+
+        *strain *= lerp(REDUCED_STRAIN_BASELINE, 1.0, scale);
+    }
+
+    sort_desc(&mut strains);
+
+    weighted_sum(strains, DECAY_WEIGHT) * difficulty_multiplier
+}
+
+pub(crate) struct Aim {
+    with_sliders: bool,
+    current_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl Aim {
+    const SKILL_MULTIPLIER: f64 = 23.55;
+    const STRAIN_DECAY_BASE: f64 = 0.15;
+
+    #[inline]
+    pub fn new(with_sliders: bool) -> Self {
+        Self {
+            with_sliders,
+            current_strain: 0.0,
+            peaks: StrainPeaks::default(),
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        current: &OsuDifficultyObject,
+        objects: &[OsuDifficultyObject],
+    ) {
+        let prev_start_time =
+            current.previous(0, objects).map_or(0.0, |prev| prev.start_time);
+        let current_strain = self.current_strain;
+
+        self.peaks.start_sections_until(
+            current.start_time,
+            current.idx == 0,
+            |time| {
+                current_strain
+                    * strain_decay(
+                        time - prev_start_time,
+                        Self::STRAIN_DECAY_BASE,
+                    )
+            },
+        );
+
+        self.current_strain *=
+            strain_decay(current.delta_time, Self::STRAIN_DECAY_BASE);
+        self.current_strain +=
+            evaluate_aim(current, objects, self.with_sliders)
+                * Self::SKILL_MULTIPLIER;
+
+        self.peaks.add(self.current_strain);
+    }
+
+    #[inline]
+    pub fn difficulty_value(self) -> f64 {
+        osu_difficulty_value(
+            self.peaks.into_peaks(),
+            REDUCED_SECTION_COUNT,
+            DEFAULT_DIFFICULTY_MULTIPLIER,
+        )
+    }
+}
+
+pub(crate) struct Speed {
+    great_window: f64,
+    current_strain: f64,
+    current_rhythm: f64,
+    object_strains: Vec<f64>,
+    peaks: StrainPeaks,
+}
+
+impl Speed {
+    const SKILL_MULTIPLIER: f64 = 1375.0;
+    const STRAIN_DECAY_BASE: f64 = 0.3;
+    const REDUCED_SECTION_COUNT: usize = 5;
+    const DIFFICULTY_MULTIPLIER: f64 = 1.04;
+
+    #[inline]
+    pub fn new(great_window: f64) -> Self {
+        Self {
+            great_window,
+            current_strain: 0.0,
+            current_rhythm: 0.0,
+            object_strains: Vec::new(),
+            peaks: StrainPeaks::default(),
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        current: &OsuDifficultyObject,
+        objects: &[OsuDifficultyObject],
+    ) {
+        let prev_start_time =
+            current.previous(0, objects).map_or(0.0, |prev| prev.start_time);
+        let total_strain = self.current_strain * self.current_rhythm;
+
+        self.peaks.start_sections_until(
+            current.start_time,
+            current.idx == 0,
+            |time| {
+                total_strain
+                    * strain_decay(
+                        time - prev_start_time,
+                        Self::STRAIN_DECAY_BASE,
+                    )
+            },
+        );
+
+        self.current_strain *=
+            strain_decay(current.strain_time, Self::STRAIN_DECAY_BASE);
+        self.current_strain +=
+            evaluate_speed(current, objects, self.great_window)
+                * Self::SKILL_MULTIPLIER;
+        self.current_rhythm =
+            evaluate_rhythm(current, objects, self.great_window);
+
+        let total_strain = self.current_strain * self.current_rhythm;
+        self.object_strains.push(total_strain);
+        self.peaks.add(total_strain);
+    }
+
+    /// Number of notes weighted by how much they contribute to the
+    /// difficulty.
+    pub fn relevant_note_count(&self) -> f64 {
+        let max_strain =
+            self.object_strains.iter().copied().fold(0.0, f64::max);
+
+        if max_strain == 0.0 {
+            return 0.0;
+        }
+
+        self.object_strains
+            .iter()
+            .map(|strain| {
+                1.0 / (1.0 + (-(strain / max_strain * 12.0 - 6.0)).exp())
+            })
+            .sum()
+    }
+
+    #[inline]
+    pub fn difficulty_value(self) -> f64 {
+        osu_difficulty_value(
+            self.peaks.into_peaks(),
+            Self::REDUCED_SECTION_COUNT,
+            Self::DIFFICULTY_MULTIPLIER,
+        )
+    }
+}
+
+pub(crate) struct Flashlight {
+    hidden: bool,
+    playfield: OsuPlayfield,
+    current_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl Flashlight {
+    const SKILL_MULTIPLIER: f64 = 0.052;
+    const STRAIN_DECAY_BASE: f64 = 0.15;
+
+    #[inline]
+    pub fn new(hidden: bool, playfield: OsuPlayfield) -> Self {
+        Self {
+            hidden,
+            playfield,
+            current_strain: 0.0,
+            peaks: StrainPeaks::default(),
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        current: &OsuDifficultyObject,
+        objects: &[OsuDifficultyObject],
+    ) {
+        let prev_start_time =
+            current.previous(0, objects).map_or(0.0, |prev| prev.start_time);
+        let current_strain = self.current_strain;
+
+        self.peaks.start_sections_until(
+            current.start_time,
+            current.idx == 0,
+            |time| {
+                current_strain
+                    * strain_decay(
+                        time - prev_start_time,
+                        Self::STRAIN_DECAY_BASE,
+                    )
+            },
+        );
+
+        self.current_strain *=
+            strain_decay(current.delta_time, Self::STRAIN_DECAY_BASE);
+        self.current_strain +=
+            evaluate_flashlight(current, objects, self.hidden, &self.playfield)
+                * Self::SKILL_MULTIPLIER;
+
+        self.peaks.add(self.current_strain);
+    }
+
+    /// Every section counts the same.
+    #[inline]
+    pub fn difficulty_value(self) -> f64 {
+        self.peaks.into_peaks().into_iter().sum::<f64>()
+            * DEFAULT_DIFFICULTY_MULTIPLIER
+    }
+}
+
+fn evaluate_aim(
+    current: &OsuDifficultyObject,
+    objects: &[OsuDifficultyObject],
+    with_sliders: bool,
+) -> f64 {
+    const WIDE_ANGLE_MULTIPLIER: f64 = 1.5;
+    const ACUTE_ANGLE_MULTIPLIER: f64 = 1.95;
+    const SLIDER_MULTIPLIER: f64 = 1.35;
+    const VELOCITY_CHANGE_MULTIPLIER: f64 = 0.75;
+
+    let (Some(last), Some(last_last)) =
+        (current.previous(0, objects), current.previous(1, objects))
+    else {
+        return 0.0;
+    };
+
+    if current.base.is_spinner() || last.base.is_spinner() {
+        return 0.0;
+    }
+
+    // Velocity to the current object, through the previous slider if any
+    let mut curr_velocity = current.lazy_jump_dist / current.strain_time;
+
+    if last.base.is_slider() && with_sliders {
+        let travel_velocity = last.travel_dist / last.travel_time;
+        let movement_velocity = current.min_jump_dist / current.min_jump_time;
+
+        curr_velocity = curr_velocity.max(movement_velocity + travel_velocity);
+    }
+
+    let mut prev_velocity = last.lazy_jump_dist / last.strain_time;
+
+    if last_last.base.is_slider() && with_sliders {
+        let travel_velocity = last_last.travel_dist / last_last.travel_time;
+        let movement_velocity = last.min_jump_dist / last.min_jump_time;
+
+        prev_velocity = prev_velocity.max(movement_velocity + travel_velocity);
+    }
+
+    let mut wide_angle_bonus = 0.0;
+    let mut acute_angle_bonus = 0.0;
+    let mut slider_bonus = 0.0;
+    let mut velocity_change_bonus = 0.0;
+
+    let mut aim_strain = curr_velocity;
+
+    // Angles only matter if the rhythm stays the same
+    if current.strain_time.max(last.strain_time)
+        < 1.25 * current.strain_time.min(last.strain_time)
+    {
+        if let (Some(curr_angle), Some(last_angle), Some(last_last_angle)) =
+            (current.angle, last.angle, last_last.angle)
+        {
+            // The smaller velocity is the base of the angle bonus
+            let angle_bonus = curr_velocity.min(prev_velocity);
+
+            wide_angle_bonus = calc_wide_angle_bonus(curr_angle);
+            acute_angle_bonus = calc_acute_angle_bonus(curr_angle);
+
+            // Only buff delta times exceeding 300 bpm 1/2
+            if current.strain_time > 100.0 {
+                acute_angle_bonus = 0.0;
+            } else {
+                acute_angle_bonus *= calc_acute_angle_bonus(last_angle)
+                    * angle_bonus.min(125.0 / current.strain_time)
+                    * (FRAC_PI_2
+                        * ((100.0 - current.strain_time) / 25.0).min(1.0))
+                    .sin()
+                    .powi(2)
+                    * (FRAC_PI_2
+                        * (current.lazy_jump_dist.clamp(50.0, 100.0) - 50.0)
+                        / 50.0)
+                        .sin()
+                        .powi(2);
+            }
+
+            // Penalize repeated wide angles, less so the more acute the
+            // previous angle is
+            wide_angle_bonus *= angle_bonus
+                * (1.0
+                    - wide_angle_bonus
+                        .min(calc_wide_angle_bonus(last_angle).powi(3)));
+
+            // Penalize repeated acute angles, less so the more obtuse the
+            // angle before the previous one is
+            acute_angle_bonus *= 0.5
+                + 0.5
+                    * (1.0
+                        - acute_angle_bonus.min(
+                            calc_acute_angle_bonus(last_last_angle).powi(3),
+                        ));
+        }
+    }
+
+    if prev_velocity.max(curr_velocity) != 0.0 {
+        // Average velocity over the whole object, not the individual jump
+        // and slider path velocities
+        prev_velocity =
+            (last.lazy_jump_dist + last_last.travel_dist) / last.strain_time;
+        curr_velocity =
+            (current.lazy_jump_dist + last.travel_dist) / current.strain_time;
+
+        let dist_ratio = (FRAC_PI_2 * (prev_velocity - curr_velocity).abs()
+            / prev_velocity.max(curr_velocity))
+        .sin()
+        .powi(2);
+
+        // Reward overlaps where the velocity is still changing
+        let overlap_velocity_buff = (125.0
+            / current.strain_time.min(last.strain_time))
+        .min((prev_velocity - curr_velocity).abs());
+
+        velocity_change_bonus = overlap_velocity_buff * dist_ratio;
+
+        // Penalize rhythm changes
+        velocity_change_bonus *= (current.strain_time.min(last.strain_time)
+            / current.strain_time.max(last.strain_time))
+        .powi(2);
+    }
+
+    if last.base.is_slider() {
+        slider_bonus = last.travel_dist / last.travel_time;
+    }
+
+    aim_strain += (acute_angle_bonus * ACUTE_ANGLE_MULTIPLIER).max(
+        wide_angle_bonus * WIDE_ANGLE_MULTIPLIER
+            + velocity_change_bonus * VELOCITY_CHANGE_MULTIPLIER,
+    );
+
+    if with_sliders {
+        aim_strain += slider_bonus * SLIDER_MULTIPLIER;
+    }
+
+    aim_strain
+}
+
+#[inline]
+fn calc_wide_angle_bonus(angle: f64) -> f64 {
+    (3.0 / 4.0 * (angle.clamp(FRAC_PI_6, 5.0 / 6.0 * PI) - FRAC_PI_6))
+        .sin()
+        .powi(2)
+}
+
+#[inline]
+fn calc_acute_angle_bonus(angle: f64) -> f64 {
+    1.0 - calc_wide_angle_bonus(angle)
+}
+
+fn evaluate_speed(
+    current: &OsuDifficultyObject,
+    objects: &[OsuDifficultyObject],
+    great_window: f64,
+) -> f64 {
+    const SINGLE_SPACING_THRESHOLD: f64 = 125.0;
+    const MIN_SPEED_BONUS: f64 = 75.0;
+    const SPEED_BALANCING_FACTOR: f64 = 40.0;
+
+    if current.base.is_spinner() {
+        return 0.0;
+    }
+
+    let prev = current.previous(0, objects);
+    let next = current.next(0, objects);
+
+    let mut strain_time = current.strain_time;
+    let great_window_full = great_window * 2.0;
+
+    // Nerf doubletappable doubles
+    let doubletapness = next.map_or(1.0, |next| {
+        let curr_delta_time = current.delta_time.max(1.0);
+        let next_delta_time = next.delta_time.max(1.0);
+        let delta_difference = (next_delta_time - curr_delta_time).abs();
+        let speed_ratio =
+            curr_delta_time / curr_delta_time.max(delta_difference);
+        let window_ratio =
+            (curr_delta_time / great_window_full).min(1.0).powi(2);
+
+        speed_ratio.powf(1.0 - window_ratio)
+    });
+
+    // Cap the delta time to the OD 300 hit window
+    strain_time /= ((strain_time / great_window_full) / 0.93).clamp(0.92, 1.0);
+
+    let speed_bonus = if strain_time < MIN_SPEED_BONUS {
+        1.0 + 0.75
+            * ((MIN_SPEED_BONUS - strain_time) / SPEED_BALANCING_FACTOR).powi(2)
+    } else {
+        1.0
+    };
+
+    let travel_dist = prev.map_or(0.0, |prev| prev.travel_dist);
+    let dist =
+        SINGLE_SPACING_THRESHOLD.min(travel_dist + current.min_jump_dist);
+
+    (speed_bonus + speed_bonus * (dist / SINGLE_SPACING_THRESHOLD).powf(3.5))
+        * doubletapness
+        / strain_time
+}
+
+fn evaluate_rhythm(
+    current: &OsuDifficultyObject,
+    objects: &[OsuDifficultyObject],
+    great_window: f64,
+) -> f64 {
+    const HISTORY_TIME_MAX: f64 = 5000.0;
+    const RHYTHM_MULTIPLIER: f64 = 0.75;
+
+    if current.base.is_spinner() {
+        return 0.0;
+    }
+
+    let mut previous_island_size = 0;
+    let mut rhythm_complexity_sum = 0.0;
+    let mut island_size = 1;
+    // Ratio of the start of the current island, tighter rhythms are buffed
+    let mut start_ratio = 0.0;
+    let mut first_delta_switch = false;
+
+    let historical_note_count = current.idx.min(32);
+
+    let mut rhythm_start = 0;
+
+    while rhythm_start + 2 < historical_note_count
+        && current.previous(rhythm_start, objects).is_some_and(|prev| {
+            current.start_time - prev.start_time < HISTORY_TIME_MAX
+        })
+    {
+        rhythm_start += 1;
+    }
+
+    for i in (1..=rhythm_start).rev() {
+        let (Some(curr), Some(prev), Some(last)) = (
+            current.previous(i - 1, objects),
+            current.previous(i, objects),
+            current.previous(i + 1, objects),
+        ) else {
+            continue;
+        };
+
+        // Scales notes from 0 to 1, limited by time or object count
+        let curr_historical_decay = ((HISTORY_TIME_MAX
+            - (current.start_time - curr.start_time))
+            / HISTORY_TIME_MAX)
+            .min(
+                (historical_note_count - i) as f64
+                    / historical_note_count as f64,
+            );
+
+        let curr_delta = curr.strain_time;
+        let prev_delta = prev.strain_time;
+        let last_delta = last.strain_time;
+
+        let curr_ratio = 1.0
+            + 6.0
+                * (PI
+                    / (prev_delta.min(curr_delta)
+                        / prev_delta.max(curr_delta)))
+                .sin()
+                .powi(2)
+                .min(0.5);
+
+        let window_penalty =
+            (((prev_delta - curr_delta).abs() - great_window * 0.6).max(0.0)
+                / (great_window * 0.6))
+                .min(1.0);
+
+        let mut effective_ratio = window_penalty * curr_ratio;
+
+        if first_delta_switch {
+            if !(prev_delta > 1.25 * curr_delta
+                || prev_delta * 1.25 < curr_delta)
+            {
+                // The island is still progressing
+                if island_size < 7 {
+                    island_size += 1;
+                }
+            } else {
+                // Speed changes into sliders are easy acc windows
+                if curr.base.is_slider() {
+                    effective_ratio *= 0.125;
+                }
+
+                // Speed changes from sliders are easier than from circles
+                if prev.base.is_slider() {
+                    effective_ratio *= 0.25;
+                }
+
+                // Repeated island size (e.g. triplet -> triplet)
+                if previous_island_size == island_size {
+                    effective_ratio *= 0.25;
+                }
+
+                // Repeated island polarity (2 -> 4, 3 -> 5)
+                if previous_island_size % 2 == island_size % 2 {
+                    effective_ratio *= 0.5;
+                }
+
+                // Previous increase happened a note ago, 1/1 -> 1/2 -> 1/4
+                if last_delta > prev_delta + 10.0
+                    && prev_delta > curr_delta + 10.0
+                {
+                    effective_ratio *= 0.125;
+                }
+
+                rhythm_complexity_sum += (effective_ratio * start_ratio).sqrt()
+                    * curr_historical_decay
+                    * ((4 + island_size) as f64).sqrt()
+                    / 2.0
+                    * ((4 + previous_island_size) as f64).sqrt()
+                    / 2.0;
+
+                start_ratio = effective_ratio;
+                previous_island_size = island_size;
+
+                // Slowing down stops the island, speeding up keeps counting
+                if prev_delta * 1.25 < curr_delta {
+                    first_delta_switch = false;
+                }
+
+                island_size = 1;
+            }
+        } else if prev_delta > 1.25 * curr_delta {
+            // Count the island until the speed changes again
+            first_delta_switch = true;
+            start_ratio = effective_ratio;
+            island_size = 1;
+        }
+    }
+
+    (4.0 + rhythm_complexity_sum * RHYTHM_MULTIPLIER).sqrt() / 2.0
+}
+
+fn evaluate_flashlight(
+    current: &OsuDifficultyObject,
+    objects: &[OsuDifficultyObject],
+    hidden: bool,
+    playfield: &OsuPlayfield,
+) -> f64 {
+    const MAX_OPACITY_BONUS: f64 = 0.4;
+    const HIDDEN_BONUS: f64 = 0.2;
+    const MIN_VELOCITY: f64 = 0.5;
+    const SLIDER_MULTIPLIER: f64 = 1.3;
+    const MIN_ANGLE_MULTIPLIER: f64 = 0.2;
+
+    if current.base.is_spinner() {
+        return 0.0;
+    }
+
+    let scaling_factor = 52.0 / playfield.radius;
+
+    let mut small_dist_nerf = 1.0;
+    let mut cumulative_strain_time = 0.0;
+    let mut result = 0.0;
+    let mut last = current;
+    let mut angle_repeat_count = 0.0;
+
+    // Iterating backwards in time from the current object
+    for i in 0..current.idx.min(10) {
+        let Some(curr) = current.previous(i, objects) else {
+            break;
+        };
+
+        if !curr.base.is_spinner() {
+            let jump_dist = (current.base.stacked_pos()
+                - curr.base.stacked_end_pos())
+            .length();
+
+            cumulative_strain_time += last.strain_time;
+
+            // Nerf objects that can be seen within the flashlight radius
+            if i == 0 {
+                small_dist_nerf = (jump_dist / 75.0).min(1.0);
+            }
+
+            // Only the first object of a stack counts
+            let stack_nerf =
+                ((curr.lazy_jump_dist / scaling_factor) / 25.0).min(1.0);
+
+            // Bonus based on how visible the object is
+            let opacity_bonus = 1.0
+                + MAX_OPACITY_BONUS
+                    * (1.0
+                        - current.opacity_at(
+                            curr.base.start_time,
+                            hidden,
+                            playfield,
+                        ));
+
+            result += stack_nerf * opacity_bonus * scaling_factor * jump_dist
+                / cumulative_strain_time;
+
+            if let (Some(curr_angle), Some(angle)) = (curr.angle, current.angle)
+            {
+                // Objects further back count less for the nerf
+                if (curr_angle - angle).abs() < 0.02 {
+                    angle_repeat_count += (1.0 - 0.1 * i as f64).max(0.0);
+                }
+            }
+        }
+
+        last = curr;
+    }
+
+    result = (small_dist_nerf * result).powi(2);
+
+    // No approach circles with hidden
+    if hidden {
+        result *= 1.0 + HIDDEN_BONUS;
+    }
+
+    // Nerf patterns with repeated angles
+    result *= MIN_ANGLE_MULTIPLIER
+        + (1.0 - MIN_ANGLE_MULTIPLIER) / (angle_repeat_count + 1.0);
+
+    if let Some(slider) = current.base.slider() {
+        // The true travel distance, independent of the circle size
+        let pixel_travel_dist = slider.lazy_travel_dist / scaling_factor;
+
+        // Longer sliders require more memorisation, repeating ones less
+        let slider_bonus = (pixel_travel_dist / current.travel_time
+            - MIN_VELOCITY)
+            .max(0.0)
+            .sqrt()
+            * pixel_travel_dist
+            / (slider.repeats + 1) as f64;
+
+        result += slider_bonus * SLIDER_MULTIPLIER;
+    }
+
+    result
+}
@@ -0,0 +1,124 @@
+//! Nested objects of sliders and juice streams.
+
+/// osu!stable moved the last tick of a slider 36ms before its end.
+pub(crate) const LEGACY_LAST_TICK_OFFSET: f64 = 36.0;
+
+/// Sliders are scored on a distance of 100 osu!pixels per beat.
+pub(crate) const BASE_SCORING_DISTANCE: f64 = 100.0;
+
+const MAX_LENGTH: f64 = 100_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SliderEventKind {
+    Head,
+    Tick,
+    Repeat,
+    LegacyLastTick,
+    Tail,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SliderEvent {
+    pub kind: SliderEventKind,
+    pub time: f64,
+    pub path_progress: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SliderEvents {
+    pub start_time: f64,
+    pub span_duration: f64,
+    pub velocity: f64,
+    pub tick_distance: f64,
+    pub total_distance: f64,
+    pub span_count: usize,
+}
+
+impl SliderEvents {
+    /// Events in the order osu!stable created them, not necessarily sorted
+    /// by time.
+    pub fn generate(&self) -> Vec<SliderEvent> {
+        let Self { start_time, span_duration, velocity, span_count, .. } =
+            *self;
+
+        let length = self.total_distance.min(MAX_LENGTH);
+        let tick_distance = self.tick_distance.clamp(0.0, length);
+        let min_distance_from_end = velocity * 10.0;
+
+        let mut events = vec![SliderEvent {
+            kind: SliderEventKind::Head,
+            time: start_time,
+            path_progress: 0.0,
+        }];
+
+        if tick_distance != 0.0 {
+            for span in 0..span_count {
+                let span_start_time = start_time + span as f64 * span_duration;
+                let reversed = span % 2 == 1;
+                let ticks_start = events.len();
+
+                let mut d = tick_distance;
+                while d <= length {
+                    if d >= length - min_distance_from_end {
+                        break;
+                    }
+
+                    let path_progress = d / length;
+                    let time_progress = if reversed {
+                        1.0 - path_progress
+                    } else {
+                        path_progress
+                    };
+
+                    events.push(SliderEvent {
+                        kind: SliderEventKind::Tick,
+                        time: span_start_time + time_progress * span_duration,
+                        path_progress,
+                    });
+
+                    d += tick_distance;
+                }
+
+                if reversed {
+                    events[ticks_start..].reverse();
+                }
+
+                if span < span_count - 1 {
+                    events.push(SliderEvent {
+                        kind: SliderEventKind::Repeat,
+                        time: span_start_time + span_duration,
+                        path_progress: ((span + 1) % 2) as f64,
+                    });
+                }
+            }
+        }
+
+        let total_duration = span_count as f64 * span_duration;
+
+        let final_span_start_time =
+            start_time + (span_count - 1) as f64 * span_duration;
+        let final_span_end_time = (start_time + total_duration / 2.0).max(
+            final_span_start_time + span_duration - LEGACY_LAST_TICK_OFFSET,
+        );
+        let mut final_progress =
+            (final_span_end_time - final_span_start_time) / span_duration;
+
+        if span_count % 2 == 0 {
+            final_progress = 1.0 - final_progress;
+        }
+
+        events.push(SliderEvent {
+            kind: SliderEventKind::LegacyLastTick,
+            time: final_span_end_time,
+            path_progress: final_progress,
+        });
+
+        events.push(SliderEvent {
+            kind: SliderEventKind::Tail,
+            time: start_time + total_duration,
+            path_progress: (span_count % 2) as f64,
+        });
+
+        events
+    }
+}
@@ -0,0 +1,82 @@
+use crate::math::{sort_desc, weighted_sum};
+
+pub(crate) const SECTION_LENGTH: f64 = 400.0;
+
+/// Highest strain of every section of a beatmap.
+#[derive(Debug, Clone)]
+pub(crate) struct StrainPeaks {
+    section_len: f64,
+    current_section_peak: f64,
+    current_section_end: f64,
+    peaks: Vec<f64>,
+}
+
+impl Default for StrainPeaks {
+    #[inline]
+    fn default() -> Self {
+        Self::new(SECTION_LENGTH)
+    }
+}
+
+impl StrainPeaks {
+    #[inline]
+    pub fn new(section_len: f64) -> Self {
+        Self {
+            section_len,
+            current_section_peak: 0.0,
+            current_section_end: 0.0,
+            peaks: Vec::new(),
+        }
+    }
+
+    /// Closes all sections ending before `time`. The strain at the start of
+    /// every new section is its initial peak, as strains do not drop to zero
+    /// between sections.
+    pub fn start_sections_until<F>(
+        &mut self,
+        time: f64,
+        is_first: bool,
+        mut initial_strain: F,
+    ) where
+        F: FnMut(f64) -> f64,
+    {
+        // The first object doesn't generate a strain
+        if is_first {
+            self.current_section_end =
+                (time / self.section_len).ceil() * self.section_len;
+        }
+
+        while time > self.current_section_end {
+            self.peaks.push(self.current_section_peak);
+            self.current_section_peak =
+                initial_strain(self.current_section_end);
+            self.current_section_end += self.section_len;
+        }
+    }
+
+    #[inline]
+    pub fn add(&mut self, strain: f64) {
+        self.current_section_peak = self.current_section_peak.max(strain);
+    }
+
+    /// All peaks, including the one of the unfinished section.
+    #[inline]
+    pub fn into_peaks(mut self) -> Vec<f64> {
+        self.peaks.push(self.current_section_peak);
+        self.peaks
+    }
+}
+
+/// Weighted sum of the peaks, sections without strain are skipped.
+#[inline]
+pub(crate) fn difficulty_value(peaks: Vec<f64>, decay_weight: f64) -> f64 {
+    let mut peaks: Vec<f64> = peaks.into_iter().filter(|&p| p > 0.0).collect();
+    sort_desc(&mut peaks);
+
+    weighted_sum(peaks, decay_weight)
+}
+
+#[inline]
+pub(crate) fn strain_decay(ms: f64, decay_base: f64) -> f64 {
+    decay_base.powf(ms / 1000.0)
+}
@@ -0,0 +1,396 @@
+//! osu!taiko difficulty and performance, the same as
+//! osu!lazer in late 2021.
+
+mod pp;
+mod skills;
+
+pub use self::pp::{performance, TaikoPerformanceAttributes};
+
+use self::skills::{Colour, Rhythm, Stamina, StaminaCheeseDetector};
+use crate::{
+    beatmap::{Beatmap, GameMode, HitObject, HitObjectKind},
+    curve::SliderPath,
+    math::difficulty_range,
+    mods::ModsExt,
+    slider::BASE_SCORING_DISTANCE,
+};
+
+const RHYTHM_SKILL_MULTIPLIER: f64 = 0.014;
+const COLOUR_SKILL_MULTIPLIER: f64 = 0.01;
+const STAMINA_SKILL_MULTIPLIER: f64 = 0.02;
+
+/// osu!stable multiplied the slider velocity of converted beatmaps.
+const LEGACY_VELOCITY_MULTIPLIER: f64 = 1.4;
+
+const SOUND_WHISTLE: u8 = 1 << 1;
+const SOUND_CLAP: u8 = 1 << 3;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TaikoDifficultyAttributes {
+    pub stamina: f64,
+    pub rhythm: f64,
+    pub colour: f64,
+    /// Hit window of a great, adjusted by the clock rate.
+    pub great_hit_window: f64,
+    pub max_combo: usize,
+    pub stars: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TaikoObjectKind {
+    Hit { rim: bool },
+    DrumRoll,
+    Swell,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TaikoObject {
+    pub start_time: f64,
+    pub kind: TaikoObjectKind,
+}
+
+impl TaikoObject {
+    #[inline]
+    pub fn is_hit(&self) -> bool {
+        matches!(self.kind, TaikoObjectKind::Hit { .. })
+    }
+
+    /// `Some(true)` for rims, `None` for drum rolls and swells.
+    #[inline]
+    pub fn hit_type(&self) -> Option<bool> {
+        match self.kind {
+            TaikoObjectKind::Hit { rim } => Some(rim),
+            _ => None,
+        }
+    }
+}
+
+/// Ratio of the current to the previous delta time, rounded to the closest
+/// common rhythm.
+#[derive(Debug, Clone, Copy)]
+struct CommonRhythm {
+    ratio: f64,
+    difficulty: f64,
+}
+
+const COMMON_RHYTHMS: [CommonRhythm; 9] = [
+    CommonRhythm { ratio: 1.0, difficulty: 0.0 },
+    CommonRhythm { ratio: 2.0 / 1.0, difficulty: 0.3 },
+    CommonRhythm { ratio: 1.0 / 2.0, difficulty: 0.5 },
+    CommonRhythm { ratio: 3.0 / 1.0, difficulty: 0.3 },
+    CommonRhythm { ratio: 1.0 / 3.0, difficulty: 0.35 },
+    // Purposefully higher, requires a hand switch in full alternating
+    CommonRhythm { ratio: 3.0 / 2.0, difficulty: 0.6 },
+    CommonRhythm { ratio: 2.0 / 3.0, difficulty: 0.4 },
+    CommonRhythm { ratio: 5.0 / 4.0, difficulty: 0.5 },
+    CommonRhythm { ratio: 4.0 / 5.0, difficulty: 0.7 },
+];
+
+#[derive(Debug, Clone)]
+pub(crate) struct TaikoDifficultyObject {
+    pub start_time: f64,
+    pub delta_time: f64,
+    /// Index into the common rhythms.
+    pub rhythm: usize,
+    pub hit_type: Option<bool>,
+    pub last_is_hit: bool,
+    /// Index of the object in the beatmap.
+    pub object_idx: usize,
+    pub stamina_cheese: bool,
+}
+
+impl TaikoDifficultyObject {
+    fn new(
+        objects: &[TaikoObject],
+        object_idx: usize,
+        clock_rate: f64,
+    ) -> Self {
+        let base = &objects[object_idx];
+        let last = &objects[object_idx - 1];
+        let last_last = &objects[object_idx - 2];
+
+        let delta_time = (base.start_time - last.start_time) / clock_rate;
+        let prev_len = (last.start_time - last_last.start_time) / clock_rate;
+        let ratio = delta_time / prev_len;
+
+        // Ties and NaN ratios keep the first rhythm
+        let mut rhythm = 0;
+        for (i, common) in COMMON_RHYTHMS.iter().enumerate() {
+            if (common.ratio - ratio).abs()
+                < (COMMON_RHYTHMS[rhythm].ratio - ratio).abs()
+            {
+                rhythm = i;
+            }
+        }
+
+        Self {
+            start_time: base.start_time / clock_rate,
+            delta_time,
+            rhythm,
+            hit_type: base.hit_type(),
+            last_is_hit: last.is_hit(),
+            object_idx,
+            stamina_cheese: false,
+        }
+    }
+
+    #[inline]
+    pub fn is_hit(&self) -> bool {
+        self.hit_type.is_some()
+    }
+
+    #[inline]
+    pub fn rhythm_difficulty(&self) -> f64 {
+        COMMON_RHYTHMS[self.rhythm].difficulty
+    }
+}
+
+pub fn calculate(map: &Beatmap, mods: u32) -> TaikoDifficultyAttributes {
+    let clock_rate = mods.clock_rate();
+    let difficulty = map.difficulty(mods);
+    let is_convert = map.mode != GameMode::Taiko;
+
+    let objects = convert_objects(map, is_convert);
+
+    let mut attrs = TaikoDifficultyAttributes {
+        // Truncated to match osu!stable
+        great_hit_window: difficulty_range(difficulty.od, 50.0, 35.0, 20.0)
+            .trunc()
+            / clock_rate,
+        max_combo: objects.iter().filter(|h| h.is_hit()).count(),
+        ..Default::default()
+    };
+
+    let mut diff_objects: Vec<TaikoDifficultyObject> = (2..objects.len())
+        .map(|i| TaikoDifficultyObject::new(&objects, i, clock_rate))
+        .collect();
+
+    StaminaCheeseDetector::new(&mut diff_objects).find_cheese();
+
+    let mut colour = Colour::default();
+    let mut rhythm = Rhythm::default();
+    let mut stamina_right = Stamina::new(true);
+    let mut stamina_left = Stamina::new(false);
+
+    for (i, current) in diff_objects.iter().enumerate() {
+        let prev_start_time =
+            i.checked_sub(1).map_or(0.0, |idx| diff_objects[idx].start_time);
+
+        colour.process(current, i == 0, prev_start_time);
+        rhythm.process(current, i == 0, prev_start_time);
+        stamina_right.process(current, i == 0, prev_start_time);
+        stamina_left.process(current, i == 0, prev_start_time);
+    }
+
+    let colour_peaks = colour.into_peaks();
+    let rhythm_peaks = rhythm.into_peaks();
+    let stamina_right_peaks = stamina_right.into_peaks();
+    let stamina_left_peaks = stamina_left.into_peaks();
+
+    let colour_rating =
+        skills::difficulty_value(&colour_peaks) * COLOUR_SKILL_MULTIPLIER;
+    let rhythm_rating =
+        skills::difficulty_value(&rhythm_peaks) * RHYTHM_SKILL_MULTIPLIER;
+    let mut stamina_rating = (skills::difficulty_value(&stamina_right_peaks)
+        + skills::difficulty_value(&stamina_left_peaks))
+        * STAMINA_SKILL_MULTIPLIER;
+
+    let mut stamina_penalty =
+        simple_colour_penalty(stamina_rating, colour_rating);
+    stamina_rating *= stamina_penalty;
+
+    // Converts have little colour variance, their stamina rating is too high
+    if is_convert && colour_rating < 0.05 {
+        stamina_penalty *= 0.25;
+    }
+
+    let combined_rating = locally_combined_difficulty(
+        &colour_peaks,
+        &rhythm_peaks,
+        &stamina_right_peaks,
+        &stamina_left_peaks,
+        stamina_penalty,
+    );
+    let separated_rating =
+        norm(1.5, &[colour_rating, rhythm_rating, stamina_rating]);
+
+    attrs.stars = rescale(1.4 * separated_rating + 0.5 * combined_rating);
+    attrs.stamina = stamina_rating;
+    attrs.rhythm = rhythm_rating;
+    attrs.colour = colour_rating;
+
+    attrs
+}
+
+fn convert_objects(map: &Beatmap, is_convert: bool) -> Vec<TaikoObject> {
+    let mut objects = Vec::with_capacity(map.hit_objects.len());
+
+    for h in map.hit_objects.iter() {
+        match &h.kind {
+            HitObjectKind::Circle | HitObjectKind::Hold { .. } => {
+                objects.push(TaikoObject {
+                    start_time: h.start_time,
+                    kind: TaikoObjectKind::Hit { rim: is_rim(h.sound) },
+                })
+            },
+            HitObjectKind::Spinner { .. } => objects.push(TaikoObject {
+                start_time: h.start_time,
+                kind: TaikoObjectKind::Swell,
+            }),
+            HitObjectKind::Slider { .. } => {
+                convert_slider(map, h, is_convert, &mut objects)
+            },
+        }
+    }
+
+    objects.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+    objects
+}
+
+/// Short sliders of converted beatmaps are split into hits, the others
+/// become drum rolls.
+fn convert_slider(
+    map: &Beatmap,
+    h: &HitObject,
+    is_convert: bool,
+    objects: &mut Vec<TaikoObject>,
+) {
+    let HitObjectKind::Slider {
+        repeats,
+        pixel_len,
+        control_points,
+        edge_sounds,
+    } = &h.kind
+    else {
+        return;
+    };
+
+    if !is_convert {
+        objects.push(TaikoObject {
+            start_time: h.start_time,
+            kind: TaikoObjectKind::DrumRoll,
+        });
+
+        return;
+    }
+
+    let path = SliderPath::new(
+        control_points,
+        (*pixel_len > 0.0).then_some(*pixel_len),
+    );
+
+    // Keep the floating point operations in this order, they need to match
+    // osu!stable
+    let spans = repeats + 1;
+    let distance = path.distance() * spans as f64 * LEGACY_VELOCITY_MULTIPLIER;
+
+    let timing_beat_len = map.beat_len_at(h.start_time);
+    let mut beat_len = timing_beat_len / map.slider_velocity_at(h.start_time);
+
+    let slider_multiplier = map.slider_multiplier * LEGACY_VELOCITY_MULTIPLIER;
+    let slider_scoring_point_distance =
+        BASE_SCORING_DISTANCE * slider_multiplier / map.slider_tick_rate;
+
+    // Velocity of the slider as drum roll
+    let taiko_velocity = slider_scoring_point_distance * map.slider_tick_rate;
+    let taiko_duration = (distance / taiko_velocity * beat_len) as i32 as f64;
+
+    let osu_velocity = taiko_velocity * (1000.0 / beat_len);
+
+    // osu!stable only uses the speed-adjusted beat length of beatmaps older
+    // than v8 to convert
+    if map.version >= 8 {
+        beat_len = timing_beat_len;
+    }
+
+    // Ticks are assumed to be 1/8 spaced within the duration of one beat
+    let tick_spacing =
+        (beat_len / map.slider_tick_rate).min(taiko_duration / spans as f64);
+
+    let split_into_hits =
+        tick_spacing > 0.0 && distance / osu_velocity * 1000.0 < 2.0 * beat_len;
+
+    if !split_into_hits {
+        objects.push(TaikoObject {
+            start_time: h.start_time,
+            kind: TaikoObjectKind::DrumRoll,
+        });
+
+        return;
+    }
+
+    let node_sounds: Vec<u8> = (0..=spans)
+        .map(|i| edge_sounds.get(i).copied().unwrap_or(h.sound))
+        .collect();
+
+    let mut node = 0;
+    let mut time = h.start_time;
+
+    while time <= h.start_time + taiko_duration + tick_spacing / 8.0 {
+        objects.push(TaikoObject {
+            start_time: time,
+            kind: TaikoObjectKind::Hit { rim: is_rim(node_sounds[node]) },
+        });
+
+        node = (node + 1) % node_sounds.len();
+        time += tick_spacing;
+    }
+}
+
+#[inline]
+fn is_rim(sound: u8) -> bool {
+    sound & (SOUND_WHISTLE | SOUND_CLAP) != 0
+}
+
+#[inline]
+fn simple_colour_penalty(stamina: f64, colour: f64) -> f64 {
+    if colour <= 0.0 {
+        return 0.79 - 0.25;
+    }
+
+    0.79 - (stamina / colour - 12.0).atan() / std::f64::consts::PI / 2.0
+}
+
+#[inline]
+fn norm(p: f64, values: &[f64]) -> f64 {
+    values.iter().map(|x| x.powf(p)).sum::<f64>().powf(1.0 / p)
+}
+
+/// Combines the skills of every section before weighting them.
+fn locally_combined_difficulty(
+    colour_peaks: &[f64],
+    rhythm_peaks: &[f64],
+    stamina_right_peaks: &[f64],
+    stamina_left_peaks: &[f64],
+    stamina_penalty: f64,
+) -> f64 {
+    let mut peaks: Vec<f64> = colour_peaks
+        .iter()
+        .zip(rhythm_peaks)
+        .zip(stamina_right_peaks.iter().zip(stamina_left_peaks))
+        .map(|((colour, rhythm), (stamina_right, stamina_left))| {
+            let colour_peak = colour * COLOUR_SKILL_MULTIPLIER;
+            let rhythm_peak = rhythm * RHYTHM_SKILL_MULTIPLIER;
+            let stamina_peak = (stamina_right + stamina_left)
+                * STAMINA_SKILL_MULTIPLIER
+                * stamina_penalty;
+
+            norm(2.0, &[colour_peak, rhythm_peak, stamina_peak])
+        })
+        .filter(|&peak| peak > 0.0)
+        .collect();
+
+    crate::math::sort_desc(&mut peaks);
+
+    crate::math::weighted_sum(peaks, 0.9)
+}
+
+#[inline]
+fn rescale(stars: f64) -> f64 {
+    if stars < 0.0 {
+        return stars;
+    }
+
+    10.43 * (stars / 8.0 + 1.0).ln()
+}
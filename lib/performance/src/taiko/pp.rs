@@ -0,0 +1,85 @@
+use super::TaikoDifficultyAttributes;
+use crate::{mods::ModsExt, ScoreState};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoPerformanceAttributes {
+    pub pp: f64,
+    pub strain: f64,
+    pub accuracy: f64,
+}
+
+/// Relax only frees the player from the colours, which is already part of
+/// the difficulty, so it is calculated as any other score.
+pub fn performance(
+    attrs: &TaikoDifficultyAttributes,
+    mods: u32,
+    state: &ScoreState,
+) -> TaikoPerformanceAttributes {
+    let total_hits = state.total_hits_taiko() as f64;
+
+    if total_hits == 0.0 {
+        return TaikoPerformanceAttributes::default();
+    }
+
+    let acc = state.accuracy_taiko();
+
+    let mut multiplier = 1.1;
+
+    if mods.nf() {
+        multiplier *= 0.9;
+    }
+
+    if mods.hd() {
+        multiplier *= 1.1;
+    }
+
+    let strain = strain(attrs, mods, state, total_hits, acc);
+    let accuracy = accuracy(attrs, total_hits, acc);
+
+    let pp =
+        (strain.powf(1.1) + accuracy.powf(1.1)).powf(1.0 / 1.1) * multiplier;
+
+    TaikoPerformanceAttributes { pp, strain, accuracy }
+}
+
+fn strain(
+    attrs: &TaikoDifficultyAttributes,
+    mods: u32,
+    state: &ScoreState,
+    total_hits: f64,
+    acc: f64,
+) -> f64 {
+    let exp_base = 5.0 * (attrs.stars / 0.0075).max(1.0) - 4.0;
+    let mut strain = exp_base * exp_base / 100_000.0;
+
+    let len_bonus = 1.0 + 0.1 * (total_hits / 1500.0).min(1.0);
+    strain *= len_bonus;
+
+    strain *= 0.985_f64.powi(state.nmiss as i32);
+
+    if mods.hd() {
+        strain *= 1.025;
+    }
+
+    if mods.fl() {
+        strain *= 1.05 * len_bonus;
+    }
+
+    strain * acc
+}
+
+fn accuracy(
+    attrs: &TaikoDifficultyAttributes,
+    total_hits: f64,
+    acc: f64,
+) -> f64 {
+    if attrs.great_hit_window <= 0.0 {
+        return 0.0;
+    }
+
+    (150.0 / attrs.great_hit_window).powf(1.1)
+        * acc.powi(15)
+        * 22.0
+        * (total_hits / 1500.0).powf(0.3).min(1.15)
+}
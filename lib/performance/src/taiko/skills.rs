@@ -0,0 +1,510 @@
+use super::TaikoDifficultyObject;
+use crate::{
+    math::{sort_desc, weighted_sum},
+    strain::{strain_decay, StrainPeaks},
+};
+use std::collections::VecDeque;
+
+const DECAY_WEIGHT: f64 = 0.9;
+
+/// Weighted sum of the peaks, sections without strain are skipped.
+pub(crate) fn difficulty_value(peaks: &[f64]) -> f64 {
+    let mut peaks: Vec<f64> =
+        peaks.iter().copied().filter(|&p| p > 0.0).collect();
+    sort_desc(&mut peaks);
+
+    weighted_sum(peaks, DECAY_WEIGHT)
+}
+
+/// Strain that decays exponentially between objects.
+#[derive(Debug, Clone)]
+struct StrainDecay {
+    skill_multiplier: f64,
+    decay_base: f64,
+    current_strain: f64,
+    peaks: StrainPeaks,
+}
+
+impl StrainDecay {
+    #[inline]
+    fn new(skill_multiplier: f64, decay_base: f64) -> Self {
+        Self {
+            skill_multiplier,
+            decay_base,
+            current_strain: 1.0,
+            peaks: StrainPeaks::default(),
+        }
+    }
+
+    /// Closes the sections before `current` with the strain of the previous
+    /// object.
+    fn start_sections_until(
+        &mut self,
+        current: &TaikoDifficultyObject,
+        is_first: bool,
+        prev_start_time: f64,
+    ) {
+        let (current_strain, decay_base) =
+            (self.current_strain, self.decay_base);
+
+        self.peaks.start_sections_until(current.start_time, is_first, |time| {
+            current_strain * strain_decay(time - prev_start_time, decay_base)
+        });
+    }
+
+    fn add(&mut self, current: &TaikoDifficultyObject, strain_value: f64) {
+        self.current_strain *=
+            strain_decay(current.delta_time, self.decay_base);
+        self.current_strain += strain_value * self.skill_multiplier;
+
+        self.peaks.add(self.current_strain);
+    }
+}
+
+/// Queue that drops its oldest item once it is full.
+#[derive(Debug, Clone)]
+struct LimitedQueue<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> LimitedQueue<T> {
+    #[inline]
+    fn new(capacity: usize) -> Self {
+        Self { items: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    #[inline]
+    fn push(&mut self, item: T) {
+        if self.items.len() == self.capacity {
+            self.items.pop_front();
+        }
+
+        self.items.push_back(item);
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.items.len() == self.capacity
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Colour {
+    strain: StrainDecay,
+    mono_history: LimitedQueue<usize>,
+    previous_hit_type: Option<bool>,
+    current_mono_len: usize,
+}
+
+impl Default for Colour {
+    fn default() -> Self {
+        Self {
+            strain: StrainDecay::new(1.0, 0.4),
+            mono_history: LimitedQueue::new(Self::MONO_HISTORY_MAX_LEN),
+            previous_hit_type: None,
+            current_mono_len: 0,
+        }
+    }
+}
+
+impl Colour {
+    const MONO_HISTORY_MAX_LEN: usize = 5;
+    const MOST_RECENT_PATTERNS_TO_COMPARE: usize = 2;
+
+    pub fn process(
+        &mut self,
+        current: &TaikoDifficultyObject,
+        is_first: bool,
+        prev_start_time: f64,
+    ) {
+        self.strain.start_sections_until(current, is_first, prev_start_time);
+        let strain_value = self.strain_value_of(current);
+        self.strain.add(current, strain_value);
+    }
+
+    #[inline]
+    pub fn into_peaks(self) -> Vec<f64> {
+        self.strain.peaks.into_peaks()
+    }
+
+    fn strain_value_of(&mut self, current: &TaikoDifficultyObject) -> f64 {
+        // Changing from or to drum rolls and swells is no colour change,
+        // streams of them don't count either
+        if !(current.last_is_hit
+            && current.is_hit()
+            && current.delta_time < 1000.0)
+        {
+            self.mono_history.items.clear();
+            self.current_mono_len = usize::from(current.is_hit());
+            self.previous_hit_type = current.hit_type;
+
+            return 0.0;
+        }
+
+        let mut object_strain = 0.0;
+
+        if self.previous_hit_type.is_some()
+            && current.hit_type != self.previous_hit_type
+        {
+            // The colour has changed
+            object_strain = 1.0;
+
+            // There need to be at least two streaks to determine a strain,
+            // two streaks with an even total length get no colour bonus
+            let history = &self.mono_history.items;
+            if history.len() < 2
+                || (history[history.len() - 1] + self.current_mono_len)
+                    .is_multiple_of(2)
+            {
+                object_strain = 0.0;
+            }
+
+            object_strain *= self.repetition_penalties();
+            self.current_mono_len = 1;
+        } else {
+            self.current_mono_len += 1;
+        }
+
+        self.previous_hit_type = current.hit_type;
+
+        object_strain
+    }
+
+    fn repetition_penalties(&mut self) -> f64 {
+        let mut penalty = 1.0;
+
+        self.mono_history.push(self.current_mono_len);
+
+        let history = &self.mono_history.items;
+        let compare = Self::MOST_RECENT_PATTERNS_TO_COMPARE;
+
+        for start in (0..history.len().saturating_sub(compare)).rev() {
+            let is_same_pattern = (0..compare).all(|i| {
+                history[start + i] == history[history.len() - compare + i]
+            });
+
+            if !is_same_pattern {
+                continue;
+            }
+
+            let notes_since: usize = history.range(start..).sum();
+            penalty *= repetition_penalty(notes_since);
+
+            break;
+        }
+
+        penalty
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Rhythm {
+    strain: StrainDecay,
+    current_strain: f64,
+    /// Rhythm and object index of the recent objects.
+    rhythm_history: LimitedQueue<(usize, usize)>,
+    notes_since_rhythm_change: usize,
+}
+
+impl Default for Rhythm {
+    fn default() -> Self {
+        Self {
+            strain: StrainDecay::new(10.0, 0.0),
+            current_strain: 0.0,
+            rhythm_history: LimitedQueue::new(Self::RHYTHM_HISTORY_MAX_LEN),
+            notes_since_rhythm_change: 0,
+        }
+    }
+}
+
+impl Rhythm {
+    const STRAIN_DECAY: f64 = 0.96;
+    const RHYTHM_HISTORY_MAX_LEN: usize = 8;
+
+    pub fn process(
+        &mut self,
+        current: &TaikoDifficultyObject,
+        is_first: bool,
+        prev_start_time: f64,
+    ) {
+        self.strain.start_sections_until(current, is_first, prev_start_time);
+        let strain_value = self.strain_value_of(current);
+        self.strain.add(current, strain_value);
+    }
+
+    #[inline]
+    pub fn into_peaks(self) -> Vec<f64> {
+        self.strain.peaks.into_peaks()
+    }
+
+    fn strain_value_of(&mut self, current: &TaikoDifficultyObject) -> f64 {
+        // Drum rolls and swells are exempt
+        if !current.is_hit() {
+            self.reset_rhythm_and_strain();
+            return 0.0;
+        }
+
+        self.current_strain *= Self::STRAIN_DECAY;
+        self.notes_since_rhythm_change += 1;
+
+        // No rhythm strain if the rhythm does not change
+        if current.rhythm_difficulty() == 0.0 {
+            return 0.0;
+        }
+
+        let mut object_strain = current.rhythm_difficulty();
+
+        object_strain *= self.repetition_penalties(current);
+        object_strain *= pattern_length_penalty(self.notes_since_rhythm_change);
+        object_strain *= self.speed_penalty(current.delta_time);
+
+        self.notes_since_rhythm_change = 0;
+        self.current_strain += object_strain;
+
+        self.current_strain
+    }
+
+    fn repetition_penalties(&mut self, current: &TaikoDifficultyObject) -> f64 {
+        let mut penalty = 1.0;
+
+        self.rhythm_history.push((current.rhythm, current.object_idx));
+
+        let history = &self.rhythm_history.items;
+
+        for compare in 2..=Self::RHYTHM_HISTORY_MAX_LEN / 2 {
+            for start in (0..history.len().saturating_sub(compare)).rev() {
+                let is_same_pattern = (0..compare).all(|i| {
+                    history[start + i].0
+                        == history[history.len() - compare + i].0
+                });
+
+                if !is_same_pattern {
+                    continue;
+                }
+
+                let notes_since = current.object_idx - history[start].1;
+                penalty *= repetition_penalty(notes_since);
+
+                break;
+            }
+        }
+
+        penalty
+    }
+
+    fn speed_penalty(&mut self, delta_time: f64) -> f64 {
+        if delta_time < 80.0 {
+            return 1.0;
+        }
+
+        if delta_time < 210.0 {
+            return (1.4 - 0.005 * delta_time).max(0.0);
+        }
+
+        self.reset_rhythm_and_strain();
+
+        0.0
+    }
+
+    #[inline]
+    fn reset_rhythm_and_strain(&mut self) {
+        self.current_strain = 0.0;
+        self.notes_since_rhythm_change = 0;
+    }
+}
+
+#[inline]
+fn repetition_penalty(notes_since: usize) -> f64 {
+    (0.032 * notes_since as f64).min(1.0)
+}
+
+#[inline]
+fn pattern_length_penalty(pattern_len: usize) -> f64 {
+    let short_pattern_penalty = (0.15 * pattern_len as f64).min(1.0);
+    let long_pattern_penalty =
+        (2.5 - 0.15 * pattern_len as f64).clamp(0.0, 1.0);
+
+    short_pattern_penalty.min(long_pattern_penalty)
+}
+
+/// Stamina of one hand, the hands alternate between objects.
+#[derive(Debug, Clone)]
+pub(crate) struct Stamina {
+    strain: StrainDecay,
+    hand: usize,
+    note_pair_duration_history: LimitedQueue<f64>,
+    off_hand_object_duration: f64,
+}
+
+impl Stamina {
+    const MAX_HISTORY_LEN: usize = 2;
+
+    #[inline]
+    pub fn new(right_hand: bool) -> Self {
+        Self {
+            strain: StrainDecay::new(1.0, 0.4),
+            hand: usize::from(right_hand),
+            note_pair_duration_history: LimitedQueue::new(
+                Self::MAX_HISTORY_LEN,
+            ),
+            off_hand_object_duration: f64::MAX,
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        current: &TaikoDifficultyObject,
+        is_first: bool,
+        prev_start_time: f64,
+    ) {
+        self.strain.start_sections_until(current, is_first, prev_start_time);
+        let strain_value = self.strain_value_of(current);
+        self.strain.add(current, strain_value);
+    }
+
+    #[inline]
+    pub fn into_peaks(self) -> Vec<f64> {
+        self.strain.peaks.into_peaks()
+    }
+
+    fn strain_value_of(&mut self, current: &TaikoDifficultyObject) -> f64 {
+        if !current.is_hit() {
+            return 0.0;
+        }
+
+        if current.object_idx % 2 != self.hand {
+            self.off_hand_object_duration = current.delta_time;
+            return 0.0;
+        }
+
+        if current.object_idx == 1 {
+            return 1.0;
+        }
+
+        let note_pair_duration =
+            current.delta_time + self.off_hand_object_duration;
+        self.note_pair_duration_history.push(note_pair_duration);
+
+        let shortest_recent_note = self
+            .note_pair_duration_history
+            .items
+            .iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+
+        let mut object_strain = 1.0 + speed_bonus(shortest_recent_note);
+
+        if current.stamina_cheese {
+            object_strain *= cheese_penalty(note_pair_duration);
+        }
+
+        object_strain
+    }
+}
+
+#[inline]
+fn cheese_penalty(note_pair_duration: f64) -> f64 {
+    if note_pair_duration > 125.0 {
+        1.0
+    } else if note_pair_duration < 100.0 {
+        0.6
+    } else {
+        0.6 + (note_pair_duration - 100.0) * 0.016
+    }
+}
+
+#[inline]
+fn speed_bonus(note_pair_duration: f64) -> f64 {
+    if note_pair_duration >= 200.0 {
+        return 0.0;
+    }
+
+    (200.0 - note_pair_duration).powi(2) / 100_000.0
+}
+
+/// Marks rolls and TL taps, patterns that can be played with less stamina
+/// than alternating.
+pub(crate) struct StaminaCheeseDetector<'a> {
+    objects: &'a mut [TaikoDifficultyObject],
+}
+
+impl<'a> StaminaCheeseDetector<'a> {
+    const ROLL_MIN_REPETITIONS: usize = 12;
+    const TL_MIN_REPETITIONS: isize = 16;
+
+    #[inline]
+    pub fn new(objects: &'a mut [TaikoDifficultyObject]) -> Self {
+        Self { objects }
+    }
+
+    pub fn find_cheese(&mut self) {
+        self.find_rolls(3);
+        self.find_rolls(4);
+
+        self.find_tl_tap(0, true);
+        self.find_tl_tap(1, true);
+        self.find_tl_tap(0, false);
+        self.find_tl_tap(1, false);
+    }
+
+    fn find_rolls(&mut self, pattern_len: usize) {
+        let mut history = LimitedQueue::new(2 * pattern_len);
+
+        // Index of the item *before* the suspected repeat's start
+        let mut idx_before_last_repeat: isize = -1;
+        let mut last_mark_end = 0;
+
+        for i in 0..self.objects.len() {
+            history.push(self.objects[i].hit_type);
+
+            if !history.is_full() {
+                continue;
+            }
+
+            let contains_pattern_repeat = (0..pattern_len)
+                .all(|j| history.items[j] == history.items[j + pattern_len]);
+
+            if !contains_pattern_repeat {
+                idx_before_last_repeat =
+                    i as isize - history.items.len() as isize + 1;
+                continue;
+            }
+
+            let repeated_len = (i as isize - idx_before_last_repeat) as usize;
+            if repeated_len < Self::ROLL_MIN_REPETITIONS {
+                continue;
+            }
+
+            self.mark_as_cheese(last_mark_end.max(i + 1 - repeated_len), i);
+            last_mark_end = i;
+        }
+    }
+
+    fn find_tl_tap(&mut self, parity: usize, rim: bool) {
+        let mut tl_len: isize = -2;
+        let mut last_mark_end = 0;
+
+        for i in (parity..self.objects.len()).step_by(2) {
+            if self.objects[i].hit_type == Some(rim) {
+                tl_len += 2;
+            } else {
+                tl_len = -2;
+            }
+
+            if tl_len < Self::TL_MIN_REPETITIONS {
+                continue;
+            }
+
+            self.mark_as_cheese(last_mark_end.max(i + 1 - tl_len as usize), i);
+            last_mark_end = i;
+        }
+    }
+
+    #[inline]
+    fn mark_as_cheese(&mut self, start: usize, end: usize) {
+        for obj in self.objects[start..=end].iter_mut() {
+            obj.stamina_cheese = true;
+        }
+    }
+}
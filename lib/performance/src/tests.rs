@@ -0,0 +1,320 @@
+const OSU: &[u8] = include_bytes!("../samples/osu.osu");
+const TAIKO: &[u8] = include_bytes!("../samples/taiko.osu");
+const CATCH: &[u8] = include_bytes!("../samples/catch.osu");
+const MANIA: &[u8] = include_bytes!("../samples/mania.osu");
+
+fn assert_approx(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "expected {expected}, got {actual}"
+    );
+}
+
+mod star_rating {
+    use super::*;
+    use crate::{
+        calculate,
+        mods::{
+            AUTOPILOT, DOUBLE_TIME, EASY, FLASHLIGHT, HALF_TIME, HARD_ROCK,
+            HIDDEN, NIGHTCORE, NO_FAIL, RELAX,
+        },
+        Beatmap, CalculateError, DifficultyAttributes, GameMode,
+    };
+
+    fn stars(map: &[u8], mode: Option<GameMode>, mods: u32) -> f64 {
+        calculate(&Beatmap::parse(map).unwrap(), mode, mods).unwrap().stars()
+    }
+
+    #[test]
+    fn test_osu() {
+        let map = Beatmap::parse(OSU).unwrap();
+        let DifficultyAttributes::Osu(attrs) =
+            calculate(&map, None, 0).unwrap()
+        else {
+            panic!("not an osu! beatmap");
+        };
+
+        assert_approx(attrs.stars, 6.425719788791335);
+        assert_approx(attrs.aim, 3.485921764283724);
+        assert_approx(attrs.speed, 2.4694209560602656);
+        assert_eq!(attrs.flashlight, 0.0);
+        assert_eq!(
+            (attrs.n_circles, attrs.n_sliders, attrs.n_spinners),
+            (56, 8, 1)
+        );
+        assert_eq!(attrs.max_combo, 73);
+
+        assert_approx(stars(OSU, None, HARD_ROCK), 6.864650867144853);
+        assert_approx(stars(OSU, None, DOUBLE_TIME), 8.580274472671535);
+        assert_approx(stars(OSU, None, HALF_TIME), 5.18618618716961);
+        assert_approx(stars(OSU, None, EASY), 5.830067524823113);
+        assert_approx(stars(OSU, None, HIDDEN | FLASHLIGHT), 6.600267585928863);
+    }
+
+    #[test]
+    fn test_taiko() {
+        assert_approx(stars(TAIKO, None, 0), 4.613205373648738);
+        assert_approx(stars(TAIKO, None, DOUBLE_TIME), 5.586119350659003);
+        assert_approx(stars(TAIKO, None, HALF_TIME), 3.9570086428361404);
+
+        // only the hit windows are changed
+        assert_approx(stars(TAIKO, None, HARD_ROCK), 4.613205373648738);
+        assert_approx(stars(TAIKO, None, EASY), 4.613205373648738);
+    }
+
+    #[test]
+    fn test_catch() {
+        assert_approx(stars(CATCH, None, 0), 4.443157059198801);
+        assert_approx(stars(CATCH, None, HARD_ROCK), 4.828393239448315);
+        assert_approx(stars(CATCH, None, DOUBLE_TIME), 5.65652685063434);
+        assert_approx(stars(CATCH, None, HALF_TIME), 3.6637410259235406);
+        assert_approx(stars(CATCH, None, EASY), 4.002885737020714);
+    }
+
+    #[test]
+    fn test_mania() {
+        let map = Beatmap::parse(MANIA).unwrap();
+        let DifficultyAttributes::Mania(attrs) =
+            calculate(&map, None, 0).unwrap()
+        else {
+            panic!("not an osu!mania beatmap");
+        };
+
+        assert_approx(attrs.stars, 2.338661328299964);
+        assert_eq!((attrs.n_keys, attrs.max_combo), (4, 112));
+
+        assert_approx(stars(MANIA, None, DOUBLE_TIME), 2.715338530314279);
+        assert_approx(stars(MANIA, None, HALF_TIME), 2.022611092481877);
+        assert_approx(stars(MANIA, None, HARD_ROCK), 2.338661328299964);
+    }
+
+    #[test]
+    fn test_converts() {
+        assert_approx(stars(OSU, Some(GameMode::Taiko), 0), 1.914921444317597);
+        assert_approx(stars(OSU, Some(GameMode::Catch), 0), 4.438621123691856);
+
+        assert!(matches!(
+            calculate(&Beatmap::parse(OSU).unwrap(), Some(GameMode::Mania), 0),
+            Err(CalculateError::UnsupportedConversion { .. })
+        ));
+        assert!(matches!(
+            calculate(&Beatmap::parse(TAIKO).unwrap(), Some(GameMode::Osu), 0),
+            Err(CalculateError::UnsupportedConversion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_mods_without_difficulty_changes() {
+        for map in [OSU, TAIKO, CATCH, MANIA] {
+            let nomod = stars(map, None, 0);
+
+            // nightcore is double time
+            assert_eq!(
+                stars(map, None, NIGHTCORE),
+                stars(map, None, DOUBLE_TIME)
+            );
+
+            // relax and autopilot are handled by the performance points
+            for mods in [RELAX, AUTOPILOT, NO_FAIL] {
+                assert_eq!(stars(map, None, mods), nomod);
+            }
+        }
+    }
+}
+
+mod performance {
+    use super::*;
+    use crate::{
+        calculate,
+        mods::{AUTOPILOT, HARD_ROCK, HIDDEN, NO_FAIL, RELAX, SPUN_OUT},
+        Beatmap, DifficultyAttributes, GameMode, PerformanceAttributes,
+        ScoreState,
+    };
+
+    fn difficulty(
+        map: &[u8],
+        mode: Option<GameMode>,
+        mods: u32,
+    ) -> DifficultyAttributes {
+        calculate(&Beatmap::parse(map).unwrap(), mode, mods).unwrap()
+    }
+
+    fn osu_pp(mods: u32, state: &ScoreState) -> PerformanceAttributes {
+        difficulty(OSU, None, mods).performance(mods, state)
+    }
+
+    const OSU_SS: ScoreState = ScoreState {
+        max_combo: 73,
+        n300: 65,
+        ngeki: 0,
+        nkatu: 0,
+        n100: 0,
+        n50: 0,
+        nmiss: 0,
+    };
+    const OSU_IMPERFECT: ScoreState = ScoreState {
+        max_combo: 40,
+        n300: 60,
+        n100: 3,
+        n50: 1,
+        nmiss: 1,
+        ngeki: 0,
+        nkatu: 0,
+    };
+
+    #[test]
+    fn test_osu() {
+        assert_approx(osu_pp(0, &OSU_SS).pp(), 263.5828758004);
+        assert_approx(osu_pp(0, &OSU_IMPERFECT).pp(), 115.96705716070511);
+        assert_approx(osu_pp(HIDDEN | HARD_ROCK, &OSU_SS).pp(), 393.4870773310);
+        assert_approx(osu_pp(NO_FAIL, &OSU_IMPERFECT).pp(), 111.78064639720367);
+        assert_approx(osu_pp(SPUN_OUT, &OSU_SS).pp(), 255.9981333410);
+    }
+
+    #[test]
+    fn test_osu_relax() {
+        assert_approx(osu_pp(RELAX, &OSU_SS).pp(), 181.3707439384);
+
+        let PerformanceAttributes::Osu(attrs) = osu_pp(RELAX, &OSU_IMPERFECT)
+        else {
+            panic!("not osu! performance");
+        };
+
+        assert_approx(attrs.pp, 55.09662349025468);
+        assert_eq!((attrs.speed, attrs.accuracy), (0.0, 0.0));
+        assert!(attrs.aim > 0.0);
+        // the 100s and 50s are counted as partial misses
+        assert_approx(attrs.effective_miss_count, 4.53043261064977);
+    }
+
+    #[test]
+    fn test_osu_autopilot() {
+        assert_approx(osu_pp(AUTOPILOT, &OSU_SS).pp(), 98.0992993272);
+
+        let PerformanceAttributes::Osu(attrs) =
+            osu_pp(AUTOPILOT, &OSU_IMPERFECT)
+        else {
+            panic!("not osu! performance");
+        };
+
+        assert_approx(attrs.pp, 31.2623823315072);
+        assert_eq!(attrs.aim, 0.0);
+        assert_approx(attrs.speed, 22.478171637748982);
+        assert_approx(attrs.accuracy, 6.2465005731897305);
+    }
+
+    #[test]
+    fn test_taiko() {
+        let attrs = difficulty(TAIKO, None, 0);
+        let ss = ScoreState { max_combo: 96, n300: 96, ..Default::default() };
+        let imperfect = ScoreState {
+            max_combo: 50,
+            n300: 90,
+            n100: 4,
+            nmiss: 2,
+            ..Default::default()
+        };
+
+        assert_approx(attrs.performance(0, &ss).pp(), 153.1816230449);
+        assert_approx(
+            attrs.performance(0, &imperfect).pp(),
+            121.64698897737586,
+        );
+        assert_approx(attrs.performance(NO_FAIL, &ss).pp(), 137.8634607404);
+
+        // relax is already part of the difficulty
+        assert_eq!(
+            attrs.performance(RELAX, &imperfect),
+            attrs.performance(0, &imperfect)
+        );
+
+        let hdhr = HIDDEN | HARD_ROCK;
+        assert_approx(
+            difficulty(TAIKO, None, hdhr).performance(hdhr, &ss).pp(),
+            193.0257378431,
+        );
+
+        // converted from osu!standard
+        assert_approx(
+            difficulty(OSU, Some(GameMode::Taiko), 0)
+                .performance(
+                    0,
+                    &ScoreState {
+                        max_combo: 72,
+                        n300: 72,
+                        ..Default::default()
+                    },
+                )
+                .pp(),
+            81.0214249290,
+        );
+    }
+
+    #[test]
+    fn test_catch() {
+        let attrs = difficulty(CATCH, None, 0);
+        let ss = ScoreState {
+            max_combo: 60,
+            n300: 60,
+            n50: 24,
+            ..Default::default()
+        };
+        let imperfect = ScoreState {
+            max_combo: 30,
+            n300: 58,
+            n50: 21,
+            nkatu: 3,
+            nmiss: 2,
+            ..Default::default()
+        };
+
+        assert_approx(attrs.performance(0, &ss).pp(), 196.4116360806);
+        assert_approx(attrs.performance(0, &imperfect).pp(), 75.73552041830595);
+        assert_approx(attrs.performance(NO_FAIL, &ss).pp(), 176.7704724726);
+
+        // relax is already part of the difficulty
+        assert_eq!(
+            attrs.performance(RELAX, &imperfect),
+            attrs.performance(0, &imperfect)
+        );
+
+        let hdhr = HIDDEN | HARD_ROCK;
+        assert_approx(
+            difficulty(CATCH, None, hdhr).performance(hdhr, &ss).pp(),
+            267.9367753952,
+        );
+    }
+
+    #[test]
+    fn test_mania() {
+        let attrs = difficulty(MANIA, None, 0);
+        let ss = ScoreState {
+            max_combo: 112,
+            ngeki: 100,
+            n300: 12,
+            ..Default::default()
+        };
+        let imperfect = ScoreState {
+            max_combo: 60,
+            ngeki: 80,
+            n300: 20,
+            nkatu: 5,
+            n100: 3,
+            n50: 2,
+            nmiss: 2,
+        };
+
+        assert_approx(attrs.performance(0, &ss).pp(), 43.6438844073);
+        assert_approx(attrs.performance(0, &imperfect).pp(), 27.26482848075622);
+        assert_approx(attrs.performance(NO_FAIL, &ss).pp(), 32.7329133055);
+    }
+
+    #[test]
+    fn test_empty_score() {
+        for map in [OSU, TAIKO, CATCH, MANIA] {
+            let attrs = difficulty(map, None, 0);
+
+            assert_eq!(attrs.performance(0, &ScoreState::default()).pp(), 0.0);
+        }
+    }
+}